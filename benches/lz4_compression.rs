@@ -0,0 +1,86 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+use rust_decimal::Decimal;
+use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+use wmjtyd_libstock::data::fields::info_type::InfoType;
+use wmjtyd_libstock::data::fields::{PriceDataField, SymbolPairField};
+use wmjtyd_libstock::data::orderbook::{OrderbookStructure, OrdersBox};
+use wmjtyd_libstock::data::serializer::{StructDeserializer, StructSerializer};
+use wmjtyd_libstock::message::compression::CompressedMessage;
+
+/// The number of the orders on each side.
+const ORDERS_PER_SIDE: i64 = 500;
+
+fn get_orders(direction: InfoType, start: i64, step: i64) -> OrdersBox {
+    let orders = (0..ORDERS_PER_SIDE)
+        .map(|i| {
+            PriceDataField::builder()
+                .price(Decimal::new(start + step * i, 2))
+                .quantity_base(Decimal::new(1000 + i * 7 % 300, 3))
+                .build()
+        })
+        .collect();
+
+    OrdersBox::builder()
+        .direction(direction)
+        .orders(orders)
+        .build()
+}
+
+/// Get an orderbook of 1000 entries.
+fn get_ob_structure() -> OrderbookStructure {
+    OrderbookStructure::builder()
+        .exchange_timestamp(1659755147000u64)
+        .exchange_type(Exchange::Binance)
+        .market_type(MarketType::Spot)
+        .symbol(SymbolPairField::from_pair("BTC/USDT"))
+        .message_type(MessageType::L2Snapshot)
+        .asks(get_orders(InfoType::Asks, 1923460, 1))
+        .bids(get_orders(InfoType::Bids, 1923459, -1))
+        .build()
+}
+
+fn encode(c: &mut Criterion) {
+    let structure = get_ob_structure();
+    let encoded_len = structure.serialize_to_vec().unwrap().len();
+
+    let mut group = c.benchmark_group("encode 1000-entry orderbook");
+    group.throughput(Throughput::Bytes(encoded_len as u64));
+    group.bench_function("uncompressed", |b| {
+        b.iter(|| black_box(&structure).serialize_to_vec().unwrap())
+    });
+    group.bench_function("lz4", |b| {
+        b.iter(|| {
+            let encoded = black_box(&structure).serialize_to_vec().unwrap();
+            CompressedMessage::compress(&encoded)
+        })
+    });
+    group.finish();
+}
+
+fn decode(c: &mut Criterion) {
+    let encoded = get_ob_structure().serialize_to_vec().unwrap();
+    let compressed = CompressedMessage::compress(&encoded);
+    println!(
+        "1000-entry orderbook: {} bytes, {} bytes compressed",
+        encoded.len(),
+        compressed.as_bytes().len()
+    );
+
+    let mut group = c.benchmark_group("decode 1000-entry orderbook");
+    group.throughput(Throughput::Bytes(encoded.len() as u64));
+    group.bench_function("uncompressed", |b| {
+        b.iter(|| OrderbookStructure::deserialize_from_bytes(black_box(&encoded)).unwrap())
+    });
+    group.bench_function("lz4", |b| {
+        b.iter(|| {
+            let encoded = black_box(&compressed).decompress().unwrap();
+            OrderbookStructure::deserialize_from_bytes(&encoded).unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, encode, decode);
+criterion_main!(benches);
@@ -0,0 +1,70 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+use wmjtyd_libstock::data::bbo::BboStructure;
+use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+use wmjtyd_libstock::data::fields::{PriceDataField, SymbolPairField};
+use wmjtyd_libstock::data::orderbook::OrderbookStructure;
+use wmjtyd_libstock::data::serializer::StructSerializer;
+
+fn get_bbo_structure() -> BboStructure {
+    BboStructure::builder()
+        .exchange_timestamp(1659755147000u64)
+        .exchange_type(Exchange::Binance)
+        .market_type(MarketType::Spot)
+        .symbol(SymbolPairField::from_pair("BTC/USDT"))
+        .message_type(MessageType::BBO)
+        .asks(
+            PriceDataField::builder()
+                .price(19234.6)
+                .quantity_base(0.5)
+                .build(),
+        )
+        .bids(
+            PriceDataField::builder()
+                .price(19234.56781234)
+                .quantity_base(2.0)
+                .build(),
+        )
+        .build()
+}
+
+fn get_orderbook_structure() -> OrderbookStructure {
+    const ENCODED_DATA: &[u8] = include_bytes!("./orderbook_serialized.bin");
+
+    wmjtyd_libstock::data::serializer::StructDeserializer::deserialize_from_bytes(ENCODED_DATA)
+        .expect("failed to deserialize the orderbook")
+}
+
+/// Print the size of both formats, and benchmark the serialization.
+fn compare<T: StructSerializer + serde::Serialize>(c: &mut Criterion, name: &str, structure: &T)
+where
+    T::Err: std::fmt::Debug,
+{
+    let binary = structure.serialize_to_vec().unwrap();
+    let msgpack = structure.serialize_msgpack().unwrap();
+    println!(
+        "{name}: binary = {} bytes, MessagePack = {} bytes ({:.2}x)",
+        binary.len(),
+        msgpack.len(),
+        msgpack.len() as f64 / binary.len() as f64
+    );
+
+    c.bench_function(&format!("serialize {name} (binary)"), |b| {
+        b.iter(|| black_box(structure).serialize_to_vec())
+    });
+    c.bench_function(&format!("serialize {name} (MessagePack)"), |b| {
+        b.iter(|| black_box(structure).serialize_msgpack())
+    });
+}
+
+fn bbo(c: &mut Criterion) {
+    compare(c, "bbo", &get_bbo_structure());
+}
+
+fn orderbook(c: &mut Criterion) {
+    compare(c, "orderbook", &get_orderbook_structure());
+}
+
+criterion_group!(benches, bbo, orderbook);
+criterion_main!(benches);
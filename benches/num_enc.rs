@@ -0,0 +1,53 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+use wmjtyd_libstock::data::num::{encode_f64, Encoder};
+
+const PRICES: [f64; 6] = [
+    12345.0,
+    0.00000001,
+    19234.56781234,
+    -43210.87654321,
+    1659.5,
+    0.1 + 0.2,
+];
+
+fn encode_via_string(c: &mut Criterion) {
+    c.bench_function("encode f64 via string", |b| {
+        b.iter(|| {
+            for price in PRICES {
+                let decimal = Decimal::from_str_exact(&black_box(price).to_string()).unwrap();
+                let _: [u8; 10] = decimal.encode().unwrap();
+            }
+        })
+    });
+}
+
+fn encode_via_from_f64(c: &mut Criterion) {
+    c.bench_function("encode f64 via Decimal::from_f64", |b| {
+        b.iter(|| {
+            for price in PRICES {
+                let decimal = Decimal::from_f64(black_box(price)).unwrap();
+                let _: [u8; 10] = decimal.encode().unwrap();
+            }
+        })
+    });
+}
+
+fn encode_fast_path(c: &mut Criterion) {
+    c.bench_function("encode f64 fast path", |b| {
+        b.iter(|| {
+            for price in PRICES {
+                encode_f64(black_box(price)).unwrap();
+            }
+        })
+    });
+}
+
+criterion_group!(
+    benches,
+    encode_via_string,
+    encode_via_from_f64,
+    encode_fast_path
+);
+criterion_main!(benches);
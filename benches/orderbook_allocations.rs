@@ -0,0 +1,101 @@
+//! Compare the heap allocations of decoding the orderbooks of several depths.
+//!
+//! Run it with and without the `smallvec` feature to see the difference:
+//!
+//! ```plain
+//! cargo bench --bench orderbook_allocations
+//! cargo bench --bench orderbook_allocations --features smallvec
+//! ```
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+use rust_decimal::Decimal;
+use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+use wmjtyd_libstock::data::fields::info_type::InfoType;
+use wmjtyd_libstock::data::fields::{PriceDataField, SymbolPairField};
+use wmjtyd_libstock::data::orderbook::{OrderbookStructure, OrdersBox};
+use wmjtyd_libstock::data::serializer::{StructDeserializer, StructSerializer};
+
+/// The allocator counting the allocations.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// The number of the orders on each side.
+const DEPTHS: [i64; 3] = [1, 5, 50];
+
+fn get_orders(direction: InfoType, depth: i64, start: i64, step: i64) -> OrdersBox {
+    let orders = (0..depth)
+        .map(|i| {
+            PriceDataField::builder()
+                .price(Decimal::new(start + step * i, 2))
+                .quantity_base(Decimal::new(1000 + i * 7 % 300, 3))
+                .build()
+        })
+        .collect();
+
+    OrdersBox::builder()
+        .direction(direction)
+        .orders(orders)
+        .build()
+}
+
+/// Get an orderbook with `depth` orders on each side.
+fn get_ob_structure(depth: i64) -> OrderbookStructure {
+    OrderbookStructure::builder()
+        .exchange_timestamp(1659755147000u64)
+        .exchange_type(Exchange::Binance)
+        .market_type(MarketType::Spot)
+        .symbol(SymbolPairField::from_pair("BTC/USDT"))
+        .message_type(MessageType::L2Snapshot)
+        .asks(get_orders(InfoType::Asks, depth, 1923460, 1))
+        .bids(get_orders(InfoType::Bids, depth, 1923459, -1))
+        .build()
+}
+
+/// Count the allocations of running `f` once.
+fn count_allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    black_box(f());
+
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode orderbook");
+
+    for depth in DEPTHS {
+        let encoded = get_ob_structure(depth).serialize_to_vec().unwrap();
+        let allocations = count_allocations(|| {
+            OrderbookStructure::deserialize_from_bytes(&encoded).unwrap()
+        });
+        println!("{depth} orders per side: {allocations} allocations per decoding");
+
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &encoded, |b, encoded| {
+            b.iter(|| OrderbookStructure::deserialize_from_bytes(black_box(encoded)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, decode);
+criterion_main!(benches);
@@ -8,7 +8,7 @@ use rust_decimal::Decimal;
 use wmjtyd_libstock::data::fields::exchange_type::Exchange;
 use wmjtyd_libstock::data::fields::info_type::InfoType;
 use wmjtyd_libstock::data::fields::{PriceDataField, SymbolPairField};
-use wmjtyd_libstock::data::orderbook::{OrderbookStructure, OrdersBox};
+use wmjtyd_libstock::data::orderbook::{OrderbookStructure, OrderbookView, OrdersBox};
 use wmjtyd_libstock::data::serializer::{StructDeserializer, StructSerializer};
 
 pub struct NoneWriter;
@@ -46,7 +46,7 @@ fn get_ob_structure() -> OrderbookStructure {
         )
         .bids(
             OrdersBox::builder()
-                .direction(black_box(InfoType::Asks))
+                .direction(black_box(InfoType::Bids))
                 .orders(
                     iter::repeat(
                         PriceDataField::builder()
@@ -90,5 +90,24 @@ fn deserialize(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, construct, serialize, deserialize);
+fn first_ask(c: &mut Criterion) {
+    let encoded = get_ob_structure().serialize_to_vec().unwrap();
+
+    let mut group = c.benchmark_group("first ask of orderbook");
+    group.bench_function("deserialize", |b| {
+        b.iter(|| {
+            let structure = OrderbookStructure::deserialize_from_bytes(black_box(&encoded));
+            structure.unwrap().asks.best_price()
+        })
+    });
+    group.bench_function("view", |b| {
+        b.iter(|| {
+            let view = OrderbookView::parse(black_box(&encoded));
+            view.unwrap().ask(0).map(Result::unwrap)
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, construct, serialize, deserialize, first_ask);
 criterion_main!(benches);
@@ -0,0 +1,143 @@
+//! The exchanges, market types and message types.
+
+use pyo3::basic::CompareOp;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+use wmjtyd_libstock::data::fields::market_type::MarketType;
+use wmjtyd_libstock::data::fields::message_type::MessageType;
+
+/// Define a Python class wrapping an enum of `libstock`.
+///
+/// The values are converted from and to their snake-case names.
+macro_rules! py_enum {
+    (
+        $(#[$meta:meta])*
+        $py_type:ident($rust_type:ty) as $py_name:literal {
+            $($name:literal => $value:expr,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[pyclass(name = $py_name)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub struct $py_type(pub $rust_type);
+
+        impl $py_type {
+            /// The name and the value of each variant.
+            const VARIANTS: &'static [(&'static str, $rust_type)] = &[$(($name, $value),)+];
+
+            /// Get the index of this value in [`Self::VARIANTS`].
+            fn index(&self) -> Option<usize> {
+                Self::VARIANTS.iter().position(|(_, value)| *value == self.0)
+            }
+        }
+
+        #[pymethods]
+        impl $py_type {
+            /// Get the value named `name`.
+            #[staticmethod]
+            fn from_name(name: &str) -> PyResult<Self> {
+                Self::VARIANTS
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .map(|(_, value)| Self(*value))
+                    .ok_or_else(|| {
+                        PyValueError::new_err(format!("unknown {}: {name}", $py_name))
+                    })
+            }
+
+            /// Get all the values.
+            #[staticmethod]
+            fn all() -> Vec<Self> {
+                Self::VARIANTS.iter().map(|(_, value)| Self(*value)).collect()
+            }
+
+            /// The snake-case name.
+            #[getter]
+            fn name(&self) -> &'static str {
+                self.index().map_or("unknown", |i| Self::VARIANTS[i].0)
+            }
+
+            fn __str__(&self) -> &'static str {
+                self.name()
+            }
+
+            fn __repr__(&self) -> String {
+                format!("{}.from_name('{}')", $py_name, self.name())
+            }
+
+            fn __hash__(&self) -> u64 {
+                self.index().map_or(u64::MAX, |i| i as u64)
+            }
+
+            fn __richcmp__(
+                &self,
+                other: PyRef<'_, Self>,
+                op: CompareOp,
+                py: Python<'_>,
+            ) -> PyObject {
+                match op {
+                    CompareOp::Eq => (*self == *other).into_py(py),
+                    CompareOp::Ne => (*self != *other).into_py(py),
+                    _ => py.NotImplemented(),
+                }
+            }
+        }
+
+        impl From<$rust_type> for $py_type {
+            fn from(value: $rust_type) -> Self {
+                Self(value)
+            }
+        }
+    };
+}
+
+py_enum! {
+    /// The exchange, such as `binance`.
+    PyExchange(Exchange) as "Exchange" {
+        "crypto" => Exchange::Crypto,
+        "ftx" => Exchange::Ftx,
+        "binance" => Exchange::Binance,
+        "huobi" => Exchange::Huobi,
+        "kucoin" => Exchange::Kucoin,
+        "okx" => Exchange::Okx,
+    }
+}
+
+py_enum! {
+    /// The market type, such as `spot`.
+    PyMarketType(MarketType) as "MarketType" {
+        "unknown" => MarketType::Unknown,
+        "spot" => MarketType::Spot,
+        "linear_future" => MarketType::LinearFuture,
+        "inverse_future" => MarketType::InverseFuture,
+        "linear_swap" => MarketType::LinearSwap,
+        "inverse_swap" => MarketType::InverseSwap,
+        "european_option" => MarketType::EuropeanOption,
+        "quanto_future" => MarketType::QuantoFuture,
+        "quanto_swap" => MarketType::QuantoSwap,
+        "american_option" => MarketType::AmericanOption,
+        "move" => MarketType::Move,
+        "bvol" => MarketType::BVOL,
+    }
+}
+
+py_enum! {
+    /// The message type, such as `bbo`.
+    PyMessageType(MessageType) as "MessageType" {
+        "other" => MessageType::Other,
+        "trade" => MessageType::Trade,
+        "bbo" => MessageType::BBO,
+        "l2_topk" => MessageType::L2TopK,
+        "l2_snapshot" => MessageType::L2Snapshot,
+        "l2_event" => MessageType::L2Event,
+        "l3_snapshot" => MessageType::L3Snapshot,
+        "l3_event" => MessageType::L3Event,
+        "ticker" => MessageType::Ticker,
+        "candlestick" => MessageType::Candlestick,
+        "open_interest" => MessageType::OpenInterest,
+        "funding_rate" => MessageType::FundingRate,
+        "long_short_ratio" => MessageType::LongShortRatio,
+        "taker_volume" => MessageType::TakerVolume,
+    }
+}
@@ -0,0 +1,33 @@
+//! The Python bindings of `libstock`.
+//!
+//! It exposes the BBO, orderbook and kline structures, and the enums
+//! they use, as the Python module `libstock`. See `README.md` for the
+//! build instructions.
+
+#![cfg(feature = "python-bindings")]
+
+mod enums;
+mod structures;
+
+pub use enums::{PyExchange, PyMarketType, PyMessageType};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+pub use structures::{PyBboStructure, PyKlineStructure, PyOrderbookStructure};
+
+/// Convert an error of `libstock` to a Python `ValueError`.
+pub(crate) fn to_py_err(e: impl std::fmt::Display) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// The Python module `libstock`.
+#[pymodule]
+fn libstock(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyExchange>()?;
+    m.add_class::<PyMarketType>()?;
+    m.add_class::<PyMessageType>()?;
+    m.add_class::<PyBboStructure>()?;
+    m.add_class::<PyOrderbookStructure>()?;
+    m.add_class::<PyKlineStructure>()?;
+
+    Ok(())
+}
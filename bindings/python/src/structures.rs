@@ -0,0 +1,363 @@
+//! The BBO, orderbook and kline structures.
+
+use pyo3::basic::CompareOp;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use wmjtyd_libstock::data::bbo::BboStructure;
+use wmjtyd_libstock::data::fields::info_type::InfoType;
+use wmjtyd_libstock::data::fields::message_type::MessageType;
+use wmjtyd_libstock::data::fields::price_data::Order;
+use wmjtyd_libstock::data::fields::{
+    DecimalField,
+    ExchangeTypeField,
+    KlineIndicatorsField,
+    MessageTypeField,
+    PriceDataField,
+    SnapshotField,
+    SymbolPairField,
+    TimestampField,
+};
+use wmjtyd_libstock::data::kline::KlineStructure;
+use wmjtyd_libstock::data::num::{decimal_from_f64, Decimal};
+use wmjtyd_libstock::data::orderbook::{OrderPriceData, OrderbookStructure, Orders, OrdersBox};
+use wmjtyd_libstock::data::serializer::{StructDeserializer, StructSerializer};
+
+use crate::enums::{PyExchange, PyMarketType, PyMessageType};
+use crate::to_py_err;
+
+/// Convert a Python float to a decimal.
+fn to_decimal(value: f64) -> PyResult<Decimal> {
+    decimal_from_f64(value).map_err(to_py_err)
+}
+
+/// Convert a decimal to a Python float.
+fn to_float(value: Decimal) -> PyResult<f64> {
+    f64::try_from(DecimalField::<10>(value)).map_err(to_py_err)
+}
+
+/// Convert a Unix timestamp in milliseconds to a [`TimestampField`].
+fn to_timestamp(ms: i64) -> PyResult<TimestampField> {
+    TimestampField::try_from_i64(ms).map_err(to_py_err)
+}
+
+/// Implement the methods shared by all the structures.
+macro_rules! impl_structure {
+    ($py_type:ident) => {
+        #[pymethods]
+        impl $py_type {
+            /// Serialize the structure to bytes.
+            fn encode_to_bytes<'py>(&self, py: Python<'py>) -> PyResult<&'py PyBytes> {
+                let encoded = self.0.serialize_to_vec().map_err(to_py_err)?;
+
+                Ok(PyBytes::new(py, &encoded))
+            }
+
+            /// Deserialize a structure from `bytes`.
+            #[staticmethod]
+            fn decode_from_bytes(bytes: &PyBytes) -> PyResult<Self> {
+                StructDeserializer::deserialize_from_bytes(bytes.as_bytes())
+                    .map(Self)
+                    .map_err(to_py_err)
+            }
+
+            /// The exchange.
+            #[getter]
+            fn exchange(&self) -> PyExchange {
+                self.0.exchange_type.0.into()
+            }
+
+            /// The market type.
+            #[getter]
+            fn market_type(&self) -> PyMarketType {
+                self.0.market_type.0.into()
+            }
+
+            /// The message type.
+            #[getter]
+            fn message_type(&self) -> PyMessageType {
+                self.0.message_type.0.into()
+            }
+
+            /// The pair, such as `BTC/USDT`.
+            #[getter]
+            fn pair(&self) -> String {
+                self.0.symbol.pair.clone()
+            }
+
+            /// The symbol ID.
+            #[getter]
+            fn symbol(&self) -> u16 {
+                self.0.symbol.symbol
+            }
+
+            /// The exchange timestamp in milliseconds.
+            #[getter]
+            fn exchange_timestamp(&self) -> u64 {
+                self.0.exchange_timestamp.0
+            }
+
+            /// The received timestamp in milliseconds.
+            #[getter]
+            fn received_timestamp(&self) -> u64 {
+                self.0.received_timestamp.0
+            }
+
+            fn __str__(&self) -> String {
+                self.0.to_string()
+            }
+
+            fn __repr__(&self) -> String {
+                format!("<{}>", self.0)
+            }
+
+            fn __richcmp__(
+                &self,
+                other: PyRef<'_, Self>,
+                op: CompareOp,
+                py: Python<'_>,
+            ) -> PyObject {
+                match op {
+                    CompareOp::Eq => (self.0 == other.0).into_py(py),
+                    CompareOp::Ne => (self.0 != other.0).into_py(py),
+                    _ => py.NotImplemented(),
+                }
+            }
+        }
+    };
+}
+
+/// The BBO structure.
+#[pyclass(name = "BboStructure")]
+#[derive(Clone, Debug)]
+pub struct PyBboStructure(pub BboStructure);
+
+impl_structure!(PyBboStructure);
+
+#[pymethods]
+impl PyBboStructure {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        exchange: PyExchange,
+        market_type: PyMarketType,
+        pair: &str,
+        exchange_timestamp: i64,
+        ask_price: f64,
+        ask_quantity: f64,
+        bid_price: f64,
+        bid_quantity: f64,
+    ) -> PyResult<Self> {
+        let price_data = |price, quantity| -> PyResult<PriceDataField> {
+            Ok(PriceDataField::builder()
+                .price(to_decimal(price)?)
+                .quantity_base(to_decimal(quantity)?)
+                .build())
+        };
+
+        Ok(Self(
+            BboStructure::builder()
+                .exchange_timestamp(to_timestamp(exchange_timestamp)?)
+                .exchange_type(ExchangeTypeField(exchange.0))
+                .market_type(market_type.0)
+                .message_type(MessageTypeField(MessageType::BBO))
+                .symbol(SymbolPairField::from_pair(pair))
+                .asks(price_data(ask_price, ask_quantity)?)
+                .bids(price_data(bid_price, bid_quantity)?)
+                .build(),
+        ))
+    }
+
+    /// The best ask price.
+    #[getter]
+    fn ask_price(&self) -> PyResult<f64> {
+        to_float(*self.0.asks.price)
+    }
+
+    /// The quantity of the best ask.
+    #[getter]
+    fn ask_quantity(&self) -> PyResult<f64> {
+        to_float(*self.0.asks.quantity_base)
+    }
+
+    /// The best bid price.
+    #[getter]
+    fn bid_price(&self) -> PyResult<f64> {
+        to_float(*self.0.bids.price)
+    }
+
+    /// The quantity of the best bid.
+    #[getter]
+    fn bid_quantity(&self) -> PyResult<f64> {
+        to_float(*self.0.bids.quantity_base)
+    }
+}
+
+/// The orderbook structure.
+#[pyclass(name = "OrderbookStructure")]
+#[derive(Clone, Debug)]
+pub struct PyOrderbookStructure(pub OrderbookStructure);
+
+impl_structure!(PyOrderbookStructure);
+
+/// Build the [`OrdersBox`] of `direction` from `(price, quantity)` pairs.
+fn to_orders_box(direction: InfoType, orders: Vec<(f64, f64)>) -> PyResult<OrdersBox> {
+    let orders = orders
+        .into_iter()
+        .map(|(price, quantity_base)| {
+            let order = Order {
+                price,
+                quantity_base,
+                quantity_quote: price * quantity_base,
+                quantity_contract: None,
+            };
+
+            OrderPriceData::try_from(&order).map_err(to_py_err)
+        })
+        .collect::<PyResult<Orders>>()?;
+
+    Ok(OrdersBox::builder()
+        .direction(direction)
+        .orders(orders)
+        .build())
+}
+
+/// Get the `(price, quantity)` pairs of an [`OrdersBox`].
+fn from_orders_box(orders: &OrdersBox) -> PyResult<Vec<(f64, f64)>> {
+    orders
+        .prices()
+        .zip(orders.quantities())
+        .map(|(price, quantity)| Ok((to_float(price)?, to_float(quantity)?)))
+        .collect()
+}
+
+#[pymethods]
+impl PyOrderbookStructure {
+    #[new]
+    fn new(
+        exchange: PyExchange,
+        market_type: PyMarketType,
+        pair: &str,
+        exchange_timestamp: i64,
+        asks: Vec<(f64, f64)>,
+        bids: Vec<(f64, f64)>,
+        snapshot: bool,
+    ) -> PyResult<Self> {
+        Ok(Self(
+            OrderbookStructure::builder()
+                .exchange_timestamp(to_timestamp(exchange_timestamp)?)
+                .exchange_type(ExchangeTypeField(exchange.0))
+                .market_type(market_type.0)
+                .message_type(MessageTypeField(MessageType::L2Event))
+                .symbol(SymbolPairField::from_pair(pair))
+                .snapshot_flag(SnapshotField(snapshot))
+                .asks(to_orders_box(InfoType::Asks, asks)?)
+                .bids(to_orders_box(InfoType::Bids, bids)?)
+                .build(),
+        ))
+    }
+
+    /// The `(price, quantity)` pairs of the asks.
+    #[getter]
+    fn asks(&self) -> PyResult<Vec<(f64, f64)>> {
+        from_orders_box(&self.0.asks)
+    }
+
+    /// The `(price, quantity)` pairs of the bids.
+    #[getter]
+    fn bids(&self) -> PyResult<Vec<(f64, f64)>> {
+        from_orders_box(&self.0.bids)
+    }
+
+    /// Whether it is a snapshot instead of a delta.
+    #[getter]
+    fn snapshot(&self) -> bool {
+        self.0.snapshot_flag.0
+    }
+
+    /// The spread, or `None` if either side is empty.
+    #[getter]
+    fn spread(&self) -> PyResult<Option<f64>> {
+        self.0.spread().map(to_float).transpose()
+    }
+}
+
+/// The kline structure.
+#[pyclass(name = "KlineStructure")]
+#[derive(Clone, Debug)]
+pub struct PyKlineStructure(pub KlineStructure);
+
+impl_structure!(PyKlineStructure);
+
+#[pymethods]
+impl PyKlineStructure {
+    #[new]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        exchange: PyExchange,
+        market_type: PyMarketType,
+        pair: &str,
+        exchange_timestamp: i64,
+        period: &str,
+        open: f64,
+        high: f64,
+        low: f64,
+        close: f64,
+        volume: f64,
+    ) -> PyResult<Self> {
+        Ok(Self(
+            KlineStructure::builder()
+                .exchange_timestamp(to_timestamp(exchange_timestamp)?)
+                .exchange_type(ExchangeTypeField(exchange.0))
+                .market_type(market_type.0)
+                .message_type(MessageTypeField(MessageType::Candlestick))
+                .symbol(SymbolPairField::from_pair(pair))
+                .period(period)
+                .indicator(
+                    KlineIndicatorsField::builder()
+                        .open(to_decimal(open)?)
+                        .high(to_decimal(high)?)
+                        .low(to_decimal(low)?)
+                        .close(to_decimal(close)?)
+                        .volume(to_decimal(volume)?)
+                        .build(),
+                )
+                .build(),
+        ))
+    }
+
+    /// The period, such as `1m`.
+    #[getter]
+    fn period(&self) -> String {
+        self.0.period.0.clone()
+    }
+
+    /// The open price.
+    #[getter]
+    fn open(&self) -> PyResult<f64> {
+        to_float(*self.0.indicator.open)
+    }
+
+    /// The highest price.
+    #[getter]
+    fn high(&self) -> PyResult<f64> {
+        to_float(*self.0.indicator.high)
+    }
+
+    /// The lowest price.
+    #[getter]
+    fn low(&self) -> PyResult<f64> {
+        to_float(*self.0.indicator.low)
+    }
+
+    /// The close price.
+    #[getter]
+    fn close(&self) -> PyResult<f64> {
+        to_float(*self.0.indicator.close)
+    }
+
+    /// The volume.
+    #[getter]
+    fn volume(&self) -> PyResult<f64> {
+        to_float(*self.0.indicator.volume)
+    }
+}
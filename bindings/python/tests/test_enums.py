@@ -0,0 +1,33 @@
+import pytest
+
+from libstock import Exchange, MarketType, MessageType
+
+
+@pytest.mark.parametrize("cls", [Exchange, MarketType, MessageType])
+def test_names_roundtrip(cls):
+    for value in cls.all():
+        assert cls.from_name(value.name) == value
+        assert str(value) == value.name
+
+
+def test_exchange():
+    binance = Exchange.from_name("binance")
+
+    assert binance.name == "binance"
+    assert binance != Exchange.from_name("okx")
+    assert repr(binance) == "Exchange.from_name('binance')"
+    assert hash(binance) == hash(Exchange.from_name("binance"))
+
+
+def test_market_type():
+    assert MarketType.from_name("linear_swap").name == "linear_swap"
+
+
+def test_message_type():
+    assert MessageType.from_name("l2_event").name == "l2_event"
+
+
+@pytest.mark.parametrize("cls", [Exchange, MarketType, MessageType])
+def test_unknown_name(cls):
+    with pytest.raises(ValueError):
+        cls.from_name("nonexistent")
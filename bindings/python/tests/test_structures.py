@@ -0,0 +1,101 @@
+import pytest
+
+from libstock import (
+    BboStructure,
+    Exchange,
+    KlineStructure,
+    MarketType,
+    MessageType,
+    OrderbookStructure,
+)
+
+BINANCE = Exchange.from_name("binance")
+SPOT = MarketType.from_name("spot")
+TIMESTAMP = 1672531200000
+
+
+def make_bbo():
+    return BboStructure(BINANCE, SPOT, "BTC/USDT", TIMESTAMP, 12345.0, 1.5, 12344.9, 2.0)
+
+
+def make_orderbook():
+    return OrderbookStructure(
+        BINANCE,
+        SPOT,
+        "BTC/USDT",
+        TIMESTAMP,
+        [(12345.0, 1.5), (12346.0, 0.5)],
+        [(12344.5, 2.0)],
+        True,
+    )
+
+
+def make_kline():
+    return KlineStructure(
+        BINANCE, SPOT, "BTC/USDT", TIMESTAMP, "1m", 12340.0, 12350.0, 12330.0, 12345.0, 42.5
+    )
+
+
+def test_bbo():
+    bbo = make_bbo()
+
+    assert bbo.exchange == BINANCE
+    assert bbo.market_type == SPOT
+    assert bbo.message_type == MessageType.from_name("bbo")
+    assert bbo.pair == "BTC/USDT"
+    assert bbo.exchange_timestamp == TIMESTAMP
+    assert bbo.ask_price == 12345.0
+    assert bbo.ask_quantity == 1.5
+    assert bbo.bid_price == 12344.9
+    assert bbo.bid_quantity == 2.0
+
+
+def test_orderbook():
+    orderbook = make_orderbook()
+
+    assert orderbook.message_type == MessageType.from_name("l2_event")
+    assert orderbook.asks == [(12345.0, 1.5), (12346.0, 0.5)]
+    assert orderbook.bids == [(12344.5, 2.0)]
+    assert orderbook.snapshot
+    assert orderbook.spread == 0.5
+
+
+def test_kline():
+    kline = make_kline()
+
+    assert kline.message_type == MessageType.from_name("candlestick")
+    assert kline.period == "1m"
+    assert (kline.open, kline.high, kline.low, kline.close) == (
+        12340.0,
+        12350.0,
+        12330.0,
+        12345.0,
+    )
+    assert kline.volume == 42.5
+
+
+@pytest.mark.parametrize("factory", [make_bbo, make_orderbook, make_kline])
+def test_roundtrip(factory):
+    structure = factory()
+    encoded = structure.encode_to_bytes()
+
+    assert isinstance(encoded, bytes)
+    assert type(structure).decode_from_bytes(encoded) == structure
+    assert str(type(structure).decode_from_bytes(encoded)) == str(structure)
+
+
+@pytest.mark.parametrize("cls", [BboStructure, OrderbookStructure, KlineStructure])
+def test_decode_invalid(cls):
+    with pytest.raises(ValueError):
+        cls.decode_from_bytes(b"\x00\x01")
+
+
+def test_not_equal():
+    other = BboStructure(BINANCE, SPOT, "ETH/USDT", TIMESTAMP, 1.0, 1.0, 1.0, 1.0)
+
+    assert make_bbo() != other
+
+
+def test_negative_timestamp():
+    with pytest.raises(ValueError):
+        BboStructure(BINANCE, SPOT, "BTC/USDT", -1, 1.0, 1.0, 1.0, 1.0)
@@ -0,0 +1,58 @@
+//! The WebAssembly bindings of `libstock`.
+//!
+//! It exposes the encoders and decoders of the BBO, kline and trade
+//! structures with `wasm-bindgen`. The structures are passed in as JSON
+//! strings, and returned as plain JavaScript objects. See `README.md`
+//! for the build instructions.
+
+#![cfg(feature = "wasm")]
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use wmjtyd_libstock::data::bbo::BboStructure;
+use wmjtyd_libstock::data::kline::KlineStructure;
+use wmjtyd_libstock::data::serializer::{StructDeserializer, StructSerializer};
+use wmjtyd_libstock::data::trade::TradeStructure;
+
+/// Convert an error of `libstock` to a JavaScript `Error`.
+fn to_js_err(e: impl std::fmt::Display) -> JsValue {
+    js_sys::Error::new(&e.to_string()).into()
+}
+
+/// Parse `json_str` as `T`.
+fn from_json<T: DeserializeOwned>(json_str: &str) -> Result<T, JsValue> {
+    let value = js_sys::JSON::parse(json_str)?;
+
+    Ok(serde_wasm_bindgen::from_value(value)?)
+}
+
+/// Convert `value` to a plain JavaScript object.
+fn to_object<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    Ok(serde_wasm_bindgen::to_value(value)?)
+}
+
+/// Define the encoder and the decoder of a structure.
+macro_rules! bind_structure {
+    ($structure:ty, encode = $encode:ident, decode = $decode:ident) => {
+        #[doc = concat!("Encode the JSON of [`", stringify!($structure), "`] to bytes.")]
+        #[wasm_bindgen]
+        pub fn $encode(json_str: &str) -> Result<Vec<u8>, JsValue> {
+            let structure: $structure = from_json(json_str)?;
+
+            structure.serialize_to_vec().map_err(to_js_err)
+        }
+
+        #[doc = concat!("Decode the bytes to [`", stringify!($structure), "`] as an object.")]
+        #[wasm_bindgen]
+        pub fn $decode(bytes: &[u8]) -> Result<JsValue, JsValue> {
+            let structure = <$structure>::deserialize_from_bytes(bytes).map_err(to_js_err)?;
+
+            to_object(&structure)
+        }
+    };
+}
+
+bind_structure!(BboStructure, encode = encode_bbo, decode = decode_bbo);
+bind_structure!(KlineStructure, encode = encode_kline, decode = decode_kline);
+bind_structure!(TradeStructure, encode = encode_trade, decode = decode_trade);
@@ -0,0 +1,143 @@
+//! Run with `wasm-pack test --node -- --features wasm`.
+
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+use wmjtyd_libstock::data::bbo::BboStructure;
+use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+use wmjtyd_libstock::data::fields::market_type::MarketType;
+use wmjtyd_libstock::data::fields::message_type::MessageType;
+use wmjtyd_libstock::data::fields::trade_side::TradeSide;
+use wmjtyd_libstock::data::fields::{
+    ExchangeTypeField,
+    KlineIndicatorsField,
+    MessageTypeField,
+    PriceDataField,
+    SymbolPairField,
+    TimestampField,
+};
+use wmjtyd_libstock::data::kline::KlineStructure;
+use wmjtyd_libstock::data::num::Decimal;
+use wmjtyd_libstock::data::serializer::StructSerializer;
+use wmjtyd_libstock::data::trade::TradeStructure;
+use wmjtyd_libstock_wasm::{
+    decode_bbo,
+    decode_kline,
+    decode_trade,
+    encode_bbo,
+    encode_kline,
+    encode_trade,
+};
+
+const TIMESTAMP: u64 = 1672531200000;
+
+fn price_data(price: i64, quantity: i64) -> PriceDataField {
+    PriceDataField::builder()
+        .price(Decimal::new(price, 1))
+        .quantity_base(Decimal::new(quantity, 1))
+        .build()
+}
+
+fn construct_bbo() -> BboStructure {
+    BboStructure::builder()
+        .exchange_timestamp(TimestampField(TIMESTAMP))
+        .exchange_type(ExchangeTypeField(Exchange::Binance))
+        .market_type(MarketType::Spot)
+        .message_type(MessageTypeField(MessageType::BBO))
+        .symbol(SymbolPairField::from_pair("BTC/USDT"))
+        .asks(price_data(123456, 15))
+        .bids(price_data(123449, 20))
+        .build()
+}
+
+fn construct_kline() -> KlineStructure {
+    KlineStructure::builder()
+        .exchange_timestamp(TimestampField(TIMESTAMP))
+        .exchange_type(ExchangeTypeField(Exchange::Binance))
+        .market_type(MarketType::Spot)
+        .message_type(MessageTypeField(MessageType::Candlestick))
+        .symbol(SymbolPairField::from_pair("BTC/USDT"))
+        .period("1m")
+        .indicator(
+            KlineIndicatorsField::builder()
+                .open(Decimal::new(123400, 1))
+                .high(Decimal::new(123500, 1))
+                .low(Decimal::new(123300, 1))
+                .close(Decimal::new(123450, 1))
+                .volume(Decimal::new(425, 1))
+                .build(),
+        )
+        .build()
+}
+
+fn construct_trade() -> TradeStructure {
+    TradeStructure::builder()
+        .exchange_timestamp(TimestampField(TIMESTAMP))
+        .exchange_type(ExchangeTypeField(Exchange::Binance))
+        .market_type(MarketType::Spot)
+        .message_type(MessageTypeField(MessageType::Trade))
+        .symbol(SymbolPairField::from_pair("BTC/USDT"))
+        .trade_side(TradeSide::Buy)
+        .trade_price(price_data(123456, 15))
+        .quantity_quote(Decimal::new(18518400, 3))
+        .trade_id("42")
+        .build()
+}
+
+/// Convert `structure` to the JSON that JavaScript would pass in.
+fn to_json<T: Serialize>(structure: &T) -> String {
+    let value = serde_wasm_bindgen::to_value(structure).unwrap();
+
+    js_sys::JSON::stringify(&value).unwrap().into()
+}
+
+/// Convert a decoded object back to the structure.
+fn from_object<T: serde::de::DeserializeOwned>(value: JsValue) -> T {
+    serde_wasm_bindgen::from_value(value).unwrap()
+}
+
+#[wasm_bindgen_test]
+fn test_bbo() {
+    let bbo = construct_bbo();
+
+    let encoded = encode_bbo(&to_json(&bbo)).unwrap();
+    assert_eq!(encoded, bbo.serialize_to_vec().unwrap());
+
+    let decoded = decode_bbo(&encoded).unwrap();
+    assert!(decoded.is_object());
+    assert_eq!(from_object::<BboStructure>(decoded), bbo);
+}
+
+#[wasm_bindgen_test]
+fn test_kline() {
+    let kline = construct_kline();
+
+    let encoded = encode_kline(&to_json(&kline)).unwrap();
+    assert_eq!(encoded, kline.serialize_to_vec().unwrap());
+    assert_eq!(
+        from_object::<KlineStructure>(decode_kline(&encoded).unwrap()),
+        kline
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_trade() {
+    let trade = construct_trade();
+
+    let encoded = encode_trade(&to_json(&trade)).unwrap();
+    assert_eq!(encoded, trade.serialize_to_vec().unwrap());
+    assert_eq!(
+        from_object::<TradeStructure>(decode_trade(&encoded).unwrap()),
+        trade
+    );
+}
+
+#[wasm_bindgen_test]
+fn test_invalid_input() {
+    assert!(encode_bbo("not json").is_err());
+    assert!(encode_bbo("{}").is_err());
+    assert!(decode_bbo(&[0, 1]).is_err());
+    assert!(decode_kline(&[]).is_err());
+}
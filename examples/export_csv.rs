@@ -0,0 +1,81 @@
+//! Export the BBO frames of a record file to CSV.
+//!
+//! Run `cargo run --example export_csv [filename]`. Without `filename`,
+//! it writes a few BBO frames to `export_csv_example` of today with
+//! [`DataWriter`], and exports them to the standard output.
+
+use crypto_market_type::MarketType;
+use crypto_msg_type::MessageType;
+use rust_decimal::Decimal;
+use wmjtyd_libstock::data::bbo::BboStructure;
+use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+use wmjtyd_libstock::data::fields::{PriceDataField, SymbolPairField};
+use wmjtyd_libstock::data::serializer::StructSerializer;
+use wmjtyd_libstock::file::export::export_csv;
+use wmjtyd_libstock::file::reader::FileReader;
+use wmjtyd_libstock::file::writer::{DataEntry, DataWriter};
+
+const EXAMPLE_FILENAME: &str = "export_csv_example";
+
+fn construct_bbo(timestamp: u64, ask: &str, bid: &str) -> BboStructure {
+    let price_data = |price| {
+        PriceDataField::builder()
+            .price(Decimal::from_str_exact(price).unwrap())
+            .quantity_base(Decimal::from_str_exact("1.5").unwrap())
+            .build()
+    };
+
+    BboStructure::builder()
+        .exchange_timestamp(timestamp)
+        .exchange_type(Exchange::Binance)
+        .market_type(MarketType::Spot)
+        .symbol(SymbolPairField::from_pair("BTC/USDT"))
+        .message_type(MessageType::BBO)
+        .asks(price_data(ask))
+        .bids(price_data(bid))
+        .build()
+}
+
+/// Write the example BBO frames to [`EXAMPLE_FILENAME`].
+async fn write_example() {
+    let mut writer = DataWriter::new();
+    let work = writer.start().await.expect("failed to start writer");
+
+    let frames = [
+        construct_bbo(1672531200000, "16547.12", "16547.01"),
+        construct_bbo(1672531200100, "16547.5", "16547.13"),
+        construct_bbo(1672531200200, "16548", "16547.99"),
+    ];
+
+    for frame in frames {
+        writer
+            .add(DataEntry {
+                filename: EXAMPLE_FILENAME.to_string(),
+                data: frame.serialize_to_vec().expect("failed to serialize"),
+            })
+            .expect("failed to add the frame");
+    }
+
+    writer.stop().expect("failed to send stop request");
+    work.await.expect("failed to stop writer");
+}
+
+#[tokio::main]
+async fn main() {
+    let filename = match std::env::args().nth(1) {
+        Some(filename) => filename,
+        None => {
+            write_example().await;
+            EXAMPLE_FILENAME.to_string()
+        }
+    };
+
+    let reader = FileReader::new(filename, 0).expect("failed to open the file");
+    let summary = export_csv::<BboStructure>(reader, std::io::stdout().lock())
+        .expect("failed to export");
+
+    eprintln!(
+        "{} rows exported, {} frames skipped.",
+        summary.rows, summary.skipped
+    );
+}
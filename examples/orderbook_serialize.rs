@@ -35,7 +35,7 @@ fn main() {
         )
         .bids(
             OrdersBox::builder()
-                .direction(InfoType::Asks)
+                .direction(InfoType::Bids)
                 .orders(
                     iter::repeat(
                         PriceDataField::builder()
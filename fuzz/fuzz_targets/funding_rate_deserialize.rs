@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wmjtyd_libstock::data::funding_rate::FundingRateStructure;
+use wmjtyd_libstock::data::serializer::StructDeserializer;
+
+fuzz_target!(|data: &[u8]| {
+    // It should either return a structure or an error, but never panic.
+    let _ = FundingRateStructure::deserialize_from_bytes(data);
+});
@@ -0,0 +1,72 @@
+#ifndef LIBSTOCK_H
+#define LIBSTOCK_H
+
+/* Generated with cbindgen. Do not edit this file manually. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// The operation succeeded.
+#define LIBSTOCK_OK 0
+
+// A required pointer is null.
+#define LIBSTOCK_ERR_NULL_POINTER -1
+
+// The input string is not valid UTF-8.
+#define LIBSTOCK_ERR_INVALID_UTF8 -2
+
+// The input JSON is not a valid structure.
+#define LIBSTOCK_ERR_INVALID_JSON -3
+
+// The output buffer is too small. The required length has been set.
+#define LIBSTOCK_ERR_BUFFER_TOO_SMALL -4
+
+// The library panicked.
+#define LIBSTOCK_ERR_PANIC -5
+
+// Encode the JSON of a BBO structure.
+//
+// `json_ptr` is a NUL-terminated string. The encoded bytes are written
+// to `out_buf`, and `*out_len` is its capacity on input and the length
+// of the encoded bytes on output.
+//
+// # Safety
+//
+// `json_ptr` must be a valid NUL-terminated string, `out_len` must be
+// valid, and `out_buf` must be valid for `*out_len` bytes.
+int32_t libstock_encode_bbo(const char *json_ptr, uint8_t *out_buf, size_t *out_len);
+
+// Decode a BBO structure to JSON.
+//
+// The JSON is written to `out_json` with a terminating NUL, and
+// `*out_json_len` is its capacity on input and the length of the JSON,
+// including the terminating NUL, on output.
+//
+// # Safety
+//
+// `buf` must be valid for `len` bytes, `out_json_len` must be valid,
+// and `out_json` must be valid for `*out_json_len` bytes.
+int32_t libstock_decode_bbo(const uint8_t *buf, size_t len, char *out_json, size_t *out_json_len);
+
+// Encode the JSON of an orderbook structure.
+//
+// See [`libstock_encode_bbo`] for the parameters.
+//
+// # Safety
+//
+// The same as [`libstock_encode_bbo`].
+int32_t libstock_encode_orderbook(const char *json_ptr, uint8_t *out_buf, size_t *out_len);
+
+// Decode an orderbook structure to JSON.
+//
+// See [`libstock_decode_bbo`] for the parameters.
+//
+// # Safety
+//
+// The same as [`libstock_decode_bbo`].
+int32_t libstock_decode_orderbook(const uint8_t *buf,
+                                  size_t len,
+                                  char *out_json,
+                                  size_t *out_json_len);
+
+#endif /* LIBSTOCK_H */
@@ -4,6 +4,7 @@ macro_rules! compat_enc {
     (
         enc = $enc_method:ident,
         dec = $dec_method:ident,
+        dec_partial = $dec_partial_method:ident,
         crawl = $crawler_type:ty,
         result = $result_type:tt,
         structure = $structure_type:ty
@@ -22,10 +23,31 @@ macro_rules! compat_enc {
         #[cfg(feature = "compat-v0_3")]
         #[deprecated = "Better using `BboStructure` directly for better performance. See CHANGELOG for details."]
         #[doc = concat!("Decode the specified bytes to a [`", stringify!($crawler_type), "`].")]
-        pub fn $dec_method(mut payload: &[u8]) -> $result_type<$crawler_type> {
-            let structure: $structure_type = $crate::data::serializer::StructDeserializer::deserialize(&mut payload)?;
+        ///
+        /// It returns [`FieldError::TrailingBytes`](crate::data::fields::FieldError::TrailingBytes)
+        /// if there are bytes after the end of the data.
+        pub fn $dec_method(payload: &[u8]) -> $result_type<$crawler_type> {
+            #[allow(deprecated)]
+            let (message, consumed) = $dec_partial_method(payload)?;
 
-            structure.try_into()
+            match payload.len() - consumed {
+                0 => Ok(message),
+                remaining => Err($crate::data::fields::FieldError::TrailingBytes { remaining }.into()),
+            }
+        }
+
+        #[cfg(feature = "compat-v0_3")]
+        #[deprecated = "Better using `BboStructure` directly for better performance. See CHANGELOG for details."]
+        #[doc = concat!("Decode a [`", stringify!($crawler_type), "`] from the beginning of the specified bytes.")]
+        ///
+        /// It returns the decoded message and the number of the bytes consumed,
+        /// so the concatenated messages can be decoded one by one.
+        pub fn $dec_partial_method(payload: &[u8]) -> $result_type<($crawler_type, usize)> {
+            let mut remaining = payload;
+            let structure: $structure_type = $crate::data::serializer::StructDeserializer::deserialize(&mut remaining)?;
+            let consumed = payload.len() - remaining.len();
+
+            Ok((structure.try_into()?, consumed))
         }
     }
 }
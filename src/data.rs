@@ -27,6 +27,21 @@ pub mod kline;
 #[cfg(feature = "crypto")]
 pub mod funding_rate;
 
+#[cfg(feature = "crypto")]
+pub mod taker_volume;
+
+#[cfg(feature = "crypto")]
+pub mod long_short_ratio;
+
+#[cfg(feature = "crypto")]
+pub mod envelope;
+
+#[cfg(feature = "msgpack")]
+pub mod msgpack;
+
+#[cfg(feature = "crypto")]
+pub use envelope::DataEnvelope;
+
 #[cfg(feature = "compat-v0_3")]
 /// The 0.3-compatible `hex` module.
 ///
@@ -74,7 +89,7 @@ pub mod hex {
 
         /// Decode the specified [`u8`] bytes to a [`Decimal`].
         #[deprecated = "Replace this to new Encoder and Decoder trait. See CHANGELOG."]
-        fn decode_bytes(value: &[u8; LEN]) -> Decimal;
+        fn decode_bytes(value: &[u8; LEN]) -> HexDataResult<Decimal>;
 
         /// Encode a number string to [`i8`] bytes safely.
         #[deprecated = "Replace this to new Encoder and Decoder trait. See CHANGELOG."]
@@ -86,7 +101,7 @@ pub mod hex {
 
         /// Decode a number string to [`i8`] bytes safely.
         #[deprecated = "Replace this to new Encoder and Decoder trait. See CHANGELOG."]
-        fn decode_i8_bytes(value: &[i8; LEN]) -> Decimal {
+        fn decode_i8_bytes(value: &[i8; LEN]) -> HexDataResult<Decimal> {
             let encoded_u8 = value.map(|v| v as u8);
 
             Self::decode_bytes(&encoded_u8)
@@ -99,8 +114,8 @@ pub mod hex {
             Ok(d.encode()?)
         }
 
-        fn decode_bytes(value: &[u8; 5]) -> Decimal {
-            Decoder::decode(value).expect("can't decode the bytes")
+        fn decode_bytes(value: &[u8; 5]) -> HexDataResult<Decimal> {
+            Ok(Decoder::decode(value)?)
         }
     }
 
@@ -110,8 +125,8 @@ pub mod hex {
             Ok(d.encode()?)
         }
 
-        fn decode_bytes(value: &[u8; 10]) -> Decimal {
-            Decoder::decode(value).expect("can't decode the bytes")
+        fn decode_bytes(value: &[u8; 10]) -> HexDataResult<Decimal> {
+            Ok(Decoder::decode(value)?)
         }
     }
 
@@ -120,7 +135,7 @@ pub mod hex {
             u32::encode_bytes(value)
         }
 
-        fn decode_bytes(value: &[u8; 5]) -> Decimal {
+        fn decode_bytes(value: &[u8; 5]) -> HexDataResult<Decimal> {
             u32::decode_bytes(value)
         }
     }
@@ -130,7 +145,7 @@ pub mod hex {
             u64::encode_bytes(value)
         }
 
-        fn decode_bytes(value: &[u8; 10]) -> Decimal {
+        fn decode_bytes(value: &[u8; 10]) -> HexDataResult<Decimal> {
             u64::decode_bytes(value)
         }
     }
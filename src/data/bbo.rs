@@ -8,21 +8,27 @@
 //! between your structure and [`BboStructure`].
 
 pub use crypto_message::BboMsg;
+use rust_decimal::Decimal;
 use typed_builder::TypedBuilder;
 
 use super::fields::{
+    DecimalField,
     EndOfDataFlag,
     ExchangeTypeField,
     FieldError,
     MarketTypeField,
     MessageTypeField,
+    OptionalU64Field,
     PriceDataField,
+    SchemaVersionField,
     SymbolPairField,
     TimestampField,
 };
 use super::serializer::{
     deserialize_block_builder,
     serialize_block_builder,
+    DataStructure,
+    FieldDeserializer,
     StructDeserializer,
     StructSerializer,
 };
@@ -31,14 +37,15 @@ use super::serializer::{
 ///
 /// You can take advantage of `builder()`
 /// to construct some fields automatically.
-#[derive(Clone, Debug, PartialEq, Eq, TypedBuilder)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BboStructure {
     /// 交易所時間戳
     #[builder(setter(into))]
     pub exchange_timestamp: TimestampField,
 
     /// 收到時間戳
-    #[builder(default)]
+    #[builder(default, setter(into))]
     pub received_timestamp: TimestampField,
 
     /// 交易所類型 (EXCHANGE)
@@ -54,24 +61,149 @@ pub struct BboStructure {
     pub message_type: MessageTypeField,
 
     /// Symbol (ID，`1`) 或 Pair（字串，`BTC/USDT`）
+    #[builder(setter(into))]
     pub symbol: SymbolPairField,
 
     /// 最優賣出報價資訊 (asks)
+    #[builder(setter(into))]
     pub asks: PriceDataField,
 
     /// 最優買入報價資訊 (bids)
+    #[builder(setter(into))]
     pub bids: PriceDataField,
 
+    /// 最優賣出報價量 (ask_quantity_quote)
+    #[builder(default, setter(into))]
+    pub asks_quantity_quote: DecimalField<10>,
+
+    /// 最優賣出合約量 (ask_quantity_contract) (1 + 10 bytes)
+    ///
+    /// 沒有合約量時為 `None`。
+    #[builder(default)]
+    pub asks_quantity_contract: Option<DecimalField<10>>,
+
+    /// 最優買入報價量 (bid_quantity_quote)
+    #[builder(default, setter(into))]
+    pub bids_quantity_quote: DecimalField<10>,
+
+    /// 最優買入合約量 (bid_quantity_contract) (1 + 10 bytes)
+    ///
+    /// 沒有合約量時為 `None`。
+    #[builder(default)]
+    pub bids_quantity_contract: Option<DecimalField<10>>,
+
+    /// 訊息 ID (id)
+    #[builder(default, setter(into))]
+    pub id: OptionalU64Field,
+
     /// 資料結尾
     #[builder(default)]
     pub end: EndOfDataFlag,
 }
 
+impl BboStructure {
+    /// The current schema version of the serialized BBO structure.
+    pub const SCHEMA_VERSION: u8 = 3;
+
+    /// Convert a [`BboMsg`] to a BBO structure.
+    ///
+    /// It is the same as [`TryFrom<&BboMsg>`](TryFrom), but easier to find.
+    pub fn from_msg(msg: &BboMsg) -> BboResult<Self> {
+        Self::try_from(msg)
+    }
+
+    /// Convert a [`BboMsg`] to a BBO structure received at `received_ms`.
+    ///
+    /// Unlike [`from_msg`](Self::from_msg), which takes the current time
+    /// as the received timestamp, it keeps the original received timestamp
+    /// when converting the historical messages.
+    pub fn try_from_with_received(msg: &BboMsg, received_ms: u64) -> BboResult<Self> {
+        let mut structure = Self::try_from(msg)?;
+        structure.received_timestamp = TimestampField(received_ms);
+
+        Ok(structure)
+    }
+
+    /// Get the latency in milliseconds, which is the received timestamp
+    /// minus the exchange timestamp.
+    ///
+    /// It is negative if the exchange timestamp is later than the received
+    /// timestamp, for example, because of the clock skew.
+    pub fn latency_ms(&self) -> i64 {
+        i64::from(self.received_timestamp) - i64::from(self.exchange_timestamp)
+    }
+
+    /// Get the spread, which is the ask price minus the bid price.
+    ///
+    /// It returns `None` if either price is zero,
+    /// which usually means that side is missing.
+    pub fn spread(&self) -> Option<Decimal> {
+        let ask_price = *self.asks.price;
+        let bid_price = *self.bids.price;
+
+        if ask_price.is_zero() || bid_price.is_zero() {
+            return None;
+        }
+
+        Some(ask_price - bid_price)
+    }
+
+    /// Is the ask price lower than the bid price?
+    ///
+    /// A crossed BBO usually indicates the data errors.
+    pub fn is_crossed(&self) -> bool {
+        *self.asks.price < *self.bids.price
+    }
+}
+
+/// Format the best prices on one line.
+///
+/// # Example
+///
+/// ```
+/// use crypto_market_type::MarketType;
+/// use crypto_msg_type::MessageType;
+/// use wmjtyd_libstock::data::bbo::BboStructure;
+/// use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+/// use wmjtyd_libstock::data::fields::{PriceDataField, SymbolPairField};
+///
+/// let price = |price: f64| PriceDataField::builder().price(price).quantity_base(1.0).build();
+/// let structure = BboStructure::builder()
+///     .exchange_timestamp(1672531200000u64)
+///     .exchange_type(Exchange::Binance)
+///     .market_type(MarketType::Spot)
+///     .message_type(MessageType::BBO)
+///     .symbol(SymbolPairField::from_pair("BTC/USDT"))
+///     .asks(price(12345.0))
+///     .bids(price(12344.9))
+///     .build();
+///
+/// assert_eq!(
+///     structure.to_string(),
+///     "BBO [binance/spot] BTC/USDT @ 2023-01-01T00:00:00.000Z ask=12345 bid=12344.9"
+/// );
+/// ```
+impl std::fmt::Display for BboStructure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "BBO [{}/{}] {} @ {} ask={} bid={}",
+            self.exchange_type.as_str(),
+            self.market_type.0,
+            self.symbol.pair,
+            self.exchange_timestamp,
+            self.asks.price.0,
+            self.bids.price.0,
+        )
+    }
+}
+
 impl StructSerializer for BboStructure {
     type Err = BboError;
 
     fn serialize(&self, writer: &mut impl std::io::Write) -> Result<(), Self::Err> {
         serialize_block_builder!(
+            SchemaVersionField(Self::SCHEMA_VERSION),
             self.exchange_timestamp,
             self.received_timestamp,
             self.exchange_type,
@@ -80,6 +212,11 @@ impl StructSerializer for BboStructure {
             self.symbol,
             self.asks,
             self.bids,
+            self.asks_quantity_quote,
+            self.asks_quantity_contract,
+            self.bids_quantity_quote,
+            self.bids_quantity_contract,
+            self.id,
             self.end => writer
         );
 
@@ -91,6 +228,8 @@ impl StructDeserializer for BboStructure {
     type Err = BboError;
 
     fn deserialize(reader: &mut impl std::io::Read) -> Result<Self, Self::Err> {
+        SchemaVersionField::deserialize_from_reader(reader)??.check(Self::SCHEMA_VERSION)?;
+
         deserialize_block_builder!(
             reader =>
             exchange_timestamp,
@@ -101,17 +240,24 @@ impl StructDeserializer for BboStructure {
             symbol,
             asks,
             bids,
+            asks_quantity_quote,
+            asks_quantity_contract,
+            bids_quantity_quote,
+            bids_quantity_contract,
+            id,
             end
         )
     }
 }
 
+impl DataStructure for BboStructure {}
+
 impl TryFrom<&BboMsg> for BboStructure {
     type Error = BboError;
 
     fn try_from(value: &BboMsg) -> Result<Self, Self::Error> {
         Ok(Self::builder()
-            .exchange_timestamp(value.timestamp)
+            .exchange_timestamp(TimestampField::try_from_i64(value.timestamp)?)
             .exchange_type(ExchangeTypeField::try_from_str(&value.exchange)?)
             .market_type(value.market_type)
             .message_type(value.msg_type)
@@ -128,6 +274,11 @@ impl TryFrom<&BboMsg> for BboStructure {
                     .quantity_base(value.bid_quantity_base)
                     .build(),
             )
+            .asks_quantity_quote(value.ask_quantity_quote)
+            .asks_quantity_contract(value.ask_quantity_contract.map(Into::into))
+            .bids_quantity_quote(value.bid_quantity_quote)
+            .bids_quantity_contract(value.bid_quantity_contract.map(Into::into))
+            .id(value.id)
             .build())
     }
 }
@@ -147,13 +298,19 @@ impl TryFrom<BboStructure> for BboMsg {
             timestamp: value.exchange_timestamp.into(),
             ask_price: value.asks.price.try_into()?,
             ask_quantity_base: value.asks.quantity_base.try_into()?,
-            ask_quantity_quote: 0.0,
-            ask_quantity_contract: None,
+            ask_quantity_quote: value.asks_quantity_quote.try_into()?,
+            ask_quantity_contract: value
+                .asks_quantity_contract
+                .map(TryInto::try_into)
+                .transpose()?,
             bid_price: value.bids.price.try_into()?,
             bid_quantity_base: value.bids.quantity_base.try_into()?,
-            bid_quantity_quote: 0.0,
-            bid_quantity_contract: None,
-            id: None,
+            bid_quantity_quote: value.bids_quantity_quote.try_into()?,
+            bid_quantity_contract: value
+                .bids_quantity_contract
+                .map(TryInto::try_into)
+                .transpose()?,
+            id: value.id.into(),
             json: String::new(),
         })
     }
@@ -174,15 +331,17 @@ pub type BboResult<T> = Result<T, BboError>;
 mod tests {
     use crypto_market_type::MarketType;
     use crypto_message::BboMsg;
+    use rust_decimal_macros::dec;
 
-    use super::BboStructure;
+    use super::{BboError, BboStructure};
+    use crate::data::fields::{FieldError, SchemaVersionField};
     use crate::data::serializer::{StructDeserializer, StructSerializer};
 
-    fn construct_bbomsg(unknown_market_type: bool) -> BboMsg {
+    fn construct_bbomsg(american_option: bool) -> BboMsg {
         BboMsg {
             exchange: "crypto".into(),
             market_type: {
-                if unknown_market_type {
+                if american_option {
                     crypto_market_type::MarketType::AmericanOption
                 } else {
                     crypto_market_type::MarketType::Spot
@@ -195,11 +354,11 @@ mod tests {
             json: "".into(),
             bid_price: 1.0,
             bid_quantity_base: 2.0,
-            bid_quantity_quote: 0.0,
-            bid_quantity_contract: None,
+            bid_quantity_quote: 3.0,
+            bid_quantity_contract: Some(6.0),
             ask_price: 4.0,
             ask_quantity_base: 5.0,
-            ask_quantity_quote: 0.0,
+            ask_quantity_quote: 20.0,
             ask_quantity_contract: None,
             id: Some(114514),
         }
@@ -225,15 +384,23 @@ mod tests {
         // assert_eq!(payload.json, decoded.json);
         assert_eq!(payload.bid_price, decoded_msg.bid_price);
         assert_eq!(payload.bid_quantity_base, decoded_msg.bid_quantity_base);
-        // assert_eq!(payload.bid_quantity_quote, decoded.bid_quantity_quote);
+        assert_eq!(payload.bid_quantity_quote, decoded_msg.bid_quantity_quote);
+        assert_eq!(
+            payload.bid_quantity_contract,
+            decoded_msg.bid_quantity_contract
+        );
         assert_eq!(payload.ask_price, decoded_msg.ask_price);
         assert_eq!(payload.ask_quantity_base, decoded_msg.ask_quantity_base);
-        // assert_eq!(payload.ask_quantity_quote, decoded.ask_quantity_quote);
-        // assert_eq!(payload.id, decoded.id);
+        assert_eq!(payload.ask_quantity_quote, decoded_msg.ask_quantity_quote);
+        assert_eq!(
+            payload.ask_quantity_contract,
+            decoded_msg.ask_quantity_contract
+        );
+        assert_eq!(payload.id, decoded_msg.id);
     }
 
     #[test]
-    fn test_bbo_encode_decode_unknown_markettype() {
+    fn test_bbo_encode_decode_american_option() {
         let payload = construct_bbomsg(true);
 
         let bbo_structure = BboStructure::try_from(&payload).unwrap();
@@ -244,7 +411,7 @@ mod tests {
         let decoded_msg = BboMsg::try_from(decoded_structure).unwrap();
 
         assert_eq!(payload.exchange, decoded_msg.exchange);
-        assert_eq!(MarketType::Unknown, decoded_msg.market_type);
+        assert_eq!(MarketType::AmericanOption, decoded_msg.market_type);
         assert_eq!(payload.symbol, decoded_msg.symbol);
         assert_eq!(payload.pair, decoded_msg.pair);
         assert_eq!(payload.msg_type, decoded_msg.msg_type);
@@ -252,11 +419,174 @@ mod tests {
         // assert_eq!(payload.json, decoded.json);
         assert_eq!(payload.bid_price, decoded_msg.bid_price);
         assert_eq!(payload.bid_quantity_base, decoded_msg.bid_quantity_base);
-        // assert_eq!(payload.bid_quantity_quote, decoded.bid_quantity_quote);
+        assert_eq!(payload.bid_quantity_quote, decoded_msg.bid_quantity_quote);
+        assert_eq!(
+            payload.bid_quantity_contract,
+            decoded_msg.bid_quantity_contract
+        );
         assert_eq!(payload.ask_price, decoded_msg.ask_price);
         assert_eq!(payload.ask_quantity_base, decoded_msg.ask_quantity_base);
-        // assert_eq!(payload.ask_quantity_quote, decoded.ask_quantity_quote);
-        // assert_eq!(payload.id, decoded.id);
+        assert_eq!(payload.ask_quantity_quote, decoded_msg.ask_quantity_quote);
+        assert_eq!(
+            payload.ask_quantity_contract,
+            decoded_msg.ask_quantity_contract
+        );
+        assert_eq!(payload.id, decoded_msg.id);
+    }
+
+    #[test]
+    fn test_bbo_received_timestamp() {
+        let bbo_structure =
+            BboStructure::try_from_with_received(&construct_bbomsg(false), 12345999).unwrap();
+        assert_eq!(bbo_structure.received_timestamp.0, 12345999);
+        assert_eq!(bbo_structure.latency_ms(), 321);
+
+        let buffer = bbo_structure.serialize_to_vec().unwrap();
+        let decoded_structure = BboStructure::deserialize_from_bytes(&buffer).unwrap();
+        assert_eq!(decoded_structure.received_timestamp.0, 12345999);
+        assert_eq!(decoded_structure, bbo_structure);
+
+        // Re-serializing keeps the original received timestamp.
+        assert_eq!(decoded_structure.serialize_to_vec().unwrap(), buffer);
+
+        let skewed =
+            BboStructure::try_from_with_received(&construct_bbomsg(false), 12345000).unwrap();
+        assert_eq!(skewed.latency_ms(), -678);
+    }
+
+    #[test]
+    fn test_bbo_schema_version() {
+        let bbo_structure = BboStructure::try_from(&construct_bbomsg(false)).unwrap();
+        let mut buffer = bbo_structure.serialize_to_vec().unwrap();
+        assert_eq!(buffer[0], BboStructure::SCHEMA_VERSION);
+
+        buffer[0] = BboStructure::SCHEMA_VERSION + 1;
+        assert!(matches!(
+            BboStructure::deserialize_from_bytes(&buffer),
+            Err(BboError::FieldError(FieldError::SchemaMismatch { .. }))
+        ));
+
+        let _guard = SchemaVersionField::skip_check();
+        let decoded_structure = BboStructure::deserialize_from_bytes(&buffer).unwrap();
+        assert_eq!(decoded_structure, bbo_structure);
+    }
+
+    #[test]
+    fn test_bbo_from_msg() {
+        let payload = construct_bbomsg(false);
+
+        assert_eq!(
+            BboStructure::from_msg(&payload).unwrap(),
+            BboStructure::try_from(&payload).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_bbo_zero_quantity_contract() {
+        let payload = BboMsg {
+            ask_quantity_contract: Some(0.0),
+            ..construct_bbomsg(false)
+        };
+
+        let bbo_structure = BboStructure::try_from(&payload).unwrap();
+        let buffer = bbo_structure.serialize_to_vec().unwrap();
+        let decoded_structure = BboStructure::deserialize_from_bytes(&buffer).unwrap();
+        let decoded_msg = BboMsg::try_from(decoded_structure).unwrap();
+
+        // Zero is a quantity, not the absence of it.
+        assert_eq!(decoded_msg.ask_quantity_contract, Some(0.0));
+        assert_eq!(decoded_msg.bid_quantity_contract, Some(6.0));
+        assert_eq!(decoded_msg.ask_quantity_quote, payload.ask_quantity_quote);
+    }
+
+    #[test]
+    fn test_bbo_negative_timestamp() {
+        let payload = BboMsg {
+            timestamp: -1,
+            ..construct_bbomsg(false)
+        };
+
+        assert!(matches!(
+            BboStructure::try_from(&payload),
+            Err(BboError::FieldError(FieldError::TimestampOutOfRange(-1)))
+        ));
+    }
+
+    #[test]
+    fn test_bbo_spread() {
+        let bbo_structure = BboStructure::try_from(&construct_bbomsg(false)).unwrap();
+
+        assert_eq!(bbo_structure.spread(), Some(dec!(3)));
+        assert!(!bbo_structure.is_crossed());
+    }
+
+    #[test]
+    fn test_bbo_spread_zero_price() {
+        let payload = BboMsg {
+            bid_price: 0.0,
+            ..construct_bbomsg(false)
+        };
+        let bbo_structure = BboStructure::try_from(&payload).unwrap();
+
+        assert_eq!(bbo_structure.spread(), None);
+    }
+
+    #[test]
+    fn test_bbo_crossed() {
+        let payload = BboMsg {
+            bid_price: 4.5,
+            ..construct_bbomsg(false)
+        };
+        let bbo_structure = BboStructure::try_from(&payload).unwrap();
+
+        assert_eq!(bbo_structure.spread(), Some(dec!(-0.5)));
+        assert!(bbo_structure.is_crossed());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_bbo_serde_json_roundtrip() {
+        let bbo_structure = BboStructure::try_from(&construct_bbomsg(false)).unwrap();
+
+        let json = serde_json::to_string_pretty(&bbo_structure).unwrap();
+        let decoded_structure: BboStructure = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded_structure, bbo_structure);
+
+        // Decimals are kept as strings.
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["asks"]["price"], "4");
+    }
+
+    #[cfg(feature = "compat-v0_3")]
+    #[test]
+    #[allow(deprecated)]
+    fn test_compat_decode_trailing_bytes() {
+        use super::{decode_bbo, decode_bbo_partial, encode_bbo};
+
+        let payload = construct_bbomsg(false);
+        let encoded = encode_bbo(&payload).unwrap();
+
+        let decoded_msg = decode_bbo(&encoded).unwrap();
+        assert_eq!(payload.ask_price, decoded_msg.ask_price);
+
+        let garbage = [encoded.as_slice(), &b"xyz"[..]].concat();
+        assert!(matches!(
+            decode_bbo(&garbage),
+            Err(BboError::FieldError(FieldError::TrailingBytes { remaining: 3 }))
+        ));
+
+        let concatenated = [encoded.as_slice(), encoded.as_slice()].concat();
+        assert!(matches!(
+            decode_bbo(&concatenated),
+            Err(BboError::FieldError(FieldError::TrailingBytes { .. }))
+        ));
+
+        let (first, consumed) = decode_bbo_partial(&concatenated).unwrap();
+        assert_eq!(consumed, encoded.len());
+        let (second, consumed) = decode_bbo_partial(&concatenated[consumed..]).unwrap();
+        assert_eq!(consumed, encoded.len());
+        assert_eq!(first.ask_price, second.ask_price);
+        assert_eq!(first.bid_price, second.bid_price);
     }
 }
 
@@ -264,6 +594,7 @@ mod tests {
 crate::compat::compat_enc!(
     enc = encode_bbo,
     dec = decode_bbo,
+    dec_partial = decode_bbo_partial,
     crawl = BboMsg,
     result = BboResult,
     structure = BboStructure
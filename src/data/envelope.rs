@@ -0,0 +1,190 @@
+//! The versioned envelope of the serialized structures.
+//! See [`DataEnvelope`].
+
+use std::io::{Read, Write};
+
+use super::fields::{FieldError, MessageTypeField};
+use super::serializer::{FieldDeserializer, FieldSerializer, StructDeserializer, StructSerializer};
+
+/// The versioned envelope wrapping a serialized structure.
+///
+/// It tags the payload with the envelope version and the kind of
+/// the structure, so the layout of the structures can be evolved
+/// behind the version byte.
+///
+/// The serialized envelope is:
+///
+/// ```plain
+/// version (1 byte) | kind (1 byte) | payload length (4 bytes, BE) | payload
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataEnvelope {
+    /// The version of the envelope.
+    pub version: u8,
+
+    /// The kind of the structure in `payload`.
+    pub kind: MessageTypeField,
+
+    /// The serialized structure.
+    pub payload: Vec<u8>,
+}
+
+impl DataEnvelope {
+    /// The current version of the envelope.
+    pub const CURRENT_VERSION: u8 = 1;
+
+    /// Create an envelope of the current version.
+    pub fn new(kind: impl Into<MessageTypeField>, payload: Vec<u8>) -> Self {
+        Self {
+            version: Self::CURRENT_VERSION,
+            kind: kind.into(),
+            payload,
+        }
+    }
+
+    /// Serialize `structure` and wrap it into an envelope of the current version.
+    pub fn wrap<T>(kind: impl Into<MessageTypeField>, structure: &T) -> Result<Self, T::Err>
+    where
+        T: StructSerializer,
+    {
+        Ok(Self::new(kind, structure.serialize_to_vec()?))
+    }
+
+    /// Deserialize the structure in the payload.
+    pub fn open<T>(&self) -> Result<T, T::Err>
+    where
+        T: StructDeserializer,
+    {
+        T::deserialize_from_bytes(&self.payload)
+    }
+}
+
+impl StructSerializer for DataEnvelope {
+    type Err = EnvelopeError;
+
+    fn serialize(&self, writer: &mut impl Write) -> Result<(), Self::Err> {
+        let payload_len = u32::try_from(self.payload.len())
+            .map_err(|_| EnvelopeError::PayloadTooLarge(self.payload.len()))?;
+
+        writer.write_all(&[self.version])?;
+        self.kind.serialize_to_writer(writer)??;
+        writer.write_all(&payload_len.to_be_bytes())?;
+        writer.write_all(&self.payload)?;
+
+        Ok(())
+    }
+}
+
+impl StructDeserializer for DataEnvelope {
+    type Err = EnvelopeError;
+
+    fn deserialize(reader: &mut impl Read) -> Result<Self, Self::Err> {
+        let mut version = [0; 1];
+        reader.read_exact(&mut version)?;
+        let [version] = version;
+
+        if version != Self::CURRENT_VERSION {
+            return Err(EnvelopeError::UnsupportedVersion(version));
+        }
+
+        let kind = MessageTypeField::deserialize_from_reader(reader)??;
+
+        let mut payload_len = [0; 4];
+        reader.read_exact(&mut payload_len)?;
+
+        let mut payload = vec![0; u32::from_be_bytes(payload_len) as usize];
+        reader.read_exact(&mut payload)?;
+
+        Ok(Self {
+            version,
+            kind,
+            payload,
+        })
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EnvelopeError {
+    #[error("field error: {0}")]
+    FieldError(#[from] FieldError),
+
+    #[error("I/O reader/writer error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("unsupported envelope version: {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("the payload is too large: {0} bytes")]
+    PayloadTooLarge(usize),
+}
+
+pub type EnvelopeResult<T> = Result<T, EnvelopeError>;
+
+#[cfg(test)]
+mod tests {
+    use crypto_msg_type::MessageType;
+
+    use super::{DataEnvelope, EnvelopeError};
+    use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+    #[test]
+    fn test_decode_v1_envelope() {
+        let encoded = [
+            1, // version
+            2, // kind: BBO
+            0, 0, 0, 3, // payload length
+            b'a', b'b', b'c', // payload
+        ];
+
+        let envelope = DataEnvelope::deserialize_from_bytes(&encoded).unwrap();
+        assert_eq!(envelope.version, DataEnvelope::CURRENT_VERSION);
+        assert_eq!(envelope.kind.0, MessageType::BBO);
+        assert_eq!(envelope.payload, b"abc");
+
+        assert_eq!(envelope.serialize_to_vec().unwrap(), encoded);
+    }
+
+    #[test]
+    fn test_decode_unknown_version() {
+        let encoded = [42, 2, 0, 0, 0, 0];
+
+        assert!(matches!(
+            DataEnvelope::deserialize_from_bytes(&encoded),
+            Err(EnvelopeError::UnsupportedVersion(42))
+        ));
+    }
+
+    #[test]
+    fn test_decode_truncated_payload() {
+        let encoded = [1, 2, 0, 0, 0, 3, b'a'];
+
+        assert!(matches!(
+            DataEnvelope::deserialize_from_bytes(&encoded),
+            Err(EnvelopeError::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_wrap_and_open() {
+        use crate::data::bbo::BboStructure;
+        use crate::data::fields::exchange_type::Exchange;
+        use crate::data::fields::{PriceDataField, SymbolPairField};
+
+        let structure = BboStructure::builder()
+            .exchange_timestamp(1659755147000u64)
+            .exchange_type(Exchange::Binance)
+            .market_type(crypto_market_type::MarketType::Spot)
+            .message_type(MessageType::BBO)
+            .symbol(SymbolPairField::from_pair("BTC/USDT"))
+            .asks(PriceDataField::builder().price(2.0).quantity_base(1.0).build())
+            .bids(PriceDataField::builder().price(1.0).quantity_base(3.0).build())
+            .build();
+
+        let envelope = DataEnvelope::wrap(MessageType::BBO, &structure).unwrap();
+        let encoded = envelope.serialize_to_vec().unwrap();
+
+        let decoded = DataEnvelope::deserialize_from_bytes(&encoded).unwrap();
+        assert_eq!(decoded, envelope);
+        assert_eq!(decoded.open::<BboStructure>().unwrap(), structure);
+    }
+}
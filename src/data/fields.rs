@@ -3,6 +3,8 @@
 mod abstracts;
 mod bimap;
 
+#[cfg(feature = "checksum")]
+pub mod checksum;
 pub mod decimal;
 pub mod eod_flag;
 pub mod exchange_type;
@@ -10,15 +12,21 @@ pub mod info_type;
 pub mod kline_indicators;
 pub mod market_type;
 pub mod message_type;
+pub mod optional_u64;
 pub mod period;
 pub mod price_data;
+pub mod schema_version;
+pub mod snapshot;
 pub mod symbol_pair;
 pub mod timestamp;
+pub mod trade_id;
 pub mod trade_side;
 
 use std::time::SystemTimeError;
 
 pub use abstracts::{Field, Interopable};
+#[cfg(feature = "checksum")]
+pub use checksum::{ChecksumField, Checksummed};
 pub use decimal::DecimalField;
 pub use either::Either;
 pub use eod_flag::EndOfDataFlag;
@@ -27,10 +35,14 @@ pub use info_type::InfoTypeField;
 pub use kline_indicators::KlineIndicatorsField;
 pub use market_type::MarketTypeField;
 pub use message_type::MessageTypeField;
+pub use optional_u64::OptionalU64Field;
 pub use period::PeriodField;
-pub use price_data::PriceDataField;
+pub use price_data::{PriceDataField, PriceDataFieldExtended};
+pub use schema_version::SchemaVersionField;
+pub use snapshot::SnapshotField;
 pub use symbol_pair::SymbolPairField;
-pub use timestamp::TimestampField;
+pub use timestamp::{TimestampField, TimestampNsField};
+pub use trade_id::TradeIdField;
 pub use trade_side::TradeSideField;
 
 use super::num::NumError;
@@ -41,6 +53,9 @@ pub enum FieldError {
     #[error("unexpected trade side ID: {0}")]
     UnexpectedTradeSide(u8),
 
+    #[error("this trade side has not been implemented: {0}")]
+    UnimplementedTradeSide(String),
+
     #[error("number encode/decode error: {0}")]
     NumError(#[from] NumError),
 
@@ -56,11 +71,42 @@ pub enum FieldError {
     #[error("this period has not been implemented: {0:?}")]
     UnimplementedPeriod(Either<String, u8>),
 
+    #[error("this message type has not been implemented: {0}")]
+    UnimplementedMessageType(u8),
+
     /// The inner value is the type (such as `f32` or `f64`).
     #[error("seems like the convert between Decimal and {0} is overflowed")]
     FloatOverflow(&'static str),
 
     #[error("data ended too early (missing \\0 in the end)!")]
     DataEndedTooEarly,
+
+    #[error("failed to parse the timestamp: {0}")]
+    TimestampParseFailed(#[from] chrono::ParseError),
+
+    #[error("the timestamp is out of range: {0} ms")]
+    TimestampOutOfRange(i128),
+
+    #[error("unexpected schema version: expected {expected}, got {got}")]
+    SchemaMismatch { expected: u8, got: u8 },
+
+    #[error("{remaining} bytes remained after the end of data")]
+    TrailingBytes { remaining: usize },
+
+    #[error("unexpected snapshot flag: {0}")]
+    UnexpectedSnapshotFlag(u8),
+
+    #[error("unexpected presence flag of an optional field: {0}")]
+    UnexpectedPresenceFlag(u8),
+
+    #[error("the trade ID is too long: {0} bytes")]
+    TradeIdTooLong(usize),
+
+    #[error("the trade ID is not valid UTF-8: {0}")]
+    InvalidTradeId(std::str::Utf8Error),
+
+    #[cfg(feature = "checksum")]
+    #[error("checksum mismatched: expected {expected:#010x}, got {actual:#010x}")]
+    ChecksumMismatch { expected: u32, actual: u32 },
 }
 pub type FieldResult<T> = Result<T, FieldError>;
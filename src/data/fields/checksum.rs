@@ -0,0 +1,182 @@
+//! The module with a field to detect the corrupted data.
+//! See [`ChecksumField`] and [`Checksummed`].
+
+use std::io::{self, Read, Write};
+
+use super::{FieldDeserializer, FieldError, FieldSerializer};
+use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+/// The CRC32 checksum of all the preceding bytes (4 bytes).
+///
+/// It is placed after the [`EndOfDataFlag`](super::EndOfDataFlag).
+/// See [`Checksummed`] for appending it to a structure.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChecksumField(pub u32);
+
+impl ChecksumField {
+    /// Compute the checksum of `data`.
+    pub fn compute(data: &[u8]) -> Self {
+        Self(crc32fast::hash(data))
+    }
+}
+
+impl FieldSerializer<4> for ChecksumField {
+    type Err = FieldError;
+
+    fn serialize(&self) -> Result<[u8; 4], Self::Err> {
+        Ok(self.0.to_be_bytes())
+    }
+}
+
+impl FieldDeserializer<4> for ChecksumField {
+    type Err = FieldError;
+
+    fn deserialize(src: &[u8; 4]) -> Result<Self, Self::Err> {
+        Ok(Self(u32::from_be_bytes(*src)))
+    }
+}
+
+/// A structure with a [`ChecksumField`] appended.
+///
+/// The checksum is computed from the serialized structure, and
+/// is validated on deserialization. A mismatched checksum results
+/// in [`FieldError::ChecksumMismatch`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Checksummed<T>(pub T);
+
+impl<T> Checksummed<T> {
+    /// Get the inner structure.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> StructSerializer for Checksummed<T>
+where
+    T: StructSerializer,
+    T::Err: From<FieldError> + From<io::Error>,
+{
+    type Err = T::Err;
+
+    fn serialize(&self, writer: &mut impl Write) -> Result<(), Self::Err> {
+        let data = self.0.serialize_to_vec()?;
+
+        writer.write_all(&data)?;
+        ChecksumField::compute(&data).serialize_to_writer(writer)??;
+
+        Ok(())
+    }
+}
+
+impl<T> StructDeserializer for Checksummed<T>
+where
+    T: StructDeserializer,
+    T::Err: From<FieldError> + From<io::Error>,
+{
+    type Err = T::Err;
+
+    fn deserialize(reader: &mut impl Read) -> Result<Self, Self::Err> {
+        let mut hashing_reader = HashingReader {
+            reader: &mut *reader,
+            hasher: crc32fast::Hasher::new(),
+        };
+
+        let inner = T::deserialize(&mut hashing_reader)?;
+        let actual = hashing_reader.hasher.finalize();
+        let ChecksumField(expected) = ChecksumField::deserialize_from_reader(reader)??;
+
+        if expected != actual {
+            return Err(FieldError::ChecksumMismatch { expected, actual }.into());
+        }
+
+        Ok(Self(inner))
+    }
+}
+
+/// The reader computing the checksum of the bytes read.
+struct HashingReader<R> {
+    reader: R,
+    hasher: crc32fast::Hasher,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.hasher.update(&buf[..len]);
+
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChecksumField;
+    use crate::data::fields::{FieldDeserializer, FieldSerializer};
+
+    #[test]
+    fn test_checksum_field() {
+        let field = ChecksumField::compute(b"123456789");
+        assert_eq!(field, ChecksumField(0xCBF43926));
+
+        let serialized = field.serialize().unwrap();
+        assert_eq!(serialized, [0xCB, 0xF4, 0x39, 0x26]);
+        assert_eq!(ChecksumField::deserialize(&serialized).unwrap(), field);
+    }
+
+    #[cfg(feature = "crypto")]
+    mod checksummed {
+        use crypto_message::BboMsg;
+
+        use super::super::Checksummed;
+        use crate::data::bbo::{BboError, BboStructure};
+        use crate::data::fields::FieldError;
+        use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+        fn construct_bbo() -> BboStructure {
+            let msg = BboMsg {
+                exchange: "binance".into(),
+                market_type: crypto_market_type::MarketType::Spot,
+                symbol: "BTCUSDT".into(),
+                pair: "BTC/USDT".into(),
+                msg_type: crypto_msg_type::MessageType::BBO,
+                timestamp: 12345678,
+                json: "".into(),
+                bid_price: 1.0,
+                bid_quantity_base: 2.0,
+                bid_quantity_quote: 0.0,
+                bid_quantity_contract: None,
+                ask_price: 4.0,
+                ask_quantity_base: 5.0,
+                ask_quantity_quote: 0.0,
+                ask_quantity_contract: None,
+                id: None,
+            };
+
+            BboStructure::try_from(&msg).unwrap()
+        }
+
+        #[test]
+        fn test_checksummed_roundtrip() {
+            let structure = Checksummed(construct_bbo());
+            let serialized = structure.serialize_to_vec().unwrap();
+
+            let decoded = Checksummed::<BboStructure>::deserialize_from_bytes(&serialized).unwrap();
+            assert_eq!(decoded.into_inner(), structure.0);
+        }
+
+        #[test]
+        fn test_checksummed_detects_corruption() {
+            let mut serialized = Checksummed(construct_bbo()).serialize_to_vec().unwrap();
+
+            // Flip a bit in the exchange timestamp.
+            serialized[3] ^= 0x01;
+
+            let result = Checksummed::<BboStructure>::deserialize_from_bytes(&serialized);
+            assert!(matches!(
+                result,
+                Err(BboError::FieldError(FieldError::ChecksumMismatch { .. }))
+            ));
+        }
+    }
+}
@@ -8,10 +8,13 @@ pub use rust_decimal::{Decimal, Error as DecimalError};
 
 use super::abstracts::derive_hsf;
 use super::{FieldDeserializer, FieldError, FieldSerializer, Interopable};
-use crate::data::num::{Decoder, Encoder};
+use crate::data::num::{decimal_from_f64, Decoder, Encoder};
 
 /// The field to store numbers serialized with [`crate::data::num`]'s methods.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// With the `serde` feature, it is (de)serialized as a string
+/// to keep the precision.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
 pub struct DecimalField<const LEN: usize>(pub Decimal);
 
 impl FieldSerializer<5> for DecimalField<5> {
@@ -30,6 +33,14 @@ impl FieldSerializer<10> for DecimalField<10> {
     }
 }
 
+impl FieldSerializer<20> for DecimalField<20> {
+    type Err = FieldError;
+
+    fn serialize(&self) -> Result<[u8; 20], Self::Err> {
+        Ok(self.0.encode()?)
+    }
+}
+
 impl FieldDeserializer<5> for DecimalField<5> {
     type Err = FieldError;
 
@@ -46,6 +57,48 @@ impl FieldDeserializer<10> for DecimalField<10> {
     }
 }
 
+impl FieldDeserializer<20> for DecimalField<20> {
+    type Err = FieldError;
+
+    fn deserialize(src: &[u8; 20]) -> Result<Self, Self::Err> {
+        Ok(Self(Decimal::decode(src)?))
+    }
+}
+
+/// The optional [`DecimalField<10>`] (11 bytes).
+///
+/// The first byte is `1` if the value is present, and `0` otherwise.
+/// The following 10 bytes are the value, which are zero if the value
+/// is absent.
+impl FieldSerializer<11> for Option<DecimalField<10>> {
+    type Err = FieldError;
+
+    fn serialize(&self) -> Result<[u8; 11], Self::Err> {
+        let mut result = [0u8; 11];
+
+        if let Some(value) = self {
+            result[0] = 1;
+            result[1..].copy_from_slice(&value.serialize()?);
+        }
+
+        Ok(result)
+    }
+}
+
+impl FieldDeserializer<11> for Option<DecimalField<10>> {
+    type Err = FieldError;
+
+    fn deserialize(src: &[u8; 11]) -> Result<Self, Self::Err> {
+        let value = src[1..].try_into().expect("should be 10 bytes");
+
+        match src[0] {
+            0 => Ok(None),
+            1 => Ok(Some(DecimalField::<10>::deserialize(value)?)),
+            flag => Err(FieldError::UnexpectedPresenceFlag(flag)),
+        }
+    }
+}
+
 impl<const LEN: usize> From<Decimal> for DecimalField<LEN> {
     fn from(d: Decimal) -> Self {
         Self(d)
@@ -59,8 +112,10 @@ impl<const LEN: usize> From<f32> for DecimalField<LEN> {
 }
 
 impl<const LEN: usize> From<f64> for DecimalField<LEN> {
+    /// Convert with the shortest representation of `f`.
+    /// See [`decimal_from_f64`].
     fn from(f: f64) -> Self {
-        Self(Decimal::from_f64(f).expect("overflow?"))
+        Self(decimal_from_f64(f).expect("overflow?"))
     }
 }
 
@@ -110,5 +165,48 @@ impl<const LEN: usize> DerefMut for DecimalField<LEN> {
 
 impl<const LEN: usize> Interopable<Decimal> for DecimalField<LEN> {}
 
+#[cfg(feature = "serde")]
+impl<const LEN: usize> serde::Serialize for DecimalField<LEN> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const LEN: usize> serde::Deserialize<'de> for DecimalField<LEN> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+
+        Decimal::from_str_exact(&s)
+            .map(Self)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 derive_hsf!(DecimalField<5>, Decimal, 5);
 derive_hsf!(DecimalField<10>, Decimal, 10);
+derive_hsf!(DecimalField<20>, Decimal, 20);
+
+#[cfg(test)]
+mod tests {
+    use rust_decimal_macros::dec;
+
+    use super::DecimalField;
+    use crate::data::fields::{FieldDeserializer, FieldSerializer};
+
+    #[test]
+    fn test_20b_field_roundtrip() {
+        let numbers = [
+            dec!(0.000000000000001234),
+            dec!(-0.123456789012345678),
+            dec!(123456789012.123456789012345678),
+        ];
+
+        for number in numbers {
+            let field = DecimalField::<20>(number);
+            let serialized = field.serialize().unwrap();
+
+            assert_eq!(DecimalField::<20>::deserialize(&serialized).unwrap(), field);
+        }
+    }
+}
@@ -5,6 +5,7 @@ use super::{Field, FieldDeserializer, FieldError, FieldSerializer};
 
 /// The flag indicating the end of data. (1 byte).
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EndOfDataFlag;
 
 impl FieldSerializer<1> for EndOfDataFlag {
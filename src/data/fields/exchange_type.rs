@@ -4,13 +4,14 @@
 use std::str::FromStr;
 
 use either::Either;
-use strum::{EnumString, FromRepr};
+use strum::{EnumString, FromRepr, IntoStaticStr};
 
 use super::abstracts::{derive_hsf, derive_interop_converters};
 use super::{FieldDeserializer, FieldError, FieldResult, FieldSerializer};
 
 /// The exchange type of a message (1 byte).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExchangeTypeField(pub Exchange);
 
 impl ExchangeTypeField {
@@ -19,6 +20,11 @@ impl ExchangeTypeField {
             .map_err(|_| FieldError::UnimplementedExchange(Either::Left(str.to_string())))?;
         Ok(Self(exchange))
     }
+
+    /// Get the lowercase name of the exchange, for example `binance`.
+    pub fn as_str(&self) -> &'static str {
+        self.0.into()
+    }
 }
 
 impl FieldSerializer<1> for ExchangeTypeField {
@@ -33,9 +39,8 @@ impl FieldDeserializer<1> for ExchangeTypeField {
     type Err = FieldError;
 
     fn deserialize(src: &[u8; 1]) -> Result<Self, Self::Err> {
-        let bit = src[0] as usize;
-        let name = Exchange::from_repr(bit)
-            .ok_or(FieldError::UnimplementedExchange(either::Right(bit)))?;
+        let name = Exchange::from_byte(src[0])
+            .ok_or(FieldError::UnimplementedExchange(either::Right(src[0].into())))?;
 
         Ok(Self(name))
     }
@@ -50,7 +55,20 @@ impl From<ExchangeTypeField> for String {
 derive_interop_converters!(ExchangeTypeField, Exchange);
 derive_hsf!(ExchangeTypeField, Exchange, 1);
 
-#[derive(Copy, Clone, FromRepr, strum::Display, EnumString, Debug, PartialEq, Eq, Hash)]
+#[derive(
+    Copy,
+    Clone,
+    FromRepr,
+    strum::Display,
+    EnumString,
+    IntoStaticStr,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 #[strum(serialize_all = "lowercase")]
 pub enum Exchange {
     Crypto = 1,
@@ -61,6 +79,26 @@ pub enum Exchange {
     Okx = 11,
 }
 
+impl Exchange {
+    /// All the supported exchanges, in the order of their identifiers.
+    pub fn all() -> &'static [Exchange] {
+        &[
+            Exchange::Crypto,
+            Exchange::Ftx,
+            Exchange::Binance,
+            Exchange::Huobi,
+            Exchange::Kucoin,
+            Exchange::Okx,
+        ]
+    }
+
+    /// Get the exchange of the identifier `b`, which is
+    /// the serialized form in [`ExchangeTypeField`].
+    pub fn from_byte(b: u8) -> Option<Exchange> {
+        Exchange::from_repr(b.into())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Exchange, ExchangeTypeField, FieldDeserializer};
@@ -120,4 +158,32 @@ mod tests {
             Exchange::Okx
         );
     }
+
+    #[test]
+    fn test_exchange_all() {
+        assert_eq!(
+            Exchange::all(),
+            [
+                Exchange::Crypto,
+                Exchange::Ftx,
+                Exchange::Binance,
+                Exchange::Huobi,
+                Exchange::Kucoin,
+                Exchange::Okx,
+            ]
+        );
+
+        // Every exchange round-trips through its identifier and name.
+        for &exchange in Exchange::all() {
+            assert_eq!(Exchange::from_byte(exchange as u8), Some(exchange));
+            assert_eq!(ExchangeTypeField(exchange).as_str(), exchange.to_string());
+        }
+    }
+
+    #[test]
+    fn test_exchange_from_byte() {
+        assert_eq!(Exchange::from_byte(3), Some(Exchange::Binance));
+        assert_eq!(Exchange::from_byte(4), None);
+        assert_eq!(ExchangeTypeField(Exchange::Binance).as_str(), "binance");
+    }
 }
@@ -10,6 +10,7 @@ use super::{Either, FieldDeserializer, FieldError, FieldResult, FieldSerializer}
 
 /// The info type (`asks` or `bids`) of a message (1 byte).
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InfoTypeField(pub InfoType);
 
 impl InfoTypeField {
@@ -44,6 +45,8 @@ derive_interop_converters!(InfoTypeField, InfoType);
 derive_hsf!(InfoTypeField, InfoType, 1);
 
 #[derive(Copy, Clone, FromRepr, strum::Display, EnumString, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 #[strum(serialize_all = "lowercase")]
 pub enum InfoType {
     Asks = 1,
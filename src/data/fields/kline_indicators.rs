@@ -6,13 +6,18 @@ use super::{DecimalField, Field, FieldDeserializer, FieldError, FieldSerializer}
 // https://moneymate.space/k線/
 // Thanks to Cindy!
 
-/// The indicators for k-line (30 bytes).
+/// The indicators for k-line (41 bytes).
 ///
 /// It includes `open`, `high`, `low`,
-/// `close`, and `volume`.
+/// `close`, `volume` and the optional `quote_volume`.
+///
+/// The first 30 bytes are the same as the legacy layout
+/// without `quote_volume`, which can still be read with
+/// [`KlineIndicatorsField::deserialize_legacy`].
 ///
 /// For more information, see <https://moneymate.space/k線/>.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KlineIndicatorsField {
     /// 開盤價 (5 bytes)
     ///
@@ -41,13 +46,46 @@ pub struct KlineIndicatorsField {
     /// 交易量 (10 bytes)
     #[builder(setter(into))]
     pub volume: DecimalField<10>,
+
+    /// 報價貨幣交易量 (1 + 10 bytes)
+    ///
+    /// Some exchanges do not report it. It is stored
+    /// as a presence flag followed by the volume.
+    #[builder(default)]
+    pub quote_volume: Option<DecimalField<10>>,
+}
+
+impl KlineIndicatorsField {
+    /// Deserialize the legacy 30-bytes indicators without `quote_volume`.
+    pub fn deserialize_legacy(src: &[u8; 30]) -> Result<Self, FieldError> {
+        let mut offset = 0;
+
+        let open = DecimalField::deserialize(arrayref::array_ref![src, offset, 5])?;
+        offset += 5;
+        let high = DecimalField::deserialize(arrayref::array_ref![src, offset, 5])?;
+        offset += 5;
+        let low = DecimalField::deserialize(arrayref::array_ref![src, offset, 5])?;
+        offset += 5;
+        let close = DecimalField::deserialize(arrayref::array_ref![src, offset, 5])?;
+        offset += 5;
+        let volume = DecimalField::deserialize(arrayref::array_ref![src, offset, 10])?;
+
+        Ok(Self {
+            open,
+            high,
+            low,
+            close,
+            volume,
+            quote_volume: None,
+        })
+    }
 }
 
-impl FieldSerializer<30> for KlineIndicatorsField {
+impl FieldSerializer<41> for KlineIndicatorsField {
     type Err = FieldError;
 
-    fn serialize(&self) -> Result<[u8; 30], Self::Err> {
-        let mut dst = [0u8; 30];
+    fn serialize(&self) -> Result<[u8; 41], Self::Err> {
+        let mut dst = [0u8; 41];
 
         let mut offset = 0;
 
@@ -60,35 +98,67 @@ impl FieldSerializer<30> for KlineIndicatorsField {
         dst[offset..offset + 5].copy_from_slice(&self.close.serialize()?);
         offset += 5;
         dst[offset..offset + 10].copy_from_slice(&self.volume.serialize()?);
+        offset += 10;
+
+        if let Some(quote_volume) = &self.quote_volume {
+            dst[offset] = 1;
+            offset += 1;
+            dst[offset..offset + 10].copy_from_slice(&quote_volume.serialize()?);
+        }
 
         Ok(dst)
     }
 }
 
-impl FieldDeserializer<30> for KlineIndicatorsField {
+impl FieldDeserializer<41> for KlineIndicatorsField {
     type Err = FieldError;
 
-    fn deserialize(src: &[u8; 30]) -> Result<Self, Self::Err> {
-        let mut offset = 0;
+    fn deserialize(src: &[u8; 41]) -> Result<Self, Self::Err> {
+        let mut indicators = Self::deserialize_legacy(arrayref::array_ref![src, 0, 30])?;
 
-        let open = DecimalField::deserialize(arrayref::array_ref![src, offset, 5])?;
-        offset += 5;
-        let high = DecimalField::deserialize(arrayref::array_ref![src, offset, 5])?;
-        offset += 5;
-        let low = DecimalField::deserialize(arrayref::array_ref![src, offset, 5])?;
-        offset += 5;
-        let close = DecimalField::deserialize(arrayref::array_ref![src, offset, 5])?;
-        offset += 5;
-        let volume = DecimalField::deserialize(arrayref::array_ref![src, offset, 10])?;
+        if src[30] != 0 {
+            let quote_volume = DecimalField::deserialize(arrayref::array_ref![src, 31, 10])?;
+            indicators.quote_volume = Some(quote_volume);
+        }
 
-        Ok(Self {
-            open,
-            high,
-            low,
-            close,
-            volume,
-        })
+        Ok(indicators)
     }
 }
 
-impl Field<30> for KlineIndicatorsField {}
+impl Field<41> for KlineIndicatorsField {}
+
+#[cfg(test)]
+mod tests {
+    use super::KlineIndicatorsField;
+    use crate::data::fields::{FieldDeserializer, FieldSerializer};
+
+    fn indicators(quote_volume: Option<f64>) -> KlineIndicatorsField {
+        KlineIndicatorsField::builder()
+            .open(1.0)
+            .high(4.0)
+            .low(0.5)
+            .close(2.0)
+            .volume(100.0)
+            .quote_volume(quote_volume.map(Into::into))
+            .build()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        for quote_volume in [None, Some(0.0), Some(12345.678)] {
+            let field = indicators(quote_volume);
+            let serialized = field.serialize().unwrap();
+
+            assert_eq!(KlineIndicatorsField::deserialize(&serialized).unwrap(), field);
+        }
+    }
+
+    #[test]
+    fn test_legacy_layout() {
+        let field = indicators(None);
+        let serialized = field.serialize().unwrap();
+
+        let legacy = arrayref::array_ref![serialized, 0, 30];
+        assert_eq!(KlineIndicatorsField::deserialize_legacy(legacy).unwrap(), field);
+    }
+}
@@ -3,21 +3,64 @@
 
 pub use crypto_market_type::MarketType;
 
-use super::abstracts::{derive_hsf, derive_interop_converters};
+use std::ops::RangeInclusive;
+
+use super::abstracts::{derive_hsf, Interopable};
 use super::bimap::create_bimap;
 use super::{FieldDeserializer, FieldError, FieldSerializer};
 
+/// The IDs reserved for the market types unknown to this version.
+///
+/// A field deserialized from these IDs is [`MarketType::Unknown`],
+/// but it keeps the raw ID and is serialized back losslessly.
+pub const RESERVED_IDS: RangeInclusive<u8> = 12..=255;
+
 /// The market type of a message (1 byte).
+///
+/// The unknown market type with an ID in [`RESERVED_IDS`]
+/// keeps its raw ID. See [`MarketTypeField::raw_id`].
+/// Note that the raw ID is not kept with Serde.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct MarketTypeField(pub MarketType);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "MarketType", into = "MarketType"))]
+pub struct MarketTypeField(pub MarketType, Option<u8>);
+
+impl MarketTypeField {
+    /// Create a field with a raw market type ID.
+    ///
+    /// The known IDs are converted to the corresponding market type.
+    pub fn from_raw_id(id: u8) -> Self {
+        match MARKET_TYPE_BIT.get_by_right(&id) {
+            Some(market_type) => Self(*market_type, None),
+            None if RESERVED_IDS.contains(&id) => Self(MarketType::Unknown, Some(id)),
+            None => Self(MarketType::Unknown, None),
+        }
+    }
+
+    /// The ID to serialize as.
+    pub fn id(&self) -> u8 {
+        match (self.0, self.1) {
+            (MarketType::Unknown, Some(raw_id)) => raw_id,
+            (market_type, _) => *MARKET_TYPE_BIT.get_by_left(&market_type).unwrap_or(&0),
+        }
+    }
+
+    /// The raw ID of the market type unknown to this version.
+    ///
+    /// It is `None` if the market type is known.
+    pub fn raw_id(&self) -> Option<u8> {
+        match self.0 {
+            MarketType::Unknown => self.1,
+            _ => None,
+        }
+    }
+}
 
 impl FieldSerializer<1> for MarketTypeField {
     type Err = FieldError;
 
     fn serialize(&self) -> Result<[u8; 1], Self::Err> {
-        let bit = *MARKET_TYPE_BIT.get_by_left(&self.0).unwrap_or(&0);
-
-        Ok([bit])
+        Ok([self.id()])
     }
 }
 
@@ -25,17 +68,38 @@ impl FieldDeserializer<1> for MarketTypeField {
     type Err = FieldError;
 
     fn deserialize(src: &[u8; 1]) -> Result<Self, Self::Err> {
-        let bit = src[0];
+        Ok(Self::from_raw_id(src[0]))
+    }
+}
 
-        let name = MARKET_TYPE_BIT
-            .get_by_right(&bit)
-            .unwrap_or(&MarketType::Unknown);
+impl From<MarketType> for MarketTypeField {
+    fn from(market_type: MarketType) -> Self {
+        Self(market_type, None)
+    }
+}
 
-        Ok(Self(*name))
+impl From<MarketTypeField> for MarketType {
+    fn from(field: MarketTypeField) -> Self {
+        field.0
     }
 }
 
-derive_interop_converters!(MarketTypeField, MarketType);
+impl std::ops::Deref for MarketTypeField {
+    type Target = MarketType;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for MarketTypeField {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl Interopable<MarketType> for MarketTypeField {}
+
 derive_hsf!(MarketTypeField, MarketType, 1);
 
 create_bimap!(MARKET_TYPE_BIT {
@@ -48,5 +112,57 @@ create_bimap!(MARKET_TYPE_BIT {
     MarketType::EuropeanOption => 6,
     MarketType::QuantoFuture => 7,
     MarketType::QuantoSwap => 8,
+    MarketType::AmericanOption => 9,
+    MarketType::Move => 10,
+    MarketType::BVOL => 11,
     // Default: MarketType::Unknown => 0,
+    // Reserved: RESERVED_IDS
 });
+
+#[cfg(test)]
+mod tests {
+    use super::{MarketType, MarketTypeField};
+    use crate::data::fields::{FieldDeserializer, FieldSerializer};
+
+    #[test]
+    fn test_known_market_types_roundtrip() {
+        let market_types = [
+            MarketType::Unknown,
+            MarketType::Spot,
+            MarketType::LinearFuture,
+            MarketType::InverseFuture,
+            MarketType::LinearSwap,
+            MarketType::InverseSwap,
+            MarketType::AmericanOption,
+            MarketType::EuropeanOption,
+            MarketType::QuantoFuture,
+            MarketType::QuantoSwap,
+            MarketType::Move,
+            MarketType::BVOL,
+        ];
+
+        for market_type in market_types {
+            let serialized = MarketTypeField::from(market_type).serialize().unwrap();
+            let deserialized = MarketTypeField::deserialize(&serialized).unwrap();
+
+            assert_eq!(deserialized.0, market_type);
+            assert_eq!(deserialized.raw_id(), None);
+        }
+    }
+
+    #[test]
+    fn test_backward_compatible_ids() {
+        assert_eq!(MarketTypeField::from(MarketType::Unknown).id(), 0);
+        assert_eq!(MarketTypeField::from(MarketType::Spot).id(), 1);
+        assert_eq!(MarketTypeField::from(MarketType::QuantoSwap).id(), 8);
+    }
+
+    #[test]
+    fn test_reserved_id_roundtrip() {
+        let deserialized = MarketTypeField::deserialize(&[200]).unwrap();
+
+        assert_eq!(deserialized.0, MarketType::Unknown);
+        assert_eq!(deserialized.raw_id(), Some(200));
+        assert_eq!(deserialized.serialize().unwrap(), [200]);
+    }
+}
@@ -1,20 +1,85 @@
 //! The module with a field to specify the type of a message.
 //! See [`MessageTypeField`].
 
+use std::cell::Cell;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+
 pub use crypto_msg_type::MessageType;
 
 use super::abstracts::derive_interop_converters;
-use super::{Field, FieldDeserializer, FieldError, FieldSerializer};
+use super::{Field, FieldDeserializer, FieldError, FieldResult, FieldSerializer};
+
+thread_local! {
+    /// If the unknown message types are deserialized leniently in this thread.
+    static LENIENT: Cell<bool> = Cell::new(false);
+}
 
 /// The type of a message (1 byte).
+///
+/// Deserializing an unknown ID fails with [`FieldError::UnimplementedMessageType`].
+/// Within [`MessageTypeField::lenient`], it is [`MessageType::Other`] instead.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MessageTypeField(pub MessageType);
 
+impl MessageTypeField {
+    /// Create a field from the ID of a message type.
+    ///
+    /// It returns [`FieldError::UnimplementedMessageType`] if the ID is unknown.
+    pub fn try_from_id(id: u8) -> FieldResult<Self> {
+        bit_deserialize_message_type(id)
+            .map(Self)
+            .ok_or(FieldError::UnimplementedMessageType(id))
+    }
+
+    /// Create a field from the ID of a message type.
+    ///
+    /// The unknown IDs are [`MessageType::Other`].
+    pub fn from_id_lenient(id: u8) -> Self {
+        Self(bit_deserialize_message_type(id).unwrap_or(MessageType::Other))
+    }
+
+    /// Deserialize the unknown IDs as [`MessageType::Other`]
+    /// in this thread until the returned guard is dropped.
+    ///
+    /// It is useful for reading the structures with the message
+    /// types added by a newer version.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wmjtyd_libstock::data::fields::message_type::MessageType;
+    /// use wmjtyd_libstock::data::fields::{FieldDeserializer, MessageTypeField};
+    ///
+    /// {
+    ///     let _guard = MessageTypeField::lenient();
+    ///     let field = MessageTypeField::deserialize(&[42]).unwrap();
+    ///     assert_eq!(field, MessageTypeField(MessageType::Other));
+    /// }
+    ///
+    /// assert!(MessageTypeField::deserialize(&[42]).is_err());
+    /// ```
+    pub fn lenient() -> LenientGuard {
+        let previous = LENIENT.with(|lenient| lenient.replace(true));
+
+        LenientGuard {
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+
+    /// The ID to serialize as.
+    pub fn id(&self) -> u8 {
+        bit_serialize_message_type(self.0)
+    }
+}
+
 impl FieldSerializer<1> for MessageTypeField {
     type Err = FieldError;
 
     fn serialize(&self) -> Result<[u8; 1], Self::Err> {
-        Ok([bit_serialize_message_type(self.0)])
+        Ok([self.id()])
     }
 }
 
@@ -22,18 +87,48 @@ impl FieldDeserializer<1> for MessageTypeField {
     type Err = FieldError;
 
     fn deserialize(src: &[u8; 1]) -> Result<Self, Self::Err> {
-        Ok(Self(bit_deserialize_message_type(src[0])))
+        if LENIENT.with(Cell::get) {
+            Ok(Self::from_id_lenient(src[0]))
+        } else {
+            Self::try_from_id(src[0])
+        }
     }
 }
 
 derive_interop_converters!(MessageTypeField, MessageType);
 
+/// The guard returned by [`MessageTypeField::lenient`].
+///
+/// The unknown IDs are rejected again when it is dropped. Like
+/// [`SkipCheckGuard`](super::schema_version::SkipCheckGuard), the guard
+/// is not [`Send`], and must not be held across an `.await`.
+#[derive(Debug)]
+#[must_use = "the unknown IDs are rejected again once the guard is dropped"]
+pub struct LenientGuard {
+    previous: bool,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for LenientGuard {
+    fn drop(&mut self) {
+        LENIENT.with(|lenient| lenient.set(self.previous));
+    }
+}
+
 // MessageType does not implement Hash; thus, it is not a
 // high-standard field (hsf).
 //
 // However, it implements Clone,
 impl Field<1> for MessageTypeField {}
 
+// The equal message types have the same identifier,
+// so hashing the identifier is consistent with `Eq`.
+impl Hash for MessageTypeField {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        bit_serialize_message_type(self.0).hash(state);
+    }
+}
+
 /// Serialize [`MessageType`] to 1 bit identifier.
 fn bit_serialize_message_type(mt: MessageType) -> u8 {
     match mt {
@@ -55,8 +150,11 @@ fn bit_serialize_message_type(mt: MessageType) -> u8 {
 }
 
 /// Deserialize a 1 bit identifier to a [`MessageType`].
-fn bit_deserialize_message_type(id: u8) -> MessageType {
-    match id {
+///
+/// It returns `None` if the identifier is unknown.
+fn bit_deserialize_message_type(id: u8) -> Option<MessageType> {
+    let message_type = match id {
+        0 => MessageType::Other,
         1 => MessageType::Trade,
         2 => MessageType::BBO,
         3 => MessageType::L2TopK,
@@ -68,6 +166,79 @@ fn bit_deserialize_message_type(id: u8) -> MessageType {
         9 => MessageType::Candlestick,
         10 => MessageType::OpenInterest,
         11 => MessageType::FundingRate,
-        _ => MessageType::Other,
+        12 => MessageType::LongShortRatio,
+        13 => MessageType::TakerVolume,
+        _ => return None,
+    };
+
+    Some(message_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MessageType, MessageTypeField};
+    use crate::data::fields::{FieldDeserializer, FieldError, FieldSerializer};
+
+    const KNOWN: [MessageType; 14] = [
+        MessageType::Other,
+        MessageType::Trade,
+        MessageType::BBO,
+        MessageType::L2TopK,
+        MessageType::L2Snapshot,
+        MessageType::L2Event,
+        MessageType::L3Snapshot,
+        MessageType::L3Event,
+        MessageType::Ticker,
+        MessageType::Candlestick,
+        MessageType::OpenInterest,
+        MessageType::FundingRate,
+        MessageType::LongShortRatio,
+        MessageType::TakerVolume,
+    ];
+
+    #[test]
+    fn test_roundtrip() {
+        for (id, message_type) in KNOWN.into_iter().enumerate() {
+            let field = MessageTypeField(message_type);
+            let serialized = field.serialize().unwrap();
+
+            assert_eq!(serialized, [id as u8], "{message_type:?}");
+            assert_eq!(MessageTypeField::deserialize(&serialized).unwrap(), field);
+            assert_eq!(MessageTypeField::try_from_id(id as u8).unwrap(), field);
+            assert_eq!(MessageTypeField::from_id_lenient(id as u8), field);
+        }
+    }
+
+    #[test]
+    fn test_unknown_id() {
+        for id in [14, 42, u8::MAX] {
+            assert!(matches!(
+                MessageTypeField::try_from_id(id),
+                Err(FieldError::UnimplementedMessageType(unknown)) if unknown == id
+            ));
+            assert_eq!(
+                MessageTypeField::from_id_lenient(id),
+                MessageTypeField(MessageType::Other)
+            );
+
+            assert!(MessageTypeField::deserialize(&[id]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_lenient_nested() {
+        let other = MessageTypeField(MessageType::Other);
+
+        let outer = MessageTypeField::lenient();
+        {
+            let _inner = MessageTypeField::lenient();
+            assert_eq!(MessageTypeField::deserialize(&[42]).unwrap(), other);
+        }
+
+        // Still lenient by the outer guard.
+        assert_eq!(MessageTypeField::deserialize(&[42]).unwrap(), other);
+
+        drop(outer);
+        assert!(MessageTypeField::deserialize(&[42]).is_err());
     }
 }
@@ -0,0 +1,80 @@
+//! The module with a field to specify an optional `u64`, such as a sequence ID.
+//! See [`OptionalU64Field`].
+
+use super::abstracts::{derive_hsf, derive_interop_converters};
+use super::{FieldDeserializer, FieldError, FieldSerializer};
+
+/// An optional `u64` (9 bytes).
+///
+/// The first byte is `1` if the value is present, and `0` otherwise.
+/// The following 8 bytes are the value in big endian, which are
+/// zero if the value is absent.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OptionalU64Field(pub Option<u64>);
+
+impl FieldSerializer<9> for OptionalU64Field {
+    type Err = FieldError;
+
+    fn serialize(&self) -> Result<[u8; 9], Self::Err> {
+        let mut result = [0u8; 9];
+
+        if let Some(value) = self.0 {
+            result[0] = 1;
+            result[1..].copy_from_slice(&value.to_be_bytes());
+        }
+
+        Ok(result)
+    }
+}
+
+impl FieldDeserializer<9> for OptionalU64Field {
+    type Err = FieldError;
+
+    fn deserialize(src: &[u8; 9]) -> Result<Self, Self::Err> {
+        let value = u64::from_be_bytes(src[1..].try_into().expect("should be 8 bytes"));
+
+        match src[0] {
+            0 => Ok(Self(None)),
+            1 => Ok(Self(Some(value))),
+            flag => Err(FieldError::UnexpectedPresenceFlag(flag)),
+        }
+    }
+}
+
+derive_interop_converters!(OptionalU64Field, Option<u64>);
+derive_hsf!(OptionalU64Field, Option<u64>, 9);
+
+#[cfg(test)]
+mod tests {
+    use super::OptionalU64Field;
+    use crate::data::fields::{FieldDeserializer, FieldError, FieldSerializer};
+
+    #[test]
+    fn test_serialize_deserialize() {
+        for value in [None, Some(0), Some(42), Some(u64::MAX)] {
+            let serialized = OptionalU64Field(value).serialize().unwrap();
+            assert_eq!(
+                OptionalU64Field::deserialize(&serialized).unwrap(),
+                OptionalU64Field(value)
+            );
+        }
+    }
+
+    #[test]
+    fn test_serialize_layout() {
+        assert_eq!(OptionalU64Field(None).serialize().unwrap(), [0; 9]);
+        assert_eq!(
+            OptionalU64Field(Some(0x0102)).serialize().unwrap(),
+            [1, 0, 0, 0, 0, 0, 0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn test_deserialize_unexpected_flag() {
+        assert!(matches!(
+            OptionalU64Field::deserialize(&[2, 0, 0, 0, 0, 0, 0, 0, 0]),
+            Err(FieldError::UnexpectedPresenceFlag(2))
+        ));
+    }
+}
@@ -3,12 +3,90 @@
 
 use super::abstracts::{derive_hsf, derive_interop_converters};
 use super::bimap::create_bimap;
-use super::{Either, FieldDeserializer, FieldError, FieldSerializer};
+use super::{Either, FieldDeserializer, FieldError, FieldResult, FieldSerializer};
 
 /// The period of a message (1 byte).
+///
+/// The supported periods are `1s`, `1m`, `2m`, `3m`, `5m`, `15m`, `30m`,
+/// `1h`, `2h`, `3h`, `4h`, `6h`, `12h`, `1d`, `3d`, `1w` and `1M` (1 month).
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PeriodField(pub String);
 
+impl PeriodField {
+    /// Parse a period, accepting the long forms of the units
+    /// such as `1min`, `4hour` and `1month`.
+    ///
+    /// The parsed period is normalized to the short form,
+    /// for example, both `1min` and `1m` are parsed to `1m`.
+    pub fn from_str_loose(s: &str) -> FieldResult<Self> {
+        let unimplemented = || FieldError::UnimplementedPeriod(Either::Left(s.to_string()));
+
+        let unit_at = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(unimplemented)?;
+        let (count, unit) = s.split_at(unit_at);
+        let count = count.parse::<u64>().map_err(|_| unimplemented())?;
+        let unit = normalize_unit(unit).ok_or_else(unimplemented)?;
+
+        let period = format!("{count}{unit}");
+        if PERIOD.get_by_left(period.as_str()).is_none() {
+            return Err(unimplemented());
+        }
+
+        Ok(Self(period))
+    }
+
+    /// Get the duration of this period in seconds.
+    ///
+    /// It returns `None` for `1M`, as the length of a month varies,
+    /// and for the unsupported periods.
+    pub fn duration_secs(&self) -> Option<u64> {
+        let secs = match self.0.as_str() {
+            "1s" => 1,
+            "1m" => 60,
+            "2m" => 2 * 60,
+            "3m" => 3 * 60,
+            "5m" => 5 * 60,
+            "15m" => 15 * 60,
+            "30m" => 30 * 60,
+            "1h" => 3600,
+            "2h" => 2 * 3600,
+            "3h" => 3 * 3600,
+            "4h" => 4 * 3600,
+            "6h" => 6 * 3600,
+            "12h" => 12 * 3600,
+            "1d" => 86400,
+            "3d" => 3 * 86400,
+            "1w" => 7 * 86400,
+            _ => return None,
+        };
+
+        Some(secs)
+    }
+}
+
+/// Normalize the unit of a period to its short form.
+fn normalize_unit(unit: &str) -> Option<&'static str> {
+    let unit = match unit {
+        "s" | "S" => "s",
+        "m" => "m",
+        "h" | "H" => "h",
+        "d" | "D" => "d",
+        "w" | "W" => "w",
+        "M" => "M",
+        _ => match unit.to_ascii_lowercase().as_str() {
+            "sec" | "secs" | "second" | "seconds" => "s",
+            "min" | "mins" | "minute" | "minutes" => "m",
+            "hour" | "hours" => "h",
+            "day" | "days" => "d",
+            "week" | "weeks" => "w",
+            "mon" | "month" | "months" => "M",
+            _ => return None,
+        },
+    };
+
+    Some(unit)
+}
+
 impl FieldSerializer<1> for PeriodField {
     type Err = FieldError;
 
@@ -51,4 +129,91 @@ create_bimap!(PERIOD {
     "5m" => 2,
     "30m" => 3,
     "1h" => 4,
+    "1s" => 5,
+    "2m" => 6,
+    "3m" => 7,
+    "2h" => 8,
+    "3h" => 9,
+    "6h" => 10,
+    "12h" => 11,
+    "3d" => 12,
+    "1w" => 13,
+    "1M" => 14,
+    "15m" => 15,
+    "4h" => 16,
+    "1d" => 17,
 });
+
+#[cfg(test)]
+mod tests {
+    use super::PeriodField;
+    use crate::data::fields::{Either, FieldDeserializer, FieldError, FieldSerializer};
+
+    #[test]
+    fn test_serialize_deserialize() {
+        let periods = [
+            "1s", "1m", "2m", "3m", "5m", "15m", "30m", "1h", "2h", "3h", "4h", "6h", "12h", "1d",
+            "3d", "1w", "1M",
+        ];
+        let mut bits = Vec::new();
+
+        for period in periods {
+            let field = PeriodField::from(period);
+            let bit = field.serialize().unwrap();
+            assert_eq!(PeriodField::deserialize(&bit).unwrap(), field);
+
+            assert!(!bits.contains(&bit[0]), "{period} shares a discriminant");
+            bits.push(bit[0]);
+        }
+
+        assert!(matches!(
+            PeriodField::from("7m").serialize(),
+            Err(FieldError::UnimplementedPeriod(Either::Left(_)))
+        ));
+        assert!(matches!(
+            PeriodField::deserialize(&[0]),
+            Err(FieldError::UnimplementedPeriod(Either::Right(0)))
+        ));
+    }
+
+    #[test]
+    fn test_duration_secs() {
+        assert_eq!(PeriodField::from("1s").duration_secs(), Some(1));
+        assert_eq!(PeriodField::from("3m").duration_secs(), Some(180));
+        assert_eq!(PeriodField::from("12h").duration_secs(), Some(43200));
+        assert_eq!(PeriodField::from("3d").duration_secs(), Some(259200));
+        assert_eq!(PeriodField::from("1w").duration_secs(), Some(604800));
+        assert_eq!(PeriodField::from("1M").duration_secs(), None);
+        assert_eq!(PeriodField::from("7m").duration_secs(), None);
+    }
+
+    #[test]
+    fn test_from_str_loose() {
+        let cases = [
+            ("1m", "1m"),
+            ("1min", "1m"),
+            ("15mins", "15m"),
+            ("1s", "1s"),
+            ("1sec", "1s"),
+            ("4hour", "4h"),
+            ("1H", "1h"),
+            ("1day", "1d"),
+            ("1D", "1d"),
+            ("1week", "1w"),
+            ("1M", "1M"),
+            ("1month", "1M"),
+            ("01m", "1m"),
+        ];
+
+        for (src, expected) in cases {
+            assert_eq!(PeriodField::from_str_loose(src).unwrap().0, expected, "{src}");
+        }
+
+        for src in ["", "m", "1", "7m", "1x", "1mo", "-1m"] {
+            assert!(matches!(
+                PeriodField::from_str_loose(src),
+                Err(FieldError::UnimplementedPeriod(Either::Left(_)))
+            ));
+        }
+    }
+}
@@ -9,7 +9,8 @@ use typed_builder::TypedBuilder;
 use super::{DecimalField, Field, FieldDeserializer, FieldError, FieldSerializer};
 
 /// The price data (10 bytes).
-#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PriceDataField {
     /// 價格 (10 bytes)
     ///
@@ -80,3 +81,106 @@ impl TryFrom<&Order> for PriceDataField {
 }
 
 impl Field<20> for PriceDataField {}
+
+/// The price data with the quantity quote (30 bytes).
+///
+/// Unlike [`PriceDataField`], it keeps `quantity_quote`, which
+/// matters for the USD-margined markets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PriceDataFieldExtended {
+    /// 價格 (10 bytes)
+    #[builder(setter(into))]
+    pub price: DecimalField<10>,
+
+    /// 基本量 (10 bytes)
+    #[builder(setter(into))]
+    pub quantity_base: DecimalField<10>,
+
+    /// 報價量 (10 bytes)
+    #[builder(setter(into))]
+    pub quantity_quote: DecimalField<10>,
+}
+
+impl FieldSerializer<30> for PriceDataFieldExtended {
+    type Err = FieldError;
+
+    fn serialize(&self) -> Result<[u8; 30], Self::Err> {
+        let mut bytes = [0; 30];
+
+        bytes[..10].copy_from_slice(&self.price.serialize()?);
+        bytes[10..20].copy_from_slice(&self.quantity_base.serialize()?);
+        bytes[20..].copy_from_slice(&self.quantity_quote.serialize()?);
+
+        Ok(bytes)
+    }
+}
+
+impl FieldDeserializer<30> for PriceDataFieldExtended {
+    type Err = FieldError;
+
+    fn deserialize(src: &[u8; 30]) -> Result<Self, Self::Err> {
+        let price = arrayref::array_ref![src, 0, 10];
+        let quantity_base = arrayref::array_ref![src, 10, 10];
+        let quantity_quote = arrayref::array_ref![src, 20, 10];
+
+        Ok(Self {
+            price: DecimalField::deserialize(price)?,
+            quantity_base: DecimalField::deserialize(quantity_base)?,
+            quantity_quote: DecimalField::deserialize(quantity_quote)?,
+        })
+    }
+}
+
+impl TryFrom<PriceDataFieldExtended> for Order {
+    type Error = FieldError;
+
+    fn try_from(value: PriceDataFieldExtended) -> Result<Self, Self::Error> {
+        Ok(Order {
+            price: value.price.try_into()?,
+            quantity_base: value.quantity_base.try_into()?,
+            quantity_quote: value.quantity_quote.try_into()?,
+            quantity_contract: None,
+        })
+    }
+}
+
+impl TryFrom<&Order> for PriceDataFieldExtended {
+    type Error = FieldError;
+
+    fn try_from(value: &Order) -> Result<Self, Self::Error> {
+        Ok(PriceDataFieldExtended {
+            price: value.price.into(),
+            quantity_base: value.quantity_base.into(),
+            quantity_quote: value.quantity_quote.into(),
+        })
+    }
+}
+
+impl Field<30> for PriceDataFieldExtended {}
+
+#[cfg(test)]
+mod tests {
+    use super::{Order, PriceDataFieldExtended};
+    use crate::data::fields::{FieldDeserializer, FieldSerializer};
+
+    #[test]
+    fn test_extended_roundtrip() {
+        let order = Order {
+            price: 19234.5,
+            quantity_base: 0.25,
+            quantity_quote: 4808.625,
+            quantity_contract: None,
+        };
+
+        let field = PriceDataFieldExtended::try_from(&order).unwrap();
+        let serialized = field.serialize().unwrap();
+        let deserialized = PriceDataFieldExtended::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, field);
+
+        let decoded_order = Order::try_from(deserialized).unwrap();
+        assert_eq!(decoded_order.price, order.price);
+        assert_eq!(decoded_order.quantity_base, order.quantity_base);
+        assert_eq!(decoded_order.quantity_quote, order.quantity_quote);
+    }
+}
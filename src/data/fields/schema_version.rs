@@ -0,0 +1,136 @@
+//! The module with a field to specify the schema version of a structure.
+//! See [`SchemaVersionField`].
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+
+use super::abstracts::{derive_hsf, derive_interop_converters};
+use super::{FieldDeserializer, FieldError, FieldResult, FieldSerializer};
+
+thread_local! {
+    /// If the schema version check is skipped in this thread.
+    static SKIP_CHECK: Cell<bool> = Cell::new(false);
+}
+
+/// The schema version of a structure (1 byte).
+///
+/// It is the very first byte of each serialized structure.
+/// The current version of a structure is defined as its
+/// `SCHEMA_VERSION` constant, for example `BboStructure::SCHEMA_VERSION`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SchemaVersionField(pub u8);
+
+impl SchemaVersionField {
+    /// Check if this version is the `expected` version.
+    ///
+    /// It always passes when the check is skipped with [`skip_check`](Self::skip_check).
+    pub fn check(&self, expected: u8) -> FieldResult<()> {
+        if self.0 == expected || SKIP_CHECK.with(Cell::get) {
+            Ok(())
+        } else {
+            Err(FieldError::SchemaMismatch {
+                expected,
+                got: self.0,
+            })
+        }
+    }
+
+    /// Skip the schema version check in this thread
+    /// until the returned guard is dropped.
+    ///
+    /// It is useful for reading the structures written by
+    /// a newer version in a forward-compatible way.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wmjtyd_libstock::data::fields::SchemaVersionField;
+    ///
+    /// {
+    ///     let _guard = SchemaVersionField::skip_check();
+    ///     assert!(SchemaVersionField(2).check(1).is_ok());
+    /// }
+    ///
+    /// assert!(SchemaVersionField(2).check(1).is_err());
+    /// ```
+    pub fn skip_check() -> SkipCheckGuard {
+        let previous = SKIP_CHECK.with(|skip| skip.replace(true));
+
+        SkipCheckGuard {
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+impl FieldSerializer<1> for SchemaVersionField {
+    type Err = FieldError;
+
+    fn serialize(&self) -> Result<[u8; 1], Self::Err> {
+        Ok([self.0])
+    }
+}
+
+impl FieldDeserializer<1> for SchemaVersionField {
+    type Err = FieldError;
+
+    fn deserialize(src: &[u8; 1]) -> Result<Self, Self::Err> {
+        Ok(Self(src[0]))
+    }
+}
+
+derive_interop_converters!(SchemaVersionField, u8);
+derive_hsf!(SchemaVersionField, u8, 1);
+
+/// The guard returned by [`SchemaVersionField::skip_check`].
+///
+/// The check is restored when it is dropped. As the check is skipped
+/// per thread, the guard is not [`Send`], and must not be held across
+/// an `.await`; otherwise the task may resume on another thread, and
+/// leave the check skipped in the original one.
+#[derive(Debug)]
+#[must_use = "the check is restored once the guard is dropped"]
+pub struct SkipCheckGuard {
+    previous: bool,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for SkipCheckGuard {
+    fn drop(&mut self) {
+        SKIP_CHECK.with(|skip| skip.set(self.previous));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SchemaVersionField;
+    use crate::data::fields::FieldError;
+
+    #[test]
+    fn test_check() {
+        assert!(SchemaVersionField(1).check(1).is_ok());
+        assert!(matches!(
+            SchemaVersionField(2).check(1),
+            Err(FieldError::SchemaMismatch {
+                expected: 1,
+                got: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn test_skip_check_nested() {
+        let outer = SchemaVersionField::skip_check();
+        {
+            let _inner = SchemaVersionField::skip_check();
+            assert!(SchemaVersionField(2).check(1).is_ok());
+        }
+
+        // Still skipped by the outer guard.
+        assert!(SchemaVersionField(2).check(1).is_ok());
+
+        drop(outer);
+        assert!(SchemaVersionField(2).check(1).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! The module with a field to specify if a message is a snapshot.
+//! See [`SnapshotField`].
+
+use super::abstracts::{derive_hsf, derive_interop_converters};
+use super::{FieldDeserializer, FieldError, FieldSerializer};
+
+/// If a message is a full snapshot or a delta (1 byte).
+///
+/// It is serialized as `1` for a snapshot, and `0` for a delta.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotField(pub bool);
+
+impl FieldSerializer<1> for SnapshotField {
+    type Err = FieldError;
+
+    fn serialize(&self) -> Result<[u8; 1], Self::Err> {
+        Ok([self.0.into()])
+    }
+}
+
+impl FieldDeserializer<1> for SnapshotField {
+    type Err = FieldError;
+
+    fn deserialize(src: &[u8; 1]) -> Result<Self, Self::Err> {
+        match src[0] {
+            0 => Ok(Self(false)),
+            1 => Ok(Self(true)),
+            flag => Err(FieldError::UnexpectedSnapshotFlag(flag)),
+        }
+    }
+}
+
+derive_interop_converters!(SnapshotField, bool);
+derive_hsf!(SnapshotField, bool, 1);
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotField;
+    use crate::data::fields::{FieldDeserializer, FieldError, FieldSerializer};
+
+    #[test]
+    fn test_serialize_deserialize() {
+        for snapshot in [false, true] {
+            let serialized = SnapshotField(snapshot).serialize().unwrap();
+            assert_eq!(serialized, [snapshot as u8]);
+            assert_eq!(
+                SnapshotField::deserialize(&serialized).unwrap(),
+                SnapshotField(snapshot)
+            );
+        }
+    }
+
+    #[test]
+    fn test_deserialize_unexpected_flag() {
+        assert!(matches!(
+            SnapshotField::deserialize(&[2]),
+            Err(FieldError::UnexpectedSnapshotFlag(2))
+        ));
+    }
+}
@@ -13,6 +13,7 @@ pub type Pair = String;
 
 /// The symbol of a message (2 bytes).
 #[derive(Debug, Clone, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SymbolPairField {
     pub symbol: Symbol,
     #[builder(setter(into))]
@@ -28,6 +29,46 @@ impl SymbolPairField {
             pair: pair.to_string(),
         }
     }
+
+    /// Create a field with the symbol ID (such as `1`) and the pair.
+    ///
+    /// If `symbol` is not a valid ID, the ID of `pair` is used,
+    /// like [`SymbolPairField::from_pair`].
+    pub fn new(symbol: &str, pair: &str) -> Self {
+        match symbol.parse::<Symbol>() {
+            Ok(symbol) => Self {
+                symbol,
+                pair: pair.to_string(),
+            },
+            Err(_) => Self::from_pair(pair),
+        }
+    }
+
+    /// Create a field with the pair `BASE/QUOTE`.
+    pub fn from_components(base: &str, quote: &str) -> Self {
+        Self::from_pair(&format!("{base}/{quote}"))
+    }
+
+    /// Get the base currency, which is the part before `/`.
+    ///
+    /// It is the whole pair if there is no `/`.
+    pub fn base(&self) -> &str {
+        self.pair.split_once('/').map_or(self.pair.as_str(), |(base, _)| base)
+    }
+
+    /// Get the quote currency, which is the part after `/`.
+    ///
+    /// It is empty if there is no `/`.
+    pub fn quote(&self) -> &str {
+        self.pair.split_once('/').map_or("", |(_, quote)| quote)
+    }
+}
+
+impl From<&str> for SymbolPairField {
+    /// See [`SymbolPairField::from_pair`].
+    fn from(pair: &str) -> Self {
+        Self::from_pair(pair)
+    }
 }
 
 impl From<(Symbol, Pair)> for SymbolPairField {
@@ -68,3 +109,42 @@ create_bimap!(SYMBOL_PAIR {
     4 => "ETH/USDT",
     5 => "ETH/USD",
 });
+
+#[cfg(test)]
+mod tests {
+    use super::SymbolPairField;
+
+    #[test]
+    fn test_from_components() {
+        let field = SymbolPairField::from_components("BTC", "USDT");
+        assert_eq!(field, SymbolPairField::from_pair("BTC/USDT"));
+        assert_eq!(field.symbol, 1);
+
+        let field = SymbolPairField::from_components("DOGE", "USDT");
+        assert_eq!(field.pair, "DOGE/USDT");
+        assert_eq!(field.symbol, 0);
+    }
+
+    #[test]
+    fn test_new() {
+        let field = SymbolPairField::new("42", "DOGE/USDT");
+        assert_eq!(field.symbol, 42);
+        assert_eq!(field.pair, "DOGE/USDT");
+
+        // Not an ID: look up the pair.
+        let field = SymbolPairField::new("ETHUSDT", "ETH/USDT");
+        assert_eq!(field.symbol, 4);
+        assert_eq!(field.pair, "ETH/USDT");
+    }
+
+    #[test]
+    fn test_base_quote() {
+        let field = SymbolPairField::from_pair("BTC/USDT");
+        assert_eq!(field.base(), "BTC");
+        assert_eq!(field.quote(), "USDT");
+
+        let field = SymbolPairField::from_pair("UNKNOWN");
+        assert_eq!(field.base(), "UNKNOWN");
+        assert_eq!(field.quote(), "");
+    }
+}
@@ -3,6 +3,8 @@
 
 use std::time::SystemTime;
 
+use chrono::{DateTime, SecondsFormat, TimeZone, Utc};
+
 use super::abstracts::{derive_hsf, derive_interop_converters};
 use super::{FieldDeserializer, FieldError, FieldResult, FieldSerializer};
 use crate::data::num::{six_byte_hex_to_unix_ms, unix_ms_to_six_byte_hex};
@@ -11,10 +13,36 @@ use crate::data::num::{six_byte_hex_to_unix_ms, unix_ms_to_six_byte_hex};
 ///
 /// The stored timestamp is in `ms`, which is a 13 digit numbers.
 /// Example: `1662300000000`
+///
+/// The timestamps larger than [`TimestampField::MAX`] can't be serialized.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TimestampField(pub u64);
 
 impl TimestampField {
+    /// The largest timestamp that fits in 6 bytes, which is in the year 10889.
+    pub const MAX: Self = Self((1 << 48) - 1);
+
+    /// Create a timestamp from the UNIX timestamp in `ms`,
+    /// which must be in `0..=TimestampField::MAX`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wmjtyd_libstock::data::fields::TimestampField;
+    ///
+    /// let timestamp = TimestampField::try_from_i64(1662300000000).unwrap();
+    /// assert_eq!(timestamp, TimestampField(1662300000000));
+    /// assert!(TimestampField::try_from_i64(-1).is_err());
+    /// ```
+    pub fn try_from_i64(ms: i64) -> FieldResult<Self> {
+        u64::try_from(ms)
+            .ok()
+            .map(Self)
+            .filter(|timestamp| timestamp.0 <= Self::MAX.0)
+            .ok_or(FieldError::TimestampOutOfRange(ms.into()))
+    }
+
     /// Create a new `ReceivedTimestamp` from the current time.
     pub fn new_from_now() -> FieldResult<Self> {
         let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
@@ -24,6 +52,54 @@ impl TimestampField {
 
         Ok(Self(now_sec))
     }
+
+    /// Parse a RFC 3339 (ISO 8601) timestamp, for example `2023-01-01T00:00:00Z`.
+    ///
+    /// The sub-millisecond part is truncated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wmjtyd_libstock::data::fields::TimestampField;
+    ///
+    /// let timestamp = TimestampField::from_rfc3339("2023-01-01T08:00:00+08:00").unwrap();
+    /// assert_eq!(timestamp, TimestampField(1672531200000));
+    /// ```
+    pub fn from_rfc3339(s: &str) -> FieldResult<Self> {
+        let ms = DateTime::parse_from_rfc3339(s)?.timestamp_millis();
+
+        Self::try_from_i64(ms)
+    }
+
+    /// Format this timestamp as a RFC 3339 string in UTC,
+    /// for example `2023-01-01T00:00:00.000Z`.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the timestamp is out of the range of [`DateTime`].
+    pub fn to_rfc3339(&self) -> String {
+        let ms = i64::try_from(self.0).expect("timestamp out of range");
+
+        Utc.timestamp_millis_opt(ms)
+            .single()
+            .expect("timestamp out of range")
+            .to_rfc3339_opts(SecondsFormat::Millis, true)
+    }
+}
+
+/// Format as RFC 3339 like [`TimestampField::to_rfc3339`],
+/// or the Unix milliseconds if it is out of the range of [`DateTime`].
+impl std::fmt::Display for TimestampField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let datetime = i64::try_from(self.0)
+            .ok()
+            .and_then(|ms| Utc.timestamp_millis_opt(ms).single());
+
+        match datetime {
+            Some(datetime) => f.write_str(&datetime.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            None => write!(f, "{}ms", self.0),
+        }
+    }
 }
 
 impl Default for TimestampField {
@@ -36,6 +112,10 @@ impl FieldSerializer<6> for TimestampField {
     type Err = FieldError;
 
     fn serialize(&self) -> Result<[u8; 6], Self::Err> {
+        if self.0 > Self::MAX.0 {
+            return Err(FieldError::TimestampOutOfRange(self.0.into()));
+        }
+
         Ok(unix_ms_to_six_byte_hex(self.0))
     }
 }
@@ -49,6 +129,8 @@ impl FieldDeserializer<6> for TimestampField {
 }
 
 impl From<i64> for TimestampField {
+    /// The negative timestamps are wrapped, and rejected on serialization.
+    /// Use [`TimestampField::try_from_i64`] to check them immediately.
     fn from(src: i64) -> Self {
         Self(src as u64)
     }
@@ -62,3 +144,178 @@ impl From<TimestampField> for i64 {
 
 derive_interop_converters!(TimestampField, u64);
 derive_hsf!(TimestampField, u64, 6);
+
+/// The timestamp field with the nanosecond precision (8 bytes).
+///
+/// The stored timestamp is in `ns`, which is a 19 digit numbers.
+/// Example: `1662300000000000000`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimestampNsField(pub u64);
+
+impl TimestampNsField {
+    /// Create a new `TimestampNsField` from the current time.
+    pub fn new_from_now() -> FieldResult<Self> {
+        let now = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+
+        debug_assert!(u64::try_from(now.as_nanos()).is_ok());
+        let now_ns = now.as_nanos() as u64;
+
+        Ok(Self(now_ns))
+    }
+
+    /// Create a new `TimestampNsField` from the UNIX timestamp in `ns`.
+    pub fn from_unix_ns(ns: u64) -> Self {
+        Self(ns)
+    }
+
+    /// Get the UNIX timestamp in `ns`.
+    pub fn to_unix_ns(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for TimestampNsField {
+    fn default() -> Self {
+        Self::new_from_now().expect("failed to get the system time")
+    }
+}
+
+impl FieldSerializer<8> for TimestampNsField {
+    type Err = FieldError;
+
+    fn serialize(&self) -> Result<[u8; 8], Self::Err> {
+        Ok(self.0.to_be_bytes())
+    }
+}
+
+impl FieldDeserializer<8> for TimestampNsField {
+    type Err = FieldError;
+
+    fn deserialize(src: &[u8; 8]) -> Result<Self, Self::Err> {
+        Ok(Self(u64::from_be_bytes(*src)))
+    }
+}
+
+impl From<TimestampField> for TimestampNsField {
+    fn from(src: TimestampField) -> Self {
+        Self(src.0 * 1_000_000)
+    }
+}
+
+impl From<TimestampNsField> for TimestampField {
+    /// The sub-millisecond part is truncated.
+    fn from(src: TimestampNsField) -> Self {
+        Self(src.0 / 1_000_000)
+    }
+}
+
+derive_interop_converters!(TimestampNsField, u64);
+derive_hsf!(TimestampNsField, u64, 8);
+
+#[cfg(test)]
+mod tests {
+    use super::{TimestampField, TimestampNsField};
+    use crate::data::fields::{FieldDeserializer, FieldError, FieldSerializer};
+
+    #[test]
+    fn test_from_rfc3339() {
+        // The same timestamp as the one in `data::num::timestamp`.
+        assert_eq!(
+            TimestampField::from_rfc3339("2022-07-05T03:26:33Z").unwrap(),
+            TimestampField(1656991593000)
+        );
+        assert_eq!(
+            TimestampField::from_rfc3339("2023-01-01T00:00:00.123Z").unwrap(),
+            TimestampField(1672531200123)
+        );
+
+        assert!(matches!(
+            TimestampField::from_rfc3339("2023-01-01 00:00:00"),
+            Err(FieldError::TimestampParseFailed(_))
+        ));
+        assert!(matches!(
+            TimestampField::from_rfc3339("1969-12-31T23:59:59Z"),
+            Err(FieldError::TimestampOutOfRange(-1000))
+        ));
+    }
+
+    #[test]
+    fn test_to_rfc3339() {
+        assert_eq!(TimestampField(1656991593000).to_rfc3339(), "2022-07-05T03:26:33.000Z");
+
+        let timestamp = TimestampField(1672531200123);
+        assert_eq!(TimestampField::from_rfc3339(&timestamp.to_rfc3339()).unwrap(), timestamp);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(TimestampField(1656991593000).to_string(), "2022-07-05T03:26:33.000Z");
+        assert_eq!(TimestampField(u64::MAX).to_string(), format!("{}ms", u64::MAX));
+    }
+
+    #[test]
+    fn test_serialize_range() {
+        let max = TimestampField::MAX;
+        let serialized = max.serialize().unwrap();
+        assert_eq!(serialized, [0xff; 6]);
+        assert_eq!(TimestampField::deserialize(&serialized).unwrap(), max);
+
+        assert!(matches!(
+            TimestampField(max.0 + 1).serialize(),
+            Err(FieldError::TimestampOutOfRange(0x1_0000_0000_0000))
+        ));
+        // The negative timestamp wrapped by `From<i64>`.
+        assert!(matches!(
+            TimestampField::from(-1i64).serialize(),
+            Err(FieldError::TimestampOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_try_from_i64() {
+        let max = TimestampField::MAX.0 as i64;
+
+        assert_eq!(TimestampField::try_from_i64(0).unwrap(), TimestampField(0));
+        assert_eq!(TimestampField::try_from_i64(max).unwrap(), TimestampField::MAX);
+        assert!(matches!(
+            TimestampField::try_from_i64(max + 1),
+            Err(FieldError::TimestampOutOfRange(_))
+        ));
+        assert!(matches!(
+            TimestampField::try_from_i64(-1),
+            Err(FieldError::TimestampOutOfRange(-1))
+        ));
+        assert!(matches!(
+            TimestampField::try_from_i64(i64::MIN),
+            Err(FieldError::TimestampOutOfRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_ns_roundtrip() {
+        let field = TimestampNsField::from_unix_ns(1662300000123456789);
+        let serialized = field.serialize().unwrap();
+
+        assert_eq!(serialized, 1662300000123456789u64.to_be_bytes());
+        assert_eq!(TimestampNsField::deserialize(&serialized).unwrap(), field);
+        assert_eq!(field.to_unix_ns(), 1662300000123456789);
+    }
+
+    #[test]
+    fn test_ms_ns_conversion() {
+        let ns = TimestampNsField::from(TimestampField(1662300000123));
+        assert_eq!(ns.to_unix_ns(), 1662300000123000000);
+
+        let ms = TimestampField::from(TimestampNsField::from_unix_ns(1662300000123456789));
+        assert_eq!(ms, TimestampField(1662300000123));
+    }
+
+    #[test]
+    fn test_ns_now() {
+        let ms = TimestampField::new_from_now().unwrap();
+        let ns = TimestampNsField::new_from_now().unwrap();
+
+        assert!(TimestampField::from(ns).0 >= ms.0);
+    }
+}
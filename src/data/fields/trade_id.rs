@@ -0,0 +1,88 @@
+//! The module with a field to specify the exchange-specific ID of a trade.
+//! See [`TradeIdField`].
+
+use super::abstracts::{derive_hsf, derive_interop_converters};
+use super::{FieldDeserializer, FieldError, FieldSerializer};
+
+/// The maximum length of a trade ID in bytes.
+pub const TRADE_ID_LEN: usize = 32;
+
+/// The exchange-specific ID of a trade (32 bytes).
+///
+/// The ID is stored as UTF-8 of at most 32 bytes. The shorter IDs
+/// are zero-padded, so they are null-terminated.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TradeIdField(pub String);
+
+impl FieldSerializer<TRADE_ID_LEN> for TradeIdField {
+    type Err = FieldError;
+
+    fn serialize(&self) -> Result<[u8; TRADE_ID_LEN], Self::Err> {
+        let id = self.0.as_bytes();
+        if id.len() > TRADE_ID_LEN {
+            return Err(FieldError::TradeIdTooLong(id.len()));
+        }
+
+        let mut result = [0u8; TRADE_ID_LEN];
+        result[..id.len()].copy_from_slice(id);
+
+        Ok(result)
+    }
+}
+
+impl FieldDeserializer<TRADE_ID_LEN> for TradeIdField {
+    type Err = FieldError;
+
+    fn deserialize(src: &[u8; TRADE_ID_LEN]) -> Result<Self, Self::Err> {
+        let len = src.iter().position(|&b| b == 0).unwrap_or(TRADE_ID_LEN);
+        let id = std::str::from_utf8(&src[..len]).map_err(FieldError::InvalidTradeId)?;
+
+        Ok(Self(id.to_string()))
+    }
+}
+
+impl From<&str> for TradeIdField {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+derive_interop_converters!(TradeIdField, String);
+derive_hsf!(TradeIdField, String, TRADE_ID_LEN);
+
+#[cfg(test)]
+mod tests {
+    use super::{TradeIdField, TRADE_ID_LEN};
+    use crate::data::fields::{FieldDeserializer, FieldError, FieldSerializer};
+
+    #[test]
+    fn test_serialize_deserialize() {
+        for id in ["", "12345678", "a1b2c3d4-e5f6-a7b8-c9d0-e1f2a3b4"] {
+            let serialized = TradeIdField::from(id).serialize().unwrap();
+            assert_eq!(&serialized[..id.len()], id.as_bytes());
+            assert!(serialized[id.len()..].iter().all(|&b| b == 0));
+
+            let deserialized = TradeIdField::deserialize(&serialized).unwrap();
+            assert_eq!(deserialized.0, id);
+        }
+    }
+
+    #[test]
+    fn test_serialize_too_long() {
+        let id = TradeIdField("x".repeat(TRADE_ID_LEN + 1));
+
+        assert!(matches!(id.serialize(), Err(FieldError::TradeIdTooLong(33))));
+    }
+
+    #[test]
+    fn test_deserialize_invalid_utf8() {
+        let mut src = [0u8; TRADE_ID_LEN];
+        src[0] = 0xff;
+
+        assert!(matches!(
+            TradeIdField::deserialize(&src),
+            Err(FieldError::InvalidTradeId(_))
+        ));
+    }
+}
@@ -1,14 +1,54 @@
 //! The module with a field to specify the [`TradeSide`] of a message.
 //! See [`TradeSideField`].
 
+use std::hash::{Hash, Hasher};
+
 pub use crypto_message::TradeSide;
 
 use super::abstracts::derive_interop_converters;
 use super::{Field, FieldDeserializer, FieldError, FieldResult, FieldSerializer};
 
 /// The [`TradeSide`] of a message (1 byte).
+///
+/// `None` means the side is unknown, which is serialized as `0`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct TradeSideField(pub TradeSide);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TradeSideField(pub Option<TradeSide>);
+
+impl TradeSideField {
+    /// The unknown side.
+    pub const UNKNOWN: Self = Self(None);
+
+    /// Parse `buy`, `sell` or `unknown`.
+    pub fn try_from_str(str: &str) -> FieldResult<Self> {
+        Ok(Self(match str {
+            "buy" => Some(TradeSide::Buy),
+            "sell" => Some(TradeSide::Sell),
+            "unknown" => None,
+            _ => Err(FieldError::UnimplementedTradeSide(str.to_string()))?,
+        }))
+    }
+
+    /// Whether the side is unknown.
+    pub fn is_unknown(&self) -> bool {
+        self.0.is_none()
+    }
+
+    /// Get `buy`, `sell` or `unknown`, which [`try_from_str`](Self::try_from_str) parses.
+    pub fn as_str(&self) -> &'static str {
+        match self.0 {
+            Some(TradeSide::Buy) => "buy",
+            Some(TradeSide::Sell) => "sell",
+            None => "unknown",
+        }
+    }
+}
+
+impl From<TradeSide> for TradeSideField {
+    fn from(side: TradeSide) -> Self {
+        Self(Some(side))
+    }
+}
 
 impl FieldSerializer<1> for TradeSideField {
     type Err = FieldError;
@@ -26,23 +66,84 @@ impl FieldDeserializer<1> for TradeSideField {
     }
 }
 
-derive_interop_converters!(TradeSideField, TradeSide);
+derive_interop_converters!(TradeSideField, Option<TradeSide>);
 
 impl Field<1> for TradeSideField {}
 
+// TradeSide does not implement Hash, so we hash its identifier.
+impl Hash for TradeSideField {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        bit_serialize_trade_side(self.0).hash(state);
+    }
+}
+
 /// Serialize [`TradeSide`] to 1 bit identifier.
-fn bit_serialize_trade_side(side: TradeSide) -> u8 {
+fn bit_serialize_trade_side(side: Option<TradeSide>) -> u8 {
     match side {
-        TradeSide::Buy => 1,
-        TradeSide::Sell => 2,
+        None => 0,
+        Some(TradeSide::Buy) => 1,
+        Some(TradeSide::Sell) => 2,
     }
 }
 
 /// Deserialize a 1 bit identifier to a [`TradeSide`].
-fn bit_deserialize_trade_side(id: u8) -> FieldResult<TradeSide> {
+fn bit_deserialize_trade_side(id: u8) -> FieldResult<Option<TradeSide>> {
     Ok(match id {
-        1 => TradeSide::Buy,
-        2 => TradeSide::Sell,
+        0 => None,
+        1 => Some(TradeSide::Buy),
+        2 => Some(TradeSide::Sell),
         _ => Err(FieldError::UnexpectedTradeSide(id))?,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{TradeSide, TradeSideField};
+    use crate::data::fields::{FieldDeserializer, FieldError, FieldSerializer};
+
+    #[test]
+    fn test_serialize_deserialize() {
+        let cases = [
+            (TradeSideField::UNKNOWN, 0),
+            (TradeSideField::from(TradeSide::Buy), 1),
+            (TradeSideField::from(TradeSide::Sell), 2),
+        ];
+
+        for (side, id) in cases {
+            assert_eq!(side.serialize().unwrap(), [id]);
+            assert_eq!(TradeSideField::deserialize(&[id]).unwrap(), side);
+        }
+    }
+
+    #[test]
+    fn test_deserialize_invalid() {
+        assert!(matches!(
+            TradeSideField::deserialize(&[3]),
+            Err(FieldError::UnexpectedTradeSide(3))
+        ));
+    }
+
+    #[test]
+    fn test_try_from_str() {
+        assert_eq!(
+            TradeSideField::try_from_str("buy").unwrap(),
+            TradeSideField(Some(TradeSide::Buy))
+        );
+        assert_eq!(
+            TradeSideField::try_from_str("sell").unwrap(),
+            TradeSideField(Some(TradeSide::Sell))
+        );
+        assert!(TradeSideField::try_from_str("unknown").unwrap().is_unknown());
+        assert!(matches!(
+            TradeSideField::try_from_str("hold"),
+            Err(FieldError::UnimplementedTradeSide(_))
+        ));
+    }
+
+    #[test]
+    fn test_as_str() {
+        for side in ["buy", "sell", "unknown"] {
+            assert_eq!(TradeSideField::try_from_str(side).unwrap().as_str(), side);
+        }
+    }
+}
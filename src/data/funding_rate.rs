@@ -11,12 +11,15 @@ use super::fields::{
     FieldError,
     MarketTypeField,
     MessageTypeField,
+    SchemaVersionField,
     SymbolPairField,
     TimestampField,
 };
 use super::serializer::{
     deserialize_block_builder,
     serialize_block_builder,
+    DataStructure,
+    FieldDeserializer,
     StructDeserializer,
     StructSerializer,
 };
@@ -29,14 +32,15 @@ pub type EstimatedRateField = DecimalField<10>;
 ///
 /// You can take advantage of `builder()`
 /// to construct some fields automatically.
-#[derive(Clone, Debug, PartialEq, Eq, TypedBuilder)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FundingRateStructure {
     /// 交易所時間戳
     #[builder(setter(into))]
     pub exchange_timestamp: TimestampField,
 
     /// 收到時間戳
-    #[builder(default)]
+    #[builder(default, setter(into))]
     pub received_timestamp: TimestampField,
 
     /// 交易所類型 (EXCHANGE)
@@ -52,6 +56,7 @@ pub struct FundingRateStructure {
     pub message_type: MessageTypeField,
 
     /// Symbol (ID，`1`) 或 Pair（字串，`BTC/USDT`）
+    #[builder(setter(into))]
     pub symbol: SymbolPairField,
 
     /// Funding rate
@@ -63,19 +68,34 @@ pub struct FundingRateStructure {
     pub funding_time: TimestampField,
 
     /// Estimated rate
-    #[builder(setter(into))]
-    pub estimated_rate: EstimatedRateField,
+    ///
+    /// Some exchanges omit it outside the funding window.
+    #[builder(default)]
+    pub estimated_rate: Option<EstimatedRateField>,
 
     /// 資料結尾
     #[builder(default)]
     pub end: EndOfDataFlag,
 }
 
+impl FundingRateStructure {
+    /// The current schema version of the serialized funding rate structure.
+    pub const SCHEMA_VERSION: u8 = 2;
+
+    /// Convert a [`FundingRateMsg`] to a funding rate structure.
+    ///
+    /// It is the same as [`TryFrom<&FundingRateMsg>`](TryFrom), but easier to find.
+    pub fn from_msg(msg: &FundingRateMsg) -> FundingRateResult<Self> {
+        Self::try_from(msg)
+    }
+}
+
 impl StructSerializer for FundingRateStructure {
     type Err = FundingRateError;
 
     fn serialize(&self, writer: &mut impl std::io::Write) -> Result<(), Self::Err> {
         serialize_block_builder!(
+            SchemaVersionField(Self::SCHEMA_VERSION),
             self.exchange_timestamp,
             self.received_timestamp,
             self.exchange_type,
@@ -97,6 +117,8 @@ impl StructDeserializer for FundingRateStructure {
     type Err = FundingRateError;
 
     fn deserialize(reader: &mut impl std::io::Read) -> Result<Self, Self::Err> {
+        SchemaVersionField::deserialize_from_reader(reader)??.check(Self::SCHEMA_VERSION)?;
+
         deserialize_block_builder!(
             reader =>
             exchange_timestamp,
@@ -113,22 +135,21 @@ impl StructDeserializer for FundingRateStructure {
     }
 }
 
+impl DataStructure for FundingRateStructure {}
+
 impl TryFrom<&FundingRateMsg> for FundingRateStructure {
     type Error = FundingRateError;
 
     fn try_from(msg: &FundingRateMsg) -> Result<Self, Self::Error> {
         Ok(Self::builder()
-            .exchange_timestamp(msg.timestamp)
+            .exchange_timestamp(TimestampField::try_from_i64(msg.timestamp)?)
             .exchange_type(ExchangeTypeField::try_from_str(&msg.exchange)?)
             .market_type(msg.market_type)
             .message_type(msg.msg_type)
             .symbol(SymbolPairField::from_pair(&msg.pair))
             .funding_rate(msg.funding_rate)
-            .funding_time(msg.funding_time)
-            .estimated_rate(
-                msg.estimated_rate
-                    .ok_or(FundingRateError::MissingEstimatedRate)?,
-            )
+            .funding_time(TimestampField::try_from_i64(msg.funding_time)?)
+            .estimated_rate(msg.estimated_rate.map(Into::into))
             .build())
     }
 }
@@ -148,7 +169,7 @@ impl TryFrom<FundingRateStructure> for FundingRateMsg {
             timestamp: s.exchange_timestamp.into(),
             funding_rate: s.funding_rate.try_into()?,
             funding_time: s.funding_time.into(),
-            estimated_rate: s.estimated_rate.to_f64(),
+            estimated_rate: s.estimated_rate.and_then(|rate| rate.to_f64()),
             json: String::new(),
         })
     }
@@ -159,9 +180,6 @@ pub enum FundingRateError {
     #[error("field error: {0}")]
     FieldError(#[from] FieldError),
 
-    #[error("estimated_rate in FundingRateMsg is None")]
-    MissingEstimatedRate,
-
     #[error("I/O reader/writer error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -175,7 +193,63 @@ pub type FundingRateResult<T> = Result<T, FundingRateError>;
 crate::compat::compat_enc!(
     enc = encode_funding_rate,
     dec = decode_funding_rate,
+    dec_partial = decode_funding_rate_partial,
     crawl = FundingRateMsg,
     result = FundingRateResult,
     structure = FundingRateStructure
 );
+
+#[cfg(test)]
+mod tests {
+    use crypto_market_type::MarketType;
+    use crypto_message::FundingRateMsg;
+
+    use super::FundingRateStructure;
+    use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+    fn construct_funding_rate_msg(estimated_rate: Option<f64>) -> FundingRateMsg {
+        FundingRateMsg {
+            exchange: "binance".into(),
+            market_type: MarketType::LinearSwap,
+            symbol: "BTCUSDT".into(),
+            pair: "BTC/USDT".into(),
+            msg_type: crypto_msg_type::MessageType::FundingRate,
+            timestamp: 1659755147000,
+            funding_rate: 0.0001,
+            funding_time: 1659772800000,
+            estimated_rate,
+            json: "".into(),
+        }
+    }
+
+    fn roundtrip(payload: &FundingRateMsg) -> FundingRateMsg {
+        let structure = FundingRateStructure::try_from(payload).unwrap();
+        let buffer = structure.serialize_to_vec().unwrap();
+        let decoded = FundingRateStructure::deserialize_from_bytes(&buffer).unwrap();
+        assert_eq!(decoded, structure);
+
+        FundingRateMsg::try_from(decoded).unwrap()
+    }
+
+    #[test]
+    fn test_funding_rate_encode_decode() {
+        let payload = construct_funding_rate_msg(Some(0.00025));
+        let decoded_msg = roundtrip(&payload);
+
+        assert_eq!(payload.exchange, decoded_msg.exchange);
+        assert_eq!(payload.pair, decoded_msg.pair);
+        assert_eq!(payload.timestamp, decoded_msg.timestamp);
+        assert_eq!(payload.funding_rate, decoded_msg.funding_rate);
+        assert_eq!(payload.funding_time, decoded_msg.funding_time);
+        assert_eq!(decoded_msg.estimated_rate, Some(0.00025));
+    }
+
+    #[test]
+    fn test_funding_rate_encode_decode_without_estimated_rate() {
+        let payload = construct_funding_rate_msg(None);
+        let decoded_msg = roundtrip(&payload);
+
+        assert_eq!(payload.funding_rate, decoded_msg.funding_rate);
+        assert_eq!(decoded_msg.estimated_rate, None);
+    }
+}
@@ -1,6 +1,10 @@
 //! The kline-related operations.
 
+mod aggregator;
+
+pub use aggregator::{GapPolicy, KlineAggregator};
 pub use crypto_message::CandlestickMsg;
+use rust_decimal::Decimal;
 use typed_builder::TypedBuilder;
 
 // FIXME: Rename to CandlestickMsg later.
@@ -14,25 +18,29 @@ use super::fields::{
     MarketTypeField,
     MessageTypeField,
     PeriodField,
+    SchemaVersionField,
     SymbolPairField,
     TimestampField,
 };
 use super::serializer::{
     deserialize_block_builder,
     serialize_block_builder,
+    DataStructure,
+    FieldDeserializer,
     StructDeserializer,
     StructSerializer,
 };
 
 /// The structure of a K-line (also known as Candlestick).
-#[derive(Clone, Debug, PartialEq, Eq, TypedBuilder)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct KlineStructure {
     /// 交易所時間戳
     #[builder(setter(into))]
     pub exchange_timestamp: TimestampField,
 
     /// 收到時間戳
-    #[builder(default)]
+    #[builder(default, setter(into))]
     pub received_timestamp: TimestampField,
 
     /// 交易所類型 (EXCHANGE)
@@ -48,6 +56,7 @@ pub struct KlineStructure {
     pub message_type: MessageTypeField,
 
     /// Symbol (ID，`1`) 或 Pair（字串，`BTC/USDT`）
+    #[builder(setter(into))]
     pub symbol: SymbolPairField,
 
     /// PERIOD
@@ -55,6 +64,7 @@ pub struct KlineStructure {
     pub period: PeriodField,
 
     /// K 線指標
+    #[builder(setter(into))]
     pub indicator: KlineIndicatorsField,
 
     /// 資料結尾
@@ -62,11 +72,143 @@ pub struct KlineStructure {
     pub end: EndOfDataFlag,
 }
 
+impl KlineStructure {
+    /// The current schema version of the serialized kline structure.
+    ///
+    /// Version 2 added `quote_volume` to [`KlineIndicatorsField`].
+    pub const SCHEMA_VERSION: u8 = 2;
+
+    /// The schema version with the 30-bytes [`KlineIndicatorsField`],
+    /// which can still be deserialized.
+    pub const LEGACY_SCHEMA_VERSION: u8 = 1;
+
+    /// Convert a [`CandlestickMsg`] to a kline structure.
+    ///
+    /// It is the same as [`TryFrom<&CandlestickMsg>`](TryFrom), but easier to find.
+    pub fn from_msg(msg: &CandlestickMsg) -> KlineResult<Self> {
+        Self::try_from(msg)
+    }
+
+    /// Deserialize the rest of a structure of [`Self::LEGACY_SCHEMA_VERSION`].
+    fn deserialize_legacy(reader: &mut impl std::io::Read) -> Result<Self, KlineError> {
+        Ok(Self {
+            exchange_timestamp: FieldDeserializer::deserialize_from_reader(reader)??,
+            received_timestamp: FieldDeserializer::deserialize_from_reader(reader)??,
+            exchange_type: FieldDeserializer::deserialize_from_reader(reader)??,
+            market_type: FieldDeserializer::deserialize_from_reader(reader)??,
+            message_type: FieldDeserializer::deserialize_from_reader(reader)??,
+            symbol: FieldDeserializer::deserialize_from_reader(reader)??,
+            period: FieldDeserializer::deserialize_from_reader(reader)??,
+            indicator: {
+                let mut buf = [0; 30];
+                reader.read_exact(&mut buf)?;
+
+                KlineIndicatorsField::deserialize_legacy(&buf)?
+            },
+            end: FieldDeserializer::deserialize_from_reader(reader)??,
+        })
+    }
+}
+
+impl KlineStructure {
+    /// Get the typical price, which is `(high + low + close) / 3`.
+    pub fn typical_price(&self) -> Decimal {
+        let indicator = &self.indicator;
+
+        (*indicator.high + *indicator.low + *indicator.close) / Decimal::from(3)
+    }
+
+    /// Get the true range, which is the greatest of `high - low`,
+    /// `|high - prev_close|` and `|low - prev_close|`.
+    ///
+    /// It is `high - low` if `prev_close` is `None`,
+    /// for example, for the first kline.
+    pub fn true_range(&self, prev_close: Option<Decimal>) -> Decimal {
+        let high = *self.indicator.high;
+        let low = *self.indicator.low;
+        let range = high - low;
+
+        match prev_close {
+            Some(prev_close) => range
+                .max((high - prev_close).abs())
+                .max((low - prev_close).abs()),
+            None => range,
+        }
+    }
+
+    /// Is the close price higher than or equal to the open price?
+    pub fn is_bullish(&self) -> bool {
+        *self.indicator.close >= *self.indicator.open
+    }
+
+    /// Is the close price lower than the open price?
+    pub fn is_bearish(&self) -> bool {
+        !self.is_bullish()
+    }
+}
+
+/// Format the OHLCV on one line.
+///
+/// # Example
+///
+/// ```
+/// use crypto_market_type::MarketType;
+/// use crypto_msg_type::MessageType;
+/// use rust_decimal::Decimal;
+/// use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+/// use wmjtyd_libstock::data::fields::{KlineIndicatorsField, SymbolPairField};
+/// use wmjtyd_libstock::data::kline::KlineStructure;
+///
+/// let structure = KlineStructure::builder()
+///     .exchange_timestamp(1672531200000u64)
+///     .exchange_type(Exchange::Binance)
+///     .market_type(MarketType::Spot)
+///     .message_type(MessageType::Candlestick)
+///     .symbol(SymbolPairField::from_pair("BTC/USDT"))
+///     .period("1m")
+///     .indicator(
+///         KlineIndicatorsField::builder()
+///             .open(Decimal::new(10, 0))
+///             .high(Decimal::new(125, 1))
+///             .low(Decimal::new(95, 1))
+///             .close(Decimal::new(12, 0))
+///             .volume(Decimal::new(300, 0))
+///             .build(),
+///     )
+///     .build();
+///
+/// assert_eq!(
+///     structure.to_string(),
+///     "Kline [binance/spot] BTC/USDT 1m @ 2023-01-01T00:00:00.000Z O=10 H=12.5 L=9.5 C=12 V=300"
+/// );
+/// ```
+impl std::fmt::Display for KlineStructure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let indicator = &self.indicator;
+
+        write!(
+            f,
+            "Kline [{}/{}] {} {} @ {} O={} H={} L={} C={} V={}",
+            self.exchange_type.as_str(),
+            self.market_type.0,
+            self.symbol.pair,
+            self.period.0,
+            self.exchange_timestamp,
+            indicator.open.0,
+            indicator.high.0,
+            indicator.low.0,
+            indicator.close.0,
+            indicator.volume.0,
+        )
+    }
+}
+
 impl StructSerializer for KlineStructure {
     type Err = KlineError;
 
     fn serialize(&self, writer: &mut impl std::io::Write) -> Result<(), Self::Err> {
         serialize_block_builder!(
+            SchemaVersionField(Self::SCHEMA_VERSION),
             self.exchange_timestamp,
             self.received_timestamp,
             self.exchange_type,
@@ -87,6 +229,13 @@ impl StructDeserializer for KlineStructure {
     type Err = KlineError;
 
     fn deserialize(reader: &mut impl std::io::Read) -> Result<Self, Self::Err> {
+        let version = SchemaVersionField::deserialize_from_reader(reader)??;
+
+        if version.0 == Self::LEGACY_SCHEMA_VERSION {
+            return Self::deserialize_legacy(reader);
+        }
+        version.check(Self::SCHEMA_VERSION)?;
+
         deserialize_block_builder!(
             reader =>
             exchange_timestamp,
@@ -102,12 +251,14 @@ impl StructDeserializer for KlineStructure {
     }
 }
 
+impl DataStructure for KlineStructure {}
+
 impl TryFrom<&CandlestickMsg> for KlineStructure {
     type Error = KlineError;
 
     fn try_from(value: &CandlestickMsg) -> Result<Self, Self::Error> {
         Ok(Self::builder()
-            .exchange_timestamp(value.timestamp)
+            .exchange_timestamp(TimestampField::try_from_i64(value.timestamp)?)
             .exchange_type(ExchangeTypeField::try_from_str(&value.exchange)?)
             .market_type(value.market_type)
             .message_type(value.msg_type)
@@ -120,6 +271,7 @@ impl TryFrom<&CandlestickMsg> for KlineStructure {
                     .low(value.low)
                     .close(value.close)
                     .volume(value.volume)
+                    .quote_volume(value.quote_volume.map(Into::into))
                     .build(),
             )
             .build())
@@ -146,7 +298,11 @@ impl TryFrom<KlineStructure> for CandlestickMsg {
             close: value.indicator.close.try_into()?,
             volume: value.indicator.volume.try_into()?,
             period: value.period.into(),
-            quote_volume: None,
+            quote_volume: value
+                .indicator
+                .quote_volume
+                .map(TryInto::try_into)
+                .transpose()?,
             // FIXME: begin_time
             begin_time: Default::default(),
         })
@@ -168,7 +324,109 @@ pub type KlineResult<T> = Result<T, KlineError>;
 crate::compat::compat_enc!(
     enc = encode_kline,
     dec = decode_kline,
+    dec_partial = decode_kline_partial,
     crawl = CandlestickMsg,
     result = KlineResult,
     structure = KlineStructure
 );
+
+#[cfg(test)]
+mod tests {
+    use crypto_market_type::MarketType;
+    use rust_decimal_macros::dec;
+
+    use super::{CandlestickMsg, KlineStructure};
+    use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+    fn construct_klinemsg(quote_volume: Option<f64>) -> CandlestickMsg {
+        CandlestickMsg {
+            exchange: "huobi".into(),
+            market_type: MarketType::Spot,
+            msg_type: crypto_msg_type::MessageType::Candlestick,
+            symbol: "btcusdt".into(),
+            pair: "BTC/USDT".into(),
+            timestamp: 1659755147000,
+            period: "1m".into(),
+            begin_time: 0,
+            open: 1.0,
+            high: 4.0,
+            low: 0.5,
+            close: 2.0,
+            volume: 100.0,
+            quote_volume,
+            json: "".into(),
+        }
+    }
+
+    #[test]
+    fn test_kline_quote_volume_roundtrip() {
+        for quote_volume in [None, Some(200.5)] {
+            let structure = KlineStructure::try_from(&construct_klinemsg(quote_volume)).unwrap();
+
+            let encoded = structure.serialize_to_vec().unwrap();
+            let decoded = KlineStructure::deserialize_from_bytes(&encoded).unwrap();
+            assert_eq!(decoded, structure);
+
+            let msg = CandlestickMsg::try_from(decoded).unwrap();
+            assert_eq!(msg.quote_volume, quote_volume);
+        }
+    }
+
+    #[test]
+    fn test_kline_legacy_schema() {
+        let structure = KlineStructure::try_from(&construct_klinemsg(None)).unwrap();
+        let mut encoded = structure.serialize_to_vec().unwrap();
+
+        // Convert to the legacy layout: drop the quote volume
+        // section (11 bytes) before the end flag.
+        encoded[0] = KlineStructure::LEGACY_SCHEMA_VERSION;
+        let end = encoded.len() - 1;
+        encoded.drain(end - 11..end);
+
+        let decoded = KlineStructure::deserialize_from_bytes(&encoded).unwrap();
+        assert_eq!(decoded, structure);
+    }
+
+    #[test]
+    fn test_kline_typical_price() {
+        let structure = KlineStructure::try_from(&construct_klinemsg(None)).unwrap();
+
+        // (4 + 0.5 + 2) / 3
+        assert_eq!(structure.typical_price(), dec!(6.5) / dec!(3));
+    }
+
+    #[test]
+    fn test_kline_true_range() {
+        let structure = KlineStructure::try_from(&construct_klinemsg(None)).unwrap();
+
+        assert_eq!(structure.true_range(None), dec!(3.5));
+        // The previous close is inside [low, high].
+        assert_eq!(structure.true_range(Some(dec!(2))), dec!(3.5));
+        // |low - prev_close| = |0.5 - 5|
+        assert_eq!(structure.true_range(Some(dec!(5))), dec!(4.5));
+        // |high - prev_close| = |4 - 0.1|
+        assert_eq!(structure.true_range(Some(dec!(0.1))), dec!(3.9));
+    }
+
+    #[test]
+    fn test_kline_bullish_bearish() {
+        let structure = KlineStructure::try_from(&construct_klinemsg(None)).unwrap();
+        assert!(structure.is_bullish());
+        assert!(!structure.is_bearish());
+
+        let msg = CandlestickMsg {
+            close: 0.5,
+            ..construct_klinemsg(None)
+        };
+        let structure = KlineStructure::try_from(&msg).unwrap();
+        assert!(!structure.is_bullish());
+        assert!(structure.is_bearish());
+
+        let msg = CandlestickMsg {
+            close: 1.0,
+            ..construct_klinemsg(None)
+        };
+        let structure = KlineStructure::try_from(&msg).unwrap();
+        assert!(structure.is_bullish());
+    }
+}
@@ -0,0 +1,536 @@
+//! The aggregator building klines from the trades.
+//!
+//! [`KlineAggregator`] buckets the trades of each symbol by their
+//! exchange timestamp. A bucket of a period `p` starting at `t`
+//! covers `[t, t + p)`, so a trade exactly on the boundary belongs
+//! to the next bucket.
+//!
+//! A bucket is completed when a trade of the same symbol at or after
+//! `t + p + lateness` is pushed. The trades arriving before that are
+//! still aggregated even if they are out of order; the trades of a
+//! completed bucket are dropped and counted in
+//! [`KlineAggregator::late_trades`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use rust_decimal::Decimal;
+
+use super::{KlineResult, KlineStructure};
+use crate::data::fields::{
+    Either,
+    ExchangeTypeField,
+    FieldError,
+    KlineIndicatorsField,
+    MarketTypeField,
+    PeriodField,
+    SymbolPairField,
+    TimestampField,
+};
+use crate::data::trade::{TradeMsg, TradeResult, TradeStructure};
+
+/// How to handle the buckets without any trade.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum GapPolicy {
+    /// Emit nothing for the empty buckets.
+    #[default]
+    Skip,
+
+    /// Emit a candle with the previous close price
+    /// and zero volume for each empty bucket.
+    Fill,
+}
+
+/// Get the duration of a period (such as `1m`) in milliseconds.
+fn period_millis(period: &str) -> Option<u64> {
+    let unit_at = period.len().checked_sub(1)?;
+    let (count, unit) = period.split_at(unit_at);
+    let count = count.parse::<u64>().ok().filter(|&c| c > 0)?;
+
+    let unit = match unit {
+        "s" => 1_000,
+        "m" => 60_000,
+        "h" => 3_600_000,
+        "d" => 86_400_000,
+        "w" => 604_800_000,
+        _ => return None,
+    };
+
+    count.checked_mul(unit)
+}
+
+/// The in-progress state of a bucket.
+#[derive(Clone, Debug)]
+struct Candle {
+    /// The timestamp and the price of the earliest trade.
+    open: (u64, Decimal),
+    /// The timestamp and the price of the latest trade.
+    close: (u64, Decimal),
+    high: Decimal,
+    low: Decimal,
+    volume: Decimal,
+    quote_volume: Decimal,
+}
+
+impl Candle {
+    fn new(timestamp: u64, price: Decimal) -> Self {
+        Self {
+            open: (timestamp, price),
+            close: (timestamp, price),
+            high: price,
+            low: price,
+            volume: Decimal::ZERO,
+            quote_volume: Decimal::ZERO,
+        }
+    }
+
+    fn push(&mut self, timestamp: u64, price: Decimal, volume: Decimal, quote_volume: Decimal) {
+        // The first trade arrived wins the open price on a tie,
+        // and the last trade arrived wins the close price.
+        if timestamp < self.open.0 {
+            self.open = (timestamp, price);
+        }
+        if timestamp >= self.close.0 {
+            self.close = (timestamp, price);
+        }
+
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.volume += volume;
+        self.quote_volume += quote_volume;
+    }
+}
+
+/// The symbol a trade belongs to.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct SymbolKey {
+    exchange_type: ExchangeTypeField,
+    market_type: MarketTypeField,
+    symbol: SymbolPairField,
+}
+
+/// The buckets of a (symbol, period).
+#[derive(Clone, Debug)]
+struct Series {
+    period: PeriodField,
+    period_ms: u64,
+    /// The latest exchange timestamp pushed.
+    watermark: u64,
+    /// The start of the bucket after the last completed one.
+    next_start: Option<u64>,
+    /// The close price of the last completed bucket.
+    last_close: Option<Decimal>,
+    candles: BTreeMap<u64, Candle>,
+}
+
+impl Series {
+    fn new(period: PeriodField, period_ms: u64) -> Self {
+        Self {
+            period,
+            period_ms,
+            watermark: 0,
+            next_start: None,
+            last_close: None,
+            candles: BTreeMap::new(),
+        }
+    }
+
+    /// Add a trade, returning `false` if its bucket has been completed.
+    fn push(&mut self, timestamp: u64, price: Decimal, volume: Decimal, quote: Decimal) -> bool {
+        let start = timestamp - timestamp % self.period_ms;
+        if matches!(self.next_start, Some(next_start) if start < next_start) {
+            return false;
+        }
+
+        self.watermark = self.watermark.max(timestamp);
+        self.candles
+            .entry(start)
+            .or_insert_with(|| Candle::new(timestamp, price))
+            .push(timestamp, price, volume, quote);
+
+        true
+    }
+
+    /// Move the buckets ended before `watermark - lateness` to `completed`.
+    ///
+    /// If `force` is `true`, all the buckets are moved.
+    fn complete(
+        &mut self,
+        key: &SymbolKey,
+        lateness: u64,
+        gap_policy: GapPolicy,
+        force: bool,
+        completed: &mut Vec<KlineStructure>,
+    ) {
+        while let Some(start) = self.candles.keys().next().copied() {
+            let end = start + self.period_ms;
+            if !force && end.saturating_add(lateness) > self.watermark {
+                break;
+            }
+
+            if let (GapPolicy::Fill, Some(mut gap), Some(close)) =
+                (gap_policy, self.next_start, self.last_close)
+            {
+                while gap < start {
+                    let empty = Candle::new(gap, close);
+                    completed.push(self.build(key, gap, &empty));
+                    gap += self.period_ms;
+                }
+            }
+
+            let candle = self.candles.remove(&start).expect("the key should exist");
+            completed.push(self.build(key, start, &candle));
+            self.last_close = Some(candle.close.1);
+            self.next_start = Some(end);
+        }
+    }
+
+    fn build(&self, key: &SymbolKey, start: u64, candle: &Candle) -> KlineStructure {
+        KlineStructure::builder()
+            .exchange_timestamp(TimestampField(start))
+            .exchange_type(key.exchange_type)
+            .market_type(key.market_type)
+            .message_type(crypto_msg_type::MessageType::Candlestick)
+            .symbol(key.symbol.clone())
+            .period(self.period.clone())
+            .indicator(
+                KlineIndicatorsField::builder()
+                    .open(candle.open.1)
+                    .high(candle.high)
+                    .low(candle.low)
+                    .close(candle.close.1)
+                    .volume(candle.volume)
+                    .quote_volume(Some(candle.quote_volume.into()))
+                    .build(),
+            )
+            .build()
+    }
+}
+
+/// The aggregator building klines of several periods from the trades.
+///
+/// See the [module documentation](self) for more information.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use wmjtyd_libstock::data::kline::{GapPolicy, KlineAggregator};
+///
+/// let aggregator = KlineAggregator::new(&["1s", "1m"])
+///     .expect("unsupported period")
+///     .lateness(Duration::from_millis(500))
+///     .gap_policy(GapPolicy::Fill);
+/// ```
+#[derive(Clone, Debug)]
+pub struct KlineAggregator {
+    periods: Vec<(PeriodField, u64)>,
+    lateness: u64,
+    gap_policy: GapPolicy,
+    series: HashMap<SymbolKey, Vec<Series>>,
+    completed: Vec<KlineStructure>,
+    late_trades: u64,
+}
+
+impl KlineAggregator {
+    /// Create an aggregator building the klines of `periods`.
+    ///
+    /// The period is a positive count followed by the unit
+    /// `s`, `m`, `h`, `d` or `w`, for example, `1s` and `5m`.
+    pub fn new(periods: &[&str]) -> KlineResult<Self> {
+        let periods = periods
+            .iter()
+            .map(|&period| match period_millis(period) {
+                Some(ms) => Ok((PeriodField::from(period), ms)),
+                None => Err(FieldError::UnimplementedPeriod(Either::Left(period.to_string()))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            periods,
+            lateness: 0,
+            gap_policy: GapPolicy::default(),
+            series: HashMap::new(),
+            completed: Vec::new(),
+            late_trades: 0,
+        })
+    }
+
+    /// Set how long to wait for the out-of-order trades
+    /// after a bucket ends. The default is `0`.
+    pub fn lateness(mut self, lateness: Duration) -> Self {
+        self.lateness = lateness.as_millis().try_into().unwrap_or(u64::MAX);
+        self
+    }
+
+    /// Set how to handle the buckets without any trade.
+    /// The default is [`GapPolicy::Skip`].
+    pub fn gap_policy(mut self, gap_policy: GapPolicy) -> Self {
+        self.gap_policy = gap_policy;
+        self
+    }
+
+    /// Add a trade to the buckets of each period.
+    pub fn push_trade(&mut self, trade: &TradeStructure) {
+        let key = SymbolKey {
+            exchange_type: trade.exchange_type,
+            market_type: trade.market_type,
+            symbol: trade.symbol.clone(),
+        };
+        let timestamp = trade.exchange_timestamp.0;
+        let price = *trade.trade_price.price;
+        let volume = *trade.trade_price.quantity_base;
+        let quote = *trade.quantity_quote;
+
+        let periods = &self.periods;
+        let series = self.series.entry(key.clone()).or_insert_with(|| {
+            periods
+                .iter()
+                .map(|(period, ms)| Series::new(period.clone(), *ms))
+                .collect()
+        });
+
+        for s in series {
+            if !s.push(timestamp, price, volume, quote) {
+                tracing::debug!("Trade of {} at {timestamp} is too late; dropped.", s.period.0);
+                self.late_trades += 1;
+                continue;
+            }
+
+            s.complete(&key, self.lateness, self.gap_policy, false, &mut self.completed);
+        }
+    }
+
+    /// Convert a [`TradeMsg`] and add it to the buckets of each period.
+    pub fn push_trade_msg(&mut self, trade: &TradeMsg) -> TradeResult<()> {
+        self.push_trade(&TradeStructure::from_msg(trade)?);
+
+        Ok(())
+    }
+
+    /// Take the completed klines, in the order they were completed.
+    pub fn poll_completed(&mut self) -> Vec<KlineStructure> {
+        std::mem::take(&mut self.completed)
+    }
+
+    /// Complete all the in-progress klines and take the completed klines.
+    ///
+    /// It is useful when the trade stream ends.
+    pub fn flush(&mut self) -> Vec<KlineStructure> {
+        for (key, series) in &mut self.series {
+            for s in series {
+                s.complete(key, self.lateness, self.gap_policy, true, &mut self.completed);
+            }
+        }
+
+        self.poll_completed()
+    }
+
+    /// Get the latest in-progress kline of a symbol and a period.
+    pub fn current(
+        &self,
+        exchange_type: ExchangeTypeField,
+        market_type: MarketTypeField,
+        symbol: &SymbolPairField,
+        period: &str,
+    ) -> Option<KlineStructure> {
+        let key = SymbolKey {
+            exchange_type,
+            market_type,
+            symbol: symbol.clone(),
+        };
+
+        let s = self.series.get(&key)?.iter().find(|s| s.period.0 == period)?;
+        let (start, candle) = s.candles.iter().next_back()?;
+
+        Some(s.build(&key, *start, candle))
+    }
+
+    /// Get the count of the trades dropped because their buckets
+    /// have been completed, for each period.
+    pub fn late_trades(&self) -> u64 {
+        self.late_trades
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crypto_market_type::MarketType;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    use super::{period_millis, GapPolicy, KlineAggregator};
+    use crate::data::fields::{ExchangeTypeField, SymbolPairField};
+    use crate::data::kline::KlineStructure;
+    use crate::data::trade::{TradeMsg, TradeStructure};
+
+    fn trade(timestamp: i64, price: f64, quantity: f64) -> TradeStructure {
+        TradeStructure::from_msg(&TradeMsg {
+            exchange: "binance".into(),
+            market_type: MarketType::Spot,
+            symbol: "BTCUSDT".into(),
+            pair: "BTC/USDT".into(),
+            msg_type: crypto_msg_type::MessageType::Trade,
+            timestamp,
+            price,
+            quantity_base: quantity,
+            quantity_quote: price * quantity,
+            quantity_contract: None,
+            side: crypto_message::TradeSide::Buy,
+            trade_id: "1".into(),
+            json: "".into(),
+        })
+        .unwrap()
+    }
+
+    /// Get `(start, open, high, low, close, volume)` of a kline.
+    fn ohlcv(kline: &KlineStructure) -> (u64, Decimal, Decimal, Decimal, Decimal, Decimal) {
+        let indicator = &kline.indicator;
+
+        (
+            kline.exchange_timestamp.0,
+            *indicator.open,
+            *indicator.high,
+            *indicator.low,
+            *indicator.close,
+            *indicator.volume,
+        )
+    }
+
+    #[test]
+    fn test_period_millis() {
+        assert_eq!(period_millis("1s"), Some(1_000));
+        assert_eq!(period_millis("5m"), Some(300_000));
+        assert_eq!(period_millis("1h"), Some(3_600_000));
+        assert_eq!(period_millis("1w"), Some(604_800_000));
+        assert_eq!(period_millis("0m"), None);
+        assert_eq!(period_millis("m"), None);
+        assert_eq!(period_millis("1M"), None);
+        assert_eq!(period_millis(""), None);
+
+        assert!(KlineAggregator::new(&["1s", "1x"]).is_err());
+    }
+
+    #[test]
+    fn test_aggregate_boundaries() {
+        let mut aggregator = KlineAggregator::new(&["1s"]).unwrap();
+
+        aggregator.push_trade(&trade(1_000, 10.0, 1.0));
+        aggregator.push_trade(&trade(1_500, 12.0, 2.0));
+        aggregator.push_trade(&trade(1_200, 8.0, 1.0));
+        assert!(aggregator.poll_completed().is_empty());
+
+        // Exactly on the boundary: completes [1000, 2000)
+        // and starts [2000, 3000).
+        aggregator.push_trade(&trade(2_000, 11.0, 3.0));
+        let completed = aggregator.poll_completed();
+        assert_eq!(
+            completed.iter().map(ohlcv).collect::<Vec<_>>(),
+            [(1_000, dec!(10), dec!(12), dec!(8), dec!(12), dec!(4))]
+        );
+        assert_eq!(
+            completed[0].indicator.quote_volume.map(|v| *v),
+            Some(dec!(42))
+        );
+        assert_eq!(completed[0].period.0, "1s");
+
+        let current = aggregator
+            .current(
+                ExchangeTypeField::try_from_str("binance").unwrap(),
+                MarketType::Spot.into(),
+                &SymbolPairField::from_pair("BTC/USDT"),
+                "1s",
+            )
+            .unwrap();
+        assert_eq!(
+            ohlcv(&current),
+            (2_000, dec!(11), dec!(11), dec!(11), dec!(11), dec!(3))
+        );
+
+        // [1000, 2000) has been completed.
+        aggregator.push_trade(&trade(1_999, 100.0, 1.0));
+        assert_eq!(aggregator.late_trades(), 1);
+
+        assert_eq!(
+            aggregator.flush().iter().map(ohlcv).collect::<Vec<_>>(),
+            [(2_000, dec!(11), dec!(11), dec!(11), dec!(11), dec!(3))]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_lateness() {
+        let mut aggregator = KlineAggregator::new(&["1s"])
+            .unwrap()
+            .lateness(Duration::from_millis(500));
+
+        aggregator.push_trade(&trade(1_100, 10.0, 1.0));
+        aggregator.push_trade(&trade(2_100, 20.0, 1.0));
+        assert!(aggregator.poll_completed().is_empty());
+
+        // Still in the lateness window; it becomes the open price.
+        aggregator.push_trade(&trade(1_000, 9.0, 1.0));
+        aggregator.push_trade(&trade(2_500, 21.0, 1.0));
+
+        assert_eq!(
+            aggregator.poll_completed().iter().map(ohlcv).collect::<Vec<_>>(),
+            [(1_000, dec!(9), dec!(10), dec!(9), dec!(10), dec!(2))]
+        );
+        assert_eq!(aggregator.late_trades(), 0);
+    }
+
+    #[test]
+    fn test_aggregate_gaps() {
+        let trades = [
+            trade(1_000, 10.0, 1.0),
+            trade(4_000, 20.0, 1.0),
+            trade(5_000, 30.0, 1.0),
+        ];
+
+        let mut aggregator = KlineAggregator::new(&["1s"]).unwrap();
+        trades.iter().for_each(|t| aggregator.push_trade(t));
+        assert_eq!(
+            aggregator.poll_completed().iter().map(ohlcv).collect::<Vec<_>>(),
+            [
+                (1_000, dec!(10), dec!(10), dec!(10), dec!(10), dec!(1)),
+                (4_000, dec!(20), dec!(20), dec!(20), dec!(20), dec!(1)),
+            ]
+        );
+
+        let mut aggregator = KlineAggregator::new(&["1s"])
+            .unwrap()
+            .gap_policy(GapPolicy::Fill);
+        trades.iter().for_each(|t| aggregator.push_trade(t));
+        assert_eq!(
+            aggregator.poll_completed().iter().map(ohlcv).collect::<Vec<_>>(),
+            [
+                (1_000, dec!(10), dec!(10), dec!(10), dec!(10), dec!(1)),
+                (2_000, dec!(10), dec!(10), dec!(10), dec!(10), dec!(0)),
+                (3_000, dec!(10), dec!(10), dec!(10), dec!(10), dec!(0)),
+                (4_000, dec!(20), dec!(20), dec!(20), dec!(20), dec!(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_multiple_periods() {
+        let mut aggregator = KlineAggregator::new(&["1s", "1m"]).unwrap();
+
+        aggregator.push_trade(&trade(59_000, 10.0, 1.0));
+        aggregator.push_trade(&trade(59_999, 11.0, 1.0));
+        aggregator.push_trade(&trade(60_000, 12.0, 1.0));
+
+        let completed = aggregator.poll_completed();
+        let periods = completed.iter().map(|k| k.period.0.as_str()).collect::<Vec<_>>();
+        assert_eq!(periods, ["1s", "1m"]);
+        assert_eq!(
+            completed.iter().map(ohlcv).collect::<Vec<_>>(),
+            [
+                (59_000, dec!(10), dec!(11), dec!(10), dec!(11), dec!(2)),
+                (0, dec!(10), dec!(11), dec!(10), dec!(11), dec!(2)),
+            ]
+        );
+    }
+}
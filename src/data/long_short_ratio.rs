@@ -0,0 +1,183 @@
+//! The long/short ratio related operations.
+
+use typed_builder::TypedBuilder;
+
+use super::fields::{
+    DecimalField,
+    EndOfDataFlag,
+    ExchangeTypeField,
+    FieldError,
+    MarketTypeField,
+    MessageTypeField,
+    SchemaVersionField,
+    SymbolPairField,
+    TimestampField,
+};
+use super::serializer::{
+    deserialize_block_builder,
+    serialize_block_builder,
+    DataStructure,
+    FieldDeserializer,
+    StructDeserializer,
+    StructSerializer,
+};
+
+/// The ratio of the long accounts to the short accounts.
+pub type LongShortRatioField = DecimalField<5>;
+/// The percentage of the long or short accounts, from 0 to 1.
+pub type AccountRatioField = DecimalField<5>;
+
+/// The structure of the long/short account ratio.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LongShortRatioStructure {
+    /// 交易所時間戳
+    #[builder(setter(into))]
+    pub exchange_timestamp: TimestampField,
+
+    /// 收到時間戳
+    #[builder(default, setter(into))]
+    pub received_timestamp: TimestampField,
+
+    /// 交易所類型 (EXCHANGE)
+    #[builder(setter(into))]
+    pub exchange_type: ExchangeTypeField,
+
+    /// 市場類型 (MARKET_TYPE)
+    #[builder(setter(into))]
+    pub market_type: MarketTypeField,
+
+    /// 訊息類型 (MESSAGE_TYPE)
+    #[builder(setter(into))]
+    pub message_type: MessageTypeField,
+
+    /// Symbol (ID，`1`) 或 Pair（字串，`BTC/USDT`）
+    #[builder(setter(into))]
+    pub symbol: SymbolPairField,
+
+    /// 多空比 (long/short ratio)
+    #[builder(setter(into))]
+    pub long_short_ratio: LongShortRatioField,
+
+    /// 多方帳戶佔比 (long account)
+    #[builder(setter(into))]
+    pub long_account: AccountRatioField,
+
+    /// 空方帳戶佔比 (short account)
+    #[builder(setter(into))]
+    pub short_account: AccountRatioField,
+
+    /// 資料結尾
+    #[builder(default)]
+    pub end: EndOfDataFlag,
+}
+
+impl LongShortRatioStructure {
+    /// The current schema version of the serialized long/short ratio structure.
+    pub const SCHEMA_VERSION: u8 = 1;
+}
+
+impl StructSerializer for LongShortRatioStructure {
+    type Err = LongShortRatioError;
+
+    fn serialize(&self, writer: &mut impl std::io::Write) -> Result<(), Self::Err> {
+        serialize_block_builder!(
+            SchemaVersionField(Self::SCHEMA_VERSION),
+            self.exchange_timestamp,
+            self.received_timestamp,
+            self.exchange_type,
+            self.market_type,
+            self.message_type,
+            self.symbol,
+            self.long_short_ratio,
+            self.long_account,
+            self.short_account,
+            self.end
+            => writer
+        );
+
+        Ok(())
+    }
+}
+
+impl StructDeserializer for LongShortRatioStructure {
+    type Err = LongShortRatioError;
+
+    fn deserialize(reader: &mut impl std::io::Read) -> Result<Self, Self::Err> {
+        SchemaVersionField::deserialize_from_reader(reader)??.check(Self::SCHEMA_VERSION)?;
+
+        deserialize_block_builder!(
+            reader =>
+            exchange_timestamp,
+            received_timestamp,
+            exchange_type,
+            market_type,
+            message_type,
+            symbol,
+            long_short_ratio,
+            long_account,
+            short_account,
+            end
+        )
+    }
+}
+
+impl DataStructure for LongShortRatioStructure {}
+
+#[derive(thiserror::Error, Debug)]
+pub enum LongShortRatioError {
+    #[error("field error: {0}")]
+    FieldError(#[from] FieldError),
+
+    #[error("I/O reader/writer error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+pub type LongShortRatioResult<T> = Result<T, LongShortRatioError>;
+
+#[cfg(test)]
+mod tests {
+    use crypto_market_type::MarketType;
+    use crypto_msg_type::MessageType;
+    use rust_decimal_macros::dec;
+
+    use super::{LongShortRatioError, LongShortRatioStructure};
+    use crate::data::fields::exchange_type::Exchange;
+    use crate::data::fields::{FieldError, SymbolPairField};
+    use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+    fn construct_structure() -> LongShortRatioStructure {
+        LongShortRatioStructure::builder()
+            .exchange_timestamp(1659755147000u64)
+            .received_timestamp(1659755147123u64)
+            .exchange_type(Exchange::Binance)
+            .market_type(MarketType::LinearSwap)
+            .message_type(MessageType::LongShortRatio)
+            .symbol(SymbolPairField::from_pair("BTC/USDT"))
+            .long_short_ratio(dec!(1.2338))
+            .long_account(dec!(0.5523))
+            .short_account(dec!(0.4477))
+            .build()
+    }
+
+    #[test]
+    fn test_long_short_ratio_encode_decode() {
+        let structure = construct_structure();
+
+        let encoded = structure.serialize_to_vec().unwrap();
+        let decoded = LongShortRatioStructure::deserialize_from_bytes(&encoded).unwrap();
+
+        assert_eq!(decoded, structure);
+    }
+
+    #[test]
+    fn test_long_short_ratio_schema_mismatch() {
+        let mut encoded = construct_structure().serialize_to_vec().unwrap();
+        encoded[0] = LongShortRatioStructure::SCHEMA_VERSION + 1;
+
+        assert!(matches!(
+            LongShortRatioStructure::deserialize_from_bytes(&encoded),
+            Err(LongShortRatioError::FieldError(FieldError::SchemaMismatch { .. }))
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+//! The [MessagePack](https://msgpack.org) support of the structures.
+//!
+//! The binary format of [`serializer`](super::serializer) is compact,
+//! but it is not interoperable with the other systems. MessagePack
+//! is a widely supported format, so the structures serialized with
+//! it can be read in the other languages.
+//!
+//! The structures are serialized as maps with the field names,
+//! and the decimal numbers are serialized as strings.
+//! See [`StructSerializer::serialize_msgpack`](super::serializer::StructSerializer::serialize_msgpack)
+//! and [`StructDeserializer::deserialize_msgpack`](super::serializer::StructDeserializer::deserialize_msgpack).
+
+/// The errors of [`msgpack`](self).
+#[derive(thiserror::Error, Debug)]
+pub enum MsgpackError {
+    #[error("failed to encode to MessagePack: {0}")]
+    EncodeFailed(#[from] rmp_serde::encode::Error),
+
+    #[error("failed to decode from MessagePack: {0}")]
+    DecodeFailed(#[from] rmp_serde::decode::Error),
+}
+
+/// The result type of [`msgpack`](self).
+pub type MsgpackResult<T> = Result<T, MsgpackError>;
+
+#[cfg(feature = "crypto")]
+macro_rules! msgpack_enc {
+    (
+        enc = $enc_method:ident,
+        dec = $dec_method:ident,
+        structure = $structure_type:ty
+    ) => {
+        #[doc = concat!("Encode a [`", stringify!($structure_type), "`] to MessagePack.")]
+        pub fn $enc_method(src: &$structure_type) -> MsgpackResult<Vec<u8>> {
+            $crate::data::serializer::StructSerializer::serialize_msgpack(src)
+        }
+
+        #[doc = concat!("Decode the MessagePack bytes to a [`", stringify!($structure_type), "`].")]
+        pub fn $dec_method(payload: &[u8]) -> MsgpackResult<$structure_type> {
+            $crate::data::serializer::StructDeserializer::deserialize_msgpack(payload)
+        }
+    };
+}
+
+#[cfg(feature = "crypto")]
+msgpack_enc!(
+    enc = encode_bbo_msgpack,
+    dec = decode_bbo_msgpack,
+    structure = super::bbo::BboStructure
+);
+
+#[cfg(feature = "crypto")]
+msgpack_enc!(
+    enc = encode_orderbook_msgpack,
+    dec = decode_orderbook_msgpack,
+    structure = super::orderbook::OrderbookStructure
+);
+
+#[cfg(feature = "crypto")]
+msgpack_enc!(
+    enc = encode_trade_msgpack,
+    dec = decode_trade_msgpack,
+    structure = super::trade::TradeStructure
+);
+
+#[cfg(feature = "crypto")]
+msgpack_enc!(
+    enc = encode_kline_msgpack,
+    dec = decode_kline_msgpack,
+    structure = super::kline::KlineStructure
+);
+
+#[cfg(feature = "crypto")]
+msgpack_enc!(
+    enc = encode_funding_rate_msgpack,
+    dec = decode_funding_rate_msgpack,
+    structure = super::funding_rate::FundingRateStructure
+);
+
+#[cfg(all(test, feature = "crypto"))]
+mod tests {
+    use crypto_market_type::MarketType;
+    use crypto_message::BboMsg;
+
+    use super::{decode_bbo_msgpack, encode_bbo_msgpack, MsgpackError};
+    use crate::data::bbo::BboStructure;
+    use crate::data::serializer::StructSerializer;
+
+    fn construct_bbo() -> BboStructure {
+        BboStructure::try_from(&BboMsg {
+            exchange: "binance".into(),
+            market_type: MarketType::Spot,
+            symbol: "BTCUSDT".into(),
+            pair: "BTC/USDT".into(),
+            msg_type: crypto_msg_type::MessageType::BBO,
+            timestamp: 1659755147000,
+            json: "".into(),
+            bid_price: 19234.56781234,
+            bid_quantity_base: 2.0,
+            bid_quantity_quote: 0.0,
+            bid_quantity_contract: None,
+            ask_price: 19234.6,
+            ask_quantity_base: 0.5,
+            ask_quantity_quote: 0.0,
+            ask_quantity_contract: None,
+            id: None,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bbo_msgpack_roundtrip() {
+        let structure = construct_bbo();
+
+        let encoded = encode_bbo_msgpack(&structure).unwrap();
+        assert_eq!(encoded, structure.serialize_msgpack().unwrap());
+
+        let decoded = decode_bbo_msgpack(&encoded).unwrap();
+        assert_eq!(decoded, structure);
+    }
+
+    #[test]
+    fn test_bbo_msgpack_invalid() {
+        assert!(matches!(
+            decode_bbo_msgpack(b"\xc1"),
+            Err(MsgpackError::DecodeFailed(_))
+        ));
+    }
+}
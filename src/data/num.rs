@@ -5,11 +5,33 @@ mod decimal_enc;
 mod timestamp;
 
 mod test_utils;
+#[cfg(test)]
+mod tests;
 
 pub use decimal_dec::*;
 pub use decimal_enc::*;
 pub use rust_decimal::{Decimal, Error as DecimalError};
 pub use timestamp::*;
 
+/// The maximum scale that [`Decimal`] supports.
+pub const MAX_SCALE: u8 = 28;
+
+/// The maximum mantissa that [`Decimal`] supports (96 bits).
+pub const MAX_MANTISSA: u128 = (1 << 96) - 1;
+
 #[derive(thiserror::Error, Debug)]
-pub enum NumError {}
+pub enum NumError {
+    #[error("can't encode a non-finite number: {0}")]
+    NonFiniteNumber(f64),
+
+    #[error("can't convert a number string to a Decimal: {0}")]
+    ParseFailed(#[from] DecimalError),
+
+    /// The inner value is the scale without the sign bit.
+    #[error("the scale should be at most {}, but got {0}", MAX_SCALE)]
+    InvalidScale(u8),
+
+    /// `max` is the largest mantissa the target can hold.
+    #[error("the mantissa {value} is too large to be encoded or decoded (max: {max})")]
+    MantissaOverflow { value: u128, max: u128 },
+}
@@ -1,4 +1,4 @@
-use super::{Decimal, NumError};
+use super::{Decimal, NumError, MAX_MANTISSA, MAX_SCALE};
 
 /// The trait for encoding a specified bytes of hex string
 /// – which is represented as `[TGT; LEN]` –
@@ -7,6 +7,8 @@ use super::{Decimal, NumError};
 /// Currently supports:
 /// - `LEN = 5`: 5-bytes hex string. For 32-bit numbers.
 /// - `LEN = 10`: 10-bytes hex string. For 64-bit numbers.
+/// - `LEN = 20`: 20-bytes hex string. For 128-bit numbers,
+///   with a 4-bytes signed scale.
 ///
 /// `TGT` can be one of `u8` or `i8`. It is `u8`
 /// by default, but if you need to support
@@ -20,16 +22,24 @@ where
     fn decode(src: &[TGT; LEN]) -> Result<Self, Self::Err>;
 }
 
-impl Decoder<5, i8> for Decimal {
-    type Err = NumError;
+macro_rules! i8_decoder {
+    ($len:expr) => {
+        impl Decoder<$len, i8> for Decimal {
+            type Err = NumError;
 
-    fn decode(src: &[i8; 5]) -> Result<Self, Self::Err> {
-        let encoded_i8 = unsafe { std::mem::transmute(src) };
+            fn decode(src: &[i8; $len]) -> Result<Self, Self::Err> {
+                let encoded_u8 = src.map(|v| v as u8);
 
-        <Self as Decoder<5, u8>>::decode(encoded_i8)
-    }
+                <Self as Decoder<$len, u8>>::decode(&encoded_u8)
+            }
+        }
+    };
 }
 
+i8_decoder!(5);
+i8_decoder!(10);
+i8_decoder!(20);
+
 impl Decoder<5, u8> for Decimal {
     type Err = NumError;
 
@@ -38,12 +48,7 @@ impl Decoder<5, u8> for Decimal {
 
         let signed_num = src[4];
 
-        let (scale, is_negative) = split_signed_scale(signed_num);
-
-        let mut decimal = Decimal::new(num_part as i64, scale as u32);
-        decimal.set_sign_negative(is_negative);
-
-        Ok(decimal)
+        build_decimal(num_part.into(), signed_num)
     }
 }
 
@@ -51,19 +56,81 @@ impl Decoder<10, u8> for Decimal {
     type Err = NumError;
 
     fn decode(src: &[u8; 10]) -> Result<Self, Self::Err> {
+        // The first byte is reserved and always 0, as the
+        // mantissa should not be larger than 64 bits.
+        if src[0] != 0 {
+            let value = src[..9]
+                .iter()
+                .fold(0u128, |acc, byte| acc << 8 | u128::from(*byte));
+
+            return Err(NumError::MantissaOverflow {
+                value,
+                max: u64::MAX.into(),
+            });
+        }
+
         let num_part = u64::from_be_bytes(*arrayref::array_ref![src, 1, 8]);
 
         let signed_num = src[9];
 
-        let (scale, is_negative) = split_signed_scale(signed_num);
+        build_decimal(num_part, signed_num)
+    }
+}
+
+impl Decoder<20, u8> for Decimal {
+    type Err = NumError;
+
+    fn decode(src: &[u8; 20]) -> Result<Self, Self::Err> {
+        let num_part = u128::from_be_bytes(*arrayref::array_ref![src, 0, 16]);
+        let signed_scale = u32::from_be_bytes(*arrayref::array_ref![src, 16, 4]);
+
+        // 0x7fff_ffff is mask, and the highest bit is the sign.
+        let scale = signed_scale & 0x7fff_ffff;
+        let is_negative = signed_scale >> 31 != 0;
 
-        let mut decimal = Decimal::new(num_part as i64, scale as u32);
+        if scale > MAX_SCALE.into() {
+            return Err(NumError::InvalidScale(u8::try_from(scale).unwrap_or(u8::MAX)));
+        }
+
+        // Decimal supports at most 96-bit mantissa.
+        if num_part > MAX_MANTISSA {
+            return Err(NumError::MantissaOverflow {
+                value: num_part,
+                max: MAX_MANTISSA,
+            });
+        }
+
+        let mut decimal = Decimal::try_from_i128_with_scale(num_part as i128, scale)
+            .map_err(|_| NumError::MantissaOverflow {
+                value: num_part,
+                max: MAX_MANTISSA,
+            })?;
         decimal.set_sign_negative(is_negative);
 
         Ok(decimal)
     }
 }
 
+/// Build a [`Decimal`] from the mantissa and the signed scale.
+///
+/// It validates the scale instead of panicking.
+fn build_decimal(num_part: u64, signed_scale: u8) -> Result<Decimal, NumError> {
+    let (scale, is_negative) = split_signed_scale(signed_scale);
+
+    if scale > MAX_SCALE {
+        return Err(NumError::InvalidScale(scale));
+    }
+
+    let mut decimal = Decimal::try_from_i128_with_scale(num_part.into(), scale.into())
+        .map_err(|_| NumError::MantissaOverflow {
+            value: num_part.into(),
+            max: MAX_MANTISSA,
+        })?;
+    decimal.set_sign_negative(is_negative);
+
+    Ok(decimal)
+}
+
 /// Split the signed scale to a scale with a negative flag.
 ///
 /// The return value is `(scale, is_negative)`.
@@ -120,6 +187,163 @@ mod tests {
         }
     }
 
+    /// A tiny xorshift generator, to feed the decoders with random bytes.
+    fn random_bytes<const LEN: usize>(state: &mut u64) -> [u8; LEN] {
+        [0; LEN].map(|_| {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+
+            *state as u8
+        })
+    }
+
+    #[test]
+    fn test_decode_random_bytes_never_panics() {
+        use super::Decimal;
+        use crate::data::num::Decoder;
+
+        let mut state = 0x2545F4914F6CDD1D;
+
+        for _ in 0..100_000 {
+            let _ = <Decimal as Decoder<5, u8>>::decode(&random_bytes(&mut state));
+            let _ = <Decimal as Decoder<10, u8>>::decode(&random_bytes(&mut state));
+            let _ = <Decimal as Decoder<20, u8>>::decode(&random_bytes(&mut state));
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_scale() {
+        use super::Decimal;
+        use crate::data::num::{Decoder, NumError, MAX_SCALE};
+
+        for scale in 0..=0x7f {
+            for sign in [NOT_SIGN, SIGN] {
+                let src_5b: [u8; 5] = [0, 0, 0, 1, scale | sign];
+                let src_10b: [u8; 10] = [0, 0, 0, 0, 0, 0, 0, 0, 1, scale | sign];
+
+                for result in [Decimal::decode(&src_5b), Decimal::decode(&src_10b)] {
+                    if scale <= MAX_SCALE {
+                        assert!(result.is_ok());
+                    } else {
+                        assert!(matches!(result, Err(NumError::InvalidScale(s)) if s == scale));
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_10b_decode_mantissa_overflow() {
+        use super::Decimal;
+        use crate::data::num::{Decoder, NumError};
+
+        let src: [u8; 10] = [1, 0, 0, 0, 0, 0, 0, 0, 1, 0];
+        assert!(matches!(
+            Decimal::decode(&src),
+            Err(NumError::MantissaOverflow { value, max })
+                if value == (1 << 64) + 1 && max == u64::MAX.into()
+        ));
+
+        // The full 64-bit mantissa is valid.
+        let src: [u8; 10] = [0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0];
+        assert_eq!(
+            Decimal::decode(&src).unwrap(),
+            Decimal::from(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_all_targets() {
+        use rust_decimal_macros::dec;
+
+        use super::Decimal;
+        use crate::data::num::{Decoder, Encoder};
+
+        let numbers = [
+            dec!(0),
+            dec!(1280),
+            dec!(512.016),
+            dec!(-1),
+            dec!(-512.016),
+            dec!(-10240000.1),
+            dec!(-0.00000001),
+        ];
+
+        for number in numbers {
+            let encoded: [u8; 5] = number.encode().unwrap();
+            assert_eq!(Decimal::decode(&encoded).unwrap(), number);
+
+            let encoded: [i8; 5] = number.encode().unwrap();
+            assert_eq!(Decimal::decode(&encoded).unwrap(), number);
+
+            let encoded: [u8; 10] = number.encode().unwrap();
+            assert_eq!(Decimal::decode(&encoded).unwrap(), number);
+
+            let encoded: [i8; 10] = number.encode().unwrap();
+            assert_eq!(Decimal::decode(&encoded).unwrap(), number);
+        }
+    }
+
+    #[test]
+    fn test_i8_matches_u8() {
+        use rust_decimal_macros::dec;
+
+        use super::Decimal;
+        use crate::data::num::Encoder;
+
+        let number = dec!(-10240000.1);
+
+        let encoded_u8: [u8; 10] = number.encode().unwrap();
+        let encoded_i8: [i8; 10] = number.encode().unwrap();
+
+        // The negative sign lives in the scale byte.
+        assert_eq!(encoded_i8[9], encoded_u8[9] as i8);
+        assert!(encoded_i8[9] < 0);
+        assert_eq!(encoded_i8.map(|v| v as u8), encoded_u8);
+    }
+
+    #[test]
+    fn test_20b_roundtrip() {
+        use rust_decimal_macros::dec;
+
+        use super::Decimal;
+        use crate::data::num::{Decoder, Encoder};
+
+        let numbers = [
+            dec!(0.000000000000001234),
+            dec!(-0.000000000000001234),
+            dec!(123456789.123456789012345678),
+            dec!(79228162514264337593543950335),
+        ];
+
+        for number in numbers {
+            let encoded: [u8; 20] = number.encode().unwrap();
+            assert_eq!(Decimal::decode(&encoded).unwrap(), number);
+
+            let encoded: [i8; 20] = number.encode().unwrap();
+            assert_eq!(Decimal::decode(&encoded).unwrap(), number);
+        }
+    }
+
+    #[test]
+    fn test_20b_decode_invalid() {
+        use super::Decimal;
+        use crate::data::num::{Decoder, NumError};
+
+        // 2^96 is too large for Decimal.
+        let mut src = [0u8; 20];
+        src[3] = 1;
+        assert!(matches!(
+            Decimal::decode(&src),
+            Err(NumError::MantissaOverflow { value, .. }) if value == 1 << 96
+        ));
+
+        let mut src = [0u8; 20];
+        src[16..20].copy_from_slice(&29u32.to_be_bytes());
+        assert!(matches!(Decimal::decode(&src), Err(NumError::InvalidScale(29))));
+    }
+
     #[test]
     fn test_10b_decode() {
         use super::Decimal;
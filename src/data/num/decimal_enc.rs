@@ -7,6 +7,8 @@ use super::{Decimal, NumError};
 /// `LEN` Currently supports:
 /// - `LEN = 5`: 5-bytes hex string. For 32-bit numbers.
 /// - `LEN = 10`: 10-bytes hex string. For 64-bit numbers.
+/// - `LEN = 20`: 20-bytes hex string. For 128-bit numbers,
+///   with a 4-bytes signed scale.
 ///
 /// `TGT` can be one of `u8` or `i8`. It is `u8`
 /// by default, but if you need to support
@@ -30,7 +32,7 @@ macro_rules! i8_encoder {
             fn encode(&self) -> Result<[i8; $len], Self::Err> {
                 let encoded_u8 = <Self as Encoder<$len, u8>>::encode(self)?;
 
-                Ok(unsafe { std::mem::transmute(encoded_u8) })
+                Ok(encoded_u8.map(|v| v as i8))
             }
         }
     };
@@ -39,7 +41,12 @@ macro_rules! i8_encoder {
 macro_rules! u8_encoder_body {
     ($self:expr, $abs_to:ty) => {{
         let is_negative = $self.is_sign_negative();
-        let num_part = <$abs_to>::try_from($self.mantissa().unsigned_abs()).expect("overflow!");
+        let mantissa = $self.mantissa().unsigned_abs();
+        let num_part =
+            <$abs_to>::try_from(mantissa).map_err(|_| NumError::MantissaOverflow {
+                value: mantissa,
+                max: <$abs_to>::MAX.into(),
+            })?;
         let scale_part = $self.scale() as u8;
 
         let num_bytes = num_part.to_be_bytes();
@@ -51,6 +58,7 @@ macro_rules! u8_encoder_body {
 
 i8_encoder!(5);
 i8_encoder!(10);
+i8_encoder!(20);
 
 impl Encoder<5, u8> for Decimal {
     type Err = NumError;
@@ -82,6 +90,73 @@ impl Encoder<10, u8> for Decimal {
     }
 }
 
+impl Encoder<20, u8> for Decimal {
+    type Err = NumError;
+
+    fn encode(&self) -> Result<[u8; 20], Self::Err> {
+        let mut result = [0u8; 20];
+
+        let num_bytes = self.mantissa().unsigned_abs().to_be_bytes();
+        let signed_scale = merge_to_signed_wide_scale(self.scale(), self.is_sign_negative());
+
+        result[0..16].copy_from_slice(&num_bytes);
+        result[16..20].copy_from_slice(&signed_scale.to_be_bytes());
+
+        Ok(result)
+    }
+}
+
+/// The largest scale `s` that `10^s` is exactly representable in [`f64`].
+const MAX_EXACT_SCALE: u32 = 22;
+
+/// The largest integer that is exactly representable in [`f64`] (`2^53`).
+const MAX_EXACT_INTEGER: f64 = 9007199254740992.0;
+
+/// Convert a [`f64`] to a [`Decimal`] with the shortest representation.
+///
+/// The result is identical to parsing `value.to_string()`
+/// with [`Decimal::from_str_exact`], but it finds the mantissa
+/// and scale arithmetically instead of formatting and parsing
+/// a string, unless the number is too large or too precise.
+pub fn decimal_from_f64(value: f64) -> Result<Decimal, NumError> {
+    if !value.is_finite() {
+        return Err(NumError::NonFiniteNumber(value));
+    }
+
+    // The signed zero is left to the slow path.
+    if value != 0.0 {
+        let mut power = 1.0;
+
+        for scale in 0..=MAX_EXACT_SCALE {
+            let scaled = (value * power).round();
+            if scaled.abs() > MAX_EXACT_INTEGER {
+                break;
+            }
+
+            // `value * power` may be rounded to a neighbor.
+            for candidate in [scaled, scaled - 1.0, scaled + 1.0] {
+                // Both operands are exact, so the division is
+                // what parsing `candidate / 10^scale` results in.
+                if candidate / power == value {
+                    return Ok(Decimal::new(candidate as i64, scale));
+                }
+            }
+
+            power *= 10.0;
+        }
+    }
+
+    Ok(Decimal::from_str_exact(&value.to_string())?)
+}
+
+/// Encode a [`f64`] to the 10-bytes hex string.
+///
+/// It is a fast path of encoding `Decimal::from_str_exact(&value.to_string())`,
+/// and the output is identical. See [`decimal_from_f64`].
+pub fn encode_f64(value: f64) -> Result<[u8; 10], NumError> {
+    decimal_from_f64(value)?.encode()
+}
+
 /// Merge the [`u8`] scale with the negative to a *signed scale*.
 ///
 /// # Principle
@@ -103,6 +178,18 @@ fn merge_to_signed_scale(scale: u8, is_negative: bool) -> u8 {
     }
 }
 
+/// Merge the [`u32`] scale with the negative to a 4-bytes *signed scale*.
+///
+/// It is the same as [`merge_to_signed_scale`], but the sign
+/// is placed at the highest bit of the [`u32`].
+fn merge_to_signed_wide_scale(scale: u32, is_negative: bool) -> u32 {
+    if is_negative {
+        (scale & 0x7fff_ffff) | 0x8000_0000
+    } else {
+        scale
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // For readability.
@@ -133,6 +220,115 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_encode_f64_identical_to_string_path() {
+        use rust_decimal::Decimal;
+
+        use super::encode_f64;
+        use crate::data::num::Encoder;
+
+        let corpus = [
+            // Integers
+            1.0,
+            42.0,
+            12345.0,
+            20000000.0,
+            9007199254740991.0,
+            // 8-decimal fractions
+            0.1,
+            0.3,
+            0.00000001,
+            0.12345678,
+            19234.56781234,
+            1659.00000001,
+            // Negatives
+            -1.0,
+            -0.5,
+            -0.00000001,
+            -43210.87654321,
+            // Imprecise in binary
+            0.1 + 0.2,
+            1.0 / 3.0,
+            // Too large or too precise for the fast path
+            1e18,
+            1.5e-25,
+        ];
+
+        for value in corpus {
+            let expected: [u8; 10] = Decimal::from_str_exact(&value.to_string())
+                .unwrap()
+                .encode()
+                .unwrap();
+
+            assert_eq!(encode_f64(value).unwrap(), expected, "value: {value}");
+        }
+    }
+
+    #[test]
+    fn test_encode_f64_non_finite() {
+        use super::encode_f64;
+        use crate::data::num::NumError;
+
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(matches!(
+                encode_f64(value),
+                Err(NumError::NonFiniteNumber(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_20b_encode() {
+        use rust_decimal_macros::dec;
+
+        use crate::data::num::Encoder;
+
+        let actual: [u8; 20] = dec!(-0.000000000000001234).encode().unwrap();
+
+        let mut expected = [0; 20];
+        expected[14..16].copy_from_slice(&1234u16.to_be_bytes());
+        expected[16..20].copy_from_slice(&[0x80, 0, 0, 18]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_5b_encode_mantissa_overflow() {
+        use rust_decimal::Decimal;
+
+        use crate::data::num::{Encoder, NumError};
+
+        let number = Decimal::from(5_000_000_000u64);
+        let result: Result<[u8; 5], _> = number.encode();
+
+        assert!(matches!(
+            result,
+            Err(NumError::MantissaOverflow { value: 5_000_000_000, max })
+                if max == u32::MAX.into()
+        ));
+
+        // The i8 encoder returns the same error.
+        let result: Result<[i8; 5], _> = (-number).encode();
+        assert!(matches!(result, Err(NumError::MantissaOverflow { .. })));
+
+        // It fits in 10 bytes.
+        let result: Result<[u8; 10], _> = number.encode();
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_10b_encode_mantissa_overflow() {
+        use rust_decimal::Decimal;
+
+        use crate::data::num::{Encoder, NumError};
+
+        let result: Result<[u8; 10], _> = Decimal::MAX.encode();
+        assert!(matches!(
+            result,
+            Err(NumError::MantissaOverflow { max, .. }) if max == u64::MAX.into()
+        ));
+    }
+
     #[test]
     fn test_10b_encode() {
         use crate::data::num::Encoder;
@@ -0,0 +1,66 @@
+//! The property-based round-trip tests of [`Encoder`] and [`Decoder`].
+
+use proptest::prelude::*;
+
+use super::{Decimal, Decoder, Encoder, NumError, MAX_MANTISSA, MAX_SCALE};
+
+/// Build a [`Decimal`] from the generated parts.
+fn build_decimal(mantissa: u128, scale: u8, is_negative: bool) -> Decimal {
+    let mut decimal = Decimal::from_i128_with_scale(mantissa as i128, scale.into());
+    decimal.set_sign_negative(is_negative);
+
+    decimal
+}
+
+/// Generate the [`Decimal`]s whose mantissa is at most `max_mantissa`,
+/// with the edge cases.
+fn decimal_strategy(max_mantissa: u128) -> impl Strategy<Value = Decimal> {
+    prop_oneof![
+        // Zero and negative zero.
+        Just(build_decimal(0, 0, false)),
+        Just(build_decimal(0, 0, true)),
+        // The largest mantissa with the max scale.
+        Just(build_decimal(max_mantissa, MAX_SCALE, false)),
+        Just(build_decimal(max_mantissa, MAX_SCALE, true)),
+        (0..=max_mantissa, 0..=MAX_SCALE, any::<bool>())
+            .prop_map(|(mantissa, scale, is_negative)| build_decimal(mantissa, scale, is_negative)),
+    ]
+}
+
+/// Encode and decode `number` with the `LEN`-bytes encoding,
+/// and check if the sign, scale and mantissa are kept.
+fn assert_roundtrip<const LEN: usize>(number: Decimal) -> Result<(), TestCaseError>
+where
+    Decimal: Encoder<LEN, u8, Err = NumError>
+        + Encoder<LEN, i8, Err = NumError>
+        + Decoder<LEN, u8, Err = NumError>
+        + Decoder<LEN, i8, Err = NumError>,
+{
+    let encoded: [u8; LEN] = number.encode().unwrap();
+    let decoded = <Decimal as Decoder<LEN, u8>>::decode(&encoded).unwrap();
+    // `Decimal::eq` ignores the scale and the sign of zero.
+    prop_assert_eq!(decoded.serialize(), number.serialize());
+
+    let encoded: [i8; LEN] = number.encode().unwrap();
+    let decoded = <Decimal as Decoder<LEN, i8>>::decode(&encoded).unwrap();
+    prop_assert_eq!(decoded.serialize(), number.serialize());
+
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn test_5b_roundtrip(number in decimal_strategy(u32::MAX.into())) {
+        assert_roundtrip::<5>(number)?;
+    }
+
+    #[test]
+    fn test_10b_roundtrip(number in decimal_strategy(u64::MAX.into())) {
+        assert_roundtrip::<10>(number)?;
+    }
+
+    #[test]
+    fn test_20b_roundtrip(number in decimal_strategy(MAX_MANTISSA)) {
+        assert_roundtrip::<20>(number)?;
+    }
+}
@@ -4,6 +4,10 @@
 ///
 /// - Make sure the encoded `u64` number do not use the 0 & 1 byte.
 ///
+/// In release mode, the high 2 bytes are dropped silently. Use
+/// [`TimestampField`](crate::data::fields::TimestampField) to reject
+/// the timestamps out of range.
+///
 /// # Example
 ///
 /// ```
@@ -1,172 +1,396 @@
 //! The order-related operations.
 
+use std::cmp::Ordering;
+
 use crypto_message::Order;
+use rust_decimal::prelude::FromPrimitive;
+
+use super::num::Decimal;
+
+/// The significant digits that the prices are compared in.
+///
+/// A [`f64`] has 15 to 17 significant digits, so the prices
+/// differing by a few ULPs are equal after rounding.
+const PRICE_SIGNIFICANT_DIGITS: u32 = 15;
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum OrderType {
     Ask,
     Bid,
 }
 
-// WIP: refactor needed!
-pub fn get_orders<'a>(new: &'a [Order], old: &'a [Order], order_type: OrderType) -> Vec<Order> {
-    let mut new_index: usize = 0;
-    let mut old_index: usize = 0;
-
+/// Get the diff from the `old` levels to the `new` levels,
+/// which [`restore_orders`] applies on `old` to restore `new`.
+///
+/// Both sides should be sorted in the order of the levels:
+/// ascending for the asks and descending for the bids. The changed
+/// and added levels are kept as is, and the removed levels are
+/// emitted with zero quantities.
+///
+/// The levels are borrowed, so the books can be diffed without being
+/// copied; see [`order_refs`].
+pub fn get_orders(new: &[&Order], old: &[&Order], order_type: OrderType) -> Vec<Order> {
     let mut result = Vec::new();
-    let mut is_new_remaining = new_index < new.len();
-    let mut is_old_remaining = old_index < old.len();
-    while is_new_remaining && is_old_remaining {
-        let latest_order = &new[new_index];
-        let old_order = &old[old_index];
-        if latest_order.price == old_order.price
-            && latest_order.quantity_quote == old_order.quantity_quote
-        {
-            old_index += 1;
-            new_index += 1;
-        } else {
-            match (
-                latest_order.price == old_order.price,
-                latest_order.quantity_quote == old_order.quantity_quote,
-            ) {
-                (true, false) => {
-                    let updated = Order {
-                        price: old_order.price,
-                        quantity_base: latest_order.quantity_base,
-                        quantity_quote: latest_order.quantity_quote,
-                        quantity_contract: latest_order.quantity_contract,
-                    };
-                    result.push(updated);
-                    old_index += 1;
-                    new_index += 1;
+    let mut new = new.iter().copied().peekable();
+    let mut old = old.iter().copied().peekable();
+
+    loop {
+        match (new.peek(), old.peek()) {
+            (Some(n), Some(o)) => match compare_price(n.price, o.price, order_type) {
+                Ordering::Less => {
+                    result.push((*n).clone());
+                    new.next();
+                }
+                Ordering::Greater => {
+                    result.push(removed(o));
+                    old.next();
                 }
-                (false, false) => {
-                    let mut cross_over = latest_order.price < old_order.price;
-                    if order_type == OrderType::Bid {
-                        cross_over = latest_order.price > old_order.price;
-                    };
-                    match cross_over {
-                        true => {
-                            let removed = Order {
-                                price: old_order.price,
-                                quantity_base: 0.0,
-                                quantity_quote: 0.0,
-                                quantity_contract: Some(0.0),
-                            };
-                            result.push(removed);
-                            old_index += 1;
-                        }
-                        false => {
-                            let added = Order {
-                                price: old_order.price,
-                                quantity_base: 0.0,
-                                quantity_quote: 0.0,
-                                quantity_contract: Some(0.0),
-                            };
-                            result.push(added);
-                            new_index += 1;
-                        }
+                Ordering::Equal => {
+                    if n.quantity_base != o.quantity_base
+                        || n.quantity_quote != o.quantity_quote
+                        || n.quantity_contract != o.quantity_contract
+                    {
+                        result.push((*n).clone());
                     }
+
+                    new.next();
+                    old.next();
                 }
-                (_, _) => {}
+            },
+            (Some(n), None) => {
+                result.push((*n).clone());
+                new.next();
+            }
+            (None, Some(o)) => {
+                result.push(removed(o));
+                old.next();
             }
+            (None, None) => break,
         }
-        is_new_remaining = new_index < new.len();
-        is_old_remaining = old_index < old.len();
-    }
-    if is_new_remaining {
-        (new_index..new.len()).for_each(|i| {
-            let order = &new[i];
-            let added = Order {
-                price: order.price,
-                quantity_base: order.quantity_base,
-                quantity_quote: order.quantity_quote,
-                quantity_contract: order.quantity_contract,
-            };
-            result.push(added);
-        });
-    } else if is_old_remaining {
-        (old_index..old.len()).for_each(|i| {
-            let order = &old[i];
-            let removed = Order {
-                price: order.price,
-                quantity_base: 0.0,
-                quantity_quote: 0.0,
-                quantity_contract: None,
-            };
-            result.push(removed);
-        });
     }
+
     result
 }
 
-pub fn restore_orders<'a>(old: &'a [Order], diff: &'a [Order], _type: OrderType) -> Vec<Order> {
+/// Get the diff from the top `n` levels of `old` to the top `n`
+/// levels of `new`, ignoring the deeper levels.
+///
+/// Both sides are sorted in the order of the levels before truncating,
+/// so they don't need to be sorted beforehand.
+pub fn get_orders_top_n(
+    new: &[Order],
+    old: &[Order],
+    order_type: OrderType,
+    n: usize,
+) -> Vec<Order> {
+    let new = top_levels(new, order_type, n);
+    let old = top_levels(old, order_type, n);
+
+    get_orders(&new, &old, order_type)
+}
+
+/// Borrow each level of `orders`, to be passed to [`get_orders`].
+pub fn order_refs(orders: &[Order]) -> Vec<&Order> {
+    orders.iter().collect()
+}
+
+/// Sort `orders` in the order of the levels, and keep the first `n` levels.
+fn top_levels(orders: &[Order], order_type: OrderType, n: usize) -> Vec<&Order> {
+    let mut orders = order_refs(orders);
+    orders.sort_by(|a, b| compare_price(a.price, b.price, order_type));
+    orders.truncate(n);
+
+    orders
+}
+
+/// Compare the prices in the order of the levels:
+/// ascending for the asks and descending for the bids.
+pub(crate) fn compare_price(a: f64, b: f64, order_type: OrderType) -> Ordering {
+    let (a, b) = (price_to_decimal(a), price_to_decimal(b));
+
+    match order_type {
+        OrderType::Ask => a.cmp(&b),
+        OrderType::Bid => b.cmp(&a),
+    }
+}
+
+/// Convert a price to [`Decimal`] for comparison.
+///
+/// The non-finite prices are treated as zero.
+fn price_to_decimal(price: f64) -> Decimal {
+    Decimal::from_f64(price)
+        .and_then(|price| price.round_sf(PRICE_SIGNIFICANT_DIGITS))
+        .unwrap_or_default()
+}
+
+/// Mark the level of `order` as removed, with zero quantities.
+fn removed(order: &Order) -> Order {
+    Order {
+        price: order.price,
+        quantity_base: 0.0,
+        quantity_quote: 0.0,
+        quantity_contract: order.quantity_contract.map(|_| 0.0),
+    }
+}
+
+/// Whether the level in a diff is removed.
+fn is_removed(order: &Order) -> bool {
+    order.quantity_base == 0.0
+}
+
+/// Apply the `diff` generated by [`get_orders`] on the `old` levels.
+///
+/// The levels of `diff` replace or are inserted into `old`, except the
+/// ones with zero `quantity_base`, which remove the levels of the same
+/// prices. Note that an empty `diff` results in no levels, rather than
+/// the `old` levels.
+pub fn restore_orders(old: &[Order], diff: &[Order], order_type: OrderType) -> Vec<Order> {
     let mut result = Vec::new();
     if diff.is_empty() {
         return result;
     }
-    let mut diff_index: usize = 0;
-    old.iter().for_each(|order| {
-        let diff_order = &diff[diff_index];
-        let mut is_coss_over = order.price > diff_order.price;
-        if _type == OrderType::Bid {
-            is_coss_over = order.price < diff_order.price;
-        };
-        match (
-            order.price == diff_order.price,
-            is_coss_over,
-            diff_order.quantity_quote,
-        ) {
-            (true, true, quote) if quote == 0.0 => {}
-            (true, _, _) => {
-                let updated = Order {
-                    price: diff_order.price,
-                    quantity_base: diff_order.quantity_base,
-                    quantity_quote: diff_order.quantity_quote,
-                    quantity_contract: diff_order.quantity_contract,
-                };
-                result.push(updated);
-            }
-            (false, true, _) => {
-                let old = Order {
-                    price: diff_order.price,
-                    quantity_base: diff_order.quantity_base,
-                    quantity_quote: diff_order.quantity_quote,
-                    quantity_contract: diff_order.quantity_contract,
-                };
-                result.push(old);
+
+    let mut old = old.iter().peekable();
+    let mut diff = diff.iter().peekable();
+
+    loop {
+        match (old.peek(), diff.peek()) {
+            (Some(o), Some(d)) => match compare_price(o.price, d.price, order_type) {
+                Ordering::Less => {
+                    result.push((*o).clone());
+                    old.next();
+                }
+                Ordering::Greater => {
+                    if !is_removed(d) {
+                        result.push((*d).clone());
+                    }
+                    diff.next();
+                }
+                Ordering::Equal => {
+                    if !is_removed(d) {
+                        result.push((*d).clone());
+                    }
+                    old.next();
+                    diff.next();
+                }
+            },
+            (Some(o), None) => {
+                result.push((*o).clone());
+                old.next();
             }
-            (false, false, _) => {
-                let added = Order {
-                    price: order.price,
-                    quantity_base: order.quantity_base,
-                    quantity_quote: order.quantity_quote,
-                    quantity_contract: order.quantity_contract,
-                };
-                let old = Order {
-                    price: diff_order.price,
-                    quantity_base: diff_order.quantity_base,
-                    quantity_quote: diff_order.quantity_quote,
-                    quantity_contract: diff_order.quantity_contract,
-                };
-                diff_index += 1;
-                result.push(added);
-                result.push(old);
+            (None, Some(d)) => {
+                if !is_removed(d) {
+                    result.push((*d).clone());
+                }
+                diff.next();
             }
+            (None, None) => break,
         }
-    });
-    // dbg!(&result);
-    if diff_index < diff.len() {
-        (diff_index..diff.len()).for_each(|i| {
-            let order = &diff[i];
-            let added = Order {
-                price: order.price,
-                quantity_base: order.quantity_base,
-                quantity_quote: order.quantity_quote,
-                quantity_contract: order.quantity_contract,
-            };
-            result.push(added);
-        });
     }
+
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use crypto_market_type::MarketType;
+    use crypto_message::{Order, OrderBookMsg};
+
+    use super::{
+        get_orders,
+        get_orders_top_n,
+        order_refs,
+        removed,
+        restore_orders,
+        OrderType,
+    };
+    use crate::data::orderbook::{generate_diff, OrderbookStructure};
+    use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+    fn order(price: f64, quantity_base: f64) -> Order {
+        Order {
+            price,
+            quantity_base,
+            quantity_quote: price * quantity_base,
+            quantity_contract: None,
+        }
+    }
+
+    /// Get the price and the base quantity of each level.
+    fn levels(orders: &[Order]) -> Vec<(f64, f64)> {
+        orders
+            .iter()
+            .map(|order| (order.price, order.quantity_base))
+            .collect()
+    }
+
+    fn construct_orderbookmsg(asks: Vec<Order>, bids: Vec<Order>) -> OrderBookMsg {
+        OrderBookMsg {
+            exchange: "binance".into(),
+            market_type: MarketType::Spot,
+            symbol: "BTCUSDT".into(),
+            pair: "BTC/USDT".into(),
+            msg_type: crypto_msg_type::MessageType::L2Event,
+            timestamp: 12345678,
+            seq_id: None,
+            prev_seq_id: None,
+            asks,
+            bids,
+            snapshot: false,
+            json: "".into(),
+        }
+    }
+
+    #[test]
+    fn test_get_orders_price_differs_by_ulp() {
+        let price = 12345.67;
+        let next_price = f64::from_bits(price.to_bits() + 1);
+        assert_ne!(price, next_price);
+
+        let old = [order(price, 2.0), order(12345.68, 1.0)];
+        let mut new = [order(next_price, 2.0), order(12345.68, 1.0)];
+        // Keep the quote quantity, so only the price differs.
+        new[0].quantity_quote = old[0].quantity_quote;
+
+        let (new, old) = (order_refs(&new), order_refs(&old));
+        assert!(get_orders(&new, &old, OrderType::Ask).is_empty());
+    }
+
+    #[test]
+    fn test_get_orders_updated() {
+        let old = [order(1.5, 2.0), order(2.5, 1.0)];
+        let new = [order(1.5, 3.0), order(2.5, 1.0)];
+
+        let diff = get_orders(&[&new[0], &new[1]], &[&old[0], &old[1]], OrderType::Ask);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].price, 1.5);
+        assert_eq!(diff[0].quantity_base, 3.0);
+    }
+
+    #[test]
+    fn test_get_orders_contract_updated() {
+        let old = [order(1.5, 2.0)];
+        let mut new = old.clone();
+        new[0].quantity_contract = Some(4.0);
+
+        let diff = get_orders(&[&new[0]], &[&old[0]], OrderType::Ask);
+        assert_eq!(diff.len(), 1);
+        assert_eq!(diff[0].quantity_contract, Some(4.0));
+    }
+
+    #[test]
+    fn test_get_orders_top_n() {
+        let old = [
+            order(3.0, 1.0),
+            order(1.0, 1.0),
+            order(2.0, 1.0),
+            order(4.0, 1.0),
+        ];
+        let new = [
+            order(1.0, 2.0),
+            order(2.0, 1.0),
+            order(4.0, 3.0),
+            order(5.0, 1.0),
+        ];
+
+        // The levels beyond the top 2 are ignored, even if they changed.
+        let diff = get_orders_top_n(&new, &old, OrderType::Ask, 2);
+        assert_eq!(levels(&diff), [(1.0, 2.0)]);
+
+        let diff = get_orders_top_n(&new, &old, OrderType::Ask, 3);
+        assert_eq!(levels(&diff), [(1.0, 2.0), (3.0, 0.0), (4.0, 3.0)]);
+
+        let diff = get_orders_top_n(&new, &old, OrderType::Bid, 2);
+        assert_eq!(levels(&diff), [(5.0, 1.0), (4.0, 3.0), (3.0, 0.0)]);
+
+        assert!(get_orders_top_n(&new, &old, OrderType::Ask, 0).is_empty());
+    }
+
+    #[test]
+    fn test_restore_orders_empty_diff() {
+        let old = [order(1.0, 1.0), order(2.0, 1.0)];
+
+        assert!(restore_orders(&old, &[], OrderType::Ask).is_empty());
+    }
+
+    #[test]
+    fn test_restore_orders_additions() {
+        let old = [order(1.0, 1.0), order(3.0, 1.0)];
+        let diff = [order(2.0, 2.0), order(4.0, 1.0)];
+
+        let restored = restore_orders(&old, &diff, OrderType::Ask);
+        assert_eq!(
+            levels(&restored),
+            [(1.0, 1.0), (2.0, 2.0), (3.0, 1.0), (4.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_restore_orders_removal() {
+        let old = [order(1.0, 1.0), order(2.0, 2.0), order(3.0, 1.0)];
+        let new = [order(1.0, 1.0), order(3.0, 1.0)];
+
+        // The removed level is emitted with zero quantities.
+        let diff = get_orders(&order_refs(&new), &order_refs(&old), OrderType::Ask);
+        assert_eq!(levels(&diff), [(2.0, 0.0)]);
+        assert_eq!(diff[0].quantity_quote, 0.0);
+
+        let restored = restore_orders(&old, &diff, OrderType::Ask);
+        assert_eq!(levels(&restored), levels(&new));
+    }
+
+    #[test]
+    fn test_restore_orders_mixed() {
+        let old = [order(1.0, 1.0), order(2.0, 2.0), order(3.0, 1.0)];
+        let diff = [
+            order(1.0, 5.0),
+            removed(&order(2.0, 2.0)),
+            order(2.5, 1.0),
+            order(4.0, 1.0),
+        ];
+
+        let restored = restore_orders(&old, &diff, OrderType::Ask);
+        assert_eq!(
+            levels(&restored),
+            [(1.0, 5.0), (2.5, 1.0), (3.0, 1.0), (4.0, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_restore_orders_bids() {
+        let old = [order(3.0, 1.0), order(2.0, 2.0), order(1.0, 1.0)];
+        let diff = [
+            removed(&order(3.0, 1.0)),
+            order(2.5, 1.0),
+            order(1.0, 4.0),
+        ];
+
+        let restored = restore_orders(&old, &diff, OrderType::Bid);
+        assert_eq!(levels(&restored), [(2.5, 1.0), (2.0, 2.0), (1.0, 4.0)]);
+    }
+
+    #[test]
+    fn test_generate_diff_restore_roundtrip() {
+        let old = construct_orderbookmsg(
+            vec![order(2.0, 1.0), order(3.0, 4.0), order(4.0, 1.0)],
+            vec![order(1.0, 5.0), order(0.5, 2.0)],
+        );
+        let latest = construct_orderbookmsg(
+            vec![order(2.0, 2.0), order(3.5, 1.0), order(4.0, 1.0)],
+            vec![order(1.0, 5.0), order(0.75, 1.0)],
+        );
+
+        let diff = generate_diff(&old, &latest);
+        let encoded = OrderbookStructure::try_from(&diff)
+            .unwrap()
+            .serialize_to_vec()
+            .unwrap();
+        let decoded = OrderbookStructure::deserialize_from_bytes(&encoded).unwrap();
+        let diff = OrderBookMsg::try_from(decoded).unwrap();
+
+        let asks = restore_orders(&old.asks, &diff.asks, OrderType::Ask);
+        let bids = restore_orders(&old.bids, &diff.bids, OrderType::Bid);
+        assert_eq!(levels(&asks), levels(&latest.asks));
+        assert_eq!(levels(&bids), levels(&latest.bids));
+    }
+}
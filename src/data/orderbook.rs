@@ -1,9 +1,15 @@
 //! The orderbook-related operations.
 
+mod book_keeper;
+mod view;
+
 use std::io::{Read, Write};
 
+pub use book_keeper::{BookKeeper, BookKeeperError, BookKeeperResult, DiffPublisher};
 pub use crypto_message::OrderBookMsg;
+use rust_decimal::Decimal;
 use typed_builder::TypedBuilder;
+pub use view::OrderbookView;
 
 use super::fields::info_type::InfoType;
 use super::fields::price_data::Order;
@@ -14,13 +20,16 @@ use super::fields::{
     InfoTypeField,
     MarketTypeField,
     MessageTypeField,
-    PriceDataField,
+    OptionalU64Field,
+    SchemaVersionField,
+    SnapshotField,
     SymbolPairField,
     TimestampField,
 };
-use super::order::{get_orders, OrderType};
+use super::order::{get_orders, order_refs, OrderType};
 use super::serializer::{
     serialize_block_builder,
+    DataStructure,
     FieldDeserializer,
     FieldSerializer,
     StructDeserializer,
@@ -28,8 +37,16 @@ use super::serializer::{
 };
 
 pub fn generate_diff(old: &OrderBookMsg, latest: &OrderBookMsg) -> OrderBookMsg {
-    let asks = get_orders(&old.asks, &latest.asks, OrderType::Ask);
-    let bids = get_orders(&old.bids, &latest.bids, OrderType::Bid);
+    let asks = get_orders(
+        &order_refs(&latest.asks),
+        &order_refs(&old.asks),
+        OrderType::Ask,
+    );
+    let bids = get_orders(
+        &order_refs(&latest.bids),
+        &order_refs(&old.bids),
+        OrderType::Bid,
+    );
 
     OrderBookMsg {
         asks,
@@ -47,17 +64,61 @@ pub fn generate_diff(old: &OrderBookMsg, latest: &OrderBookMsg) -> OrderBookMsg
     }
 }
 
+/// Keep the top `k` levels of each side of `msg`.
+///
+/// The asks are sorted ascending and the bids are sorted descending
+/// by price before truncating. The other fields are kept as is.
+pub fn top_k(msg: &OrderBookMsg, k: usize) -> OrderBookMsg {
+    let mut msg = msg.clone();
+
+    msg.asks.sort_by(|a, b| a.price.total_cmp(&b.price));
+    msg.asks.truncate(k);
+    msg.bids.sort_by(|a, b| b.price.total_cmp(&a.price));
+    msg.bids.truncate(k);
+
+    msg
+}
+
+/// The price data of each order in [`OrdersBox`].
+///
+/// It is [`PriceDataFieldExtended`](super::fields::PriceDataFieldExtended)
+/// with the `extended-price` feature, which keeps the quantity quote.
+#[cfg(not(feature = "extended-price"))]
+pub type OrderPriceData = super::fields::PriceDataField;
+
+/// The price data of each order in [`OrdersBox`].
+///
+/// It is [`PriceDataFieldExtended`](super::fields::PriceDataFieldExtended)
+/// with the `extended-price` feature, which keeps the quantity quote.
+#[cfg(feature = "extended-price")]
+pub type OrderPriceData = super::fields::PriceDataFieldExtended;
+
+/// The orders stored in [`OrdersBox`].
+///
+/// It is a [`SmallVec`](smallvec::SmallVec) with the `smallvec` feature,
+/// which stores up to 8 orders inline without allocating on the heap.
+#[cfg(not(feature = "smallvec"))]
+pub type Orders = Vec<OrderPriceData>;
+
+/// The orders stored in [`OrdersBox`].
+///
+/// It is a [`SmallVec`](smallvec::SmallVec) with the `smallvec` feature,
+/// which stores up to 8 orders inline without allocating on the heap.
+#[cfg(feature = "smallvec")]
+pub type Orders = smallvec::SmallVec<[OrderPriceData; 8]>;
+
 /// The box storing the direction and the orders.
 ///
 /// This type is designed especially for the [`OrderbookStructure`].
 /// As its size is variant, we don't implement it as a [`Field`](super::fields::Field),
 /// and its serialization and deserialization method need to be written manually.
 #[derive(Clone, Debug, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrdersBox {
     #[builder(setter(into))]
     direction: InfoTypeField,
 
-    orders: Vec<PriceDataField>,
+    orders: Orders,
 }
 
 impl OrdersBox {
@@ -89,6 +150,87 @@ impl OrdersBox {
     }
 }
 
+impl OrdersBox {
+    /// Get the direction of the orders.
+    pub fn direction(&self) -> InfoType {
+        self.direction.0
+    }
+
+    /// Get the price of the first order.
+    ///
+    /// It is the best price when the asks are sorted ascending
+    /// and the bids are sorted descending by price.
+    pub fn best_price(&self) -> Option<Decimal> {
+        self.price_at(0)
+    }
+
+    /// Get the count of the orders.
+    pub fn depth(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Get the price of the `i`-th order.
+    ///
+    /// It returns `None` if `i` is out of bounds.
+    pub fn price_at(&self, i: usize) -> Option<Decimal> {
+        self.orders.get(i).map(|order| *order.price)
+    }
+
+    /// Iterate the price of each order.
+    pub fn prices(&self) -> impl Iterator<Item = Decimal> + '_ {
+        self.orders.iter().map(|order| *order.price)
+    }
+
+    /// Iterate the base quantity of each order.
+    pub fn quantities(&self) -> impl Iterator<Item = Decimal> + '_ {
+        self.orders.iter().map(|order| *order.quantity_base)
+    }
+
+    /// Sum the base quantity of all the orders.
+    pub fn total_volume(&self) -> Decimal {
+        self.orders.iter().map(|order| *order.quantity_base).sum()
+    }
+
+    /// Get the volume-weighted average price of the orders,
+    /// which is `Σ(price × quantity_base) / Σ(quantity_base)`.
+    ///
+    /// It returns `None` if the total volume is zero.
+    pub fn vwap(&self) -> Option<Decimal> {
+        let total_volume = self.total_volume();
+
+        if total_volume.is_zero() {
+            return None;
+        }
+
+        let turnover: Decimal = self
+            .orders
+            .iter()
+            .map(|order| *order.price * *order.quantity_base)
+            .sum();
+
+        Some(turnover / total_volume)
+    }
+
+    /// Sort the orders from the best price, and keep the first `k` orders.
+    ///
+    /// The best price is the lowest one for the asks,
+    /// and the highest one for the bids.
+    fn truncate_to_top_k(&mut self, k: usize, side: InfoType) {
+        match side {
+            InfoType::Asks => self.orders.sort_by(|a, b| a.price.0.cmp(&b.price.0)),
+            InfoType::Bids => self.orders.sort_by(|a, b| b.price.0.cmp(&a.price.0)),
+        }
+
+        self.orders.truncate(k);
+    }
+
+    /// Sort the orders from the best price according to the direction,
+    /// and remove the orders beyond `depth`.
+    pub fn truncate_to_depth(&mut self, depth: usize) {
+        self.truncate_to_top_k(depth, self.direction());
+    }
+}
+
 impl OrdersBox {
     /// Serialize the input and write the whole serialized
     /// content to the writer.
@@ -120,10 +262,10 @@ impl OrdersBox {
         let order_len = Self::deserialize_orders_size(&size_buf);
 
         /* Orders */
-        let mut orders = Vec::with_capacity(order_len);
+        let mut orders = Orders::with_capacity(order_len);
 
         for _ in 0..order_len {
-            let order = PriceDataField::deserialize_from_reader(reader)??;
+            let order = OrderPriceData::deserialize_from_reader(reader)??;
             orders.push(order);
         }
 
@@ -135,14 +277,15 @@ impl OrdersBox {
 ///
 /// You can take advantage of `builder()`
 /// to construct some fields automatically.
-#[derive(Clone, Debug, PartialEq, Eq, TypedBuilder)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OrderbookStructure {
     /// 交易所時間戳
     #[builder(setter(into))]
     pub exchange_timestamp: TimestampField,
 
     /// 收到時間戳
-    #[builder(default)]
+    #[builder(default, setter(into))]
     pub received_timestamp: TimestampField,
 
     /// 交易所類型 (EXCHANGE)
@@ -158,12 +301,27 @@ pub struct OrderbookStructure {
     pub message_type: MessageTypeField,
 
     /// Symbol (ID，`1`) 或 Pair（字串，`BTC/USDT`）
+    #[builder(setter(into))]
     pub symbol: SymbolPairField,
 
+    /// 快照 (snapshot) 或增量 (delta)
+    #[builder(default = SnapshotField(true), setter(into))]
+    pub snapshot_flag: SnapshotField,
+
+    /// 序號 (seq_id)
+    #[builder(default, setter(into))]
+    pub seq_id: OptionalU64Field,
+
+    /// 前一個序號 (prev_seq_id)
+    #[builder(default, setter(into))]
+    pub prev_seq_id: OptionalU64Field,
+
     /// 賣方 (asks) 的資料
+    #[builder(setter(into))]
     pub asks: OrdersBox,
 
     /// 買方 (bids) 的資料
+    #[builder(setter(into))]
     pub bids: OrdersBox,
 
     /// 資料結尾
@@ -171,17 +329,218 @@ pub struct OrderbookStructure {
     pub end: EndOfDataFlag,
 }
 
+impl OrderbookStructure {
+    /// The current schema version of the serialized orderbook structure.
+    pub const SCHEMA_VERSION: u8 = 2;
+
+    /// Convert an [`OrderBookMsg`] to an orderbook structure.
+    ///
+    /// It is the same as [`TryFrom<&OrderBookMsg>`](TryFrom), but easier to find.
+    pub fn from_msg(msg: &OrderBookMsg) -> OrderbookResult<Self> {
+        Self::try_from(msg)
+    }
+
+    /// Convert an [`OrderBookMsg`] to an orderbook structure received at `received_ms`.
+    ///
+    /// Unlike [`from_msg`](Self::from_msg), which takes the current time
+    /// as the received timestamp, it keeps the original received timestamp
+    /// when converting the historical messages.
+    pub fn try_from_with_received(msg: &OrderBookMsg, received_ms: u64) -> OrderbookResult<Self> {
+        let mut structure = Self::try_from(msg)?;
+        structure.received_timestamp = TimestampField(received_ms);
+
+        Ok(structure)
+    }
+
+    /// Get the latency in milliseconds, which is the received timestamp
+    /// minus the exchange timestamp.
+    ///
+    /// It is negative if the exchange timestamp is later than the received
+    /// timestamp, for example, because of the clock skew.
+    pub fn latency_ms(&self) -> i64 {
+        i64::from(self.received_timestamp) - i64::from(self.exchange_timestamp)
+    }
+}
+
+impl OrderbookStructure {
+    /// Get the spread, which is the best ask minus the best bid.
+    ///
+    /// It returns `None` if either side is empty.
+    pub fn spread(&self) -> Option<Decimal> {
+        let (best_ask, best_bid) = self.best_prices()?;
+
+        Some(best_ask - best_bid)
+    }
+
+    /// Get the mid price, which is the average of the best ask and the best bid.
+    ///
+    /// It returns `None` if either side is empty.
+    pub fn mid_price(&self) -> Option<Decimal> {
+        let (best_ask, best_bid) = self.best_prices()?;
+
+        Some((best_ask + best_bid) / Decimal::TWO)
+    }
+
+    /// Sum the base quantity of all the asks.
+    pub fn total_ask_volume(&self) -> Decimal {
+        self.asks.total_volume()
+    }
+
+    /// Sum the base quantity of all the bids.
+    pub fn total_bid_volume(&self) -> Decimal {
+        self.bids.total_volume()
+    }
+
+    /// Get the imbalance of the orderbook, which is
+    /// `(bid_volume - ask_volume) / (bid_volume + ask_volume)`.
+    ///
+    /// It ranges from `-1` (asks only) to `1` (bids only),
+    /// and it is `0` if the orderbook is empty.
+    pub fn imbalance(&self) -> Decimal {
+        let ask_volume = self.total_ask_volume();
+        let bid_volume = self.total_bid_volume();
+        let total_volume = bid_volume + ask_volume;
+
+        if total_volume.is_zero() {
+            return Decimal::ZERO;
+        }
+
+        (bid_volume - ask_volume) / total_volume
+    }
+
+    /// Get the volume-weighted average price of the specified side.
+    ///
+    /// See [`OrdersBox::vwap`].
+    pub fn vwap(&self, side: InfoType) -> Option<Decimal> {
+        match side {
+            InfoType::Asks => self.asks.vwap(),
+            InfoType::Bids => self.bids.vwap(),
+        }
+    }
+
+    /// Keep the top `k` levels of each side.
+    ///
+    /// The asks are sorted ascending and the bids are sorted
+    /// descending by price before truncating.
+    pub fn truncate_to_top_k(&mut self, k: usize) {
+        self.asks.truncate_to_top_k(k, InfoType::Asks);
+        self.bids.truncate_to_top_k(k, InfoType::Bids);
+    }
+
+    /// Get a copy of this structure with the top `n` levels of each side.
+    ///
+    /// See [`OrderbookStructure::truncate_to_top_k`].
+    pub fn top_n(&self, n: usize) -> OrderbookStructure {
+        let mut structure = self.clone();
+        structure.truncate_to_top_k(n);
+
+        structure
+    }
+
+    fn best_prices(&self) -> Option<(Decimal, Decimal)> {
+        Some((self.asks.best_price()?, self.bids.best_price()?))
+    }
+
+    /// Check if `asks` and `bids` have the direction of their sides.
+    ///
+    /// It is checked before serializing and after deserializing.
+    pub fn validate(&self) -> OrderbookResult<()> {
+        check_direction(self.asks.direction(), InfoType::Asks)?;
+        check_direction(self.bids.direction(), InfoType::Bids)?;
+
+        Ok(())
+    }
+}
+
+/// Check if the direction of an [`OrdersBox`] is `expected`.
+fn check_direction(actual: InfoType, expected: InfoType) -> OrderbookResult<()> {
+    if actual != expected {
+        return Err(OrderbookError::DirectionMismatch { expected, actual });
+    }
+
+    Ok(())
+}
+
+/// Format the spread and the depth of each side on one line.
+///
+/// The spread is `-` if either side is empty.
+///
+/// # Example
+///
+/// ```
+/// use crypto_market_type::MarketType;
+/// use crypto_message::Order;
+/// use crypto_msg_type::MessageType;
+/// use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+/// use wmjtyd_libstock::data::fields::info_type::InfoType;
+/// use wmjtyd_libstock::data::fields::SymbolPairField;
+/// use wmjtyd_libstock::data::orderbook::{OrderbookStructure, OrdersBox};
+///
+/// let orders = |direction: InfoType, prices: &[f64]| {
+///     let order = |price| Order {
+///         price,
+///         quantity_base: 1.0,
+///         quantity_quote: price,
+///         quantity_contract: None,
+///     };
+///
+///     OrdersBox::builder()
+///         .direction(direction)
+///         .orders(prices.iter().map(|&p| (&order(p)).try_into().unwrap()).collect())
+///         .build()
+/// };
+/// let structure = OrderbookStructure::builder()
+///     .exchange_timestamp(1672531200000u64)
+///     .exchange_type(Exchange::Binance)
+///     .market_type(MarketType::Spot)
+///     .message_type(MessageType::L2Event)
+///     .symbol(SymbolPairField::from_pair("BTC/USDT"))
+///     .asks(orders(InfoType::Asks, &[2.5, 3.0]))
+///     .bids(orders(InfoType::Bids, &[2.0]))
+///     .build();
+///
+/// assert_eq!(
+///     structure.to_string(),
+///     "Orderbook [binance/spot] BTC/USDT @ 2023-01-01T00:00:00.000Z spread=0.5 depth=2/1"
+/// );
+/// ```
+impl std::fmt::Display for OrderbookStructure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Orderbook [{}/{}] {} @ {} spread=",
+            self.exchange_type.as_str(),
+            self.market_type.0,
+            self.symbol.pair,
+            self.exchange_timestamp,
+        )?;
+
+        match self.spread() {
+            Some(spread) => write!(f, "{spread}")?,
+            None => f.write_str("-")?,
+        }
+
+        write!(f, " depth={}/{}", self.asks.depth(), self.bids.depth())
+    }
+}
+
 impl StructSerializer for OrderbookStructure {
     type Err = OrderbookError;
 
     fn serialize(&self, writer: &mut impl Write) -> Result<(), Self::Err> {
+        self.validate()?;
+
         serialize_block_builder!(
+            SchemaVersionField(Self::SCHEMA_VERSION),
             self.exchange_timestamp,
             self.received_timestamp,
             self.exchange_type,
             self.market_type,
             self.message_type,
-            self.symbol
+            self.symbol,
+            self.snapshot_flag,
+            self.seq_id,
+            self.prev_seq_id
             => writer
         );
 
@@ -199,12 +558,17 @@ impl StructDeserializer for OrderbookStructure {
     type Err = OrderbookError;
 
     fn deserialize(reader: &mut impl Read) -> Result<Self, Self::Err> {
+        SchemaVersionField::deserialize_from_reader(reader)??.check(Self::SCHEMA_VERSION)?;
+
         let exchange_timestamp = TimestampField::deserialize_from_reader(reader)??;
         let received_timestamp = TimestampField::deserialize_from_reader(reader)??;
         let exchange_type = ExchangeTypeField::deserialize_from_reader(reader)??;
         let market_type = MarketTypeField::deserialize_from_reader(reader)??;
         let message_type = MessageTypeField::deserialize_from_reader(reader)??;
         let symbol = SymbolPairField::deserialize_from_reader(reader)??;
+        let snapshot_flag = SnapshotField::deserialize_from_reader(reader)??;
+        let seq_id = OptionalU64Field::deserialize_from_reader(reader)??;
+        let prev_seq_id = OptionalU64Field::deserialize_from_reader(reader)??;
 
         // OrdersBox is not a standard FieldDeserializer.
         let asks = OrdersBox::deserialize_from_reader(reader)?;
@@ -212,30 +576,41 @@ impl StructDeserializer for OrderbookStructure {
 
         let end = EndOfDataFlag::deserialize_from_reader(reader)??;
 
-        Ok(Self {
+        let structure = Self {
             exchange_timestamp,
             received_timestamp,
             exchange_type,
             market_type,
             message_type,
             symbol,
+            snapshot_flag,
+            seq_id,
+            prev_seq_id,
             asks,
             bids,
             end,
-        })
+        };
+        structure.validate()?;
+
+        Ok(structure)
     }
 }
 
+impl DataStructure for OrderbookStructure {}
+
 impl TryFrom<&OrderBookMsg> for OrderbookStructure {
     type Error = OrderbookError;
 
     fn try_from(value: &OrderBookMsg) -> Result<Self, Self::Error> {
         Ok(Self::builder()
-            .exchange_timestamp(value.timestamp)
+            .exchange_timestamp(TimestampField::try_from_i64(value.timestamp)?)
             .exchange_type(ExchangeTypeField::try_from_str(&value.exchange)?)
             .market_type(value.market_type)
             .message_type(value.msg_type)
             .symbol(SymbolPairField::from_pair(&value.pair))
+            .snapshot_flag(value.snapshot)
+            .seq_id(value.seq_id)
+            .prev_seq_id(value.prev_seq_id)
             .asks(
                 OrdersBox::builder()
                     .direction(InfoType::Asks)
@@ -244,7 +619,7 @@ impl TryFrom<&OrderBookMsg> for OrderbookStructure {
                             .asks
                             .iter()
                             .map(TryInto::try_into)
-                            .collect::<Result<Vec<PriceDataField>, _>>()?,
+                            .collect::<Result<Orders, _>>()?,
                     )
                     .build(),
             )
@@ -256,7 +631,7 @@ impl TryFrom<&OrderBookMsg> for OrderbookStructure {
                             .bids
                             .iter()
                             .map(TryInto::try_into)
-                            .collect::<Result<Vec<PriceDataField>, _>>()?,
+                            .collect::<Result<Orders, _>>()?,
                     )
                     .build(),
             )
@@ -289,11 +664,11 @@ impl TryFrom<OrderbookStructure> for OrderBookMsg {
             pair,
             msg_type: value.message_type.into(),
             timestamp: value.exchange_timestamp.into(),
-            snapshot: true,
+            snapshot: value.snapshot_flag.into(),
             asks,
             bids,
-            seq_id: None,
-            prev_seq_id: None,
+            seq_id: value.seq_id.into(),
+            prev_seq_id: value.prev_seq_id.into(),
             json: String::new(),
         })
     }
@@ -306,14 +681,386 @@ pub enum OrderbookError {
 
     #[error("I/O reader/writer error: {0}")]
     IoError(#[from] std::io::Error),
+
+    /// The orders of a side have the direction of the other side.
+    #[error("the orders should be {expected}, but got {actual}")]
+    DirectionMismatch { expected: InfoType, actual: InfoType },
 }
 
 pub type OrderbookResult<T> = Result<T, OrderbookError>;
 
+#[cfg(test)]
+mod tests {
+    use crypto_market_type::MarketType;
+    use crypto_message::{Order, OrderBookMsg};
+    use rust_decimal_macros::dec;
+
+    use super::{top_k, OrderbookError, OrderbookStructure};
+    use crate::data::fields::info_type::InfoType;
+    use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+    fn construct_orderbookmsg(market_type: MarketType) -> OrderBookMsg {
+        let order = |price, quantity_base| Order {
+            price,
+            quantity_base,
+            quantity_quote: price * quantity_base,
+            quantity_contract: None,
+        };
+
+        OrderBookMsg {
+            exchange: "binance".into(),
+            market_type,
+            symbol: "BTCUSDT".into(),
+            pair: "BTC/USDT".into(),
+            msg_type: crypto_msg_type::MessageType::L2Event,
+            timestamp: 12345678,
+            seq_id: None,
+            prev_seq_id: None,
+            asks: vec![order(2.0, 1.0), order(3.0, 4.0)],
+            bids: vec![order(1.0, 5.0)],
+            snapshot: true,
+            json: "".into(),
+        }
+    }
+
+    fn roundtrip(payload: &OrderBookMsg) -> OrderBookMsg {
+        let structure = OrderbookStructure::try_from(payload).unwrap();
+        let buffer = structure.serialize_to_vec().unwrap();
+        let decoded = OrderbookStructure::deserialize_from_bytes(&buffer).unwrap();
+
+        OrderBookMsg::try_from(decoded).unwrap()
+    }
+
+    #[test]
+    fn test_orderbook_encode_decode() {
+        let payload = construct_orderbookmsg(MarketType::Spot);
+        let decoded_msg = roundtrip(&payload);
+
+        assert_eq!(payload.exchange, decoded_msg.exchange);
+        assert_eq!(payload.market_type, decoded_msg.market_type);
+        assert_eq!(payload.pair, decoded_msg.pair);
+        assert_eq!(payload.msg_type, decoded_msg.msg_type);
+        assert_eq!(payload.timestamp, decoded_msg.timestamp);
+        assert_eq!(payload.asks.len(), decoded_msg.asks.len());
+        assert_eq!(payload.bids.len(), decoded_msg.bids.len());
+    }
+
+    #[test]
+    fn test_orderbook_received_timestamp() {
+        let payload = construct_orderbookmsg(MarketType::Spot);
+        let structure = OrderbookStructure::try_from_with_received(&payload, 12345999).unwrap();
+        assert_eq!(structure.received_timestamp.0, 12345999);
+        assert_eq!(structure.latency_ms(), 321);
+
+        let buffer = structure.serialize_to_vec().unwrap();
+        let decoded = OrderbookStructure::deserialize_from_bytes(&buffer).unwrap();
+        assert_eq!(decoded.received_timestamp.0, 12345999);
+        assert_eq!(decoded, structure);
+
+        // Re-serializing keeps the original received timestamp.
+        assert_eq!(decoded.serialize_to_vec().unwrap(), buffer);
+    }
+
+    #[cfg(feature = "extended-price")]
+    #[test]
+    fn test_orderbook_encode_decode_quantity_quote() {
+        let payload = construct_orderbookmsg(MarketType::LinearSwap);
+        let decoded_msg = roundtrip(&payload);
+
+        for (expected, actual) in payload.asks.iter().zip(&decoded_msg.asks) {
+            assert_eq!(expected.quantity_quote, actual.quantity_quote);
+        }
+    }
+
+    #[test]
+    fn test_orderbook_encode_decode_snapshot_seq_id() {
+        let payload = OrderBookMsg {
+            snapshot: false,
+            seq_id: Some(1002),
+            prev_seq_id: Some(1001),
+            ..construct_orderbookmsg(MarketType::Spot)
+        };
+        let decoded_msg = roundtrip(&payload);
+
+        assert!(!decoded_msg.snapshot);
+        assert_eq!(decoded_msg.seq_id, Some(1002));
+        assert_eq!(decoded_msg.prev_seq_id, Some(1001));
+
+        let decoded_msg = roundtrip(&construct_orderbookmsg(MarketType::Spot));
+        assert!(decoded_msg.snapshot);
+        assert_eq!(decoded_msg.seq_id, None);
+        assert_eq!(decoded_msg.prev_seq_id, None);
+    }
+
+    #[test]
+    fn test_orderbook_encode_decode_american_option() {
+        let payload = construct_orderbookmsg(MarketType::AmericanOption);
+        let decoded_msg = roundtrip(&payload);
+
+        assert_eq!(MarketType::AmericanOption, decoded_msg.market_type);
+    }
+
+    #[test]
+    fn test_orderbook_spread_mid_price() {
+        let payload = construct_orderbookmsg(MarketType::Spot);
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        assert_eq!(structure.asks.best_price(), Some(dec!(2)));
+        assert_eq!(structure.bids.best_price(), Some(dec!(1)));
+        assert_eq!(structure.spread(), Some(dec!(1)));
+        assert_eq!(structure.mid_price(), Some(dec!(1.5)));
+    }
+
+    #[test]
+    fn test_orderbook_spread_mid_price_empty_side() {
+        let mut payload = construct_orderbookmsg(MarketType::Spot);
+        payload.bids.clear();
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        assert_eq!(structure.bids.best_price(), None);
+        assert_eq!(structure.spread(), None);
+        assert_eq!(structure.mid_price(), None);
+    }
+
+    #[test]
+    fn test_orderbook_volumes_imbalance() {
+        let payload = construct_orderbookmsg(MarketType::Spot);
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        assert_eq!(structure.total_ask_volume(), dec!(5));
+        assert_eq!(structure.total_bid_volume(), dec!(5));
+        assert_eq!(structure.imbalance(), dec!(0));
+
+        let mut payload = construct_orderbookmsg(MarketType::Spot);
+        payload.asks.truncate(1);
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        // (5 - 1) / (5 + 1)
+        assert_eq!(structure.total_ask_volume(), dec!(1));
+        assert_eq!(structure.imbalance(), dec!(4) / dec!(6));
+    }
+
+    #[test]
+    fn test_orderbook_vwap() {
+        let payload = construct_orderbookmsg(MarketType::Spot);
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        // (2 × 1 + 3 × 4) / (1 + 4)
+        assert_eq!(structure.vwap(InfoType::Asks), Some(dec!(2.8)));
+        // (1 × 5) / 5
+        assert_eq!(structure.vwap(InfoType::Bids), Some(dec!(1)));
+    }
+
+    #[test]
+    fn test_orderbook_vwap_empty() {
+        let mut payload = construct_orderbookmsg(MarketType::Spot);
+        payload.asks.clear();
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        assert_eq!(structure.vwap(InfoType::Asks), None);
+        assert_eq!(structure.vwap(InfoType::Bids), Some(dec!(1)));
+    }
+
+    fn construct_unsorted_orderbookmsg() -> OrderBookMsg {
+        let order = |price| Order {
+            price,
+            quantity_base: 1.0,
+            quantity_quote: price,
+            quantity_contract: None,
+        };
+
+        OrderBookMsg {
+            asks: vec![order(5.0), order(2.0), order(4.0), order(3.0)],
+            bids: vec![order(0.5), order(1.5), order(1.0)],
+            ..construct_orderbookmsg(MarketType::Spot)
+        }
+    }
+
+    fn prices(orders: &[Order]) -> Vec<f64> {
+        orders.iter().map(|order| order.price).collect()
+    }
+
+    #[test]
+    fn test_orderbook_top_k() {
+        let payload = construct_unsorted_orderbookmsg();
+
+        let truncated = top_k(&payload, 2);
+        assert_eq!(prices(&truncated.asks), [2.0, 3.0]);
+        assert_eq!(prices(&truncated.bids), [1.5, 1.0]);
+        assert_eq!(truncated.timestamp, payload.timestamp);
+        assert_eq!(truncated.pair, payload.pair);
+
+        let truncated = top_k(&payload, 10);
+        assert_eq!(prices(&truncated.asks), [2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(prices(&truncated.bids), [1.5, 1.0, 0.5]);
+
+        let payload = OrderBookMsg {
+            bids: vec![],
+            ..payload
+        };
+        let truncated = top_k(&payload, 2);
+        assert_eq!(prices(&truncated.asks), [2.0, 3.0]);
+        assert!(truncated.bids.is_empty());
+    }
+
+    #[test]
+    fn test_orderbook_truncate_to_top_k() {
+        let payload = construct_unsorted_orderbookmsg();
+        let mut structure = OrderbookStructure::try_from(&payload).unwrap();
+        structure.truncate_to_top_k(2);
+
+        let decoded_msg = OrderBookMsg::try_from(structure.clone()).unwrap();
+        assert_eq!(prices(&decoded_msg.asks), [2.0, 3.0]);
+        assert_eq!(prices(&decoded_msg.bids), [1.5, 1.0]);
+        assert_eq!(structure.spread(), Some(dec!(0.5)));
+
+        structure.truncate_to_top_k(0);
+        assert_eq!(structure.asks.best_price(), None);
+        assert_eq!(structure.bids.best_price(), None);
+    }
+
+    #[test]
+    fn test_orderbook_top_n() {
+        let payload = construct_unsorted_orderbookmsg();
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        let truncated = structure.top_n(2);
+        assert_eq!(truncated.asks.prices().collect::<Vec<_>>(), [dec!(2), dec!(3)]);
+        assert_eq!(truncated.bids.prices().collect::<Vec<_>>(), [dec!(1.5), dec!(1)]);
+        assert_eq!(truncated.exchange_timestamp, structure.exchange_timestamp);
+        assert_eq!(truncated.symbol, structure.symbol);
+
+        // The original structure is untouched.
+        assert_eq!(structure.asks.depth(), 4);
+        assert_eq!(structure.bids.depth(), 3);
+    }
+
+    #[test]
+    fn test_orders_box_truncate_to_depth() {
+        let payload = construct_unsorted_orderbookmsg();
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        let mut asks = structure.asks.clone();
+        asks.truncate_to_depth(3);
+        assert_eq!(asks.prices().collect::<Vec<_>>(), [dec!(2), dec!(3), dec!(4)]);
+
+        let mut bids = structure.bids.clone();
+        bids.truncate_to_depth(2);
+        assert_eq!(bids.prices().collect::<Vec<_>>(), [dec!(1.5), dec!(1)]);
+
+        bids.truncate_to_depth(5);
+        assert_eq!(bids.depth(), 2);
+    }
+
+    #[test]
+    fn test_orderbook_direction_mismatch() {
+        let payload = construct_orderbookmsg(MarketType::Spot);
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+        assert!(structure.validate().is_ok());
+        let encoded = structure.serialize_to_vec().unwrap();
+
+        let mut mismatched = structure.clone();
+        std::mem::swap(&mut mismatched.asks, &mut mismatched.bids);
+        assert!(matches!(
+            mismatched.serialize_to_vec(),
+            Err(OrderbookError::DirectionMismatch {
+                expected: InfoType::Asks,
+                actual: InfoType::Bids,
+            })
+        ));
+
+        // Overwrite the direction of the bids, which is before the end flag.
+        let mut bids = Vec::new();
+        structure.bids.serialize_to_writer(&mut bids).unwrap();
+        let mut corrupted = encoded;
+        let bids_at = corrupted.len() - 1 - bids.len();
+        assert_eq!(corrupted[bids_at], InfoType::Bids as u8);
+        corrupted[bids_at] = InfoType::Asks as u8;
+        assert!(matches!(
+            OrderbookStructure::deserialize_from_bytes(&corrupted),
+            Err(OrderbookError::DirectionMismatch {
+                expected: InfoType::Bids,
+                actual: InfoType::Asks,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_orders_box_accessors() {
+        let payload = construct_orderbookmsg(MarketType::Spot);
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        assert_eq!(structure.asks.depth(), 2);
+        assert_eq!(structure.asks.best_price(), Some(dec!(2)));
+        assert_eq!(structure.asks.price_at(1), Some(dec!(3)));
+        assert_eq!(structure.asks.price_at(2), None);
+        assert_eq!(structure.asks.prices().collect::<Vec<_>>(), [dec!(2), dec!(3)]);
+        assert_eq!(structure.asks.quantities().collect::<Vec<_>>(), [dec!(1), dec!(4)]);
+
+        assert_eq!(structure.bids.depth(), 1);
+        assert_eq!(structure.bids.prices().collect::<Vec<_>>(), [dec!(1)]);
+        assert_eq!(structure.bids.quantities().collect::<Vec<_>>(), [dec!(5)]);
+
+        let mut payload = construct_orderbookmsg(MarketType::Spot);
+        payload.asks.clear();
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        assert_eq!(structure.asks.depth(), 0);
+        assert_eq!(structure.asks.price_at(0), None);
+        assert_eq!(structure.asks.prices().count(), 0);
+    }
+
+    #[test]
+    fn test_orderbook_imbalance_empty() {
+        let mut payload = construct_orderbookmsg(MarketType::Spot);
+        payload.asks.clear();
+        payload.bids.clear();
+        let structure = OrderbookStructure::try_from(&payload).unwrap();
+
+        assert_eq!(structure.total_ask_volume(), dec!(0));
+        assert_eq!(structure.total_bid_volume(), dec!(0));
+        assert_eq!(structure.imbalance(), dec!(0));
+    }
+
+    #[cfg(feature = "compat-v0_3")]
+    #[test]
+    #[allow(deprecated)]
+    fn test_compat_decode_trailing_bytes() {
+        use super::{decode_orderbook, decode_orderbook_partial, encode_orderbook, OrderbookError};
+        use crate::data::fields::FieldError;
+
+        let payload = construct_orderbookmsg(MarketType::Spot);
+        let encoded = encode_orderbook(&payload).unwrap();
+
+        let decoded_msg = decode_orderbook(&encoded).unwrap();
+        assert_eq!(payload.asks.len(), decoded_msg.asks.len());
+
+        let garbage = [encoded.as_slice(), &b"xyz"[..]].concat();
+        assert!(matches!(
+            decode_orderbook(&garbage),
+            Err(OrderbookError::FieldError(FieldError::TrailingBytes { remaining: 3 }))
+        ));
+
+        let concatenated = [encoded.as_slice(), encoded.as_slice()].concat();
+        assert!(matches!(
+            decode_orderbook(&concatenated),
+            Err(OrderbookError::FieldError(FieldError::TrailingBytes { .. }))
+        ));
+
+        let (first, consumed) = decode_orderbook_partial(&concatenated).unwrap();
+        assert_eq!(consumed, encoded.len());
+        let (second, consumed) = decode_orderbook_partial(&concatenated[consumed..]).unwrap();
+        assert_eq!(consumed, encoded.len());
+        assert_eq!(first.asks.len(), second.asks.len());
+        assert_eq!(first.bids.len(), second.bids.len());
+    }
+}
+
 /* 0.3.0 compatible methods */
 crate::compat::compat_enc!(
     enc = encode_orderbook,
     dec = decode_orderbook,
+    dec_partial = decode_orderbook_partial,
     crawl = OrderBookMsg,
     result = OrderbookResult,
     structure = OrderbookStructure
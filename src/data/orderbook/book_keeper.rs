@@ -0,0 +1,411 @@
+//! The helpers for the snapshot + diff orderbook streams.
+//! See [`BookKeeper`] and [`DiffPublisher`].
+
+use std::time::{Duration, Instant};
+
+use crypto_message::{Order, OrderBookMsg};
+
+use crate::data::order::{compare_price, get_orders, order_refs, restore_orders, OrderType};
+
+/// The consumer of a snapshot + diff orderbook stream.
+///
+/// It keeps the current book, replaces it on every snapshot and
+/// applies the diffs on it. A level in a diff with zero `quantity_base`
+/// removes the level with the same price, and the other levels
+/// replace or are inserted into the book.
+///
+/// The continuity of the diffs is checked with their `seq_id` and
+/// `prev_seq_id`. Once a gap is detected, the diffs are rejected
+/// until the next snapshot.
+///
+/// The levels are merged with [`restore_orders`], except that
+/// a side with no levels in the diff is kept as is.
+#[derive(Clone, Default)]
+pub struct BookKeeper {
+    book: Option<OrderBookMsg>,
+    needs_snapshot: bool,
+}
+
+impl BookKeeper {
+    /// Create a book keeper waiting for the first snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current book.
+    ///
+    /// It is `None` before the first snapshot. The asks are sorted
+    /// ascending and the bids are sorted descending by price.
+    pub fn current(&self) -> Option<&OrderBookMsg> {
+        self.book.as_ref()
+    }
+
+    /// Whether a snapshot is required to apply the diffs,
+    /// because of a gap or the missing first snapshot.
+    pub fn needs_snapshot(&self) -> bool {
+        self.needs_snapshot || self.book.is_none()
+    }
+
+    /// Apply a snapshot or a diff, and return the current book.
+    pub fn apply(&mut self, msg: &OrderBookMsg) -> BookKeeperResult<&OrderBookMsg> {
+        if msg.snapshot {
+            let mut book = msg.clone();
+            sort_levels(&mut book.asks, OrderType::Ask);
+            sort_levels(&mut book.bids, OrderType::Bid);
+            self.needs_snapshot = false;
+
+            return Ok(self.book.insert(book));
+        }
+
+        let book = match &mut self.book {
+            Some(book) if !self.needs_snapshot => book,
+            _ => return Err(BookKeeperError::SnapshotRequired),
+        };
+
+        if let Err(e) = check_continuity(book, msg) {
+            self.needs_snapshot = true;
+            return Err(e);
+        }
+
+        apply_levels(&mut book.asks, &msg.asks, OrderType::Ask);
+        apply_levels(&mut book.bids, &msg.bids, OrderType::Bid);
+        book.timestamp = msg.timestamp;
+        book.seq_id = msg.seq_id;
+        book.prev_seq_id = msg.prev_seq_id;
+
+        Ok(book)
+    }
+}
+
+/// The producer of a snapshot + diff orderbook stream.
+///
+/// It emits a snapshot for the first book, every `snapshot_every`
+/// messages and every `snapshot_interval`, and the diffs against
+/// the previous book otherwise. The messages are numbered with
+/// its own `seq_id` and `prev_seq_id`, which [`BookKeeper`] checks.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use wmjtyd_libstock::data::orderbook::{BookKeeper, DiffPublisher, OrderBookMsg};
+///
+/// fn relay(books: &[OrderBookMsg]) {
+///     let mut publisher = DiffPublisher::new(100, Duration::from_secs(60));
+///     let mut keeper = BookKeeper::new();
+///
+///     for book in books {
+///         let msg = publisher.emit(book);
+///         let current = keeper.apply(&msg).expect("no message should be lost");
+///
+///         assert_eq!(current.asks.len(), book.asks.len());
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct DiffPublisher {
+    snapshot_every: usize,
+    snapshot_interval: Duration,
+    last: Option<OrderBookMsg>,
+    since_snapshot: usize,
+    last_snapshot_at: Instant,
+    next_seq_id: u64,
+}
+
+impl DiffPublisher {
+    /// Create a publisher emitting a snapshot every `snapshot_every`
+    /// messages or every `snapshot_interval`, whichever comes first.
+    pub fn new(snapshot_every: usize, snapshot_interval: Duration) -> Self {
+        Self {
+            snapshot_every,
+            snapshot_interval,
+            last: None,
+            since_snapshot: 0,
+            last_snapshot_at: Instant::now(),
+            next_seq_id: 0,
+        }
+    }
+
+    /// Emit the message for the latest `book`.
+    pub fn emit(&mut self, book: &OrderBookMsg) -> OrderBookMsg {
+        let mut book = book.clone();
+        sort_levels(&mut book.asks, OrderType::Ask);
+        sort_levels(&mut book.bids, OrderType::Bid);
+
+        let seq_id = self.next_seq_id;
+        self.next_seq_id += 1;
+
+        let msg = match &self.last {
+            Some(last) if !self.snapshot_due() => {
+                self.since_snapshot += 1;
+
+                OrderBookMsg {
+                    asks: get_orders(
+                        &order_refs(&book.asks),
+                        &order_refs(&last.asks),
+                        OrderType::Ask,
+                    ),
+                    bids: get_orders(
+                        &order_refs(&book.bids),
+                        &order_refs(&last.bids),
+                        OrderType::Bid,
+                    ),
+                    snapshot: false,
+                    seq_id: Some(seq_id),
+                    prev_seq_id: seq_id.checked_sub(1),
+                    json: String::new(),
+                    ..book.clone()
+                }
+            }
+            _ => {
+                self.since_snapshot = 1;
+                self.last_snapshot_at = Instant::now();
+
+                OrderBookMsg {
+                    snapshot: true,
+                    seq_id: Some(seq_id),
+                    prev_seq_id: seq_id.checked_sub(1),
+                    ..book.clone()
+                }
+            }
+        };
+
+        self.last = Some(book);
+        msg
+    }
+
+    fn snapshot_due(&self) -> bool {
+        self.since_snapshot >= self.snapshot_every
+            || self.last_snapshot_at.elapsed() >= self.snapshot_interval
+    }
+}
+
+fn sort_levels(levels: &mut [Order], order_type: OrderType) {
+    levels.sort_by(|a, b| compare_price(a.price, b.price, order_type));
+}
+
+/// Check if `diff` is the next message of `book`.
+///
+/// It passes if either of them has no sequence ID.
+fn check_continuity(book: &OrderBookMsg, diff: &OrderBookMsg) -> BookKeeperResult<()> {
+    let current = match book.seq_id {
+        Some(current) => current,
+        None => return Ok(()),
+    };
+    let prev = diff
+        .prev_seq_id
+        .or_else(|| diff.seq_id.and_then(|seq_id| seq_id.checked_sub(1)));
+
+    match prev {
+        Some(prev) if prev != current => Err(BookKeeperError::SequenceGap {
+            expected: current,
+            got: prev,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Apply the `diff` on the sorted `levels`.
+///
+/// The `diff` is sorted before merging, and an empty `diff`
+/// keeps the `levels` as is.
+fn apply_levels(levels: &mut Vec<Order>, diff: &[Order], order_type: OrderType) {
+    if diff.is_empty() {
+        return;
+    }
+
+    let mut diff = diff.to_vec();
+    sort_levels(&mut diff, order_type);
+    *levels = restore_orders(levels, &diff, order_type);
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum BookKeeperError {
+    #[error("a snapshot is required before applying the diffs")]
+    SnapshotRequired,
+
+    #[error("sequence gap: expected the diff after {expected}, got the diff after {got}")]
+    SequenceGap { expected: u64, got: u64 },
+}
+
+pub type BookKeeperResult<T> = Result<T, BookKeeperError>;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use crypto_market_type::MarketType;
+    use crypto_message::{Order, OrderBookMsg};
+
+    use super::{BookKeeper, BookKeeperError, DiffPublisher};
+
+    /// The deterministic pseudo-random number generator.
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next(&mut self) -> u64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            self.0 >> 33
+        }
+    }
+
+    fn construct_book(rng: &mut Lcg, timestamp: i64) -> OrderBookMsg {
+        let mut levels = |best: f64, step: f64| {
+            (0..10)
+                .filter_map(|i| {
+                    let random = rng.next();
+                    if random % 3 == 0 {
+                        return None;
+                    }
+
+                    let price = best + step * i as f64;
+                    let quantity_base = (random % 4 + 1) as f64;
+
+                    Some(Order {
+                        price,
+                        quantity_base,
+                        quantity_quote: price * quantity_base,
+                        quantity_contract: None,
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        let asks = levels(100.0, 0.5);
+        let bids = levels(99.5, -0.5);
+
+        OrderBookMsg {
+            exchange: "binance".into(),
+            market_type: MarketType::Spot,
+            symbol: "BTCUSDT".into(),
+            pair: "BTC/USDT".into(),
+            msg_type: crypto_msg_type::MessageType::L2Event,
+            timestamp,
+            seq_id: None,
+            prev_seq_id: None,
+            asks,
+            bids,
+            snapshot: true,
+            json: "".into(),
+        }
+    }
+
+    fn levels(orders: &[Order]) -> Vec<(f64, f64)> {
+        orders
+            .iter()
+            .map(|order| (order.price, order.quantity_base))
+            .collect()
+    }
+
+    fn assert_same_book(actual: &OrderBookMsg, expected: &OrderBookMsg) {
+        assert_eq!(levels(&actual.asks), levels(&expected.asks));
+        assert_eq!(levels(&actual.bids), levels(&expected.bids));
+        assert_eq!(actual.timestamp, expected.timestamp);
+    }
+
+    #[test]
+    fn test_producer_consumer() {
+        let (sender, receiver) = mpsc::channel();
+
+        let producer = thread::spawn(move || {
+            let mut rng = Lcg(42);
+            let mut publisher = DiffPublisher::new(5, Duration::from_secs(3600));
+
+            for timestamp in 0..100 {
+                let source = construct_book(&mut rng, timestamp);
+                let msg = publisher.emit(&source);
+                sender.send((source, msg)).unwrap();
+            }
+        });
+
+        let mut keeper = BookKeeper::new();
+        let mut snapshots = 0;
+
+        for (source, msg) in receiver {
+            if msg.snapshot {
+                snapshots += 1;
+            }
+
+            let current = keeper.apply(&msg).unwrap();
+            assert_same_book(current, &source);
+            assert!(!keeper.needs_snapshot());
+        }
+
+        producer.join().unwrap();
+        assert_eq!(snapshots, 20);
+    }
+
+    #[test]
+    fn test_gap_requires_snapshot() {
+        let mut rng = Lcg(7);
+        let mut publisher = DiffPublisher::new(4, Duration::from_secs(3600));
+        let sources = (0..5)
+            .map(|timestamp| construct_book(&mut rng, timestamp))
+            .collect::<Vec<_>>();
+        let messages = sources
+            .iter()
+            .map(|source| publisher.emit(source))
+            .collect::<Vec<_>>();
+
+        let mut keeper = BookKeeper::new();
+        assert!(keeper.needs_snapshot());
+        assert_eq!(
+            keeper.apply(&messages[1]).err(),
+            Some(BookKeeperError::SnapshotRequired)
+        );
+
+        keeper.apply(&messages[0]).unwrap();
+
+        // messages[1] is lost.
+        assert_eq!(
+            keeper.apply(&messages[2]).err(),
+            Some(BookKeeperError::SequenceGap {
+                expected: 0,
+                got: 1,
+            })
+        );
+        assert!(keeper.needs_snapshot());
+        assert_eq!(
+            keeper.apply(&messages[3]).err(),
+            Some(BookKeeperError::SnapshotRequired)
+        );
+        assert_same_book(keeper.current().unwrap(), &sources[0]);
+
+        assert!(messages[4].snapshot);
+        let current = keeper.apply(&messages[4]).unwrap();
+        assert_same_book(current, &sources[4]);
+        assert!(!keeper.needs_snapshot());
+    }
+
+    #[test]
+    fn test_snapshot_interval() {
+        let mut rng = Lcg(1);
+        let mut publisher = DiffPublisher::new(usize::MAX, Duration::ZERO);
+
+        for timestamp in 0..3 {
+            let source = construct_book(&mut rng, timestamp);
+            assert!(publisher.emit(&source).snapshot);
+        }
+    }
+
+    #[test]
+    fn test_unchanged_book_emits_empty_diff() {
+        let mut rng = Lcg(3);
+        let mut publisher = DiffPublisher::new(10, Duration::from_secs(3600));
+        let mut keeper = BookKeeper::new();
+        let source = construct_book(&mut rng, 0);
+
+        keeper.apply(&publisher.emit(&source)).unwrap();
+
+        let diff = publisher.emit(&source);
+        assert!(!diff.snapshot);
+        assert!(diff.asks.is_empty());
+        assert!(diff.bids.is_empty());
+        assert_same_book(keeper.apply(&diff).unwrap(), &source);
+    }
+}
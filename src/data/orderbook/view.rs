@@ -0,0 +1,391 @@
+//! The borrowed view over a serialized orderbook.
+//! See [`OrderbookView`].
+
+use std::io;
+
+use super::{check_direction, OrderPriceData, OrderbookResult, OrderbookStructure, OrdersBox};
+use crate::data::fields::info_type::InfoType;
+use crate::data::fields::symbol_pair::Symbol;
+use crate::data::fields::{
+    EndOfDataFlag,
+    ExchangeTypeField,
+    FieldDeserializer,
+    InfoTypeField,
+    MarketTypeField,
+    MessageTypeField,
+    OptionalU64Field,
+    SchemaVersionField,
+    SnapshotField,
+    SymbolPairField,
+    TimestampField,
+};
+
+/// The size of a serialized [`OrderPriceData`].
+#[cfg(not(feature = "extended-price"))]
+const ORDER_LEN: usize = 20;
+
+/// The size of a serialized [`OrderPriceData`].
+#[cfg(feature = "extended-price")]
+const ORDER_LEN: usize = 30;
+
+/// The borrowed view over a serialized [`OrderbookStructure`].
+///
+/// [`parse`](Self::parse) validates the header and the layout
+/// without any allocation, and the orders are decoded from the
+/// underlying slice only when they are accessed. It is useful
+/// when you only need the best prices or a few levels.
+///
+/// # Example
+///
+/// ```
+/// use crypto_market_type::MarketType;
+/// use crypto_message::Order;
+/// use crypto_msg_type::MessageType;
+/// use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+/// use wmjtyd_libstock::data::fields::info_type::InfoType;
+/// use wmjtyd_libstock::data::fields::SymbolPairField;
+/// use wmjtyd_libstock::data::orderbook::{OrderbookStructure, OrderbookView, OrdersBox};
+/// use wmjtyd_libstock::data::serializer::StructSerializer;
+///
+/// let orders = |direction: InfoType, price: f64| {
+///     let order = Order {
+///         price,
+///         quantity_base: 1.0,
+///         quantity_quote: price,
+///         quantity_contract: None,
+///     };
+///
+///     OrdersBox::builder()
+///         .direction(direction)
+///         .orders(std::iter::once((&order).try_into().unwrap()).collect())
+///         .build()
+/// };
+///
+/// let structure = OrderbookStructure::builder()
+///     .exchange_timestamp(1659755147000u64)
+///     .exchange_type(Exchange::Binance)
+///     .market_type(MarketType::Spot)
+///     .message_type(MessageType::L2Event)
+///     .symbol(SymbolPairField::from_pair("BTC/USDT"))
+///     .asks(orders(InfoType::Asks, 2.0))
+///     .bids(orders(InfoType::Bids, 1.0))
+///     .build();
+/// let encoded = structure.serialize_to_vec().unwrap();
+///
+/// let view = OrderbookView::parse(&encoded).unwrap();
+/// assert_eq!(view.timestamp().0, 1659755147000);
+/// assert_eq!(view.ask_count(), 1);
+///
+/// let best_ask = view.ask(0).unwrap().unwrap();
+/// assert_eq!(f64::try_from(best_ask.price).unwrap(), 2.0);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct OrderbookView<'a> {
+    exchange_timestamp: TimestampField,
+    received_timestamp: TimestampField,
+    exchange_type: ExchangeTypeField,
+    market_type: MarketTypeField,
+    message_type: MessageTypeField,
+    symbol: Symbol,
+    snapshot_flag: SnapshotField,
+    seq_id: OptionalU64Field,
+    prev_seq_id: OptionalU64Field,
+    asks: OrdersView<'a>,
+    bids: OrdersView<'a>,
+}
+
+impl<'a> OrderbookView<'a> {
+    /// Parse the serialized orderbook in `src`.
+    ///
+    /// It returns the same errors as [`OrderbookStructure`]'s
+    /// deserializer, except the errors of the orders, which are
+    /// returned when the orders are accessed.
+    pub fn parse(src: &'a [u8]) -> OrderbookResult<Self> {
+        let mut cursor = Cursor(src);
+
+        let version = SchemaVersionField::deserialize(cursor.read()?)?;
+        version.check(OrderbookStructure::SCHEMA_VERSION)?;
+
+        let exchange_timestamp = TimestampField::deserialize(cursor.read()?)?;
+        let received_timestamp = TimestampField::deserialize(cursor.read()?)?;
+        let exchange_type = ExchangeTypeField::deserialize(cursor.read()?)?;
+        let market_type = MarketTypeField::deserialize(cursor.read()?)?;
+        let message_type = MessageTypeField::deserialize(cursor.read()?)?;
+        let symbol = Symbol::from_be_bytes(*cursor.read()?);
+        let snapshot_flag = SnapshotField::deserialize(cursor.read()?)?;
+        let seq_id = OptionalU64Field::deserialize(cursor.read()?)?;
+        let prev_seq_id = OptionalU64Field::deserialize(cursor.read()?)?;
+
+        let asks = OrdersView::parse(&mut cursor, InfoType::Asks)?;
+        let bids = OrdersView::parse(&mut cursor, InfoType::Bids)?;
+
+        EndOfDataFlag::deserialize(cursor.read()?)?;
+
+        Ok(Self {
+            exchange_timestamp,
+            received_timestamp,
+            exchange_type,
+            market_type,
+            message_type,
+            symbol,
+            snapshot_flag,
+            seq_id,
+            prev_seq_id,
+            asks,
+            bids,
+        })
+    }
+
+    /// The exchange timestamp.
+    pub fn timestamp(&self) -> TimestampField {
+        self.exchange_timestamp
+    }
+
+    /// The received timestamp.
+    pub fn received_timestamp(&self) -> TimestampField {
+        self.received_timestamp
+    }
+
+    /// The exchange.
+    pub fn exchange(&self) -> ExchangeTypeField {
+        self.exchange_type
+    }
+
+    /// The market type.
+    pub fn market_type(&self) -> MarketTypeField {
+        self.market_type
+    }
+
+    /// The message type.
+    pub fn message_type(&self) -> MessageTypeField {
+        self.message_type
+    }
+
+    /// The symbol ID.
+    ///
+    /// Use [`symbol_pair`](Self::symbol_pair) to get the pair of it.
+    pub fn symbol(&self) -> Symbol {
+        self.symbol
+    }
+
+    /// The symbol and its pair.
+    ///
+    /// Unlike the other accessors, it allocates the pair.
+    pub fn symbol_pair(&self) -> OrderbookResult<SymbolPairField> {
+        Ok(SymbolPairField::deserialize(&self.symbol.to_be_bytes())?)
+    }
+
+    /// Whether it is a snapshot instead of a delta.
+    pub fn is_snapshot(&self) -> bool {
+        self.snapshot_flag.0
+    }
+
+    /// The sequence ID.
+    pub fn seq_id(&self) -> Option<u64> {
+        self.seq_id.0
+    }
+
+    /// The previous sequence ID.
+    pub fn prev_seq_id(&self) -> Option<u64> {
+        self.prev_seq_id.0
+    }
+
+    /// The number of the asks.
+    pub fn ask_count(&self) -> usize {
+        self.asks.len()
+    }
+
+    /// The number of the bids.
+    pub fn bid_count(&self) -> usize {
+        self.bids.len()
+    }
+
+    /// Decode the ask at `index`.
+    ///
+    /// It returns `None` if `index` is out of bounds.
+    pub fn ask(&self, index: usize) -> Option<OrderbookResult<OrderPriceData>> {
+        self.asks.get(index)
+    }
+
+    /// Decode the bid at `index`.
+    ///
+    /// It returns `None` if `index` is out of bounds.
+    pub fn bid(&self, index: usize) -> Option<OrderbookResult<OrderPriceData>> {
+        self.bids.get(index)
+    }
+}
+
+/// The borrowed view over a serialized [`OrdersBox`].
+#[derive(Clone, Copy, Debug)]
+struct OrdersView<'a> {
+    orders: &'a [u8],
+}
+
+impl<'a> OrdersView<'a> {
+    fn parse(cursor: &mut Cursor<'a>, expected: InfoType) -> OrderbookResult<Self> {
+        let direction = InfoTypeField::deserialize(cursor.read()?)?;
+        check_direction(direction.0, expected)?;
+
+        let length = OrdersBox::deserialize_orders_size(cursor.read()?);
+        let orders = cursor.take(length * ORDER_LEN)?;
+
+        Ok(Self { orders })
+    }
+
+    fn len(&self) -> usize {
+        self.orders.len() / ORDER_LEN
+    }
+
+    fn get(&self, index: usize) -> Option<OrderbookResult<OrderPriceData>> {
+        let start = index.checked_mul(ORDER_LEN)?;
+        let src = self.orders.get(start..start + ORDER_LEN)?;
+        let src: &[u8; ORDER_LEN] = src.try_into().expect("the slice should be ORDER_LEN bytes");
+
+        Some(OrderPriceData::deserialize(src).map_err(Into::into))
+    }
+}
+
+/// The cursor reading the bytes from a slice without copying.
+struct Cursor<'a>(&'a [u8]);
+
+impl<'a> Cursor<'a> {
+    /// Take the next `len` bytes.
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+
+        let (taken, remaining) = self.0.split_at(len);
+        self.0 = remaining;
+
+        Ok(taken)
+    }
+
+    /// Take the next `LEN` bytes as an array.
+    fn read<const LEN: usize>(&mut self) -> io::Result<&'a [u8; LEN]> {
+        Ok(self
+            .take(LEN)?
+            .try_into()
+            .expect("the slice should be LEN bytes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_market_type::MarketType;
+    use crypto_message::Order;
+    use crypto_msg_type::MessageType;
+
+    use super::OrderbookView;
+    use crate::data::fields::exchange_type::Exchange;
+    use crate::data::fields::info_type::InfoType;
+    use crate::data::fields::SymbolPairField;
+    use crate::data::orderbook::{OrderPriceData, OrderbookError, OrderbookStructure, OrdersBox};
+    use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+    fn construct_orders(direction: InfoType, prices: &[f64]) -> OrdersBox {
+        let order = |price| Order {
+            price,
+            quantity_base: 1.5,
+            quantity_quote: price * 1.5,
+            quantity_contract: None,
+        };
+
+        OrdersBox::builder()
+            .direction(direction)
+            .orders(
+                prices
+                    .iter()
+                    .map(|&price| OrderPriceData::try_from(&order(price)).unwrap())
+                    .collect(),
+            )
+            .build()
+    }
+
+    fn construct_orderbook() -> Vec<u8> {
+        OrderbookStructure::builder()
+            .exchange_timestamp(1659755147000u64)
+            .received_timestamp(1659755147123u64)
+            .exchange_type(Exchange::Binance)
+            .market_type(MarketType::Spot)
+            .message_type(MessageType::L2Event)
+            .symbol(SymbolPairField::from_pair("BTC/USDT"))
+            .snapshot_flag(false)
+            .seq_id(Some(42))
+            .prev_seq_id(Some(41))
+            .asks(construct_orders(InfoType::Asks, &[2.0, 3.0, 4.5]))
+            .bids(construct_orders(InfoType::Bids, &[1.0, 0.5]))
+            .build()
+            .serialize_to_vec()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_view_agrees_with_deserializer() {
+        let encoded = construct_orderbook();
+        let structure = OrderbookStructure::deserialize_from_bytes(&encoded).unwrap();
+        let view = OrderbookView::parse(&encoded).unwrap();
+
+        assert_eq!(view.timestamp(), structure.exchange_timestamp);
+        assert_eq!(view.received_timestamp(), structure.received_timestamp);
+        assert_eq!(view.exchange(), structure.exchange_type);
+        assert_eq!(view.market_type(), structure.market_type);
+        assert_eq!(view.message_type(), structure.message_type);
+        assert_eq!(view.symbol(), structure.symbol.symbol);
+        assert_eq!(view.symbol_pair().unwrap(), structure.symbol);
+        assert_eq!(view.is_snapshot(), *structure.snapshot_flag);
+        assert_eq!(view.seq_id(), *structure.seq_id);
+        assert_eq!(view.prev_seq_id(), *structure.prev_seq_id);
+
+        let asks = (0..view.ask_count()).map(|i| view.ask(i).unwrap().unwrap());
+        assert!(asks.eq(structure.asks.orders.iter().cloned()));
+
+        let bids = (0..view.bid_count()).map(|i| view.bid(i).unwrap().unwrap());
+        assert!(bids.eq(structure.bids.orders.iter().cloned()));
+
+        assert!(view.ask(view.ask_count()).is_none());
+        assert!(view.bid(view.bid_count()).is_none());
+    }
+
+    #[test]
+    fn test_view_truncated() {
+        let encoded = construct_orderbook();
+
+        for len in 0..encoded.len() {
+            assert!(matches!(
+                OrderbookView::parse(&encoded[..len]),
+                Err(OrderbookError::IoError(_))
+            ));
+            assert!(OrderbookStructure::deserialize_from_bytes(&encoded[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_view_invalid_end() {
+        let mut encoded = construct_orderbook();
+        *encoded.last_mut().unwrap() = b'x';
+
+        assert!(matches!(
+            OrderbookView::parse(&encoded),
+            Err(OrderbookError::FieldError(_))
+        ));
+        assert!(OrderbookStructure::deserialize_from_bytes(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_view_direction_mismatch() {
+        let mut encoded = construct_orderbook();
+        // The direction of the asks follows the 37-byte header.
+        assert_eq!(encoded[37], InfoType::Asks as u8);
+        encoded[37] = InfoType::Bids as u8;
+
+        assert!(matches!(
+            OrderbookView::parse(&encoded),
+            Err(OrderbookError::DirectionMismatch { .. })
+        ));
+        assert!(matches!(
+            OrderbookStructure::deserialize_from_bytes(&encoded),
+            Err(OrderbookError::DirectionMismatch { .. })
+        ));
+    }
+}
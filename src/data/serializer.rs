@@ -6,7 +6,17 @@
 //!
 //! For example, see `examples/concept_struct.rs` and our implementations.
 
-use std::io::{self, Read, Write};
+use std::fmt::Debug;
+use std::io::{self, BufRead, BufReader, Cursor, Read, Write};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+use tokio::io::{AsyncRead, ReadBuf};
+
+#[cfg(feature = "msgpack")]
+use super::msgpack::MsgpackResult;
 
 /// The serializer for fields.
 pub trait FieldSerializer<const LEN: usize>
@@ -60,6 +70,46 @@ where
     type Err;
 
     fn serialize(&self, writer: &mut impl Write) -> Result<(), Self::Err>;
+
+    /// Serialize the structure to a new [`Vec`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Write;
+    ///
+    /// use wmjtyd_libstock::data::serializer::StructSerializer;
+    ///
+    /// struct Pair(u8, u8);
+    ///
+    /// impl StructSerializer for Pair {
+    ///     type Err = std::io::Error;
+    ///
+    ///     fn serialize(&self, writer: &mut impl Write) -> Result<(), Self::Err> {
+    ///         writer.write_all(&[self.0, self.1])
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Pair(1, 2).serialize_to_vec().unwrap(), vec![1, 2]);
+    /// ```
+    fn serialize_to_vec(&self) -> Result<Vec<u8>, Self::Err> {
+        let mut buf = Vec::new();
+        self.serialize(&mut buf)?;
+
+        Ok(buf)
+    }
+
+    /// Serialize the structure to [MessagePack](https://msgpack.org).
+    ///
+    /// Unlike [`serialize`](Self::serialize), the output is interoperable
+    /// with the other systems, but larger.
+    #[cfg(feature = "msgpack")]
+    fn serialize_msgpack(&self) -> MsgpackResult<Vec<u8>>
+    where
+        Self: serde::Serialize,
+    {
+        Ok(rmp_serde::to_vec_named(self)?)
+    }
 }
 
 /// The deserializer for structures.
@@ -70,6 +120,284 @@ where
     type Err;
 
     fn deserialize(reader: &mut impl Read) -> Result<Self, Self::Err>;
+
+    /// Deserialize the structure from the bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::io::Read;
+    ///
+    /// use wmjtyd_libstock::data::serializer::StructDeserializer;
+    ///
+    /// #[derive(Debug, PartialEq, Eq)]
+    /// struct Pair(u8, u8);
+    ///
+    /// impl StructDeserializer for Pair {
+    ///     type Err = std::io::Error;
+    ///
+    ///     fn deserialize(reader: &mut impl Read) -> Result<Self, Self::Err> {
+    ///         let mut buf = [0; 2];
+    ///         reader.read_exact(&mut buf)?;
+    ///
+    ///         Ok(Pair(buf[0], buf[1]))
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(Pair::deserialize_from_bytes(&[1, 2]).unwrap(), Pair(1, 2));
+    /// ```
+    fn deserialize_from_bytes(bytes: &[u8]) -> Result<Self, Self::Err> {
+        Self::deserialize(&mut Cursor::new(bytes))
+    }
+
+    /// Deserialize the structure from [MessagePack](https://msgpack.org).
+    ///
+    /// See [`StructSerializer::serialize_msgpack`].
+    #[cfg(feature = "msgpack")]
+    fn deserialize_msgpack(bytes: &[u8]) -> MsgpackResult<Self>
+    where
+        Self: serde::de::DeserializeOwned,
+    {
+        Ok(rmp_serde::from_slice(bytes)?)
+    }
+}
+
+/// The marker of the data structures, such as the BBO and trade structures.
+///
+/// The bindings can rely on it to clone, compare, print, serialize
+/// and deserialize any structure.
+pub trait DataStructure: Clone + Debug + PartialEq + StructSerializer + StructDeserializer {}
+
+/// The deserializer reading the structures continuously from a stream.
+///
+/// It is useful to parse the structures written back to back,
+/// for example, from a TCP stream or a file. The reader is buffered
+/// internally, so the partial reads are handled.
+///
+/// It ends when the reader reaches EOF between two structures.
+/// If the reader ends in the middle of a structure, or any error occurred,
+/// the error is returned and the iterator ends.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Read;
+///
+/// use wmjtyd_libstock::data::serializer::{StreamingDeserializer, StructDeserializer};
+///
+/// #[derive(Debug, PartialEq, Eq)]
+/// struct Pair(u8, u8);
+///
+/// impl StructDeserializer for Pair {
+///     type Err = std::io::Error;
+///
+///     fn deserialize(reader: &mut impl Read) -> Result<Self, Self::Err> {
+///         let mut buf = [0; 2];
+///         reader.read_exact(&mut buf)?;
+///
+///         Ok(Pair(buf[0], buf[1]))
+///     }
+/// }
+///
+/// let records = [1, 2, 3, 4];
+/// let pairs = StreamingDeserializer::<_, Pair>::new(&records[..])
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+///
+/// assert_eq!(pairs, vec![Pair(1, 2), Pair(3, 4)]);
+/// ```
+pub struct StreamingDeserializer<R, T> {
+    reader: BufReader<R>,
+    finished: bool,
+    _structure: PhantomData<fn() -> T>,
+}
+
+impl<R: Read, T> StreamingDeserializer<R, T> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            finished: false,
+            _structure: PhantomData,
+        }
+    }
+
+    /// Unwrap this deserializer, returning the underlying reader.
+    ///
+    /// Note that the buffered data is lost.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner()
+    }
+}
+
+impl<R, T> Iterator for StreamingDeserializer<R, T>
+where
+    R: Read,
+    T: StructDeserializer,
+    T::Err: From<io::Error>,
+{
+    type Item = Result<T, T::Err>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        // Check if we have reached EOF between two structures.
+        match self.reader.fill_buf() {
+            Ok([]) => {
+                self.finished = true;
+                return None;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                self.finished = true;
+                return Some(Err(e.into()));
+            }
+        }
+
+        let result = T::deserialize(&mut self.reader);
+        if result.is_err() {
+            self.finished = true;
+        }
+
+        Some(result)
+    }
+}
+
+/// The asynchronous version of [`StreamingDeserializer`].
+///
+/// As [`StructDeserializer`] is synchronous, the data is read into
+/// the internal buffer until a whole structure can be deserialized.
+pub struct AsyncStreamingDeserializer<R, T> {
+    reader: R,
+    buffer: Vec<u8>,
+    reader_eof: bool,
+    finished: bool,
+    _structure: PhantomData<fn() -> T>,
+}
+
+impl<R: AsyncRead + Unpin, T> AsyncStreamingDeserializer<R, T> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            reader_eof: false,
+            finished: false,
+            _structure: PhantomData,
+        }
+    }
+
+    /// Unwrap this deserializer, returning the underlying reader.
+    ///
+    /// Note that the buffered data is lost.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Try to deserialize a structure from the buffered data.
+    ///
+    /// It returns `None` if more data is needed.
+    fn try_deserialize(&mut self) -> Option<Result<T, T::Err>>
+    where
+        T: StructDeserializer,
+    {
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        let mut reader = EofTrackingReader::new(&self.buffer);
+
+        match T::deserialize(&mut reader) {
+            Ok(structure) => {
+                let consumed = self.buffer.len() - reader.remaining.len();
+                self.buffer.drain(..consumed);
+
+                Some(Ok(structure))
+            }
+            // The structure is incomplete. Read more if possible.
+            Err(_) if reader.hit_eof && !self.reader_eof => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+impl<R, T> Stream for AsyncStreamingDeserializer<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: StructDeserializer,
+    T::Err: From<io::Error>,
+{
+    type Item = Result<T, T::Err>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.finished {
+                return Poll::Ready(None);
+            }
+
+            if let Some(result) = this.try_deserialize() {
+                if result.is_err() {
+                    this.finished = true;
+                }
+
+                return Poll::Ready(Some(result));
+            }
+
+            if this.reader_eof {
+                // EOF between two structures.
+                this.finished = true;
+                return Poll::Ready(None);
+            }
+
+            let mut chunk = [0; 8192];
+            let mut read_buf = ReadBuf::new(&mut chunk);
+
+            match Pin::new(&mut this.reader).poll_read(cx, &mut read_buf) {
+                Poll::Ready(Ok(())) => {
+                    let filled = read_buf.filled();
+
+                    if filled.is_empty() {
+                        this.reader_eof = true;
+                    } else {
+                        this.buffer.extend_from_slice(filled);
+                    }
+                }
+                Poll::Ready(Err(e)) => {
+                    this.finished = true;
+                    return Poll::Ready(Some(Err(e.into())));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// The reader over a slice, recording if the reader has reached its end.
+struct EofTrackingReader<'a> {
+    remaining: &'a [u8],
+    hit_eof: bool,
+}
+
+impl<'a> EofTrackingReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            remaining: data,
+            hit_eof: false,
+        }
+    }
+}
+
+impl Read for EofTrackingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining.is_empty() && !buf.is_empty() {
+            self.hit_eof = true;
+            return Ok(0);
+        }
+
+        self.remaining.read(buf)
+    }
 }
 
 macro_rules! serialize_block_builder {
@@ -91,3 +419,169 @@ macro_rules! deserialize_block_builder {
 }
 
 pub(crate) use {deserialize_block_builder, serialize_block_builder};
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "crypto")]
+    mod structures {
+        use crypto_market_type::MarketType;
+        use crypto_message::{BboMsg, CandlestickMsg};
+        use futures::StreamExt;
+
+        use super::super::{
+            AsyncStreamingDeserializer,
+            StreamingDeserializer,
+            StructDeserializer,
+            StructSerializer,
+        };
+        use crate::data::bbo::{BboError, BboStructure};
+        use crate::data::kline::{KlineError, KlineStructure};
+
+        fn construct_bbo(timestamp: i64) -> BboStructure {
+            BboStructure::try_from(&BboMsg {
+                exchange: "binance".into(),
+                market_type: MarketType::Spot,
+                symbol: "BTCUSDT".into(),
+                pair: "BTC/USDT".into(),
+                msg_type: crypto_msg_type::MessageType::BBO,
+                timestamp,
+                json: "".into(),
+                bid_price: 1.0,
+                bid_quantity_base: 2.0,
+                bid_quantity_quote: 0.0,
+                bid_quantity_contract: None,
+                ask_price: 4.0,
+                ask_quantity_base: 5.0,
+                ask_quantity_quote: 0.0,
+                ask_quantity_contract: None,
+                id: None,
+            })
+            .unwrap()
+        }
+
+        fn construct_kline(timestamp: i64) -> KlineStructure {
+            KlineStructure::try_from(&CandlestickMsg {
+                exchange: "binance".into(),
+                market_type: MarketType::Spot,
+                msg_type: crypto_msg_type::MessageType::Candlestick,
+                symbol: "BTCUSDT".into(),
+                pair: "BTC/USDT".into(),
+                timestamp,
+                period: "1m".into(),
+                begin_time: 0,
+                open: 1.0,
+                high: 4.0,
+                low: 0.5,
+                close: 2.0,
+                volume: 100.0,
+                quote_volume: None,
+                json: "".into(),
+            })
+            .unwrap()
+        }
+
+        /// Serialize the structures back to back.
+        fn concat<T: StructSerializer>(structures: &[T]) -> Vec<u8>
+        where
+            T::Err: std::fmt::Debug,
+        {
+            structures
+                .iter()
+                .flat_map(|s| s.serialize_to_vec().unwrap())
+                .collect()
+        }
+
+        /// The reader returning at most 3 bytes per read.
+        struct TrickleReader<'a>(&'a [u8]);
+
+        impl std::io::Read for TrickleReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let len = buf.len().min(3);
+                self.0.read(&mut buf[..len])
+            }
+        }
+
+        #[test]
+        fn test_streaming_bbo() {
+            let expected = vec![construct_bbo(1), construct_bbo(2), construct_bbo(3)];
+            let data = concat(&expected);
+
+            let actual = StreamingDeserializer::<_, BboStructure>::new(TrickleReader(&data))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn test_streaming_kline() {
+            let expected = vec![construct_kline(1), construct_kline(2)];
+            let data = concat(&expected);
+
+            let actual = StreamingDeserializer::<_, KlineStructure>::new(data.as_slice())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(actual, expected);
+        }
+
+        #[test]
+        fn test_streaming_truncated() {
+            let structures = [construct_bbo(1), construct_bbo(2)];
+            let data = concat(&structures);
+            let truncated = &data[..data.len() - 1];
+
+            let mut deserializer = StreamingDeserializer::<_, BboStructure>::new(truncated);
+
+            assert_eq!(deserializer.next().unwrap().unwrap(), structures[0]);
+            assert!(matches!(deserializer.next(), Some(Err(BboError::IoError(_)))));
+            assert!(deserializer.next().is_none());
+        }
+
+        #[tokio::test]
+        async fn test_async_streaming_kline() {
+            let expected = vec![construct_kline(1), construct_kline(2), construct_kline(3)];
+            let data = concat(&expected);
+
+            // Feed the data in small chunks to test the partial reads.
+            let (mut writer, reader) = tokio::io::duplex(4);
+            let feeder = tokio::spawn(async move {
+                use tokio::io::AsyncWriteExt;
+
+                writer.write_all(&data).await.unwrap();
+            });
+
+            let actual = AsyncStreamingDeserializer::<_, KlineStructure>::new(reader)
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            feeder.await.unwrap();
+            assert_eq!(actual, expected);
+        }
+
+        #[tokio::test]
+        async fn test_async_streaming_truncated() {
+            let structures = [construct_kline(1), construct_kline(2)];
+            let data = concat(&structures);
+            let truncated = &data[..data.len() - 1];
+
+            let mut deserializer =
+                AsyncStreamingDeserializer::<_, KlineStructure>::new(truncated);
+
+            assert_eq!(deserializer.next().await.unwrap().unwrap(), structures[0]);
+            assert!(matches!(deserializer.next().await, Some(Err(KlineError::IoError(_)))));
+            assert!(deserializer.next().await.is_none());
+        }
+
+        #[test]
+        fn test_deserialize_from_bytes() {
+            let bbo = construct_bbo(1);
+            let bytes = bbo.serialize_to_vec().unwrap();
+
+            assert_eq!(BboStructure::deserialize_from_bytes(&bytes).unwrap(), bbo);
+        }
+    }
+}
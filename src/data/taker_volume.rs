@@ -0,0 +1,166 @@
+//! The taker volume related operations.
+
+use typed_builder::TypedBuilder;
+
+use super::fields::{
+    DecimalField,
+    EndOfDataFlag,
+    ExchangeTypeField,
+    FieldError,
+    MarketTypeField,
+    MessageTypeField,
+    PeriodField,
+    SchemaVersionField,
+    SymbolPairField,
+    TimestampField,
+};
+use super::serializer::{
+    deserialize_block_builder,
+    serialize_block_builder,
+    DataStructure,
+    FieldDeserializer,
+    StructDeserializer,
+    StructSerializer,
+};
+
+/// The taker volume, which may have 18+ decimal places.
+pub type TakerVolumeField = DecimalField<20>;
+
+/// The structure of the taker buy/sell volume in a period.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TakerVolumeStructure {
+    /// 交易所時間戳
+    #[builder(setter(into))]
+    pub exchange_timestamp: TimestampField,
+
+    /// 收到時間戳
+    #[builder(default, setter(into))]
+    pub received_timestamp: TimestampField,
+
+    /// 交易所類型 (EXCHANGE)
+    #[builder(setter(into))]
+    pub exchange_type: ExchangeTypeField,
+
+    /// 市場類型 (MARKET_TYPE)
+    #[builder(setter(into))]
+    pub market_type: MarketTypeField,
+
+    /// 訊息類型 (MESSAGE_TYPE)
+    #[builder(setter(into))]
+    pub message_type: MessageTypeField,
+
+    /// Symbol (ID，`1`) 或 Pair（字串，`BTC/USDT`）
+    #[builder(setter(into))]
+    pub symbol: SymbolPairField,
+
+    /// PERIOD
+    #[builder(setter(into))]
+    pub period: PeriodField,
+
+    /// Taker buy volume
+    #[builder(setter(into))]
+    pub buy_volume: TakerVolumeField,
+
+    /// Taker sell volume
+    #[builder(setter(into))]
+    pub sell_volume: TakerVolumeField,
+
+    /// 資料結尾
+    #[builder(default)]
+    pub end: EndOfDataFlag,
+}
+
+impl TakerVolumeStructure {
+    /// The current schema version of the serialized taker volume structure.
+    pub const SCHEMA_VERSION: u8 = 1;
+}
+
+impl StructSerializer for TakerVolumeStructure {
+    type Err = TakerVolumeError;
+
+    fn serialize(&self, writer: &mut impl std::io::Write) -> Result<(), Self::Err> {
+        serialize_block_builder!(
+            SchemaVersionField(Self::SCHEMA_VERSION),
+            self.exchange_timestamp,
+            self.received_timestamp,
+            self.exchange_type,
+            self.market_type,
+            self.message_type,
+            self.symbol,
+            self.period,
+            self.buy_volume,
+            self.sell_volume,
+            self.end
+            => writer
+        );
+
+        Ok(())
+    }
+}
+
+impl StructDeserializer for TakerVolumeStructure {
+    type Err = TakerVolumeError;
+
+    fn deserialize(reader: &mut impl std::io::Read) -> Result<Self, Self::Err> {
+        SchemaVersionField::deserialize_from_reader(reader)??.check(Self::SCHEMA_VERSION)?;
+
+        deserialize_block_builder!(
+            reader =>
+            exchange_timestamp,
+            received_timestamp,
+            exchange_type,
+            market_type,
+            message_type,
+            symbol,
+            period,
+            buy_volume,
+            sell_volume,
+            end
+        )
+    }
+}
+
+impl DataStructure for TakerVolumeStructure {}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TakerVolumeError {
+    #[error("field error: {0}")]
+    FieldError(#[from] FieldError),
+
+    #[error("I/O reader/writer error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+pub type TakerVolumeResult<T> = Result<T, TakerVolumeError>;
+
+#[cfg(test)]
+mod tests {
+    use crypto_market_type::MarketType;
+    use crypto_msg_type::MessageType;
+    use rust_decimal_macros::dec;
+
+    use super::TakerVolumeStructure;
+    use crate::data::fields::exchange_type::Exchange;
+    use crate::data::fields::SymbolPairField;
+    use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+    #[test]
+    fn test_taker_volume_encode_decode() {
+        let structure = TakerVolumeStructure::builder()
+            .exchange_timestamp(1659755147000u64)
+            .exchange_type(Exchange::Binance)
+            .market_type(MarketType::LinearSwap)
+            .message_type(MessageType::TakerVolume)
+            .symbol(SymbolPairField::from_pair("SHIB/USDT"))
+            .period("5m")
+            .buy_volume(dec!(0.000000000000001234))
+            .sell_volume(dec!(123456789.123456789012345678))
+            .build();
+
+        let encoded = structure.serialize_to_vec().unwrap();
+        let decoded = TakerVolumeStructure::deserialize_from_bytes(&encoded).unwrap();
+
+        assert_eq!(decoded, structure);
+    }
+}
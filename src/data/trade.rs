@@ -4,19 +4,24 @@ pub use crypto_message::TradeMsg;
 use typed_builder::TypedBuilder;
 
 use super::fields::{
+    DecimalField,
     EndOfDataFlag,
     ExchangeTypeField,
     FieldError,
     MarketTypeField,
     MessageTypeField,
     PriceDataField,
+    SchemaVersionField,
     SymbolPairField,
     TimestampField,
+    TradeIdField,
     TradeSideField,
 };
 use super::serializer::{
     deserialize_block_builder,
     serialize_block_builder,
+    DataStructure,
+    FieldDeserializer,
     StructDeserializer,
     StructSerializer,
 };
@@ -25,14 +30,53 @@ use super::serializer::{
 ///
 /// You can take advantage of `builder()`
 /// to construct some fields automatically.
-#[derive(Clone, Debug, PartialEq, Eq, TypedBuilder)]
+///
+/// # Example
+///
+/// The setters accept any value convertible to the field,
+/// so there is no need to wrap the values manually.
+///
+/// ```
+/// use crypto_market_type::MarketType;
+/// use crypto_msg_type::MessageType;
+/// use wmjtyd_libstock::data::fields::exchange_type::Exchange;
+/// use wmjtyd_libstock::data::fields::trade_side::TradeSide;
+/// use wmjtyd_libstock::data::fields::PriceDataField;
+/// use wmjtyd_libstock::data::trade::TradeStructure;
+///
+/// let structure = TradeStructure::builder()
+///     .exchange_timestamp(1672531200000u64)
+///     .received_timestamp(1672531200005u64)
+///     .exchange_type(Exchange::Binance)
+///     .market_type(MarketType::Spot)
+///     .message_type(MessageType::Trade)
+///     .symbol("BTC/USDT")
+///     .trade_side(TradeSide::Buy)
+///     .trade_price(PriceDataField::builder().price(12345.0).quantity_base(2.0).build())
+///     .quantity_quote(24690.0)
+///     .trade_id("123456")
+///     .build();
+///
+/// assert_eq!(structure.symbol.pair, "BTC/USDT");
+/// assert_eq!(structure.received_timestamp.0, 1672531200005);
+/// ```
+///
+/// The values which are not convertible are still rejected:
+///
+/// ```compile_fail
+/// use wmjtyd_libstock::data::trade::TradeStructure;
+///
+/// let builder = TradeStructure::builder().trade_side("buy");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash, TypedBuilder)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TradeStructure {
     /// 交易所時間戳
     #[builder(setter(into))]
     pub exchange_timestamp: TimestampField,
 
     /// 收到時間戳
-    #[builder(default)]
+    #[builder(default, setter(into))]
     pub received_timestamp: TimestampField,
 
     /// 交易所類型 (EXCHANGE)
@@ -48,27 +92,50 @@ pub struct TradeStructure {
     pub message_type: MessageTypeField,
 
     /// Symbol (ID，`1`) 或 Pair（字串，`BTC/USDT`）
+    #[builder(setter(into))]
     pub symbol: SymbolPairField,
 
     /// 交易方向 (Trade side)
     ///
-    /// Buy or Sell?
+    /// Buy, Sell or unknown?
     #[builder(setter(into))]
     pub trade_side: TradeSideField,
 
     /// 交易價格資訊
+    #[builder(setter(into))]
     pub trade_price: PriceDataField,
 
+    /// 成交額 (quantity quote)
+    #[builder(setter(into))]
+    pub quantity_quote: DecimalField<10>,
+
+    /// 交易 ID (trade ID)，最長 32 bytes
+    #[builder(default, setter(into))]
+    pub trade_id: TradeIdField,
+
     /// 資料結尾
     #[builder(default)]
     pub end: EndOfDataFlag,
 }
 
+impl TradeStructure {
+    /// The current schema version of the serialized trade structure.
+    pub const SCHEMA_VERSION: u8 = 2;
+
+    /// Convert a [`TradeMsg`] to a trade structure.
+    ///
+    /// It is the same as [`TryFrom<&TradeMsg>`](TryFrom), but easier to find.
+    pub fn from_msg(msg: &TradeMsg) -> TradeResult<Self> {
+        Self::try_from(msg)
+    }
+}
+
 impl StructSerializer for TradeStructure {
     type Err = TradeError;
 
     fn serialize(&self, writer: &mut impl std::io::Write) -> Result<(), Self::Err> {
         serialize_block_builder!(
+            SchemaVersionField(Self::SCHEMA_VERSION),
             self.exchange_timestamp,
             self.received_timestamp,
             self.exchange_type,
@@ -77,6 +144,8 @@ impl StructSerializer for TradeStructure {
             self.symbol,
             self.trade_side,
             self.trade_price,
+            self.quantity_quote,
+            self.trade_id,
             self.end
             => writer
         );
@@ -89,6 +158,8 @@ impl StructDeserializer for TradeStructure {
     type Err = TradeError;
 
     fn deserialize(reader: &mut impl std::io::Read) -> Result<Self, Self::Err> {
+        SchemaVersionField::deserialize_from_reader(reader)??.check(Self::SCHEMA_VERSION)?;
+
         deserialize_block_builder!(
             reader =>
             exchange_timestamp,
@@ -99,17 +170,21 @@ impl StructDeserializer for TradeStructure {
             symbol,
             trade_side,
             trade_price,
+            quantity_quote,
+            trade_id,
             end
         )
     }
 }
 
+impl DataStructure for TradeStructure {}
+
 impl TryFrom<&TradeMsg> for TradeStructure {
     type Error = TradeError;
 
     fn try_from(msg: &TradeMsg) -> Result<Self, Self::Error> {
         Ok(TradeStructure::builder()
-            .exchange_timestamp(msg.timestamp)
+            .exchange_timestamp(TimestampField::try_from_i64(msg.timestamp)?)
             .exchange_type(ExchangeTypeField::try_from_str(&msg.exchange)?)
             .market_type(msg.market_type)
             .message_type(msg.msg_type)
@@ -121,6 +196,8 @@ impl TryFrom<&TradeMsg> for TradeStructure {
                     .quantity_base(msg.quantity_base)
                     .build(),
             )
+            .quantity_quote(msg.quantity_quote)
+            .trade_id(msg.trade_id.as_str())
             .build())
     }
 }
@@ -138,12 +215,12 @@ impl TryFrom<TradeStructure> for TradeMsg {
             pair,
             symbol: symbol.to_string(),
             timestamp: value.exchange_timestamp.into(),
-            side: value.trade_side.into(),
+            side: value.trade_side.0.ok_or(TradeError::UnknownSide)?,
             price: value.trade_price.price.try_into()?,
             quantity_base: value.trade_price.quantity_base.try_into()?,
-            quantity_quote: 0.0,
+            quantity_quote: value.quantity_quote.try_into()?,
             quantity_contract: None,
-            trade_id: String::new(),
+            trade_id: value.trade_id.into(),
             json: String::new(),
         })
     }
@@ -156,6 +233,10 @@ pub enum TradeError {
 
     #[error("I/O reader/writer error: {0}")]
     IoError(#[from] std::io::Error),
+
+    /// [`TradeMsg`] can't represent the unknown side.
+    #[error("the trade side is unknown")]
+    UnknownSide,
 }
 
 pub type TradeResult<T> = Result<T, TradeError>;
@@ -164,7 +245,98 @@ pub type TradeResult<T> = Result<T, TradeError>;
 crate::compat::compat_enc!(
     enc = encode_trade,
     dec = decode_trade,
+    dec_partial = decode_trade_partial,
     crawl = TradeMsg,
     result = TradeResult,
     structure = TradeStructure
 );
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crypto_market_type::MarketType;
+    use crypto_message::{TradeMsg, TradeSide};
+
+    use super::{TradeError, TradeStructure};
+    use crate::data::fields::{FieldError, TradeSideField};
+    use crate::data::serializer::{StructDeserializer, StructSerializer};
+
+    fn construct_trademsg(trade_id: &str) -> TradeMsg {
+        TradeMsg {
+            exchange: "binance".into(),
+            market_type: MarketType::Spot,
+            symbol: "BTCUSDT".into(),
+            pair: "BTC/USDT".into(),
+            msg_type: crypto_msg_type::MessageType::Trade,
+            timestamp: 12345678,
+            price: 3.5,
+            quantity_base: 6.0,
+            quantity_quote: 21.0,
+            quantity_contract: None,
+            side: TradeSide::Sell,
+            trade_id: trade_id.into(),
+            json: "".into(),
+        }
+    }
+
+    fn roundtrip(payload: &TradeMsg) -> TradeMsg {
+        let structure = TradeStructure::try_from(payload).unwrap();
+        let buffer = structure.serialize_to_vec().unwrap();
+        let decoded = TradeStructure::deserialize_from_bytes(&buffer).unwrap();
+
+        TradeMsg::try_from(decoded).unwrap()
+    }
+
+    #[test]
+    fn test_trade_encode_decode() {
+        for trade_id in ["", "2138475612", "0123456789abcdef0123456789abcdef"] {
+            let payload = construct_trademsg(trade_id);
+            let decoded_msg = roundtrip(&payload);
+
+            assert_eq!(payload.exchange, decoded_msg.exchange);
+            assert_eq!(payload.pair, decoded_msg.pair);
+            assert_eq!(payload.timestamp, decoded_msg.timestamp);
+            assert_eq!(payload.side, decoded_msg.side);
+            assert_eq!(payload.price, decoded_msg.price);
+            assert_eq!(payload.quantity_base, decoded_msg.quantity_base);
+            assert_eq!(payload.quantity_quote, decoded_msg.quantity_quote);
+            assert_eq!(payload.trade_id, decoded_msg.trade_id);
+        }
+    }
+
+    #[test]
+    fn test_trade_encode_decode_unknown_side() {
+        let mut structure = TradeStructure::try_from(&construct_trademsg("42")).unwrap();
+        structure.trade_side = TradeSideField::UNKNOWN;
+
+        let buffer = structure.serialize_to_vec().unwrap();
+        let decoded = TradeStructure::deserialize_from_bytes(&buffer).unwrap();
+        assert!(decoded.trade_side.is_unknown());
+        assert_eq!(decoded, structure);
+
+        assert!(matches!(TradeMsg::try_from(decoded), Err(TradeError::UnknownSide)));
+    }
+
+    #[test]
+    fn test_trade_id_too_long() {
+        let payload = construct_trademsg(&"1".repeat(33));
+        let structure = TradeStructure::try_from(&payload).unwrap();
+
+        assert!(matches!(
+            structure.serialize_to_vec(),
+            Err(TradeError::FieldError(FieldError::TradeIdTooLong(33)))
+        ));
+    }
+
+    #[test]
+    fn test_trade_hash() {
+        let structure = TradeStructure::try_from(&construct_trademsg("42")).unwrap();
+        let mut other_side = structure.clone();
+        other_side.trade_side = TradeSideField::UNKNOWN;
+
+        let set = HashSet::from([structure.clone(), structure.clone(), other_side]);
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&structure));
+    }
+}
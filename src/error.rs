@@ -0,0 +1,859 @@
+//! The unified error of this crate, with the stable error codes.
+//! See [`LibstockError`].
+//!
+//! The error codes are grouped by module:
+//!
+//! - 1xxx, [`ErrorCategory::Data`]:
+//!   - 1000–1099: [`NumError`]
+//!   - 1100–1199: `FieldError`
+//!   - 1200–1299: the structure errors, such as `BboError`, `EnvelopeError` and `BookKeeperError`
+//!   - 1300–1399: `MsgpackError`
+//! - 2xxx, [`ErrorCategory::File`]:
+//!   - 2000–2099: [`WriteError`]
+//!   - 2100–2199: [`ReadError`]
+//!   - 2200–2299: `ExportError`
+//! - 3xxx, [`ErrorCategory::Message`]:
+//!   - 3000–3099: `NanomsgError`
+//!   - 3100–3199: `ZeromqError`
+//!   - 3200–3299: `TypedMessageError`
+//!   - 3300–3399: [`FanoutError`]
+//!   - 3400–3499: `MemoryError`
+//!   - 3500–3599: the errors of [`MessageError`] itself, such as `NotSubscribed`
+//!   - 3600–3699: [`TopicMessageError`]
+//!   - 3700–3799: `TcpError`
+//! - 4xxx, [`ErrorCategory::Notification`]:
+//!   - 4000–4099: `SlackError`
+//!   - 4100–4199: `DiscordError`
+//!   - 4200–4299: `NotificationError`
+//!
+//! The wrapping variants take the code of the wrapped error. For example,
+//! `FieldError::NumError` has the code of its [`NumError`], and
+//! `BboError::FieldError` has the code of its `FieldError`.
+//!
+//! The codes are stable: a code is never reused or reassigned,
+//! and the new variants get the next unused code in their range.
+
+use crate::data::num::NumError;
+use crate::file::reader::ReadError;
+use crate::file::writer::WriteError;
+use crate::message::envelope::TopicMessageError;
+use crate::message::fanout::FanoutError;
+use crate::message::MessageError;
+
+/// The category of a [`LibstockError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// The errors in [`mod@crate::data`].
+    Data,
+    /// The errors in [`mod@crate::file`].
+    File,
+    /// The errors in [`mod@crate::message`].
+    Message,
+    /// The errors in the `slack` module.
+    Notification,
+}
+
+/// The error of any module in this crate.
+///
+/// # Example
+///
+/// ```
+/// use wmjtyd_libstock::data::num::NumError;
+/// use wmjtyd_libstock::error::{ErrorCategory, LibstockError};
+///
+/// let error = LibstockError::from(NumError::InvalidScale(42));
+///
+/// assert_eq!(error.error_code(), 1003);
+/// assert_eq!(error.category(), ErrorCategory::Data);
+/// ```
+#[derive(thiserror::Error, Debug)]
+pub enum LibstockError {
+    #[error("{0}")]
+    Num(#[from] NumError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    Field(#[from] crate::data::fields::FieldError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    Bbo(#[from] crate::data::bbo::BboError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    Orderbook(#[from] crate::data::orderbook::OrderbookError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    Kline(#[from] crate::data::kline::KlineError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    Trade(#[from] crate::data::trade::TradeError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    FundingRate(#[from] crate::data::funding_rate::FundingRateError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    TakerVolume(#[from] crate::data::taker_volume::TakerVolumeError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    LongShortRatio(#[from] crate::data::long_short_ratio::LongShortRatioError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    Envelope(#[from] crate::data::envelope::EnvelopeError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    BookKeeper(#[from] crate::data::orderbook::BookKeeperError),
+
+    #[cfg(feature = "msgpack")]
+    #[error("{0}")]
+    Msgpack(#[from] crate::data::msgpack::MsgpackError),
+
+    #[error("{0}")]
+    Write(#[from] WriteError),
+
+    #[error("{0}")]
+    Read(#[from] ReadError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    Export(#[from] crate::file::export::ExportError),
+
+    /// The errors of [`Nanomsg`](crate::message::nanomsg)
+    /// and [`Zeromq`](crate::message::zeromq).
+    #[error("{0}")]
+    Message(#[from] MessageError),
+
+    #[error("{0}")]
+    Fanout(#[from] FanoutError),
+
+    #[error("{0}")]
+    TopicMessage(#[from] TopicMessageError),
+
+    #[cfg(feature = "crypto")]
+    #[error("{0}")]
+    TypedMessage(#[from] crate::message::typed::TypedMessageError),
+
+    /// The errors of Slack and Discord.
+    #[cfg(feature = "slack")]
+    #[error("{0}")]
+    Notification(#[from] crate::slack::traits::NotificationError),
+}
+
+impl LibstockError {
+    /// The stable numeric code of this error.
+    ///
+    /// See [the module documentation](self) for the ranges.
+    pub fn error_code(&self) -> u32 {
+        match self {
+            Self::Num(e) => num_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::Field(e) => data::field_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::Bbo(e) => data::bbo_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::Orderbook(e) => data::orderbook_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::Kline(e) => data::kline_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::Trade(e) => data::trade_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::FundingRate(e) => data::funding_rate_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::TakerVolume(e) => data::taker_volume_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::LongShortRatio(e) => data::long_short_ratio_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::Envelope(e) => data::envelope_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::BookKeeper(e) => data::book_keeper_error_code(e),
+            #[cfg(feature = "msgpack")]
+            Self::Msgpack(e) => msgpack_error_code(e),
+            Self::Write(e) => write_error_code(e),
+            Self::Read(e) => read_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::Export(e) => export_error_code(e),
+            Self::Message(e) => message::message_error_code(e),
+            Self::Fanout(e) => message::fanout_error_code(e),
+            Self::TopicMessage(e) => message::topic_message_error_code(e),
+            #[cfg(feature = "crypto")]
+            Self::TypedMessage(e) => message::typed_message_error_code(e),
+            #[cfg(feature = "slack")]
+            Self::Notification(e) => notification::notification_error_code(e),
+        }
+    }
+
+    /// The category of this error.
+    pub fn category(&self) -> ErrorCategory {
+        match self.error_code() / 1000 {
+            1 => ErrorCategory::Data,
+            2 => ErrorCategory::File,
+            3 => ErrorCategory::Message,
+            4 => ErrorCategory::Notification,
+            code => unreachable!("error code out of the ranges: {code}xxx"),
+        }
+    }
+}
+
+#[cfg(feature = "nanomsg")]
+impl From<crate::message::nanomsg::NanomsgError> for LibstockError {
+    fn from(e: crate::message::nanomsg::NanomsgError) -> Self {
+        Self::Message(e.into())
+    }
+}
+
+#[cfg(feature = "zeromq")]
+impl From<crate::message::zeromq::ZeromqError> for LibstockError {
+    fn from(e: crate::message::zeromq::ZeromqError) -> Self {
+        Self::Message(e.into())
+    }
+}
+
+impl From<crate::message::memory::MemoryError> for LibstockError {
+    fn from(e: crate::message::memory::MemoryError) -> Self {
+        Self::Message(e.into())
+    }
+}
+
+#[cfg(feature = "tcp")]
+impl From<crate::message::tcp::TcpError> for LibstockError {
+    fn from(e: crate::message::tcp::TcpError) -> Self {
+        Self::Message(e.into())
+    }
+}
+
+#[cfg(feature = "slack")]
+impl From<crate::slack::SlackError> for LibstockError {
+    fn from(e: crate::slack::SlackError) -> Self {
+        Self::Notification(e.into())
+    }
+}
+
+#[cfg(feature = "discord")]
+impl From<crate::slack::discord::DiscordError> for LibstockError {
+    fn from(e: crate::slack::discord::DiscordError) -> Self {
+        Self::Notification(e.into())
+    }
+}
+
+pub type LibstockResult<T> = Result<T, LibstockError>;
+
+fn num_error_code(e: &NumError) -> u32 {
+    match e {
+        NumError::NonFiniteNumber(_) => 1001,
+        NumError::ParseFailed(_) => 1002,
+        NumError::InvalidScale(_) => 1003,
+        NumError::MantissaOverflow { .. } => 1004,
+    }
+}
+
+#[cfg(feature = "crypto")]
+mod data {
+    use super::num_error_code;
+    use crate::data::bbo::BboError;
+    use crate::data::envelope::EnvelopeError;
+    use crate::data::fields::FieldError;
+    use crate::data::funding_rate::FundingRateError;
+    use crate::data::kline::KlineError;
+    use crate::data::long_short_ratio::LongShortRatioError;
+    use crate::data::orderbook::{BookKeeperError, OrderbookError};
+    use crate::data::taker_volume::TakerVolumeError;
+    use crate::data::trade::TradeError;
+
+    /// The code of the I/O errors when reading or writing a structure.
+    const STRUCTURE_IO_ERROR: u32 = 1201;
+
+    pub(super) fn field_error_code(e: &FieldError) -> u32 {
+        match e {
+            FieldError::UnexpectedTradeSide(_) => 1101,
+            FieldError::NumError(e) => num_error_code(e),
+            FieldError::SystemTimeError(_) => 1102,
+            FieldError::UnimplementedExchange(_) => 1103,
+            FieldError::UnimplementedInfoType(_) => 1104,
+            FieldError::UnimplementedPeriod(_) => 1105,
+            FieldError::FloatOverflow(_) => 1106,
+            FieldError::DataEndedTooEarly => 1107,
+            FieldError::TimestampParseFailed(_) => 1108,
+            FieldError::TimestampOutOfRange(_) => 1109,
+            FieldError::SchemaMismatch { .. } => 1110,
+            #[cfg(feature = "checksum")]
+            FieldError::ChecksumMismatch { .. } => 1111,
+            FieldError::TrailingBytes { .. } => 1112,
+            FieldError::UnexpectedSnapshotFlag(_) => 1113,
+            FieldError::UnexpectedPresenceFlag(_) => 1114,
+            FieldError::TradeIdTooLong(_) => 1115,
+            FieldError::InvalidTradeId(_) => 1116,
+            FieldError::UnimplementedTradeSide(_) => 1117,
+            FieldError::UnimplementedMessageType(_) => 1118,
+        }
+    }
+
+    pub(super) fn bbo_error_code(e: &BboError) -> u32 {
+        match e {
+            BboError::FieldError(e) => field_error_code(e),
+            BboError::IoError(_) => STRUCTURE_IO_ERROR,
+        }
+    }
+
+    pub(super) fn orderbook_error_code(e: &OrderbookError) -> u32 {
+        match e {
+            OrderbookError::FieldError(e) => field_error_code(e),
+            OrderbookError::IoError(_) => STRUCTURE_IO_ERROR,
+            OrderbookError::DirectionMismatch { .. } => 1209,
+        }
+    }
+
+    pub(super) fn kline_error_code(e: &KlineError) -> u32 {
+        match e {
+            KlineError::FieldError(e) => field_error_code(e),
+            KlineError::IoError(_) => STRUCTURE_IO_ERROR,
+        }
+    }
+
+    pub(super) fn trade_error_code(e: &TradeError) -> u32 {
+        match e {
+            TradeError::FieldError(e) => field_error_code(e),
+            TradeError::IoError(_) => STRUCTURE_IO_ERROR,
+            TradeError::UnknownSide => 1208,
+        }
+    }
+
+    pub(super) fn funding_rate_error_code(e: &FundingRateError) -> u32 {
+        match e {
+            FundingRateError::FieldError(e) => field_error_code(e),
+            FundingRateError::IoError(_) => STRUCTURE_IO_ERROR,
+            // 1202 was `MissingEstimatedRate`, which has been removed.
+            FundingRateError::NoEndCharacter => 1203,
+        }
+    }
+
+    pub(super) fn taker_volume_error_code(e: &TakerVolumeError) -> u32 {
+        match e {
+            TakerVolumeError::FieldError(e) => field_error_code(e),
+            TakerVolumeError::IoError(_) => STRUCTURE_IO_ERROR,
+        }
+    }
+
+    pub(super) fn long_short_ratio_error_code(e: &LongShortRatioError) -> u32 {
+        match e {
+            LongShortRatioError::FieldError(e) => field_error_code(e),
+            LongShortRatioError::IoError(_) => STRUCTURE_IO_ERROR,
+        }
+    }
+
+    pub(super) fn envelope_error_code(e: &EnvelopeError) -> u32 {
+        match e {
+            EnvelopeError::FieldError(e) => field_error_code(e),
+            EnvelopeError::IoError(_) => STRUCTURE_IO_ERROR,
+            EnvelopeError::UnsupportedVersion(_) => 1204,
+            EnvelopeError::PayloadTooLarge(_) => 1205,
+        }
+    }
+
+    pub(super) fn book_keeper_error_code(e: &BookKeeperError) -> u32 {
+        match e {
+            BookKeeperError::SnapshotRequired => 1206,
+            BookKeeperError::SequenceGap { .. } => 1207,
+        }
+    }
+}
+
+#[cfg(feature = "msgpack")]
+fn msgpack_error_code(e: &crate::data::msgpack::MsgpackError) -> u32 {
+    use crate::data::msgpack::MsgpackError;
+
+    match e {
+        MsgpackError::EncodeFailed(_) => 1301,
+        MsgpackError::DecodeFailed(_) => 1302,
+    }
+}
+
+fn write_error_code(e: &WriteError) -> u32 {
+    match e {
+        WriteError::InvalidPath(_) => 2001,
+        WriteError::DataDirCreationFailed(_) => 2002,
+        WriteError::PushChannelFailed => 2003,
+        WriteError::FileOpenFailed(_) => 2004,
+        WriteError::CompressFailed(_) => 2005,
+        WriteError::HeaderWriteFailed(_) => 2006,
+        WriteError::DataWriteFailed(_) => 2007,
+        WriteError::FlushFailed(_) => 2008,
+        WriteError::FrameTooLarge(_) => 2009,
+        WriteError::ValidationFailed(_) => 2010,
+        WriteError::QueueFull => 2011,
+        WriteError::RecoverFailed(_) => 2012,
+        #[allow(deprecated)]
+        WriteError::LengthWriteFailed(_) => 2013,
+    }
+}
+
+fn read_error_code(e: &ReadError) -> u32 {
+    match e {
+        ReadError::TruncatedFrame { .. } => 2101,
+        ReadError::Io(_) => 2102,
+        ReadError::Deserialize(_) => 2103,
+    }
+}
+
+#[cfg(feature = "crypto")]
+fn export_error_code(e: &crate::file::export::ExportError) -> u32 {
+    use crate::file::export::ExportError;
+
+    match e {
+        ExportError::ReadFailed(e) => read_error_code(e),
+        ExportError::WriteFailed(_) => 2201,
+    }
+}
+
+mod message {
+    use crate::message::envelope::TopicMessageError;
+    use crate::message::fanout::FanoutError;
+    use crate::message::memory::MemoryError;
+    #[cfg(feature = "crypto")]
+    use crate::message::typed::TypedMessageError;
+    use crate::message::MessageError;
+
+    pub(super) fn message_error_code(e: &MessageError) -> u32 {
+        match e {
+            #[cfg(feature = "nanomsg")]
+            MessageError::NanomsgError(e) => nanomsg_error_code(e),
+            #[cfg(feature = "zeromq")]
+            MessageError::ZeromqError(e) => zeromq_error_code(e),
+            MessageError::MemoryError(e) => memory_error_code(e),
+            #[cfg(feature = "tcp")]
+            MessageError::TcpError(e) => tcp_error_code(e),
+            MessageError::NotSubscribed(_) => 3501,
+            #[cfg(feature = "lz4")]
+            MessageError::DecompressFailed(_) => 3502,
+        }
+    }
+
+    #[cfg(feature = "nanomsg")]
+    fn nanomsg_error_code(e: &crate::message::nanomsg::NanomsgError) -> u32 {
+        use crate::message::nanomsg::NanomsgError;
+
+        match e {
+            NanomsgError::CreateSocketFailed(_) => 3001,
+            NanomsgError::ConnectFailed(_) => 3002,
+            NanomsgError::DisconnectFailed(_) => 3003,
+            NanomsgError::BindFailed(_) => 3004,
+            NanomsgError::UnbindFailed(_) => 3005,
+            NanomsgError::NoSuchEndpoint(_) => 3006,
+            NanomsgError::ReadFailed(_) => 3007,
+            NanomsgError::WriteFailed(_) => 3008,
+            NanomsgError::SubscribeFailed(_) => 3009,
+            NanomsgError::UnsubscribeFailed(_) => 3010,
+            NanomsgError::SetOptionFailed(_) => 3011,
+            NanomsgError::EndpointUnreachable(_) => 3012,
+        }
+    }
+
+    #[cfg(feature = "zeromq")]
+    fn zeromq_error_code(e: &crate::message::zeromq::ZeromqError) -> u32 {
+        use crate::message::zeromq::ZeromqError;
+
+        match e {
+            ZeromqError::CreateSocketFailed(_) => 3101,
+            ZeromqError::ConnectFailed(_) => 3102,
+            ZeromqError::DisconnectFailed(_) => 3103,
+            ZeromqError::BindFailed(_) => 3104,
+            ZeromqError::UnbindFailed(_) => 3105,
+            ZeromqError::RecvFailed(_) => 3106,
+            ZeromqError::SendFailed(_) => 3107,
+            ZeromqError::SubscribeFailed(_) => 3108,
+            ZeromqError::UnsubscribeFailed(_) => 3109,
+            ZeromqError::PollFailed(_) => 3110,
+            ZeromqError::SetOptionFailed(_) => 3111,
+            ZeromqError::MonitorFailed(_) => 3112,
+            ZeromqError::EndpointUnreachable(_) => 3113,
+        }
+    }
+
+    fn memory_error_code(e: &MemoryError) -> u32 {
+        match e {
+            MemoryError::InvalidUri(_) => 3401,
+            MemoryError::AddressInUse(_) => 3402,
+            MemoryError::NoSuchEndpoint(_) => 3403,
+            MemoryError::RecvTimeout => 3404,
+        }
+    }
+
+    #[cfg(feature = "tcp")]
+    fn tcp_error_code(e: &crate::message::tcp::TcpError) -> u32 {
+        use crate::message::tcp::TcpError;
+
+        match e {
+            TcpError::InvalidUri(_) => 3701,
+            TcpError::BindFailed(_) => 3702,
+            TcpError::ConnectFailed(_) => 3703,
+            TcpError::NoSuchEndpoint(_) => 3704,
+            TcpError::RecvTimeout => 3705,
+        }
+    }
+
+    pub(super) fn fanout_error_code(e: &FanoutError) -> u32 {
+        match e {
+            FanoutError::NoSuchChild(_) => 3301,
+            FanoutError::BindFailed { .. } => 3302,
+            FanoutError::UnbindFailed { .. } => 3303,
+        }
+    }
+
+    pub(super) fn topic_message_error_code(e: &TopicMessageError) -> u32 {
+        match e {
+            TopicMessageError::SerializeFailed(_) => 3601,
+            TopicMessageError::WriteFailed(_) => 3602,
+        }
+    }
+
+    #[cfg(feature = "crypto")]
+    pub(super) fn typed_message_error_code(e: &TypedMessageError) -> u32 {
+        match e {
+            TypedMessageError::SerializeFailed(_) => 3201,
+            TypedMessageError::DeserializeFailed { .. } => 3202,
+            TypedMessageError::WriteFailed(_) => 3203,
+            TypedMessageError::ReceiveFailed(_) => 3204,
+            TypedMessageError::Closed => 3205,
+            TypedMessageError::EmptyMessage => 3206,
+            TypedMessageError::UnknownMessageType { .. } => 3207,
+        }
+    }
+}
+
+#[cfg(feature = "slack")]
+mod notification {
+    use crate::slack::traits::NotificationError;
+    use crate::slack::SlackError;
+
+    pub(super) fn notification_error_code(e: &NotificationError) -> u32 {
+        match e {
+            NotificationError::InvalidEndpoint(_) => 4201,
+            NotificationError::BuildFailed(_) => 4202,
+            NotificationError::SendTaskFailed(_) => 4203,
+            NotificationError::SlackError(e) => slack_error_code(e),
+            #[cfg(feature = "discord")]
+            NotificationError::DiscordError(e) => discord_error_code(e),
+        }
+    }
+
+    fn slack_error_code(e: &SlackError) -> u32 {
+        match e {
+            #[allow(deprecated)]
+            SlackError::ConstructPayloadFailed(_) => 4001,
+            #[allow(deprecated)]
+            SlackError::SendMessageFailed(_) => 4003,
+            SlackError::RateLimited { .. } => 4004,
+            #[cfg(feature = "slack-async")]
+            SlackError::SendTaskFailed(_) => 4005,
+            #[cfg(feature = "slack-async")]
+            SlackError::BatcherClosed => 4006,
+            SlackError::SendRequestFailed(_) => 4007,
+        }
+    }
+
+    #[cfg(feature = "discord")]
+    fn discord_error_code(e: &crate::slack::discord::DiscordError) -> u32 {
+        use crate::slack::discord::DiscordError;
+
+        match e {
+            DiscordError::InvalidEndpoint(_) => 4101,
+            DiscordError::SendMessageFailed(_) => 4102,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorCategory, LibstockError};
+    use crate::data::num::NumError;
+    use crate::file::reader::ReadError;
+    use crate::file::writer::WriteError;
+
+    fn assert_code(error: impl Into<LibstockError>, code: u32, category: ErrorCategory) {
+        let error = error.into();
+
+        assert_eq!(error.error_code(), code, "{error:?}");
+        assert_eq!(error.category(), category, "{error:?}");
+    }
+
+    fn io_error() -> std::io::Error {
+        std::io::Error::new(std::io::ErrorKind::Other, "mocked")
+    }
+
+    #[test]
+    fn test_data_error_codes() {
+        assert_code(NumError::InvalidScale(42), 1003, ErrorCategory::Data);
+        assert_code(
+            NumError::MantissaOverflow { value: 1, max: 0 },
+            1004,
+            ErrorCategory::Data,
+        );
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_structure_error_codes() {
+        use crate::data::bbo::BboError;
+        use crate::data::envelope::EnvelopeError;
+        use crate::data::fields::info_type::InfoType;
+        use crate::data::fields::FieldError;
+        use crate::data::funding_rate::FundingRateError;
+        use crate::data::kline::KlineError;
+        use crate::data::long_short_ratio::LongShortRatioError;
+        use crate::data::orderbook::{BookKeeperError, OrderbookError};
+        use crate::data::trade::TradeError;
+
+        assert_code(FieldError::DataEndedTooEarly, 1107, ErrorCategory::Data);
+        assert_code(
+            FieldError::UnimplementedMessageType(42),
+            1118,
+            ErrorCategory::Data,
+        );
+        assert_code(
+            FieldError::SchemaMismatch {
+                expected: 1,
+                got: 2,
+            },
+            1110,
+            ErrorCategory::Data,
+        );
+        assert_code(
+            FieldError::NumError(NumError::InvalidScale(42)),
+            1003,
+            ErrorCategory::Data,
+        );
+        assert_code(BboError::IoError(io_error()), 1201, ErrorCategory::Data);
+        assert_code(
+            OrderbookError::FieldError(FieldError::UnexpectedTradeSide(9)),
+            1101,
+            ErrorCategory::Data,
+        );
+        assert_code(KlineError::IoError(io_error()), 1201, ErrorCategory::Data);
+        assert_code(TradeError::IoError(io_error()), 1201, ErrorCategory::Data);
+        assert_code(FundingRateError::NoEndCharacter, 1203, ErrorCategory::Data);
+        assert_code(
+            LongShortRatioError::FieldError(FieldError::DataEndedTooEarly),
+            1107,
+            ErrorCategory::Data,
+        );
+        assert_code(
+            EnvelopeError::UnsupportedVersion(42),
+            1204,
+            ErrorCategory::Data,
+        );
+        assert_code(
+            BookKeeperError::SequenceGap {
+                expected: 1,
+                got: 3,
+            },
+            1207,
+            ErrorCategory::Data,
+        );
+        assert_code(
+            OrderbookError::DirectionMismatch {
+                expected: InfoType::Asks,
+                actual: InfoType::Bids,
+            },
+            1209,
+            ErrorCategory::Data,
+        );
+    }
+
+    #[test]
+    fn test_file_error_codes() {
+        assert_code(WriteError::PushChannelFailed, 2003, ErrorCategory::File);
+        assert_code(WriteError::QueueFull, 2011, ErrorCategory::File);
+        assert_code(
+            WriteError::RecoverFailed(io_error()),
+            2012,
+            ErrorCategory::File,
+        );
+        #[allow(deprecated)]
+        let length_write_failed = WriteError::LengthWriteFailed(io_error());
+        assert_code(length_write_failed, 2013, ErrorCategory::File);
+        assert_code(
+            WriteError::ValidationFailed("mocked".to_string()),
+            2010,
+            ErrorCategory::File,
+        );
+        assert_code(
+            WriteError::FlushFailed(io_error()),
+            2008,
+            ErrorCategory::File,
+        );
+        assert_code(
+            ReadError::TruncatedFrame {
+                expected: 4,
+                got: 1,
+            },
+            2101,
+            ErrorCategory::File,
+        );
+        assert_code(ReadError::Io(io_error()), 2102, ErrorCategory::File);
+        assert_code(
+            ReadError::Deserialize(io_error().into()),
+            2103,
+            ErrorCategory::File,
+        );
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_export_error_codes() {
+        use crate::file::export::ExportError;
+
+        assert_code(
+            ExportError::ReadFailed(ReadError::Io(io_error())),
+            2102,
+            ErrorCategory::File,
+        );
+        assert_code(
+            ExportError::WriteFailed(io_error()),
+            2201,
+            ErrorCategory::File,
+        );
+    }
+
+    #[cfg(feature = "nanomsg")]
+    #[test]
+    fn test_nanomsg_error_codes() {
+        use crate::message::nanomsg::NanomsgError;
+
+        assert_code(
+            NanomsgError::NoSuchEndpoint("ipc:///tmp/libstock".to_string()),
+            3006,
+            ErrorCategory::Message,
+        );
+        assert_code(
+            NanomsgError::ReadFailed(io_error()),
+            3007,
+            ErrorCategory::Message,
+        );
+    }
+
+    #[cfg(feature = "zeromq")]
+    #[test]
+    fn test_zeromq_error_codes() {
+        use crate::message::zeromq::ZeromqError;
+
+        assert_code(
+            ZeromqError::RecvFailed(zmq2::Error::EAGAIN),
+            3106,
+            ErrorCategory::Message,
+        );
+        assert_code(
+            ZeromqError::EndpointUnreachable("ipc:///tmp/libstock".to_string()),
+            3113,
+            ErrorCategory::Message,
+        );
+    }
+
+    #[test]
+    fn test_memory_error_codes() {
+        use crate::message::memory::MemoryError;
+
+        assert_code(MemoryError::RecvTimeout, 3404, ErrorCategory::Message);
+    }
+
+    #[cfg(feature = "tcp")]
+    #[test]
+    fn test_tcp_error_codes() {
+        use crate::message::tcp::TcpError;
+
+        assert_code(
+            TcpError::InvalidUri("127.0.0.1:5555".to_string()),
+            3701,
+            ErrorCategory::Message,
+        );
+        assert_code(TcpError::RecvTimeout, 3705, ErrorCategory::Message);
+    }
+
+    #[test]
+    fn test_message_error_codes() {
+        use crate::message::MessageError;
+
+        assert_code(
+            MessageError::NotSubscribed(b"bbo".to_vec()),
+            3501,
+            ErrorCategory::Message,
+        );
+        #[cfg(feature = "lz4")]
+        assert_code(
+            MessageError::DecompressFailed(io_error()),
+            3502,
+            ErrorCategory::Message,
+        );
+    }
+
+    #[test]
+    fn test_fanout_error_codes() {
+        use crate::message::fanout::FanoutError;
+
+        assert_code(FanoutError::NoSuchChild(2), 3301, ErrorCategory::Message);
+    }
+
+    #[test]
+    fn test_topic_message_error_codes() {
+        use crate::message::envelope::TopicMessageError;
+
+        assert_code(
+            TopicMessageError::WriteFailed(io_error()),
+            3602,
+            ErrorCategory::Message,
+        );
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_typed_message_error_codes() {
+        use crate::message::typed::TypedMessageError;
+
+        assert_code(TypedMessageError::Closed, 3205, ErrorCategory::Message);
+        assert_code(
+            TypedMessageError::UnknownMessageType {
+                tag: 42,
+                raw: vec![],
+            },
+            3207,
+            ErrorCategory::Message,
+        );
+    }
+
+    #[cfg(feature = "slack")]
+    #[test]
+    fn test_notification_error_codes() {
+        use crate::slack::traits::NotificationError;
+        use crate::slack::SlackError;
+
+        assert_code(
+            SlackError::RateLimited {
+                retry_after_ms: 1000,
+            },
+            4004,
+            ErrorCategory::Notification,
+        );
+        assert_code(
+            NotificationError::BuildFailed("mocked".to_string()),
+            4202,
+            ErrorCategory::Notification,
+        );
+    }
+
+    #[cfg(feature = "discord")]
+    #[test]
+    fn test_discord_error_codes() {
+        use crate::slack::discord::DiscordError;
+
+        assert_code(
+            DiscordError::InvalidEndpoint("mocked".to_string()),
+            4101,
+            ErrorCategory::Notification,
+        );
+    }
+}
@@ -0,0 +1,394 @@
+//! The C interface of the serializers.
+//!
+//! The structures are passed in and out as JSON, in the same format
+//! as their Serde representation, and encoded to the binary format
+//! of [`mod@crate::data`].
+//!
+//! Every function returns [`LIBSTOCK_OK`] on success, or a negative
+//! error code on failure:
+//!
+//! - `-1` to `-99`: the errors of the interface itself, such as
+//!   [`LIBSTOCK_ERR_BUFFER_TOO_SMALL`].
+//! - `-1000` and below: the negated [`LibstockError::error_code`].
+//!
+//! The output buffers are passed with a pointer to their capacity.
+//! On return, it is set to the length of the output, or the length
+//! required if the buffer is too small. Pass a capacity of `0` to query
+//! the required length.
+//!
+//! The header `include/libstock.h` is generated with `cbindgen`. To link
+//! the library from C, build it as a static library:
+//!
+//! ```sh
+//! cargo rustc --release --no-default-features --features ffi --crate-type staticlib
+//! ```
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, UnwindSafe};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::data::bbo::BboStructure;
+use crate::data::orderbook::OrderbookStructure;
+use crate::data::serializer::{StructDeserializer, StructSerializer};
+use crate::error::LibstockError;
+
+/// The operation succeeded.
+pub const LIBSTOCK_OK: i32 = 0;
+
+/// A required pointer is null.
+pub const LIBSTOCK_ERR_NULL_POINTER: i32 = -1;
+
+/// The input string is not valid UTF-8.
+pub const LIBSTOCK_ERR_INVALID_UTF8: i32 = -2;
+
+/// The input JSON is not a valid structure.
+pub const LIBSTOCK_ERR_INVALID_JSON: i32 = -3;
+
+/// The output buffer is too small. The required length has been set.
+pub const LIBSTOCK_ERR_BUFFER_TOO_SMALL: i32 = -4;
+
+/// The library panicked.
+pub const LIBSTOCK_ERR_PANIC: i32 = -5;
+
+type FfiResult = Result<(), i32>;
+
+/// Negate the error code of `e`.
+fn error_code(e: impl Into<LibstockError>) -> i32 {
+    let code = e.into().error_code();
+
+    -i32::try_from(code).unwrap_or(i32::MAX)
+}
+
+/// Run `f`, converting its result and its panics to a return code.
+fn run(f: impl FnOnce() -> FfiResult + UnwindSafe) -> i32 {
+    match catch_unwind(f) {
+        Ok(Ok(())) => LIBSTOCK_OK,
+        Ok(Err(code)) => code,
+        Err(_) => LIBSTOCK_ERR_PANIC,
+    }
+}
+
+/// Copy `data` to the buffer `out` with the capacity `*out_len`.
+///
+/// # Safety
+///
+/// `out_len` must be valid, and `out` must be valid for `*out_len` bytes.
+unsafe fn write_output(data: &[u8], out: *mut u8, out_len: *mut usize) -> FfiResult {
+    let capacity = *out_len;
+    *out_len = data.len();
+
+    if data.len() > capacity {
+        return Err(LIBSTOCK_ERR_BUFFER_TOO_SMALL);
+    }
+    if out.is_null() {
+        return Err(LIBSTOCK_ERR_NULL_POINTER);
+    }
+
+    std::ptr::copy_nonoverlapping(data.as_ptr(), out, data.len());
+    Ok(())
+}
+
+/// Encode the JSON of `T` at `json_ptr` to `out_buf`.
+///
+/// # Safety
+///
+/// See [`libstock_encode_bbo`].
+unsafe fn encode<T>(json_ptr: *const c_char, out_buf: *mut u8, out_len: *mut usize) -> FfiResult
+where
+    T: DeserializeOwned + StructSerializer,
+    T::Err: Into<LibstockError>,
+{
+    if json_ptr.is_null() || out_len.is_null() {
+        return Err(LIBSTOCK_ERR_NULL_POINTER);
+    }
+
+    let json = CStr::from_ptr(json_ptr)
+        .to_str()
+        .map_err(|_| LIBSTOCK_ERR_INVALID_UTF8)?;
+    let structure: T = serde_json::from_str(json).map_err(|_| LIBSTOCK_ERR_INVALID_JSON)?;
+    let encoded = structure.serialize_to_vec().map_err(error_code)?;
+
+    write_output(&encoded, out_buf, out_len)
+}
+
+/// Decode `T` from `buf` to the JSON in `out_json`.
+///
+/// # Safety
+///
+/// See [`libstock_decode_bbo`].
+unsafe fn decode<T>(
+    buf: *const u8,
+    len: usize,
+    out_json: *mut c_char,
+    out_json_len: *mut usize,
+) -> FfiResult
+where
+    T: Serialize + StructDeserializer,
+    T::Err: Into<LibstockError>,
+{
+    if buf.is_null() || out_json_len.is_null() {
+        return Err(LIBSTOCK_ERR_NULL_POINTER);
+    }
+
+    let bytes = std::slice::from_raw_parts(buf, len);
+    let structure = T::deserialize_from_bytes(bytes).map_err(error_code)?;
+    let mut json = serde_json::to_vec(&structure).map_err(|_| LIBSTOCK_ERR_INVALID_JSON)?;
+    json.push(b'\0');
+
+    write_output(&json, out_json.cast(), out_json_len)
+}
+
+/// Encode the JSON of a BBO structure.
+///
+/// `json_ptr` is a NUL-terminated string. The encoded bytes are written
+/// to `out_buf`, and `*out_len` is its capacity on input and the length
+/// of the encoded bytes on output.
+///
+/// # Safety
+///
+/// `json_ptr` must be a valid NUL-terminated string, `out_len` must be
+/// valid, and `out_buf` must be valid for `*out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn libstock_encode_bbo(
+    json_ptr: *const c_char,
+    out_buf: *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    run(|| encode::<BboStructure>(json_ptr, out_buf, out_len))
+}
+
+/// Decode a BBO structure to JSON.
+///
+/// The JSON is written to `out_json` with a terminating NUL, and
+/// `*out_json_len` is its capacity on input and the length of the JSON,
+/// including the terminating NUL, on output.
+///
+/// # Safety
+///
+/// `buf` must be valid for `len` bytes, `out_json_len` must be valid,
+/// and `out_json` must be valid for `*out_json_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn libstock_decode_bbo(
+    buf: *const u8,
+    len: usize,
+    out_json: *mut c_char,
+    out_json_len: *mut usize,
+) -> i32 {
+    run(|| decode::<BboStructure>(buf, len, out_json, out_json_len))
+}
+
+/// Encode the JSON of an orderbook structure.
+///
+/// See [`libstock_encode_bbo`] for the parameters.
+///
+/// # Safety
+///
+/// The same as [`libstock_encode_bbo`].
+#[no_mangle]
+pub unsafe extern "C" fn libstock_encode_orderbook(
+    json_ptr: *const c_char,
+    out_buf: *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    run(|| encode::<OrderbookStructure>(json_ptr, out_buf, out_len))
+}
+
+/// Decode an orderbook structure to JSON.
+///
+/// See [`libstock_decode_bbo`] for the parameters.
+///
+/// # Safety
+///
+/// The same as [`libstock_decode_bbo`].
+#[no_mangle]
+pub unsafe extern "C" fn libstock_decode_orderbook(
+    buf: *const u8,
+    len: usize,
+    out_json: *mut c_char,
+    out_json_len: *mut usize,
+) -> i32 {
+    run(|| decode::<OrderbookStructure>(buf, len, out_json, out_json_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, CString};
+
+    use rust_decimal_macros::dec;
+
+    use super::*;
+    use crate::data::fields::exchange_type::Exchange;
+    use crate::data::fields::info_type::InfoType;
+    use crate::data::fields::market_type::MarketType;
+    use crate::data::fields::message_type::MessageType;
+    use crate::data::fields::price_data::Order;
+    use crate::data::fields::{
+        ExchangeTypeField,
+        MessageTypeField,
+        PriceDataField,
+        SymbolPairField,
+        TimestampField,
+    };
+    use crate::data::orderbook::{OrderPriceData, OrdersBox};
+
+    fn construct_bbo() -> BboStructure {
+        BboStructure::builder()
+            .exchange_timestamp(TimestampField(1672531200000))
+            .received_timestamp(TimestampField(1672531200001))
+            .exchange_type(ExchangeTypeField(Exchange::Binance))
+            .market_type(MarketType::Spot)
+            .message_type(MessageTypeField(MessageType::BBO))
+            .symbol(SymbolPairField::from_pair("BTC/USDT"))
+            .asks(
+                PriceDataField::builder()
+                    .price(dec!(12345.6))
+                    .quantity_base(dec!(1.5))
+                    .build(),
+            )
+            .bids(
+                PriceDataField::builder()
+                    .price(dec!(12344.9))
+                    .quantity_base(dec!(2))
+                    .build(),
+            )
+            .build()
+    }
+
+    fn construct_orderbook() -> OrderbookStructure {
+        let order = |price, quantity_base| {
+            let order = Order {
+                price,
+                quantity_base,
+                quantity_quote: price * quantity_base,
+                quantity_contract: None,
+            };
+
+            OrderPriceData::try_from(&order).unwrap()
+        };
+
+        OrderbookStructure::builder()
+            .exchange_timestamp(TimestampField(1672531200000))
+            .received_timestamp(TimestampField(1672531200001))
+            .exchange_type(ExchangeTypeField(Exchange::Binance))
+            .market_type(MarketType::Spot)
+            .message_type(MessageTypeField(MessageType::L2Event))
+            .symbol(SymbolPairField::from_pair("BTC/USDT"))
+            .asks(
+                OrdersBox::builder()
+                    .direction(InfoType::Asks)
+                    .orders(std::iter::once(order(12345.5, 1.5)).collect())
+                    .build(),
+            )
+            .bids(
+                OrdersBox::builder()
+                    .direction(InfoType::Bids)
+                    .orders(std::iter::once(order(12344.5, 2.0)).collect())
+                    .build(),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_bbo_roundtrip() {
+        let bbo = construct_bbo();
+        let json = CString::new(serde_json::to_string(&bbo).unwrap()).unwrap();
+
+        let mut encoded = [0; 256];
+        let mut encoded_len = encoded.len();
+        let code =
+            unsafe { libstock_encode_bbo(json.as_ptr(), encoded.as_mut_ptr(), &mut encoded_len) };
+        assert_eq!(code, LIBSTOCK_OK);
+        assert_eq!(&encoded[..encoded_len], bbo.serialize_to_vec().unwrap());
+
+        let mut decoded = [0 as c_char; 1024];
+        let mut decoded_len = decoded.len();
+        let code = unsafe {
+            libstock_decode_bbo(
+                encoded.as_ptr(),
+                encoded_len,
+                decoded.as_mut_ptr(),
+                &mut decoded_len,
+            )
+        };
+        assert_eq!(code, LIBSTOCK_OK);
+
+        let decoded = unsafe { CStr::from_ptr(decoded.as_ptr()) };
+        assert_eq!(decoded.to_bytes_with_nul().len(), decoded_len);
+        assert_eq!(
+            serde_json::from_slice::<BboStructure>(decoded.to_bytes()).unwrap(),
+            bbo
+        );
+    }
+
+    #[test]
+    fn test_orderbook_roundtrip() {
+        let orderbook = construct_orderbook();
+        let json = CString::new(serde_json::to_string(&orderbook).unwrap()).unwrap();
+
+        let mut encoded = [0; 256];
+        let mut encoded_len = encoded.len();
+        let code = unsafe {
+            libstock_encode_orderbook(json.as_ptr(), encoded.as_mut_ptr(), &mut encoded_len)
+        };
+        assert_eq!(code, LIBSTOCK_OK);
+
+        let mut decoded = [0 as c_char; 1024];
+        let mut decoded_len = decoded.len();
+        let code = unsafe {
+            libstock_decode_orderbook(
+                encoded.as_ptr(),
+                encoded_len,
+                decoded.as_mut_ptr(),
+                &mut decoded_len,
+            )
+        };
+        assert_eq!(code, LIBSTOCK_OK);
+
+        let decoded = unsafe { CStr::from_ptr(decoded.as_ptr()) };
+        assert_eq!(
+            serde_json::from_slice::<OrderbookStructure>(decoded.to_bytes()).unwrap(),
+            orderbook
+        );
+    }
+
+    #[test]
+    fn test_buffer_too_small() {
+        let bbo = construct_bbo();
+        let json = CString::new(serde_json::to_string(&bbo).unwrap()).unwrap();
+
+        // Query the required length.
+        let mut encoded_len = 0;
+        let code =
+            unsafe { libstock_encode_bbo(json.as_ptr(), std::ptr::null_mut(), &mut encoded_len) };
+        assert_eq!(code, LIBSTOCK_ERR_BUFFER_TOO_SMALL);
+        assert_eq!(encoded_len, bbo.serialize_to_vec().unwrap().len());
+
+        let mut encoded = vec![0; encoded_len - 1];
+        let code =
+            unsafe { libstock_encode_bbo(json.as_ptr(), encoded.as_mut_ptr(), &mut encoded_len) };
+        assert_eq!(code, LIBSTOCK_ERR_BUFFER_TOO_SMALL);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let mut buf = [0; 256];
+        let mut len = buf.len();
+
+        let code = unsafe { libstock_encode_bbo(std::ptr::null(), buf.as_mut_ptr(), &mut len) };
+        assert_eq!(code, LIBSTOCK_ERR_NULL_POINTER);
+
+        let json = CString::new("{}").unwrap();
+        let code = unsafe { libstock_encode_bbo(json.as_ptr(), buf.as_mut_ptr(), &mut len) };
+        assert_eq!(code, LIBSTOCK_ERR_INVALID_JSON);
+
+        // The data ends too early.
+        let mut json = [0 as c_char; 256];
+        let mut json_len = json.len();
+        let code =
+            unsafe { libstock_decode_bbo(buf.as_ptr(), 1, json.as_mut_ptr(), &mut json_len) };
+        assert!(code <= -1000, "{code}");
+    }
+}
@@ -3,16 +3,40 @@
 #[deprecated(since = "0.4.0", note = "We don't use this identifier anymore.")]
 pub mod ident;
 
+pub mod compression;
+pub mod datadir;
+#[cfg(feature = "crypto")]
+pub mod export;
+pub mod format;
 pub mod reader;
+pub mod recorder;
 pub mod timestamp;
 pub mod writer;
 
-mod datadir;
-
 #[cfg(test)]
 mod tests {
     use super::reader::FileReader;
-    use super::writer::{DataEntry, DataWriter};
+    use super::writer::{DataEntry, DataWriter, DataWriterConfig};
+
+    /// Write `contents` to `filename` with a writer configured with `config`.
+    async fn write_entries(config: DataWriterConfig, filename: &str, contents: &[Vec<u8>]) {
+        let mut writer = DataWriter::with_config(config);
+        let writer_thread = writer.start().await.expect("failed to spawn writer");
+
+        for content in contents {
+            writer
+                .add(DataEntry {
+                    filename: filename.to_string(),
+                    data: content.clone(),
+                })
+                .expect("failed to add content");
+        }
+
+        writer.stop().expect("failed to stop writer");
+        writer_thread
+            .await
+            .expect("failed to wait writer thread to stop");
+    }
 
     #[tokio::test]
     async fn test_read_write() {
@@ -51,6 +75,7 @@ mod tests {
             writer_thread
                 .await
                 .expect("failed to wait writer thread to stop");
+            assert_eq!(writer.entries_written(), 2);
 
             //////////////////
             // Reader Part ///
@@ -65,4 +90,730 @@ mod tests {
             assert!(reader.next().is_none());
         }
     }
+
+    #[tokio::test]
+    async fn test_rotate_by_size() {
+        use super::datadir::{default_base, segment_path};
+        use super::format::HEADER_LEN;
+        use super::timestamp::get_date;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let contents = (0..5)
+            .map(|i| format!("ent-{i}").into_bytes())
+            .collect::<Vec<_>>();
+
+        // Each frame takes 7 bytes, so a segment can hold
+        // two frames after the file header.
+        write_entries(
+            DataWriterConfig::new().max_file_bytes(HEADER_LEN as u64 + 16),
+            &filename,
+            &contents,
+        )
+        .await;
+
+        let (base, date) = (default_base(), get_date());
+        for segment in 0..3 {
+            assert!(segment_path(&base, &filename, date, segment).exists());
+        }
+        assert!(!segment_path(&base, &filename, date, 3).exists());
+
+        let reader = FileReader::new(filename.to_string(), 0)
+            .expect("failed to start the reader to read the written data.");
+
+        assert_eq!(reader.collect::<Vec<_>>(), contents);
+    }
+
+    #[tokio::test]
+    async fn test_rotate_counts_header() {
+        use super::datadir::{default_base, segment_path};
+        use super::format::HEADER_LEN;
+        use super::timestamp::get_date;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let contents = (0..3)
+            .map(|i| format!("ent-{i}").into_bytes())
+            .collect::<Vec<_>>();
+
+        // A segment fits the header and exactly one frame of 7 bytes.
+        let max_file_bytes = HEADER_LEN as u64 + 7;
+        write_entries(
+            DataWriterConfig::new().max_file_bytes(max_file_bytes),
+            &filename,
+            &contents,
+        )
+        .await;
+
+        let (base, date) = (default_base(), get_date());
+        for segment in 0..3 {
+            let path = segment_path(&base, &filename, date, segment);
+            assert_eq!(std::fs::metadata(path).unwrap().len(), max_file_bytes);
+        }
+        assert!(!segment_path(&base, &filename, date, 3).exists());
+    }
+
+    #[tokio::test]
+    async fn test_partition_by_hour() {
+        use super::datadir::{default_base, partition_directory, path_in, PartitionGranularity};
+        use super::timestamp::get_datetime;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let contents = vec![b"partitioned".to_vec()];
+
+        let before = get_datetime();
+        write_entries(
+            DataWriterConfig::new().partition_by_hour(true),
+            &filename,
+            &contents,
+        )
+        .await;
+        let after = get_datetime();
+
+        // The hour may change during writing.
+        let base = default_base();
+        let written = [before, after]
+            .into_iter()
+            .map(|time| partition_directory(&base, time, PartitionGranularity::Hour))
+            .map(|directory| path_in(&directory, &filename))
+            .find(|path| path.exists())
+            .expect("the file should be in the hour directory");
+
+        let hour_directory = written.parent().unwrap();
+        assert_eq!(hour_directory.file_name().unwrap().len(), 2);
+        assert!(!path_in(hour_directory.parent().unwrap(), &filename).exists());
+    }
+
+    #[tokio::test]
+    async fn test_partition_roundtrip() {
+        use super::datadir::{default_base, list_files, PartitionGranularity};
+        use super::format::HEADER_LEN;
+        use super::reader::AsyncFileReader;
+        use super::timestamp::get_date;
+
+        for granularity in [
+            PartitionGranularity::Day,
+            PartitionGranularity::Hour,
+            PartitionGranularity::Minute,
+        ] {
+            let filename = uuid::Uuid::new_v4().to_string();
+            let contents = (0..5)
+                .map(|i| format!("ent-{i}").into_bytes())
+                .collect::<Vec<_>>();
+
+            // Rotate the segments in the partition directories, too.
+            write_entries(
+                DataWriterConfig::new()
+                    .partition_by(granularity)
+                    .max_file_bytes(HEADER_LEN as u64 + 16),
+                &filename,
+                &contents,
+            )
+            .await;
+
+            let reader = FileReader::new(filename.clone(), 0)
+                .expect("failed to read the written data");
+            assert_eq!(reader.collect::<Vec<_>>(), contents, "{granularity:?}");
+
+            let mut reader = AsyncFileReader::new(filename.clone(), 0)
+                .await
+                .expect("failed to read the written data");
+            for content in &contents {
+                assert_eq!(&reader.next_frame().await.unwrap().unwrap(), content);
+            }
+            assert!(reader.next_frame().await.unwrap().is_none());
+
+            assert!(list_files(&default_base(), get_date()).contains(&filename));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reverse_iter() {
+        use super::format::HEADER_LEN;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let contents = (0..10)
+            .map(|i| format!("entry-{i}").into_bytes())
+            .collect::<Vec<_>>();
+
+        // Each frame takes 9 bytes, so the entries are rotated
+        // into the segments of three frames.
+        write_entries(
+            DataWriterConfig::new().max_file_bytes(HEADER_LEN as u64 + 27),
+            &filename,
+            &contents,
+        )
+        .await;
+
+        let reader = FileReader::new(filename.clone(), 0).expect("failed to read the written data");
+        let reversed = contents.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(reader.reverse_iter().collect::<Vec<_>>(), reversed);
+
+        // Start from the middle of the second segment.
+        let mut reader = FileReader::new(filename, 0).expect("failed to read the written data");
+        for content in &contents[..4] {
+            assert_eq!(&reader.next().unwrap(), content);
+        }
+        assert_eq!(reader.reverse_iter().collect::<Vec<_>>(), &reversed[..6]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_entries_reported() {
+        use super::datadir::{date_directory, default_base};
+        use super::timestamp::get_date;
+        use super::writer::WriteError;
+
+        // Place a file where the writer expects a directory.
+        let blocker = uuid::Uuid::new_v4().to_string();
+        let data_dir = date_directory(&default_base(), get_date());
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::write(data_dir.join(&blocker), b"").unwrap();
+
+        let (sender, receiver) = flume::unbounded();
+        let filename = format!("{blocker}/nested");
+        let contents = (0..3)
+            .map(|i| format!("lost-{i}").into_bytes())
+            .collect::<Vec<_>>();
+
+        let mut writer = DataWriter::with_config(DataWriterConfig::new().failure_channel(sender));
+        let writer_thread = writer.start().await.expect("failed to spawn writer");
+
+        for content in &contents {
+            writer
+                .add(DataEntry {
+                    filename: filename.clone(),
+                    data: content.clone(),
+                })
+                .expect("failed to add content");
+        }
+
+        writer.stop().expect("failed to stop writer");
+        writer_thread
+            .await
+            .expect("failed to wait writer thread to stop");
+
+        let failed = receiver.try_iter().collect::<Vec<_>>();
+        assert_eq!(failed.len(), 3);
+        for (failed, content) in failed.iter().zip(&contents) {
+            assert_eq!(failed.entry.filename, filename);
+            assert_eq!(&failed.entry.data, content);
+            assert!(matches!(failed.error, WriteError::FileOpenFailed(_)));
+        }
+
+        let stats = writer.stats();
+        assert_eq!(stats.entries_written, 0);
+        assert_eq!(stats.entries_failed, 3);
+        assert_eq!(stats.bytes_written, 0);
+        assert_eq!(stats.queue_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn test_on_error_called() {
+        use std::sync::{Arc, Mutex};
+
+        use super::datadir::{date_directory, default_base};
+        use super::timestamp::get_date;
+        use super::writer::WriteError;
+
+        // Place a file where the writer expects a directory.
+        let blocker = uuid::Uuid::new_v4().to_string();
+        let data_dir = date_directory(&default_base(), get_date());
+        std::fs::create_dir_all(&data_dir).unwrap();
+        std::fs::write(data_dir.join(&blocker), b"").unwrap();
+
+        let open_failed = Arc::new(Mutex::new(Vec::new()));
+        let config = DataWriterConfig::new().on_error(Arc::new({
+            let open_failed = open_failed.clone();
+            move |error: &WriteError| {
+                let is_open_failed = matches!(error, WriteError::FileOpenFailed(_));
+                open_failed.lock().unwrap().push(is_open_failed);
+            }
+        }));
+        let contents = vec![b"lost-0".to_vec(), b"lost-1".to_vec()];
+        write_entries(config, &format!("{blocker}/nested"), &contents).await;
+
+        assert_eq!(*open_failed.lock().unwrap(), [true, true]);
+    }
+
+    #[tokio::test]
+    async fn test_writer_stats() {
+        let filename = uuid::Uuid::new_v4().to_string();
+        let mut writer = DataWriter::new();
+
+        for content in [b"Hello".to_vec(), b"world!".to_vec()] {
+            writer
+                .add(DataEntry {
+                    filename: filename.clone(),
+                    data: content,
+                })
+                .expect("failed to add content");
+        }
+        assert_eq!(writer.stats().queue_depth, 2);
+
+        let writer_thread = writer.start().await.expect("failed to spawn writer");
+        writer.stop().expect("failed to stop writer");
+        writer_thread
+            .await
+            .expect("failed to wait writer thread to stop");
+
+        let stats = writer.stats();
+        assert_eq!(stats.entries_written, 2);
+        // Each frame has a 2-byte length section.
+        assert_eq!(stats.bytes_written, 15);
+        assert_eq!(stats.entries_failed, 0);
+        assert_eq!(stats.queue_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn test_atomic_writes_recover_torn_tail() {
+        use std::io::Write;
+
+        use super::datadir::{default_base, ident_path};
+        use super::reader::ReadError;
+        use super::timestamp::get_date;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let config = DataWriterConfig::new().atomic_writes(true);
+        let contents = vec![b"first".to_vec(), b"second".to_vec()];
+        write_entries(config.clone(), &filename, &contents).await;
+        assert_eq!(read_entries(&filename), contents);
+
+        // Simulate a crash during writing the third entry,
+        // which leaves a partial frame at the end of the file.
+        let path = ident_path(&default_base(), &filename, get_date());
+        let complete_len = std::fs::metadata(&path).unwrap().len();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(&[0, 5, b't', b'h'])
+            .unwrap();
+
+        let mut reader = FileReader::new(filename.clone(), 0).expect("failed to open the file");
+        for content in &contents {
+            assert_eq!(&reader.next_frame().unwrap().unwrap(), content);
+        }
+        assert!(matches!(
+            reader.next_frame(),
+            Err(ReadError::TruncatedFrame {
+                expected: 5,
+                got: 2
+            })
+        ));
+
+        // The next daemon truncates the partial frame before writing.
+        write_entries(config, &filename, &[b"third".to_vec()]).await;
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), complete_len + 7);
+        assert_eq!(
+            read_entries(&filename),
+            [b"first".to_vec(), b"second".to_vec(), b"third".to_vec()]
+        );
+    }
+
+    #[cfg(feature = "compress-zstd")]
+    #[tokio::test]
+    async fn test_atomic_writes_keep_compressed_torn_tail() {
+        use std::io::Write;
+
+        use super::compression::Compression;
+        use super::datadir::{default_base, ident_path};
+        use super::timestamp::get_date;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let compression = Compression::Zstd { level: 3 };
+        let config = DataWriterConfig::new()
+            .compression(compression)
+            .atomic_writes(true);
+        write_entries(config.clone(), &filename, &[b"first".to_vec()]).await;
+
+        // Simulate a crash during writing the second entry.
+        let path = ident_path(&default_base(), &filename, get_date());
+        let frame = compression.compress(b"\x00\x06second").unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(&frame[..frame.len() / 2])
+            .unwrap();
+        let torn = std::fs::read(&path).unwrap();
+
+        // The frames of a compressed file can't be walked,
+        // so the partial frame is kept as is.
+        write_entries(config, &filename, &[b"third".to_vec()]).await;
+        let written = std::fs::read(&path).unwrap();
+        assert!(written.len() > torn.len());
+        assert!(written.starts_with(&torn));
+    }
+
+    #[tokio::test]
+    async fn test_atomic_writes_short_files() {
+        use super::datadir::{default_base, ident_path};
+        use super::timestamp::get_date;
+
+        let config = DataWriterConfig::new().atomic_writes(true);
+        let (base, date) = (default_base(), get_date());
+
+        // A legacy file shorter than the header is kept…
+        let legacy_filename = uuid::Uuid::new_v4().to_string();
+        let legacy_path = ident_path(&base, &legacy_filename, date);
+        std::fs::create_dir_all(legacy_path.parent().unwrap()).unwrap();
+        std::fs::write(&legacy_path, b"\x00\x03abc").unwrap();
+
+        write_entries(config.clone(), &legacy_filename, &[b"def".to_vec()]).await;
+        assert_eq!(
+            read_entries(&legacy_filename),
+            [b"abc".to_vec(), b"def".to_vec()]
+        );
+
+        // …while a torn header is truncated.
+        let torn_filename = uuid::Uuid::new_v4().to_string();
+        std::fs::write(ident_path(&base, &torn_filename, date), b"LST").unwrap();
+
+        write_entries(config, &torn_filename, &[b"def".to_vec()]).await;
+        assert_eq!(read_entries(&torn_filename), [b"def".to_vec()]);
+    }
+
+    /// Create an entry of `data` to write to `filename`.
+    fn entry(filename: &str, data: &[u8]) -> DataEntry {
+        DataEntry {
+            filename: filename.to_string(),
+            data: data.to_vec(),
+        }
+    }
+
+    /// Read all the entries written to `filename`.
+    fn read_entries(filename: &str) -> Vec<Vec<u8>> {
+        FileReader::new(filename.to_string(), 0)
+            .expect("failed to open the file")
+            .collect()
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_bounded_try_add() {
+        use super::writer::WriteError;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+
+        // The daemon is not started, so nothing is taken from the queue.
+        // The runtime has another worker for the daemon, as `stop()`
+        // blocks the current one until the queue has space.
+        let writer = DataWriter::with_capacity(2);
+        assert_eq!(writer.capacity(), Some(2));
+        writer.try_add(entry(&filename, b"1")).unwrap();
+        writer.try_add(entry(&filename, b"2")).unwrap();
+        assert_eq!(writer.queue_depth(), 2);
+
+        assert!(matches!(
+            writer.try_add(entry(&filename, b"3")),
+            Err(WriteError::QueueFull)
+        ));
+        assert_eq!(writer.queue_depth(), 2);
+
+        let writer_thread = writer.start().await.expect("failed to spawn writer");
+        writer.stop().expect("failed to stop writer");
+        writer_thread
+            .await
+            .expect("failed to wait writer thread to stop");
+
+        assert_eq!(read_entries(&filename), [b"1".to_vec(), b"2".to_vec()]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_bounded_add_blocking() {
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let writer = Arc::new(DataWriter::with_capacity(2));
+        writer.try_add(entry(&filename, b"1")).unwrap();
+        writer.try_add(entry(&filename, b"2")).unwrap();
+
+        let blocked = std::thread::spawn({
+            let writer = writer.clone();
+            let third = entry(&filename, b"3");
+            move || writer.add_blocking(third)
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!blocked.is_finished());
+
+        // Start the daemon to make space for the blocked entry.
+        let writer_thread = writer.start().await.expect("failed to spawn writer");
+        blocked.join().unwrap().expect("failed to add content");
+        writer.stop().expect("failed to stop writer");
+        writer_thread
+            .await
+            .expect("failed to wait writer thread to stop");
+
+        assert_eq!(
+            read_entries(&filename),
+            [b"1".to_vec(), b"2".to_vec(), b"3".to_vec()]
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_bounded_add_async() {
+        use std::time::Duration;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let writer = DataWriter::with_capacity(2);
+        writer.try_add(entry(&filename, b"1")).unwrap();
+        writer.try_add(entry(&filename, b"2")).unwrap();
+
+        // The queue is full, so it waits until the timeout.
+        let pending = writer.add_async(entry(&filename, b"3"));
+        let result = tokio::time::timeout(Duration::from_millis(100), pending).await;
+        assert!(result.is_err());
+        assert_eq!(writer.queue_depth(), 2);
+
+        let writer_thread = writer.start().await.expect("failed to spawn writer");
+        writer
+            .add_async(entry(&filename, b"4"))
+            .await
+            .expect("failed to add content");
+        writer.stop().expect("failed to stop writer");
+        writer_thread
+            .await
+            .expect("failed to wait writer thread to stop");
+
+        assert_eq!(
+            read_entries(&filename),
+            [b"1".to_vec(), b"2".to_vec(), b"4".to_vec()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_legacy_and_new_format() {
+        use super::datadir::{default_base, ident_path};
+        use super::format::MAGIC;
+        use super::timestamp::get_date;
+
+        // The legacy files have no header and `u16` length sections.
+        const LEGACY_FIXTURE: &[u8] = b"\x00\x05Hello\x00\x00\x00\x06world!";
+
+        let legacy_filename = uuid::Uuid::new_v4().to_string();
+        let legacy_path = ident_path(&default_base(), &legacy_filename, get_date());
+        std::fs::create_dir_all(legacy_path.parent().unwrap()).unwrap();
+        std::fs::write(legacy_path, LEGACY_FIXTURE).unwrap();
+
+        let new_filename = uuid::Uuid::new_v4().to_string();
+        let contents = [b"Hello".to_vec(), b"".to_vec(), b"world!".to_vec()];
+        write_entries(DataWriterConfig::new(), &new_filename, &contents).await;
+
+        let new_path = ident_path(&default_base(), &new_filename, get_date());
+        let written = std::fs::read(new_path).unwrap();
+        assert!(written.starts_with(MAGIC));
+
+        let legacy = FileReader::new(legacy_filename, 0).expect("failed to read legacy file");
+        let new = FileReader::new(new_filename, 0).expect("failed to read new file");
+
+        let legacy = legacy.collect::<Vec<_>>();
+        assert_eq!(legacy, contents);
+        assert_eq!(new.collect::<Vec<_>>(), legacy);
+    }
+
+    #[tokio::test]
+    async fn test_u32_length_width() {
+        use super::format::LengthWidth;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let contents = vec![vec![b'x'; 100_000], b"small".to_vec()];
+
+        write_entries(
+            DataWriterConfig::new().length_width(LengthWidth::U32),
+            &filename,
+            &contents,
+        )
+        .await;
+
+        let reader = FileReader::new(filename, 0).expect("failed to read the written data");
+        assert_eq!(reader.collect::<Vec<_>>(), contents);
+    }
+
+    #[tokio::test]
+    async fn test_u16_frame_too_large() {
+        use super::writer::WriteError;
+
+        let (sender, receiver) = flume::unbounded();
+        let filename = uuid::Uuid::new_v4().to_string();
+
+        write_entries(
+            DataWriterConfig::new().failure_channel(sender),
+            &filename,
+            &[vec![b'x'; 100_000]],
+        )
+        .await;
+
+        let failed = receiver.try_iter().collect::<Vec<_>>();
+        assert_eq!(failed.len(), 1);
+        assert!(matches!(failed[0].error, WriteError::FrameTooLarge(100_000)));
+    }
+
+    #[tokio::test]
+    async fn test_append_another_width() {
+        use super::datadir::{default_base, ident_path, segment_path};
+        use super::format::LengthWidth;
+        use super::timestamp::get_date;
+
+        let (base, date) = (default_base(), get_date());
+        let u16_config = DataWriterConfig::new();
+        let u32_config = DataWriterConfig::new().length_width(LengthWidth::U32);
+
+        // Appending `u32` frames to a legacy file starts a new segment.
+        let legacy_filename = uuid::Uuid::new_v4().to_string();
+        let legacy_path = ident_path(&base, &legacy_filename, date);
+        std::fs::create_dir_all(legacy_path.parent().unwrap()).unwrap();
+        std::fs::write(&legacy_path, b"\x00\x05Hello").unwrap();
+
+        write_entries(u32_config.clone(), &legacy_filename, &[b"world!".to_vec()]).await;
+        assert_eq!(std::fs::read(legacy_path).unwrap(), b"\x00\x05Hello");
+        assert!(segment_path(&base, &legacy_filename, date, 0).exists());
+        assert_eq!(
+            read_entries(&legacy_filename),
+            [b"Hello".to_vec(), b"world!".to_vec()]
+        );
+
+        // So does appending `u16` frames to a `u32` file, and the writers
+        // keep appending to the last segment since then.
+        let filename = uuid::Uuid::new_v4().to_string();
+        write_entries(u32_config.clone(), &filename, &[b"1".to_vec()]).await;
+        write_entries(u16_config, &filename, &[b"2".to_vec()]).await;
+        write_entries(u32_config.clone(), &filename, &[b"3".to_vec()]).await;
+        write_entries(u32_config, &filename, &[b"4".to_vec()]).await;
+
+        assert!(segment_path(&base, &filename, date, 1).exists());
+        assert!(!segment_path(&base, &filename, date, 2).exists());
+        assert_eq!(
+            read_entries(&filename),
+            [b"1".to_vec(), b"2".to_vec(), b"3".to_vec(), b"4".to_vec()]
+        );
+    }
+
+    #[cfg(any(feature = "compress-zstd", feature = "compress-gzip"))]
+    async fn test_compression(compression: super::compression::Compression) {
+        use super::datadir::{default_base, ident_path, segment_path};
+        use super::timestamp::get_date;
+
+        let plain_filename = uuid::Uuid::new_v4().to_string();
+        let compressed_filename = uuid::Uuid::new_v4().to_string();
+        let contents = (0..100)
+            .map(|i| format!("Hello, world! {i}").into_bytes())
+            .collect::<Vec<_>>();
+
+        write_entries(DataWriterConfig::new(), &plain_filename, &contents).await;
+        write_entries(
+            DataWriterConfig::new().compression(compression),
+            &compressed_filename,
+            &contents,
+        )
+        .await;
+
+        // The files should differ on disk…
+        let (base, date) = (default_base(), get_date());
+        assert_ne!(
+            std::fs::read(ident_path(&base, &plain_filename, date)).unwrap(),
+            std::fs::read(ident_path(&base, &compressed_filename, date)).unwrap(),
+        );
+
+        // …but be identical at the logical level.
+        let plain = FileReader::new(plain_filename.clone(), 0).expect("failed to read plain file");
+        let compressed = FileReader::new(compressed_filename.clone(), 0)
+            .expect("failed to read compressed file");
+
+        let plain = plain.collect::<Vec<_>>();
+        assert_eq!(plain, contents);
+        assert_eq!(compressed.collect::<Vec<_>>(), plain);
+
+        let compressed = FileReader::new(compressed_filename.clone(), 0)
+            .expect("failed to read compressed file");
+        let reversed = contents.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(compressed.reverse_iter().collect::<Vec<_>>(), reversed);
+
+        // Appending to a file of another compression starts a new segment.
+        let more = [b"more".to_vec()];
+        write_entries(DataWriterConfig::new(), &compressed_filename, &more).await;
+        write_entries(
+            DataWriterConfig::new().compression(compression),
+            &plain_filename,
+            &more,
+        )
+        .await;
+
+        assert!(segment_path(&base, &plain_filename, date, 0).exists());
+        assert!(segment_path(&base, &compressed_filename, date, 0).exists());
+        let appended = [contents.as_slice(), more.as_slice()].concat();
+        assert_eq!(read_entries(&plain_filename), appended);
+        assert_eq!(read_entries(&compressed_filename), appended);
+    }
+
+    #[cfg(feature = "compress-zstd")]
+    #[tokio::test]
+    async fn test_zstd_read_write() {
+        test_compression(super::compression::Compression::Zstd { level: 3 }).await;
+    }
+
+    #[cfg(feature = "compress-gzip")]
+    #[tokio::test]
+    async fn test_gzip_read_write() {
+        test_compression(super::compression::Compression::Gzip { level: 6 }).await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_read_tail() {
+        use std::time::Duration;
+
+        use futures::StreamExt;
+
+        use super::reader::AsyncFileReader;
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let contents = (0..5)
+            .map(|i| format!("live-{i}").into_bytes())
+            .collect::<Vec<_>>();
+
+        let mut writer = DataWriter::new();
+        let writer_thread = writer.start().await.expect("failed to spawn writer");
+
+        // Create the file before tailing it.
+        writer
+            .add(DataEntry {
+                filename: filename.to_string(),
+                data: contents[0].clone(),
+            })
+            .expect("failed to add content");
+        while writer.entries_written() == 0 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let reader = AsyncFileReader::new(filename.to_string(), 0)
+            .await
+            .expect("failed to start the async reader")
+            .tail(Duration::from_millis(10));
+        let reader_task = tokio::task::spawn(async move {
+            Box::pin(reader.into_stream())
+                .take(5)
+                .map(|frame| frame.expect("failed to read frame"))
+                .collect::<Vec<_>>()
+                .await
+        });
+
+        // Append the rest while the reader is tailing.
+        for content in &contents[1..] {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            writer
+                .add(DataEntry {
+                    filename: filename.to_string(),
+                    data: content.clone(),
+                })
+                .expect("failed to add content");
+        }
+
+        let received = tokio::time::timeout(Duration::from_secs(5), reader_task)
+            .await
+            .expect("reader timed out")
+            .expect("reader task failed");
+        assert_eq!(received, contents);
+
+        writer.stop().expect("failed to stop writer");
+        writer_thread
+            .await
+            .expect("failed to wait writer thread to stop");
+    }
 }
@@ -0,0 +1,167 @@
+//! The compression support of the record files.
+//!
+//! A compressed file starts with a 4-byte magic header,
+//! which indicates the compression algorithm. Each frame
+//! written by the [`DataWriter`](super::writer::DataWriter)
+//! is compressed as an independent member (gzip) or frame (zstd),
+//! so the file can still be appended to. Files without the
+//! magic header are considered uncompressed.
+//!
+//! The compression algorithms are gated behind the features:
+//!
+//! - `compress-zstd`: `Compression::Zstd`
+//! - `compress-gzip`: `Compression::Gzip`
+
+use std::io::{self, Read};
+
+/// The magic header of a compressed file, without the algorithm byte.
+pub(crate) const MAGIC_PREFIX: &[u8; 3] = b"LSZ";
+
+/// The length of the magic header.
+pub const MAGIC_LEN: usize = MAGIC_PREFIX.len() + 1;
+
+/// The compression algorithm of the record files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// Don't compress.
+    #[default]
+    None,
+
+    /// Compress with [Zstandard](https://facebook.github.io/zstd/).
+    ///
+    /// `level` is the compression level, ranged from 1 to 21.
+    #[cfg(feature = "compress-zstd")]
+    Zstd { level: i32 },
+
+    /// Compress with gzip.
+    ///
+    /// `level` is the compression level, ranged from 0 to 9.
+    #[cfg(feature = "compress-gzip")]
+    Gzip { level: u32 },
+}
+
+impl Compression {
+    /// The algorithm byte in the magic header.
+    fn id(&self) -> u8 {
+        match self {
+            Compression::None => 0,
+            #[cfg(feature = "compress-zstd")]
+            Compression::Zstd { .. } => 1,
+            #[cfg(feature = "compress-gzip")]
+            Compression::Gzip { .. } => 2,
+        }
+    }
+
+    /// The magic header to write at the beginning of a new file.
+    ///
+    /// It is empty if the file is not compressed.
+    pub fn header(&self) -> Vec<u8> {
+        match self {
+            Compression::None => Vec::new(),
+            #[allow(unreachable_patterns)]
+            _ => {
+                let mut header = MAGIC_PREFIX.to_vec();
+                header.push(self.id());
+                header
+            }
+        }
+    }
+
+    /// Compress a frame as an independent member.
+    pub fn compress(&self, frame: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(frame.to_vec()),
+            #[cfg(feature = "compress-zstd")]
+            Compression::Zstd { level } => zstd::stream::encode_all(frame, *level),
+            #[cfg(feature = "compress-gzip")]
+            Compression::Gzip { level } => {
+                use std::io::Write;
+
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::new(*level));
+                encoder.write_all(frame)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Check if the file starting with `header` is compressed.
+///
+/// `header` should contain at least [`MAGIC_LEN`] bytes to be detected.
+pub fn is_compressed(header: &[u8]) -> bool {
+    header.len() >= MAGIC_LEN && &header[..MAGIC_PREFIX.len()] == MAGIC_PREFIX
+}
+
+/// Detect the compression of `reader` with its magic header,
+/// and wrap `reader` with the corresponding decoder.
+///
+/// The returned reader emits the decompressed content,
+/// without the magic header.
+pub fn decompressed_reader<R>(mut reader: R) -> io::Result<Box<dyn Read + Send>>
+where
+    R: Read + Send + 'static,
+{
+    let mut header = Vec::with_capacity(MAGIC_LEN);
+    reader
+        .by_ref()
+        .take(MAGIC_LEN as u64)
+        .read_to_end(&mut header)?;
+
+    if !is_compressed(&header) {
+        // Not compressed. Put the consumed bytes back.
+        return Ok(Box::new(io::Cursor::new(header).chain(reader)));
+    }
+
+    match header[MAGIC_PREFIX.len()] {
+        #[cfg(feature = "compress-zstd")]
+        1 => Ok(Box::new(zstd::stream::read::Decoder::new(reader)?)),
+        #[cfg(feature = "compress-gzip")]
+        2 => Ok(Box::new(flate2::read::MultiGzDecoder::new(reader))),
+        id => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("unsupported compression algorithm: {id}"),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::{decompressed_reader, Compression};
+
+    fn roundtrip(compression: Compression) {
+        let frames: [&[u8]; 3] = [b"\x00\x03abc", b"\x00\x02de", b"\x00\x00"];
+
+        let mut file = compression.header();
+        for frame in frames {
+            file.extend(compression.compress(frame).unwrap());
+        }
+
+        let mut decompressed = Vec::new();
+        decompressed_reader(std::io::Cursor::new(file))
+            .unwrap()
+            .read_to_end(&mut decompressed)
+            .unwrap();
+
+        assert_eq!(decompressed, frames.concat());
+    }
+
+    #[test]
+    fn test_uncompressed_roundtrip() {
+        roundtrip(Compression::None);
+    }
+
+    #[cfg(feature = "compress-zstd")]
+    #[test]
+    fn test_zstd_roundtrip() {
+        roundtrip(Compression::Zstd { level: 3 });
+    }
+
+    #[cfg(feature = "compress-gzip")]
+    #[test]
+    fn test_gzip_roundtrip() {
+        roundtrip(Compression::Gzip { level: 6 });
+    }
+}
@@ -1,22 +1,462 @@
-use std::path::PathBuf;
+//! The layout of the data directory.
+//!
+//! The files of a date are placed at `<base>/<yyyymmdd>/<name>.csv`,
+//! where `<base>` is `./record` by default (see [`default_base`]).
+//! If a file is rotated by size, its segment `N` is placed at
+//! `<base>/<yyyymmdd>/<name>.N.csv`.
+//!
+//! The writer can also partition the date directories by hour or minute
+//! (see [`PartitionGranularity`]), which places the files at
+//! `<base>/<yyyymmdd>/<HH>/<name>.csv` or `<base>/<yyyymmdd>/<HH>/<MM>/<name>.csv`.
+//! The listing helpers, such as [`existing_paths`], look into all the
+//! [`partition_directories`] of a date, so the partitioned files are read
+//! as if they were in the date directory.
+//!
+//! [`DataWriter`](super::writer::DataWriter) and
+//! [`FileReader`](super::reader::FileReader) place the files with
+//! the helpers here, so you can use them to locate the record files.
 
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, NaiveDateTime};
 use concat_string::concat_string;
 
-/// Get the data directory.
-///
-/// Currently, the data directory is `./record/<timestamp>`.
-pub fn get_data_directory(timestamp: &str) -> PathBuf {
+/// The format of the date directories.
+pub const DATE_FORMAT: &str = "%Y%m%d";
+
+/// The format of the hour directories.
+pub const HOUR_FORMAT: &str = "%H";
+
+/// The format of the minute directories.
+pub const MINUTE_FORMAT: &str = "%M";
+
+/// How the files of a date are partitioned into the subdirectories.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PartitionGranularity {
+    /// Place the files in `<base>/<yyyymmdd>/`.
+    #[default]
+    Day,
+
+    /// Place the files in `<base>/<yyyymmdd>/<HH>/`.
+    Hour,
+
+    /// Place the files in `<base>/<yyyymmdd>/<HH>/<MM>/`.
+    Minute,
+}
+
+/// Get the default base directory, which is `./record`.
+pub fn default_base() -> PathBuf {
     let mut path = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
 
     path.push("record");
-    path.push(timestamp);
     path
 }
 
-/// Get the exact filename to write to.
-pub fn get_ident_path(timestamp: &str, identifier: &str) -> PathBuf {
-    let mut path = get_data_directory(timestamp);
-    path.push(concat_string!(identifier, ".csv"));
+/// Get the directory of the files of `date`.
+pub fn date_directory(base: &Path, date: NaiveDate) -> PathBuf {
+    base.join(date.format(DATE_FORMAT).to_string())
+}
+
+/// Get the directory of the files written at `time`,
+/// partitioned by `granularity`.
+pub fn partition_directory(
+    base: &Path,
+    time: NaiveDateTime,
+    granularity: PartitionGranularity,
+) -> PathBuf {
+    let mut path = date_directory(base, time.date());
+
+    match granularity {
+        PartitionGranularity::Day => {}
+        PartitionGranularity::Hour => {
+            path.push(time.format(HOUR_FORMAT).to_string());
+        }
+        PartitionGranularity::Minute => {
+            path.push(time.format(HOUR_FORMAT).to_string());
+            path.push(time.format(MINUTE_FORMAT).to_string());
+        }
+    }
 
     path
 }
+
+/// Get the existing partition directories of `date`.
+///
+/// The date directory comes first, and then each hour directory
+/// followed by its minute directories, in ascending order. It returns
+/// an empty list if the date directory doesn't exist.
+pub fn partition_directories(base: &Path, date: NaiveDate) -> Vec<PathBuf> {
+    let directory = date_directory(base, date);
+    if !directory.is_dir() {
+        return Vec::new();
+    }
+
+    let hour_directories = numbered_subdirectories(&directory, 24);
+    let mut directories = vec![directory];
+    for hour_directory in hour_directories {
+        let minute_directories = numbered_subdirectories(&hour_directory, 60);
+
+        directories.push(hour_directory);
+        directories.extend(minute_directories);
+    }
+
+    directories
+}
+
+/// List the subdirectories of `directory` named as the two-digit
+/// numbers below `limit`, such as the hours and minutes, in ascending order.
+fn numbered_subdirectories(directory: &Path, limit: u32) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::debug!("Failed to read {dir}: {e}", dir = directory.display());
+            return Vec::new();
+        }
+    };
+
+    let mut directories = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter(|entry| {
+            let dirname = entry.file_name();
+            let number = dirname.to_str().filter(|number| number.len() == 2);
+
+            matches!(number.map(str::parse::<u32>), Some(Ok(number)) if number < limit)
+        })
+        .map(|entry| entry.path())
+        .collect::<Vec<_>>();
+    directories.sort_unstable();
+
+    directories
+}
+
+/// Get the exact path of the file `name` of `date`.
+pub fn ident_path(base: &Path, name: &str, date: NaiveDate) -> PathBuf {
+    path_in(&date_directory(base, date), name)
+}
+
+/// Get the exact path of a segment of a rotated file.
+pub fn segment_path(base: &Path, name: &str, date: NaiveDate, segment: u32) -> PathBuf {
+    segment_path_in(&date_directory(base, date), name, segment)
+}
+
+/// Get the last existing segment of `name`.
+///
+/// It returns `0` if there is no segment.
+pub fn last_segment(base: &Path, name: &str, date: NaiveDate) -> u32 {
+    last_segment_in(&date_directory(base, date), name)
+}
+
+/// Get the exact path of the file `name` in `directory`,
+/// for example, a [`partition_directory`].
+pub fn path_in(directory: &Path, name: &str) -> PathBuf {
+    directory.join(concat_string!(name, ".csv"))
+}
+
+/// Get the exact path of a segment of a rotated file in `directory`.
+pub fn segment_path_in(directory: &Path, name: &str, segment: u32) -> PathBuf {
+    directory.join(concat_string!(name, ".", segment.to_string(), ".csv"))
+}
+
+/// Get the last existing segment of `name` in `directory`.
+///
+/// It returns `0` if there is no segment.
+pub fn last_segment_in(directory: &Path, name: &str) -> u32 {
+    let mut segment = 0;
+
+    while segment_path_in(directory, name, segment + 1).exists() {
+        segment += 1;
+    }
+
+    segment
+}
+
+/// Get all the existing files of `name` of `date` in the written order.
+///
+/// The files are listed in the order of the [`partition_directories`],
+/// and see [`existing_paths_in`] for the order in a directory.
+pub fn existing_paths(base: &Path, name: &str, date: NaiveDate) -> Vec<PathBuf> {
+    partition_directories(base, date)
+        .iter()
+        .flat_map(|directory| existing_paths_in(directory, name))
+        .collect()
+}
+
+/// Get all the existing files of `name` in `directory` in the written order.
+///
+/// The unrotated file comes first, and then the rotated segments.
+pub fn existing_paths_in(directory: &Path, name: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    let unrotated = path_in(directory, name);
+    if unrotated.exists() {
+        paths.push(unrotated);
+    }
+
+    for segment in 0.. {
+        let path = segment_path_in(directory, name, segment);
+        if !path.exists() {
+            break;
+        }
+
+        paths.push(path);
+    }
+
+    paths
+}
+
+/// List the dates having any file of `name`, in ascending order.
+///
+/// The entries of `base` not named as [`DATE_FORMAT`] are ignored.
+/// It returns an empty list if `base` can't be read.
+pub fn list_dates(base: &Path, name: &str) -> Vec<NaiveDate> {
+    let entries = match std::fs::read_dir(base) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::debug!("Failed to read {base}: {e}", base = base.display());
+            return Vec::new();
+        }
+    };
+
+    let mut dates = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let dirname = entry.file_name();
+            NaiveDate::parse_from_str(dirname.to_str()?, DATE_FORMAT).ok()
+        })
+        .filter(|date| !existing_paths(base, name, *date).is_empty())
+        .collect::<Vec<_>>();
+    dates.sort_unstable();
+
+    dates
+}
+
+/// List the names of the files of `date`, in ascending order.
+///
+/// The segments of a rotated file are listed as the name of the file.
+/// Only the files directly in the [`partition_directories`] are listed.
+/// It returns an empty list if the date directory can't be read.
+pub fn list_files(base: &Path, date: NaiveDate) -> Vec<String> {
+    partition_directories(base, date)
+        .iter()
+        .flat_map(|directory| list_files_in(directory))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// List the names of the files directly in `directory`.
+fn list_files_in(directory: &Path) -> Vec<String> {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::debug!("Failed to read {dir}: {e}", dir = directory.display());
+            return Vec::new();
+        }
+    };
+
+    let filenames = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect::<BTreeSet<_>>();
+
+    filenames
+        .iter()
+        .filter_map(|filename| {
+            let name = filename.strip_suffix(".csv")?;
+
+            Some(strip_segment(name, &filenames).to_string())
+        })
+        .collect()
+}
+
+/// Strip the `.N` segment suffix of `name`, if it is a segment of
+/// a rotated file, which means the segment 0 or the unrotated file
+/// of the stem is also in `filenames`.
+///
+/// For example, `v1.2` is the segment 2 of `v1` if `v1.0.csv` exists,
+/// and the name of an unrotated file otherwise.
+fn strip_segment<'a>(name: &'a str, filenames: &BTreeSet<String>) -> &'a str {
+    match name.rsplit_once('.') {
+        Some((stem, segment))
+            if !segment.is_empty()
+                && segment.bytes().all(|b| b.is_ascii_digit())
+                && (filenames.contains(&concat_string!(stem, ".0.csv"))
+                    || filenames.contains(&concat_string!(stem, ".csv"))) =>
+        {
+            stem
+        }
+        _ => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use chrono::NaiveDate;
+
+    use super::{
+        date_directory,
+        existing_paths,
+        ident_path,
+        list_dates,
+        list_files,
+        partition_directories,
+        partition_directory,
+        path_in,
+        segment_path,
+        segment_path_in,
+        PartitionGranularity,
+    };
+
+    /// Create an empty directory in the temporary directory.
+    fn create_base() -> PathBuf {
+        let base = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&base).unwrap();
+
+        base
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn touch(path: PathBuf) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"").unwrap();
+    }
+
+    #[test]
+    fn test_paths() {
+        let base = PathBuf::from("/data");
+        let day = date(2022, 8, 6);
+
+        assert_eq!(
+            ident_path(&base, "bbo", day),
+            PathBuf::from("/data/20220806/bbo.csv")
+        );
+        assert_eq!(
+            segment_path(&base, "bbo", day, 2),
+            PathBuf::from("/data/20220806/bbo.2.csv")
+        );
+    }
+
+    #[test]
+    fn test_partition_directory() {
+        let base = PathBuf::from("/data");
+        let time = date(2023, 1, 1).and_hms_opt(15, 4, 5).unwrap();
+
+        assert_eq!(
+            partition_directory(&base, time, PartitionGranularity::Day),
+            PathBuf::from("/data/20230101")
+        );
+        assert_eq!(
+            partition_directory(&base, time, PartitionGranularity::Hour),
+            PathBuf::from("/data/20230101/15")
+        );
+        assert_eq!(
+            partition_directory(&base, time, PartitionGranularity::Minute),
+            PathBuf::from("/data/20230101/15/04")
+        );
+
+        let directory = partition_directory(&base, time, PartitionGranularity::Day);
+        assert_eq!(path_in(&directory, "bbo"), ident_path(&base, "bbo", time.date()));
+    }
+
+    #[test]
+    fn test_list_dates() {
+        let base = create_base();
+        let [first, second, third] = [
+            date(2022, 8, 6),
+            date(2022, 8, 7),
+            date(2022, 9, 1),
+        ];
+
+        touch(ident_path(&base, "bbo", second));
+        touch(segment_path(&base, "bbo", first, 0));
+        touch(ident_path(&base, "trade", third));
+        // Not the date directories.
+        touch(base.join("2022-08-08").join("bbo.csv"));
+        touch(base.join("latest").join("bbo.csv"));
+        touch(base.join("20220810"));
+
+        assert_eq!(list_dates(&base, "bbo"), [first, second]);
+        assert_eq!(list_dates(&base, "trade"), [third]);
+        assert!(list_dates(&base, "kline").is_empty());
+        assert!(list_dates(&base.join("missing"), "bbo").is_empty());
+
+        std::fs::remove_dir_all(base).unwrap();
+    }
+
+    #[test]
+    fn test_list_files() {
+        let base = create_base();
+        let day = date(2022, 8, 6);
+
+        touch(ident_path(&base, "bbo", day));
+        touch(segment_path(&base, "bbo", day, 0));
+        touch(segment_path(&base, "bbo", day, 1));
+        touch(segment_path(&base, "trade", day, 0));
+        touch(ident_path(&base, "v1.kline", day));
+        // Not a segment, as neither `kline.0.csv` nor `kline.csv` exists.
+        touch(ident_path(&base, "kline.5", day));
+        // Not the record files.
+        touch(base.join("20220806").join("notes.txt"));
+        touch(base.join("20220806").join("nested").join("bbo.csv"));
+
+        assert_eq!(
+            list_files(&base, day),
+            ["bbo", "kline.5", "trade", "v1.kline"]
+        );
+        assert!(list_files(&base, date(2022, 8, 7)).is_empty());
+
+        std::fs::remove_dir_all(base).unwrap();
+    }
+
+    #[test]
+    fn test_partitioned_files() {
+        let base = create_base();
+        let day = date(2022, 8, 6);
+        let time = |hour, minute| day.and_hms_opt(hour, minute, 0).unwrap();
+        let hour = partition_directory(&base, time(9, 0), PartitionGranularity::Hour);
+        let minute = partition_directory(&base, time(9, 5), PartitionGranularity::Minute);
+        let later_hour = partition_directory(&base, time(13, 0), PartitionGranularity::Hour);
+
+        touch(ident_path(&base, "bbo", day));
+        touch(path_in(&later_hour, "bbo"));
+        touch(path_in(&minute, "bbo"));
+        touch(segment_path_in(&minute, "trade", 0));
+        touch(path_in(&hour, "bbo"));
+        // Not the partition directories.
+        touch(base.join("20220806").join("24").join("kline.csv"));
+        touch(base.join("20220806").join("9").join("kline.csv"));
+
+        assert_eq!(
+            partition_directories(&base, day),
+            [
+                date_directory(&base, day),
+                hour.clone(),
+                minute.clone(),
+                later_hour.clone(),
+            ]
+        );
+        assert_eq!(
+            existing_paths(&base, "bbo", day),
+            [
+                ident_path(&base, "bbo", day),
+                path_in(&hour, "bbo"),
+                path_in(&minute, "bbo"),
+                path_in(&later_hour, "bbo"),
+            ]
+        );
+        assert_eq!(list_files(&base, day), ["bbo", "trade"]);
+        assert_eq!(list_dates(&base, "trade"), [day]);
+        assert!(partition_directories(&base, date(2022, 8, 7)).is_empty());
+
+        std::fs::remove_dir_all(base).unwrap();
+    }
+}
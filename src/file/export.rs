@@ -0,0 +1,312 @@
+//! Export the record files to CSV.
+//!
+//! The frames of a record file are decoded as a structure, such as
+//! [`BboStructure`], and written as the rows of a CSV with a header.
+//! The timestamps are written as the Unix milliseconds, and the decimals
+//! as the plain decimal strings, so the CSV can be loaded with
+//! `pandas.read_csv` directly.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use wmjtyd_libstock::data::bbo::BboStructure;
+//! use wmjtyd_libstock::file::export::export_csv;
+//! use wmjtyd_libstock::file::reader::FileReader;
+//!
+//! let reader = FileReader::new("binance_spot_bbo_BTCUSDT".to_string(), 0)
+//!     .expect("failed to open the file");
+//! let summary = export_csv::<BboStructure>(reader, std::io::stdout().lock())
+//!     .expect("failed to export");
+//!
+//! eprintln!("{} rows exported, {} frames skipped", summary.rows, summary.skipped);
+//! ```
+
+use std::fmt::Display;
+use std::io::Write;
+
+use super::reader::{FileReader, ReadError};
+use crate::data::bbo::BboStructure;
+use crate::data::kline::KlineStructure;
+use crate::data::num::Decimal;
+use crate::data::serializer::StructDeserializer;
+use crate::data::trade::TradeStructure;
+
+/// The structure which can be exported as a CSV row.
+pub trait CsvRecord: StructDeserializer {
+    /// The names of the columns.
+    const HEADER: &'static [&'static str];
+
+    /// Get the values of the columns, in the order of [`HEADER`](Self::HEADER).
+    fn csv_fields(&self) -> Vec<String>;
+}
+
+impl CsvRecord for BboStructure {
+    const HEADER: &'static [&'static str] = &[
+        "exchange_timestamp",
+        "received_timestamp",
+        "exchange",
+        "market_type",
+        "pair",
+        "ask_price",
+        "ask_quantity",
+        "bid_price",
+        "bid_quantity",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.exchange_timestamp.0.to_string(),
+            self.received_timestamp.0.to_string(),
+            self.exchange_type.as_str().to_string(),
+            self.market_type.0.to_string(),
+            self.symbol.pair.clone(),
+            format_decimal(self.asks.price.0),
+            format_decimal(self.asks.quantity_base.0),
+            format_decimal(self.bids.price.0),
+            format_decimal(self.bids.quantity_base.0),
+        ]
+    }
+}
+
+impl CsvRecord for TradeStructure {
+    const HEADER: &'static [&'static str] = &[
+        "exchange_timestamp",
+        "received_timestamp",
+        "exchange",
+        "market_type",
+        "pair",
+        "side",
+        "price",
+        "quantity_base",
+        "quantity_quote",
+        "trade_id",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.exchange_timestamp.0.to_string(),
+            self.received_timestamp.0.to_string(),
+            self.exchange_type.as_str().to_string(),
+            self.market_type.0.to_string(),
+            self.symbol.pair.clone(),
+            self.trade_side.as_str().to_string(),
+            format_decimal(self.trade_price.price.0),
+            format_decimal(self.trade_price.quantity_base.0),
+            format_decimal(self.quantity_quote.0),
+            self.trade_id.0.clone(),
+        ]
+    }
+}
+
+impl CsvRecord for KlineStructure {
+    const HEADER: &'static [&'static str] = &[
+        "exchange_timestamp",
+        "received_timestamp",
+        "exchange",
+        "market_type",
+        "pair",
+        "period",
+        "open",
+        "high",
+        "low",
+        "close",
+        "volume",
+    ];
+
+    fn csv_fields(&self) -> Vec<String> {
+        let indicator = &self.indicator;
+
+        vec![
+            self.exchange_timestamp.0.to_string(),
+            self.received_timestamp.0.to_string(),
+            self.exchange_type.as_str().to_string(),
+            self.market_type.0.to_string(),
+            self.symbol.pair.clone(),
+            self.period.0.clone(),
+            format_decimal(indicator.open.0),
+            format_decimal(indicator.high.0),
+            format_decimal(indicator.low.0),
+            format_decimal(indicator.close.0),
+            format_decimal(indicator.volume.0),
+        ]
+    }
+}
+
+/// Format `value` as a plain decimal string without the trailing zeros.
+fn format_decimal(value: Decimal) -> String {
+    value.normalize().to_string()
+}
+
+/// The result of [`export_csv`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    /// The number of the rows written.
+    pub rows: u64,
+
+    /// The number of the frames failed to decode.
+    pub skipped: u64,
+}
+
+/// Decode each frame of `reader` as `S`, and write them to `out` as CSV.
+///
+/// The frames failed to decode are skipped, with their indexes (from 0)
+/// and the errors printed to the standard error.
+pub fn export_csv<S>(mut reader: FileReader, mut out: impl Write) -> ExportResult<ExportSummary>
+where
+    S: CsvRecord,
+    <S as StructDeserializer>::Err: Display,
+{
+    let mut summary = ExportSummary::default();
+    write_row(&mut out, S::HEADER.iter().copied())?;
+
+    let mut index = 0u64;
+    while let Some(frame) = reader.next_frame()? {
+        match S::deserialize_from_bytes(&frame) {
+            Ok(structure) => {
+                let fields = structure.csv_fields();
+                write_row(&mut out, fields.iter().map(String::as_str))?;
+                summary.rows += 1;
+            }
+            Err(e) => {
+                eprintln!("frame {index}: failed to decode: {e}");
+                summary.skipped += 1;
+            }
+        }
+
+        index += 1;
+    }
+
+    out.flush()?;
+    Ok(summary)
+}
+
+/// Write a CSV row, quoting the fields when needed.
+fn write_row<'a>(
+    out: &mut impl Write,
+    fields: impl Iterator<Item = &'a str>,
+) -> std::io::Result<()> {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+
+        if field.contains(&[',', '"', '\n', '\r'][..]) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            out.write_all(field.as_bytes())?;
+        }
+    }
+
+    out.write_all(b"\n")
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    #[error("failed to read the frame: {0}")]
+    ReadFailed(#[from] ReadError),
+
+    #[error("failed to write the CSV: {0}")]
+    WriteFailed(#[from] std::io::Error),
+}
+
+pub type ExportResult<T> = Result<T, ExportError>;
+
+#[cfg(test)]
+mod tests {
+    use crypto_market_type::MarketType;
+    use crypto_msg_type::MessageType;
+
+    use super::{export_csv, write_row, ExportSummary};
+    use crate::data::bbo::BboStructure;
+    use crate::data::fields::exchange_type::Exchange;
+    use crate::data::fields::{PriceDataField, SymbolPairField};
+    use crate::data::num::Decimal;
+    use crate::data::serializer::StructSerializer;
+    use crate::file::reader::FileReader;
+    use crate::file::writer::{DataEntry, DataWriter};
+
+    fn construct_bbo(timestamp: u64, ask: i64, bid: i64) -> BboStructure {
+        let price_data = |price| {
+            PriceDataField::builder()
+                .price(Decimal::new(price, 2))
+                .quantity_base(Decimal::new(15, 1))
+                .build()
+        };
+
+        BboStructure::builder()
+            .exchange_timestamp(timestamp)
+            .received_timestamp(timestamp + 5)
+            .exchange_type(Exchange::Binance)
+            .market_type(MarketType::Spot)
+            .message_type(MessageType::BBO)
+            .symbol(SymbolPairField::from_pair("BTC/USDT"))
+            .asks(price_data(ask))
+            .bids(price_data(bid))
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_export_bbo() {
+        let filename = uuid::Uuid::new_v4().to_string();
+        let first = construct_bbo(1672531200000, 1234567, 1234500);
+        let second = construct_bbo(1672531200100, 1234600, 1234512);
+        let frames = [
+            first.serialize_to_vec().unwrap(),
+            b"garbage".to_vec(),
+            second.serialize_to_vec().unwrap(),
+        ];
+
+        let mut writer = DataWriter::new();
+        let writer_thread = writer.start().await.expect("failed to spawn writer");
+        for frame in frames {
+            writer
+                .add(DataEntry {
+                    filename: filename.clone(),
+                    data: frame,
+                })
+                .expect("failed to add frame");
+        }
+        writer.stop().expect("failed to stop writer");
+        writer_thread
+            .await
+            .expect("failed to wait writer thread to stop");
+
+        let reader = FileReader::new(filename, 0).expect("failed to open the file");
+        let mut out = Vec::new();
+        let summary = export_csv::<BboStructure>(reader, &mut out).unwrap();
+        assert_eq!(
+            summary,
+            ExportSummary {
+                rows: 2,
+                skipped: 1
+            }
+        );
+
+        let csv = String::from_utf8(out).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some(
+                "exchange_timestamp,received_timestamp,exchange,market_type,pair,\
+                 ask_price,ask_quantity,bid_price,bid_quantity"
+            )
+        );
+        assert_eq!(
+            lines.next(),
+            Some("1672531200000,1672531200005,binance,spot,BTC/USDT,12345.67,1.5,12345,1.5")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("1672531200100,1672531200105,binance,spot,BTC/USDT,12346,1.5,12345.12,1.5")
+        );
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn test_write_row_quoting() {
+        let mut out = Vec::new();
+        write_row(&mut out, ["plain", "a,b", "say \"hi\""].into_iter()).unwrap();
+
+        assert_eq!(out, b"plain,\"a,b\",\"say \"\"hi\"\"\"\n");
+    }
+}
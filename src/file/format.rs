@@ -0,0 +1,211 @@
+//! The frame format of the record files.
+//!
+//! A record file starts with a 6-byte [`FileHeader`]:
+//!
+//! - the magic bytes `LSTK` (4 bytes),
+//! - the format version (1 byte), and
+//! - the width of the frame length section (1 byte), see [`LengthWidth`].
+//!
+//! The header is followed by the frames, each of which is the length
+//! of the data in big endian and the data. For compressed files,
+//! the header is written as the first compressed frame, after
+//! the magic header of the [compression](super::compression).
+//!
+//! The files without the header are the legacy files written before
+//! the header was introduced, whose frames have a `u16` length section.
+
+use std::io::{self, Read};
+
+/// The magic bytes at the beginning of a record file.
+pub const MAGIC: &[u8; 4] = b"LSTK";
+
+/// The current format version.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// The length of a [`FileHeader`].
+pub const HEADER_LEN: usize = MAGIC.len() + 2;
+
+/// The width of the length section of each frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LengthWidth {
+    /// The `u16` length, which limits the data to 64 KiB.
+    #[default]
+    U16,
+
+    /// The `u32` length, for the large payloads such as
+    /// the full orderbooks.
+    U32,
+}
+
+impl LengthWidth {
+    /// The bytes of the length section.
+    pub fn bytes(&self) -> usize {
+        match self {
+            LengthWidth::U16 => 2,
+            LengthWidth::U32 => 4,
+        }
+    }
+
+    /// Get the width of the specified bytes.
+    fn from_bytes(bytes: u8) -> Option<Self> {
+        match bytes {
+            2 => Some(LengthWidth::U16),
+            4 => Some(LengthWidth::U32),
+            _ => None,
+        }
+    }
+
+    /// Encode `len` to the length section.
+    ///
+    /// It returns `None` if `len` can't fit in this width.
+    pub fn encode(&self, len: usize) -> Option<Vec<u8>> {
+        Some(match self {
+            LengthWidth::U16 => u16::try_from(len).ok()?.to_be_bytes().to_vec(),
+            LengthWidth::U32 => u32::try_from(len).ok()?.to_be_bytes().to_vec(),
+        })
+    }
+
+    /// Decode the length section in `src`.
+    ///
+    /// # Panics
+    ///
+    /// It panics if `src` is shorter than [`bytes`](Self::bytes).
+    pub fn decode(&self, src: &[u8]) -> usize {
+        match self {
+            LengthWidth::U16 => u16::from_be_bytes([src[0], src[1]]).into(),
+            LengthWidth::U32 => u32::from_be_bytes([src[0], src[1], src[2], src[3]]) as usize,
+        }
+    }
+}
+
+/// The header of a record file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FileHeader {
+    /// The format version.
+    pub version: u8,
+    /// The width of the length section of each frame.
+    pub length_width: LengthWidth,
+}
+
+impl FileHeader {
+    /// Create a header of the current format version.
+    pub fn new(length_width: LengthWidth) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            length_width,
+        }
+    }
+
+    /// The header of the legacy files, which have no header.
+    pub fn legacy() -> Self {
+        Self {
+            version: 0,
+            length_width: LengthWidth::U16,
+        }
+    }
+
+    /// Serialize this header.
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut header = [0u8; HEADER_LEN];
+        header[..MAGIC.len()].copy_from_slice(MAGIC);
+        header[MAGIC.len()] = self.version;
+        header[MAGIC.len() + 1] = self.length_width.bytes() as u8;
+
+        header
+    }
+
+    /// Parse the header at the beginning of `src`.
+    ///
+    /// It returns `Ok(None)` if `src` doesn't start with [`MAGIC`],
+    /// which means it is a legacy file.
+    pub fn parse(src: &[u8]) -> io::Result<Option<Self>> {
+        if src.len() < HEADER_LEN || &src[..MAGIC.len()] != MAGIC {
+            return Ok(None);
+        }
+
+        let version = src[MAGIC.len()];
+        if version != FORMAT_VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("unsupported record file version: {version}"),
+            ));
+        }
+
+        let width = src[MAGIC.len() + 1];
+        let length_width = LengthWidth::from_bytes(width).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("invalid frame length width: {width}"),
+            )
+        })?;
+
+        Ok(Some(Self {
+            version,
+            length_width,
+        }))
+    }
+}
+
+/// Read the [`FileHeader`] of `reader`.
+///
+/// The returned reader emits the frames after the header. For the legacy
+/// files, the header is [`FileHeader::legacy`] and nothing is consumed.
+pub fn read_header<R>(mut reader: R) -> io::Result<(FileHeader, Box<dyn Read + Send>)>
+where
+    R: Read + Send + 'static,
+{
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    reader
+        .by_ref()
+        .take(HEADER_LEN as u64)
+        .read_to_end(&mut header)?;
+
+    match FileHeader::parse(&header)? {
+        Some(header) => Ok((header, Box::new(reader))),
+        // Not a header. Put the consumed bytes back.
+        None => Ok((
+            FileHeader::legacy(),
+            Box::new(io::Cursor::new(header).chain(reader)),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read};
+
+    use super::{read_header, FileHeader, LengthWidth};
+
+    #[test]
+    fn test_header_roundtrip() {
+        for length_width in [LengthWidth::U16, LengthWidth::U32] {
+            let header = FileHeader::new(length_width);
+            assert_eq!(FileHeader::parse(&header.to_bytes()).unwrap(), Some(header));
+        }
+    }
+
+    #[test]
+    fn test_header_invalid() {
+        assert!(FileHeader::parse(b"LSTK\x02\x02").is_err());
+        assert!(FileHeader::parse(b"LSTK\x01\x03").is_err());
+        assert_eq!(FileHeader::parse(b"\x00\x03abc\x00").unwrap(), None);
+    }
+
+    #[test]
+    fn test_read_header_legacy() {
+        let (header, mut reader) = read_header(Cursor::new(b"\x00\x03abc".to_vec())).unwrap();
+        assert_eq!(header, FileHeader::legacy());
+
+        let mut content = Vec::new();
+        reader.read_to_end(&mut content).unwrap();
+        assert_eq!(content, b"\x00\x03abc");
+    }
+
+    #[test]
+    fn test_length_width() {
+        assert_eq!(LengthWidth::U16.encode(0x0102), Some(vec![1, 2]));
+        assert_eq!(LengthWidth::U16.encode(0x10000), None);
+        assert_eq!(LengthWidth::U32.encode(0x10000), Some(vec![0, 1, 0, 0]));
+        assert_eq!(LengthWidth::U32.decode(&[0, 1, 0, 0]), 0x10000);
+    }
+}
@@ -1,47 +1,923 @@
 //! Read the specified file and return the content stream.
+//!
+//! # Example
+//!
+//! Read all the BBO structures written to the file `bbo` today:
+//!
+//! ```no_run
+//! # #[cfg(feature = "crypto")]
+//! # fn main() -> Result<(), wmjtyd_libstock::file::reader::ReadError> {
+//! use wmjtyd_libstock::data::bbo::BboStructure;
+//! use wmjtyd_libstock::file::reader::FileReader;
+//!
+//! let reader = FileReader::new("bbo".to_string(), 0)?;
+//!
+//! for bbo in reader.typed_iter::<BboStructure>() {
+//!     println!("{:?}", bbo?);
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "crypto"))]
+//! # fn main() {}
+//! ```
 
+use std::collections::VecDeque;
 use std::fs::File;
-use std::io::Read;
+use std::io::{Read, Seek, SeekFrom};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
 
-use chrono::{Duration, Local};
+use chrono::{Duration, Local, NaiveDate};
+use futures::Stream;
+use tokio::io::AsyncReadExt;
 
-use crate::file::datadir::get_ident_path;
-use crate::file::timestamp::fmt_timestamp;
+use crate::data::serializer::StructDeserializer;
+use crate::file::compression::{decompressed_reader, is_compressed, MAGIC_PREFIX};
+use crate::file::datadir::{default_base, existing_paths, ident_path};
+use crate::file::format::{read_header, FileHeader, LengthWidth, HEADER_LEN, MAGIC};
 
+/// The reader of the files written by [`DataWriter`](super::writer::DataWriter).
+///
+/// If the file has been rotated by size, the reader chains
+/// all the segments transparently in the written order.
+/// Compressed files are detected and decompressed transparently.
+///
+/// Both the files with a [`FileHeader`] and the legacy files
+/// without the header are supported.
 pub struct FileReader {
-    file: File,
+    file: Box<dyn Read + Send>,
+    /// The path of `file`.
+    path: PathBuf,
+    /// The count of the frames read from `file`.
+    frames_read: usize,
+
+    /// The width of the length section of the frames in `file`.
+    length_width: LengthWidth,
+
+    /// The segments to read after `file` is exhausted.
+    remaining_segments: VecDeque<PathBuf>,
+
+    /// The frame read by [`FileReader::peek`] but not consumed yet.
+    peeked: Option<Vec<u8>>,
+}
+
+/// Get the local date of `day` days ago.
+fn get_date_of_day(day: i64) -> NaiveDate {
+    (Local::now() - Duration::days(day)).naive_local().date()
 }
 
 impl FileReader {
     pub fn new(filename: String, day: i64) -> std::io::Result<FileReader> {
-        let time = Local::now() - Duration::days(day);
-        let timestamp = fmt_timestamp(&time);
+        let (base, date) = (default_base(), get_date_of_day(day));
 
-        let path = get_ident_path(&timestamp, &filename);
+        let mut paths = VecDeque::from(existing_paths(&base, &filename, date));
+        // If there is no such a file, we let `File::open` report the error.
+        let path = paths
+            .pop_front()
+            .unwrap_or_else(|| ident_path(&base, &filename, date));
         tracing::info!("Creating a writer to read {path}", path = path.display());
 
-        File::open(path).map(|file| FileReader { file })
+        let (header, file) = open_file(&path)?;
+
+        Ok(FileReader {
+            file,
+            path,
+            frames_read: 0,
+            length_width: header.length_width,
+            remaining_segments: paths,
+            peeked: None,
+        })
+    }
+
+    /// Read the next frame without consuming it.
+    ///
+    /// The frame is cached, so the following [`peek`](Self::peek) and
+    /// [`next_frame`](Self::next_frame) return it without reading again.
+    pub fn peek(&mut self) -> ReadResult<Option<&[u8]>> {
+        if self.peeked.is_none() {
+            self.peeked = self.next_frame()?;
+        }
+
+        Ok(self.peeked.as_deref())
+    }
+
+    /// Iterate the frames not read yet in reverse order,
+    /// from the last frame of the last segment.
+    ///
+    /// Each segment is indexed in a first pass, which only reads the
+    /// length sections, and then the frames are read by seeking to
+    /// the indexed offsets. The compressed segments can't be seeked,
+    /// so their frames are decompressed into the memory instead.
+    ///
+    /// A truncated trailing frame is skipped.
+    pub fn reverse_iter(self) -> ReverseFileIter {
+        // The peeked frame has been read but not consumed.
+        let frames_consumed = self.frames_read - usize::from(self.peeked.is_some());
+
+        let mut pending = vec![(self.path, frames_consumed)];
+        pending.extend(self.remaining_segments.into_iter().map(|path| (path, 0)));
+
+        ReverseFileIter {
+            pending,
+            current: None,
+        }
+    }
+
+    /// Read the next frame.
+    ///
+    /// It returns `Ok(None)` when all the files are cleanly ended,
+    /// and [`ReadError::TruncatedFrame`] if a file ends in the middle
+    /// of a frame, for example, a partially-written trailing frame.
+    pub fn next_frame(&mut self) -> ReadResult<Option<Vec<u8>>> {
+        if let Some(data) = self.peeked.take() {
+            return Ok(Some(data));
+        }
+
+        loop {
+            if let Some(data) = self.read_frame()? {
+                return Ok(Some(data));
+            }
+
+            if !self.next_segment()? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Read the next frame and deserialize it as `T`.
+    ///
+    /// It returns `Ok(None)` when all the files are cleanly ended,
+    /// and [`ReadError::Deserialize`] if the frame is not a valid `T`.
+    pub fn read_typed<T>(&mut self) -> ReadResult<Option<T>>
+    where
+        T: StructDeserializer,
+        T::Err: Into<DeserializeError>,
+    {
+        match self.next_frame()? {
+            Some(frame) => T::deserialize_from_bytes(&frame)
+                .map(Some)
+                .map_err(|e| ReadError::Deserialize(e.into())),
+            None => Ok(None),
+        }
+    }
+
+    /// Convert this reader to an iterator of the frames deserialized as `T`.
+    ///
+    /// See [`TypedFileIter`] for more information.
+    pub fn typed_iter<T>(self) -> TypedFileIter<T>
+    where
+        T: StructDeserializer,
+        T::Err: Into<DeserializeError>,
+    {
+        TypedFileIter {
+            reader: self,
+            _structure: PhantomData,
+        }
+    }
+
+    /// Read a frame from the current file.
+    ///
+    /// It returns `Ok(None)` if the current file is cleanly ended.
+    fn read_frame(&mut self) -> ReadResult<Option<Vec<u8>>> {
+        let width = self.length_width.bytes();
+        let mut data_len_section = [0u8; 4];
+        match read_full(&mut self.file, &mut data_len_section[..width])? {
+            0 => return Ok(None),
+            got if got == width => {}
+            got => {
+                return Err(ReadError::TruncatedFrame {
+                    expected: width,
+                    got,
+                })
+            }
+        }
+
+        let data_len = self.length_width.decode(&data_len_section);
+        let mut data = vec![0u8; data_len];
+
+        let got = read_full(&mut self.file, &mut data)?;
+        if got != data_len {
+            return Err(ReadError::TruncatedFrame {
+                expected: data_len,
+                got,
+            });
+        }
+
+        self.frames_read += 1;
+        Ok(Some(data))
+    }
+
+    /// Switch to the next segment.
+    ///
+    /// Returns `false` if there is no more segment to read.
+    fn next_segment(&mut self) -> ReadResult<bool> {
+        let path = match self.remaining_segments.pop_front() {
+            Some(path) => path,
+            None => return Ok(false),
+        };
+
+        tracing::debug!("Switching to the segment {path}", path = path.display());
+        let (header, file) = open_file(&path)?;
+        self.file = file;
+        self.path = path;
+        self.frames_read = 0;
+        self.length_width = header.length_width;
+
+        Ok(true)
+    }
+}
+
+/// The iterator of the frames deserialized as `T`.
+///
+/// Unlike the iterator of [`FileReader`], the errors are returned.
+/// If a frame fails to deserialize, the iteration continues
+/// from the next frame.
+pub struct TypedFileIter<T> {
+    reader: FileReader,
+    _structure: PhantomData<fn() -> T>,
+}
+
+impl<T> Iterator for TypedFileIter<T>
+where
+    T: StructDeserializer,
+    T::Err: Into<DeserializeError>,
+{
+    type Item = ReadResult<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.read_typed().transpose()
+    }
+}
+
+/// The iterator of the frames in reverse order.
+///
+/// See [`FileReader::reverse_iter`].
+pub struct ReverseFileIter {
+    /// The segments not iterated yet in the written order,
+    /// with the count of the leading frames to skip.
+    pending: Vec<(PathBuf, usize)>,
+
+    /// The segment being iterated.
+    current: Option<SegmentFrames>,
+}
+
+impl ReverseFileIter {
+    /// Read the previous frame.
+    ///
+    /// It returns `Ok(None)` when all the segments are iterated.
+    pub fn next_frame(&mut self) -> ReadResult<Option<Vec<u8>>> {
+        loop {
+            if let Some(segment) = &mut self.current {
+                if let Some(frame) = segment.next_back()? {
+                    return Ok(Some(frame));
+                }
+            }
+
+            let (path, skip) = match self.pending.pop() {
+                Some(pending) => pending,
+                None => return Ok(None),
+            };
+
+            tracing::debug!("Indexing the segment {path}", path = path.display());
+            self.current = Some(SegmentFrames::index(&path, skip)?);
+        }
+    }
+}
+
+/// The infallible iterator of the frames in reverse order.
+///
+/// Any error is logged and considered as the end of the file.
+/// Use [`ReverseFileIter::next_frame`] to handle the errors.
+impl Iterator for ReverseFileIter {
+    type Item = Vec<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_frame() {
+            Ok(frame) => frame,
+            Err(e) => {
+                tracing::error!("Failed to read the frame: {e}. Returning None.");
+                None
+            }
+        }
+    }
+}
+
+/// The frames of a segment to read in reverse order.
+enum SegmentFrames {
+    /// The start offsets of the frames in an uncompressed file.
+    Indexed {
+        file: File,
+        length_width: LengthWidth,
+        offsets: Vec<u64>,
+    },
+
+    /// The frames of a compressed file, which can't be seeked.
+    Buffered(Vec<Vec<u8>>),
+}
+
+impl SegmentFrames {
+    /// Index the frames of the file at `path`, except the first `skip` frames.
+    fn index(path: &Path, skip: usize) -> ReadResult<Self> {
+        let mut file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+
+        // The file header is longer than the magic header of the compression.
+        let mut header = [0u8; HEADER_LEN];
+        let header_len = read_full(&mut file, &mut header)?;
+        let header = &header[..header_len];
+
+        if is_compressed(header) {
+            let (header, decompressed) = open_file(path)?;
+            let mut reader = FileReader {
+                file: decompressed,
+                path: path.to_path_buf(),
+                frames_read: 0,
+                length_width: header.length_width,
+                remaining_segments: VecDeque::new(),
+                peeked: None,
+            };
+
+            let mut frames = Vec::new();
+            while let Some(frame) = read_untruncated(reader.read_frame())? {
+                frames.push(frame);
+            }
+            frames.drain(..skip.min(frames.len()));
+
+            return Ok(Self::Buffered(frames));
+        }
+
+        let (length_width, mut offset) = match FileHeader::parse(header)? {
+            Some(header) => (header.length_width, HEADER_LEN as u64),
+            None => (FileHeader::legacy().length_width, 0),
+        };
+        let width = length_width.bytes();
+
+        // Read the length sections only, and skip the data.
+        let mut offsets = Vec::new();
+        let mut data_len_section = [0u8; 4];
+        loop {
+            file.seek(SeekFrom::Start(offset))?;
+            if read_full(&mut file, &mut data_len_section[..width])? < width {
+                break;
+            }
+
+            let frame_len = (width + length_width.decode(&data_len_section)) as u64;
+            if offset + frame_len > file_len {
+                tracing::warn!("Skipping the truncated frame at {offset}.");
+                break;
+            }
+
+            offsets.push(offset);
+            offset += frame_len;
+        }
+        offsets.drain(..skip.min(offsets.len()));
+
+        Ok(Self::Indexed {
+            file,
+            length_width,
+            offsets,
+        })
+    }
+
+    /// Read the last frame not read yet.
+    fn next_back(&mut self) -> ReadResult<Option<Vec<u8>>> {
+        match self {
+            Self::Indexed {
+                file,
+                length_width,
+                offsets,
+            } => {
+                let offset = match offsets.pop() {
+                    Some(offset) => offset,
+                    None => return Ok(None),
+                };
+
+                let width = length_width.bytes();
+                let mut data_len_section = [0u8; 4];
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut data_len_section[..width])?;
+
+                let mut data = vec![0u8; length_width.decode(&data_len_section)];
+                file.read_exact(&mut data)?;
+
+                Ok(Some(data))
+            }
+            Self::Buffered(frames) => Ok(frames.pop()),
+        }
+    }
+}
+
+/// Consider a truncated trailing frame as the end of the file.
+fn read_untruncated(frame: ReadResult<Option<Vec<u8>>>) -> ReadResult<Option<Vec<u8>>> {
+    match frame {
+        Err(ReadError::TruncatedFrame { expected, got }) => {
+            tracing::warn!("Skipping the truncated frame: expected {expected} bytes, got {got}.");
+            Ok(None)
+        }
+        frame => frame,
     }
 }
 
+/// Open a file, decompress it if needed, and read its header.
+/// Check if `buffer` may be the beginning of a header being written,
+/// which is the [`FileHeader`] or the magic header of the compression.
+fn is_partial_header(buffer: &[u8]) -> bool {
+    let is_prefix_of = |magic: &[u8]| magic.starts_with(&buffer[..buffer.len().min(magic.len())]);
+
+    buffer.len() < HEADER_LEN && (is_prefix_of(&MAGIC[..]) || is_prefix_of(&MAGIC_PREFIX[..]))
+}
+
+fn open_file(path: impl AsRef<Path>) -> std::io::Result<(FileHeader, Box<dyn Read + Send>)> {
+    read_header(decompressed_reader(File::open(path)?)?)
+}
+
+/// Read until `buf` is full or the reader is ended.
+///
+/// Returns the number of bytes read.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(filled)
+}
+
 // http utp utp:quic
+/// The infallible iterator of the frames.
+///
+/// Any error is logged and considered as the end of the file.
+/// Use [`FileReader::next_frame`] to handle the errors.
 impl Iterator for FileReader {
     type Item = Vec<u8>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut data_len_section = [0u8; 2];
-        if let Ok(()) = self.file.read_exact(&mut data_len_section) {
-            let data_len = u16::from_be_bytes(data_len_section) as usize;
-            let mut data = vec![0u8; data_len];
-
-            if let Err(e) = self.file.read_exact(&mut data) {
-                tracing::error!("Failed to read the complete data: {e}. Returning None.");
+        match self.next_frame() {
+            Ok(frame) => frame,
+            Err(e) => {
+                tracing::error!("Failed to read the frame: {e}. Returning None.");
                 None
-            } else {
-                Some(data)
+            }
+        }
+    }
+}
+
+/// The asynchronous reader of the files written by
+/// [`DataWriter`](super::writer::DataWriter), for tokio-based consumers.
+///
+/// It has the same frame semantics as [`FileReader`], including
+/// chaining the rotated segments. With [`AsyncFileReader::tail`],
+/// it can also follow a file that is still being appended to,
+/// like `tail -f`.
+///
+/// Note that compressed files are not supported yet.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use futures::StreamExt;
+/// use wmjtyd_libstock::file::reader::AsyncFileReader;
+///
+/// # async fn example() -> std::io::Result<()> {
+/// let reader = AsyncFileReader::new("test".to_string(), 0)
+///     .await?
+///     .tail(Duration::from_millis(100));
+/// let mut frames = Box::pin(reader.into_stream());
+///
+/// while let Some(frame) = frames.next().await {
+///     println!("{:?}", frame);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncFileReader {
+    base: PathBuf,
+    date: NaiveDate,
+    filename: String,
+
+    file: tokio::fs::File,
+    /// The index of `file` in the existing paths of `filename`.
+    segment_index: usize,
+
+    /// The width of the length section of the frames in `file`.
+    length_width: LengthWidth,
+
+    /// Whether the header of `file` may be still being written,
+    /// see [`check_header`](Self::check_header).
+    header_pending: bool,
+
+    /// The bytes read from `file` but not consumed yet.
+    buffer: Vec<u8>,
+
+    /// The interval to poll for the growth of the file.
+    ///
+    /// `None` means we don't tail the file.
+    tail_interval: Option<std::time::Duration>,
+}
+
+impl AsyncFileReader {
+    pub async fn new(filename: String, day: i64) -> std::io::Result<AsyncFileReader> {
+        let (base, date) = (default_base(), get_date_of_day(day));
+
+        let path = existing_paths(&base, &filename, date)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| ident_path(&base, &filename, date));
+        tracing::info!(
+            "Creating an async reader to read {path}",
+            path = path.display()
+        );
+
+        let mut reader = AsyncFileReader {
+            file: tokio::fs::File::open(path).await?,
+            base,
+            date,
+            filename,
+            segment_index: 0,
+            length_width: LengthWidth::U16,
+            header_pending: false,
+            buffer: Vec::new(),
+            tail_interval: None,
+        };
+        reader.check_header().await?;
+
+        Ok(reader)
+    }
+
+    /// Follow the file when it is ended, and poll
+    /// for the growth of the file every `interval`.
+    ///
+    /// In this mode, the reader never returns `Ok(None)`.
+    pub fn tail(mut self, interval: std::time::Duration) -> Self {
+        self.tail_interval = Some(interval);
+        self
+    }
+
+    /// Read the next frame.
+    ///
+    /// See [`FileReader::next_frame`] for more information.
+    pub async fn next_frame(&mut self) -> ReadResult<Option<Vec<u8>>> {
+        loop {
+            if self.header_pending {
+                self.check_header().await?;
+            }
+
+            if let Some(frame) = self.take_frame() {
+                return Ok(Some(frame));
+            }
+
+            // Read more bytes to complete the frame.
+            if self.fill_buffer().await? > 0 {
+                continue;
+            }
+
+            // The current file is ended.
+            if self.buffer.is_empty() && self.next_segment().await? {
+                continue;
+            }
+
+            match self.tail_interval {
+                Some(interval) => tokio::time::sleep(interval).await,
+                None if self.buffer.is_empty() => return Ok(None),
+                None => return Err(self.truncated_error()),
+            }
+        }
+    }
+
+    /// Convert this reader to a [`Stream`] of frames.
+    ///
+    /// The stream ends after yielding an error.
+    pub fn into_stream(self) -> impl Stream<Item = ReadResult<Vec<u8>>> {
+        futures::stream::unfold(Some(self), |reader| async move {
+            let mut reader = reader?;
+
+            match reader.next_frame().await {
+                Ok(Some(frame)) => Some((Ok(frame), Some(reader))),
+                Ok(None) => None,
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+
+    /// Take a complete frame from the buffer.
+    fn take_frame(&mut self) -> Option<Vec<u8>> {
+        let width = self.length_width.bytes();
+        if self.header_pending || self.buffer.len() < width {
+            return None;
+        }
+
+        let data_len = self.length_width.decode(&self.buffer);
+        if self.buffer.len() < width + data_len {
+            return None;
+        }
+
+        let frame = self.buffer[width..width + data_len].to_vec();
+        self.buffer.drain(..width + data_len);
+
+        Some(frame)
+    }
+
+    /// Read the available bytes into the buffer.
+    ///
+    /// Returns the number of bytes read.
+    async fn fill_buffer(&mut self) -> std::io::Result<usize> {
+        let mut chunk = [0u8; 8192];
+        let len = self.file.read(&mut chunk).await?;
+        self.buffer.extend_from_slice(&chunk[..len]);
+
+        Ok(len)
+    }
+
+    /// Check the header of a newly-opened file,
+    /// and consume the [`FileHeader`] if any.
+    ///
+    /// A file being written may end in the middle of its header, so the
+    /// header is pending until [`HEADER_LEN`] bytes are read, or the bytes
+    /// read can't be the beginning of a header. [`next_frame`](Self::next_frame)
+    /// checks the pending header again before taking a frame.
+    async fn check_header(&mut self) -> std::io::Result<()> {
+        // The file header is longer than the magic header of the compression.
+        while self.buffer.len() < HEADER_LEN && self.fill_buffer().await? > 0 {}
+
+        self.header_pending = is_partial_header(&self.buffer);
+        if self.header_pending {
+            return Ok(());
+        }
+
+        if is_compressed(&self.buffer) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "AsyncFileReader does not support compressed files",
+            ));
+        }
+
+        self.length_width = match FileHeader::parse(&self.buffer)? {
+            Some(header) => {
+                self.buffer.drain(..HEADER_LEN);
+                header.length_width
+            }
+            None => FileHeader::legacy().length_width,
+        };
+
+        Ok(())
+    }
+
+    /// Switch to the next segment, if any.
+    async fn next_segment(&mut self) -> ReadResult<bool> {
+        // Rescan the paths, as new segments may be created when tailing.
+        let path = match existing_paths(&self.base, &self.filename, self.date)
+            .into_iter()
+            .nth(self.segment_index + 1)
+        {
+            Some(path) => path,
+            None => return Ok(false),
+        };
+
+        tracing::debug!("Switching to the segment {path}", path = path.display());
+        self.file = tokio::fs::File::open(path).await?;
+        self.segment_index += 1;
+        self.check_header().await?;
+
+        Ok(true)
+    }
+
+    /// Construct the [`ReadError::TruncatedFrame`] of the buffer.
+    fn truncated_error(&self) -> ReadError {
+        let width = self.length_width.bytes();
+
+        if self.buffer.len() < width {
+            ReadError::TruncatedFrame {
+                expected: width,
+                got: self.buffer.len(),
             }
         } else {
-            None
+            ReadError::TruncatedFrame {
+                expected: self.length_width.decode(&self.buffer),
+                got: self.buffer.len() - width,
+            }
         }
     }
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReadError {
+    /// The file ended in the middle of a frame.
+    ///
+    /// `expected` is the declared length of the section
+    /// being read, and `got` is the bytes actually read.
+    #[error("truncated frame: expected {expected} bytes, got {got} bytes")]
+    TruncatedFrame { expected: usize, got: usize },
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// The frame can't be deserialized as the requested structure.
+    #[error("failed to deserialize the frame: {0}")]
+    Deserialize(#[source] DeserializeError),
+}
+
+pub type ReadResult<T> = Result<T, ReadError>;
+
+/// The error of deserializing a frame, see [`ReadError::Deserialize`].
+pub type DeserializeError = Box<dyn std::error::Error + Send + Sync>;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::{AsyncFileReader, FileReader, ReadError};
+    use crate::data::serializer::StructDeserializer;
+    use crate::file::datadir::{default_base, ident_path};
+    use crate::file::timestamp::get_date;
+
+    /// Write `content` to the file of `filename` directly.
+    fn prepare_file(filename: &str, content: &[u8]) {
+        let path = ident_path(&default_base(), filename, get_date());
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_clean_end() {
+        let filename = uuid::Uuid::new_v4().to_string();
+        prepare_file(&filename, b"\x00\x03abc\x00\x00");
+
+        let mut reader = FileReader::new(filename, 0).unwrap();
+        assert_eq!(reader.next_frame().unwrap().unwrap(), b"abc");
+        assert_eq!(reader.next_frame().unwrap().unwrap(), b"");
+        assert!(reader.next_frame().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_truncated_data() {
+        let filename = uuid::Uuid::new_v4().to_string();
+        prepare_file(&filename, b"\x00\x03abc\x00\x05xy");
+
+        let mut reader = FileReader::new(filename.clone(), 0).unwrap();
+        assert_eq!(reader.next_frame().unwrap().unwrap(), b"abc");
+        assert!(matches!(
+            reader.next_frame(),
+            Err(ReadError::TruncatedFrame {
+                expected: 5,
+                got: 2
+            })
+        ));
+
+        // The infallible iterator stops at the truncated frame.
+        let reader = FileReader::new(filename, 0).unwrap();
+        assert_eq!(reader.collect::<Vec<_>>(), vec![b"abc".to_vec()]);
+    }
+
+    #[test]
+    fn test_truncated_length() {
+        let filename = uuid::Uuid::new_v4().to_string();
+        prepare_file(&filename, b"\x00\x03abc\x00");
+
+        let mut reader = FileReader::new(filename, 0).unwrap();
+        assert_eq!(reader.next_frame().unwrap().unwrap(), b"abc");
+        assert!(matches!(
+            reader.next_frame(),
+            Err(ReadError::TruncatedFrame {
+                expected: 2,
+                got: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_reverse_iter() {
+        let filename = uuid::Uuid::new_v4().to_string();
+        prepare_file(&filename, b"\x00\x03abc\x00\x00\x00\x01d\x00\x05xy");
+
+        // The truncated trailing frame is skipped.
+        let reader = FileReader::new(filename.clone(), 0).unwrap();
+        assert_eq!(
+            reader.reverse_iter().collect::<Vec<_>>(),
+            [b"d".to_vec(), b"".to_vec(), b"abc".to_vec()]
+        );
+
+        // The frames already read are not returned.
+        let mut reader = FileReader::new(filename, 0).unwrap();
+        assert_eq!(reader.next_frame().unwrap().unwrap(), b"abc");
+        assert_eq!(
+            reader.reverse_iter().collect::<Vec<_>>(),
+            [b"d".to_vec(), b"".to_vec()]
+        );
+    }
+
+    #[test]
+    fn test_peek() {
+        let filename = uuid::Uuid::new_v4().to_string();
+        prepare_file(&filename, b"\x00\x03abc\x00\x01d");
+
+        let mut reader = FileReader::new(filename, 0).unwrap();
+        assert_eq!(reader.peek().unwrap(), Some(&b"abc"[..]));
+        assert_eq!(reader.peek().unwrap(), Some(&b"abc"[..]));
+        assert_eq!(reader.next().unwrap(), b"abc");
+
+        assert_eq!(reader.peek().unwrap(), Some(&b"d"[..]));
+        assert_eq!(reader.next().unwrap(), b"d");
+        assert_eq!(reader.peek().unwrap(), None);
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn test_reverse_iter_after_peek() {
+        let filename = uuid::Uuid::new_v4().to_string();
+        prepare_file(&filename, b"\x00\x03abc\x00\x01d");
+
+        let mut reader = FileReader::new(filename, 0).unwrap();
+        assert_eq!(reader.peek().unwrap(), Some(&b"abc"[..]));
+        assert_eq!(
+            reader.reverse_iter().collect::<Vec<_>>(),
+            [b"d".to_vec(), b"abc".to_vec()]
+        );
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    struct Pair(u8, u8);
+
+    impl StructDeserializer for Pair {
+        type Err = std::io::Error;
+
+        fn deserialize(reader: &mut impl Read) -> Result<Self, Self::Err> {
+            let mut buf = [0; 2];
+            reader.read_exact(&mut buf)?;
+
+            Ok(Pair(buf[0], buf[1]))
+        }
+    }
+
+    #[test]
+    fn test_read_typed() {
+        let filename = uuid::Uuid::new_v4().to_string();
+        prepare_file(&filename, b"\x00\x02ab\x00\x01c\x00\x02de");
+
+        let mut reader = FileReader::new(filename.clone(), 0).unwrap();
+        assert_eq!(reader.read_typed::<Pair>().unwrap(), Some(Pair(b'a', b'b')));
+        assert!(matches!(reader.read_typed::<Pair>(), Err(ReadError::Deserialize(_))));
+        assert_eq!(reader.read_typed::<Pair>().unwrap(), Some(Pair(b'd', b'e')));
+        assert_eq!(reader.read_typed::<Pair>().unwrap(), None);
+
+        // The iteration continues after the invalid frame.
+        let reader = FileReader::new(filename, 0).unwrap();
+        let pairs = reader.typed_iter::<Pair>().collect::<Vec<_>>();
+        assert_eq!(pairs.len(), 3);
+        assert_eq!(pairs[0].as_ref().unwrap(), &Pair(b'a', b'b'));
+        assert!(matches!(pairs[1], Err(ReadError::Deserialize(_))));
+        assert_eq!(pairs[2].as_ref().unwrap(), &Pair(b'd', b'e'));
+    }
+
+    #[tokio::test]
+    async fn test_async_tail_partial_header() {
+        use std::io::Write;
+        use std::time::Duration;
+
+        use crate::file::format::{FileHeader, LengthWidth};
+
+        // The writer has only written a part of the header so far.
+        let filename = uuid::Uuid::new_v4().to_string();
+        let header = FileHeader::new(LengthWidth::U32).to_bytes();
+        prepare_file(&filename, &header[..3]);
+
+        let mut reader = AsyncFileReader::new(filename.clone(), 0)
+            .await
+            .unwrap()
+            .tail(Duration::from_millis(10));
+
+        let path = ident_path(&default_base(), &filename, get_date());
+        let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(&header[3..]).unwrap();
+        file.write_all(b"\x00\x00\x00\x03abc").unwrap();
+
+        let frame = tokio::time::timeout(Duration::from_secs(5), reader.next_frame())
+            .await
+            .expect("reader timed out");
+        assert_eq!(frame.unwrap().unwrap(), b"abc");
+    }
+
+    #[tokio::test]
+    async fn test_async_truncated_data() {
+        let filename = uuid::Uuid::new_v4().to_string();
+        prepare_file(&filename, b"\x00\x03abc\x00\x05xy");
+
+        let mut reader = AsyncFileReader::new(filename, 0).await.unwrap();
+        assert_eq!(reader.next_frame().await.unwrap().unwrap(), b"abc");
+        assert!(matches!(
+            reader.next_frame().await,
+            Err(ReadError::TruncatedFrame {
+                expected: 5,
+                got: 2
+            })
+        ));
+    }
+}
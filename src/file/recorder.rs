@@ -0,0 +1,359 @@
+//! The recorder persisting the messages of a subscriber to files.
+//!
+//! [`Recorder`] wires an [`AsyncSubscriber`] to a [`DataWriter`]:
+//! it matches the topic prefix of each message, optionally checks
+//! the payload by parsing it as a structure, and writes the payload
+//! (without the topic) to the file of the topic. The recorded frames
+//! can be read back with [`FileReader`](super::reader::FileReader).
+//!
+//! Like [`SubscriptionRouter`](crate::message::router::SubscriptionRouter),
+//! the recorder does not subscribe the topics for you, and the longest
+//! matching prefix wins. The messages matching no topic are dropped.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "crypto")]
+//! # #[tokio::main]
+//! # async fn main() {
+//! use wmjtyd_libstock::data::bbo::BboStructure;
+//! use wmjtyd_libstock::file::recorder::{validate_as, Recorder};
+//! use wmjtyd_libstock::file::writer::DataWriter;
+//! use wmjtyd_libstock::message::memory::MemorySubscriber;
+//! use wmjtyd_libstock::message::traits::{Connect, Subscribe};
+//!
+//! let mut subscriber = MemorySubscriber::new().expect("failed to create subscriber");
+//! subscriber.connect("inproc-mem://bbo").expect("failed to connect");
+//! subscriber.subscribe(b"bbo ").expect("failed to subscribe");
+//!
+//! // Write the messages of `bbo ` to the file `bbo`.
+//! let mut recorder = Recorder::new(subscriber, DataWriter::new(), |topic| {
+//!     String::from_utf8_lossy(topic).trim().to_string()
+//! });
+//! recorder.add_topic(b"bbo ", Some(validate_as::<BboStructure>));
+//!
+//! let handle = recorder.start().await.expect("failed to start recorder");
+//!
+//! // Stop it somewhere else.
+//! recorder.stop();
+//! handle.await.unwrap().expect("subscriber failed");
+//! # }
+//! # #[cfg(not(feature = "crypto"))]
+//! # fn main() {}
+//! ```
+
+use std::collections::HashMap;
+
+use tokio::task::JoinHandle;
+
+use super::writer::{DataEntry, DataWriter, WriteError, WriteResult};
+use crate::data::serializer::StructDeserializer;
+use crate::flag::{AsyncFlag, AtomicCounter};
+use crate::message::traits::{AsyncSubscriber, StreamExt};
+
+/// The function checking if a payload is valid.
+///
+/// It returns the reason if the payload is invalid.
+pub type Validator = fn(&[u8]) -> Result<(), String>;
+
+/// Check if a payload can be deserialized as `T`.
+///
+/// Use it as the [`Validator`] of a topic, for example
+/// `validate_as::<BboStructure>`.
+pub fn validate_as<T>(payload: &[u8]) -> Result<(), String>
+where
+    T: StructDeserializer,
+    T::Err: std::fmt::Display,
+{
+    T::deserialize_from_bytes(payload)
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// The statistics of a topic of a [`Recorder`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TopicStats {
+    /// The count of the messages received.
+    pub received: u64,
+    /// The count of the messages sent to the writer.
+    pub recorded: u64,
+    /// The count of the messages rejected by the validator.
+    pub invalid: u64,
+}
+
+/// The counters of a topic, shared between a [`Recorder`] and its task.
+#[derive(Clone, Debug, Default)]
+struct TopicCounters {
+    received: AtomicCounter,
+    recorded: AtomicCounter,
+    invalid: AtomicCounter,
+}
+
+#[derive(Clone, Debug)]
+struct Topic {
+    prefix: Vec<u8>,
+    filename: String,
+    validator: Option<Validator>,
+    counters: TopicCounters,
+}
+
+/// Find the topic with the longest prefix matching `message`.
+fn find_topic<'a>(topics: &'a [Topic], message: &[u8]) -> Option<&'a Topic> {
+    topics
+        .iter()
+        .filter(|topic| message.starts_with(&topic.prefix))
+        .max_by_key(|topic| topic.prefix.len())
+}
+
+/// The recorder writing the messages of a subscriber to files.
+///
+/// See the [module documentation](self) for more information.
+pub struct Recorder<S> {
+    subscriber: Option<S>,
+    writer: Option<DataWriter>,
+    filename_of: Box<dyn Fn(&[u8]) -> String + Send + Sync>,
+    topics: Vec<Topic>,
+
+    /// Set to stop the task.
+    stopping: AsyncFlag,
+}
+
+impl<S> Recorder<S>
+where
+    S: AsyncSubscriber + Send + 'static,
+    S::Err: Send,
+{
+    /// Create a recorder writing the messages of `subscriber` with `writer`.
+    ///
+    /// `filename_of` maps a topic to the filename to write as.
+    pub fn new(
+        subscriber: S,
+        writer: DataWriter,
+        filename_of: impl Fn(&[u8]) -> String + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            subscriber: Some(subscriber),
+            writer: Some(writer),
+            filename_of: Box::new(filename_of),
+            topics: Vec::new(),
+            stopping: AsyncFlag::new(),
+        }
+    }
+
+    /// Record the messages starting with `prefix`.
+    ///
+    /// If `validator` is specified, the payloads it rejects are not written,
+    /// but reported to the failure channel of the writer with
+    /// [`WriteError::ValidationFailed`]. See
+    /// [`DataWriterConfig::failure_channel`](super::writer::DataWriterConfig::failure_channel).
+    ///
+    /// The topics added after [`start`](Self::start) take no effect.
+    pub fn add_topic(&mut self, prefix: &[u8], validator: Option<Validator>) {
+        let filename = (self.filename_of)(prefix);
+        let topic = Topic {
+            prefix: prefix.to_vec(),
+            filename,
+            validator,
+            counters: TopicCounters::default(),
+        };
+
+        match self.topics.iter_mut().find(|t| t.prefix == prefix) {
+            Some(existing) => *existing = topic,
+            None => self.topics.push(topic),
+        }
+    }
+
+    /// Get the statistics of each topic.
+    pub fn stats(&self) -> HashMap<Vec<u8>, TopicStats> {
+        self.topics
+            .iter()
+            .map(|topic| {
+                let stats = TopicStats {
+                    received: topic.counters.received.get(),
+                    recorded: topic.counters.recorded.get(),
+                    invalid: topic.counters.invalid.get(),
+                };
+
+                (topic.prefix.clone(), stats)
+            })
+            .collect()
+    }
+
+    /// Has the task been started and not been stopped?
+    pub fn is_running(&self) -> bool {
+        self.subscriber.is_none() && !self.stopping.get()
+    }
+
+    /// Spawn the task receiving and recording the messages.
+    ///
+    /// The task stops when [`stop`](Self::stop) is called or the subscriber
+    /// ends, and then it stops the writer after the pending entries are
+    /// written. It returns the first error of the subscriber, if any.
+    ///
+    /// # Panics
+    ///
+    /// It panics if the recorder has been started.
+    pub async fn start(&mut self) -> WriteResult<JoinHandle<Result<(), S::Err>>> {
+        let subscriber = self.subscriber.take().expect("the recorder has been started");
+        let mut writer = self.writer.take().expect("the recorder has been started");
+        let writer_handle = writer.start().await?;
+
+        let topics = self.topics.clone();
+        let stopping = self.stopping.clone();
+
+        Ok(tokio::task::spawn(async move {
+            let stop_signal = Box::pin(async move { stopping.wait_for(true).await });
+            let mut messages = Box::pin(subscriber.take_until(stop_signal));
+
+            let mut result = Ok(());
+            while let Some(message) = messages.next().await {
+                match message {
+                    Ok(message) => record(&mut writer, &topics, &message),
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    }
+                }
+            }
+
+            if let Err(e) = writer.stop() {
+                tracing::error!("Failed to stop the writer: {e}");
+            } else if let Err(e) = writer_handle.await {
+                tracing::error!("Failed to wait for the writer to stop: {e}");
+            }
+
+            result
+        }))
+    }
+
+    /// Stop the task.
+    pub fn stop(&self) {
+        self.stopping.set();
+    }
+}
+
+/// Write `message` to the file of its topic.
+fn record(writer: &mut DataWriter, topics: &[Topic], message: &[u8]) {
+    let topic = match find_topic(topics, message) {
+        Some(topic) => topic,
+        None => {
+            tracing::trace!("No topic for the message; dropped.");
+            return;
+        }
+    };
+    topic.counters.received.increment();
+
+    let entry = DataEntry {
+        filename: topic.filename.clone(),
+        data: message[topic.prefix.len()..].to_vec(),
+    };
+
+    if let Some(validator) = topic.validator {
+        if let Err(reason) = validator(&entry.data) {
+            tracing::warn!("Invalid message of {entry}: {reason}");
+            topic.counters.invalid.increment();
+            writer.report_failure(entry, WriteError::ValidationFailed(reason));
+            return;
+        }
+    }
+
+    match writer.add(entry) {
+        Ok(()) => {
+            topic.counters.recorded.increment();
+        }
+        Err(e) => tracing::error!("Failed to add the message to the writer: {e}"),
+    }
+}
+
+#[cfg(all(test, feature = "crypto"))]
+mod tests {
+    use std::time::Duration;
+
+    use super::{validate_as, Recorder, TopicStats};
+    use crate::data::bbo::{BboMsg, BboStructure};
+    use crate::data::serializer::{StructDeserializer, StructSerializer};
+    use crate::file::reader::FileReader;
+    use crate::file::writer::{DataWriter, DataWriterConfig, WriteError};
+    use crate::message::memory::{MemoryPublisher, MemorySubscriber};
+    use crate::message::traits::{Bind, Connect, Subscribe, Write};
+
+    fn construct_bbo(timestamp: i64) -> BboStructure {
+        BboStructure::from_msg(&BboMsg {
+            exchange: "binance".into(),
+            market_type: crypto_market_type::MarketType::Spot,
+            symbol: "BTCUSDT".into(),
+            pair: "BTC/USDT".into(),
+            msg_type: crypto_msg_type::MessageType::BBO,
+            timestamp,
+            json: "".into(),
+            bid_price: 1.0,
+            bid_quantity_base: 2.0,
+            bid_quantity_quote: 2.0,
+            bid_quantity_contract: None,
+            ask_price: 4.0,
+            ask_quantity_base: 5.0,
+            ask_quantity_quote: 20.0,
+            ask_quantity_contract: None,
+            id: None,
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_record_bbo() {
+        const URI: &str = "inproc-mem://libstock_recorder_bbo";
+
+        let filename = uuid::Uuid::new_v4().to_string();
+        let (failure_tx, failure_rx) = flume::unbounded();
+
+        let mut publisher = MemoryPublisher::new().unwrap();
+        publisher.bind(URI).unwrap();
+        let mut subscriber = MemorySubscriber::new().unwrap();
+        subscriber.connect(URI).unwrap();
+        subscriber.subscribe(b"").unwrap();
+
+        let writer = DataWriter::with_config(DataWriterConfig::new().failure_channel(failure_tx));
+        let mut recorder = Recorder::new(subscriber, writer, {
+            let filename = filename.clone();
+            move |_| filename.clone()
+        });
+        recorder.add_topic(b"bbo ", Some(validate_as::<BboStructure>));
+        let handle = recorder.start().await.unwrap();
+        assert!(recorder.is_running());
+
+        let expected = [construct_bbo(1659755147000), construct_bbo(1659755148000)];
+        for bbo in &expected {
+            let message = [&b"bbo "[..], &bbo.serialize_to_vec().unwrap()].concat();
+            publisher.write_all(&message).unwrap();
+        }
+        publisher.write_all(b"bbo garbage").unwrap();
+        publisher.write_all(b"trade ignored").unwrap();
+
+        // Wait for the recorder to receive all the messages.
+        while recorder.stats()[&b"bbo ".to_vec()].received < 3 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        recorder.stop();
+        handle.await.unwrap().unwrap();
+        assert!(!recorder.is_running());
+
+        assert_eq!(
+            recorder.stats()[&b"bbo ".to_vec()],
+            TopicStats {
+                received: 3,
+                recorded: 2,
+                invalid: 1,
+            }
+        );
+
+        let failed = failure_rx.try_recv().unwrap();
+        assert_eq!(failed.entry.data, b"garbage");
+        assert!(matches!(failed.error, WriteError::ValidationFailed(_)));
+
+        let decoded = FileReader::new(filename, 0)
+            .unwrap()
+            .map(|frame| BboStructure::deserialize_from_bytes(&frame).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(decoded, expected);
+    }
+}
@@ -1,6 +1,6 @@
 //! The timestamp utils for [`wmjtyd_libstock::data`](super).
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime};
 
 /// Get the timestamp whose format is `%Y%m%d` of current time.
 pub fn get_timestamp() -> String {
@@ -9,13 +9,29 @@ pub fn get_timestamp() -> String {
     fmt_timestamp(&local_time)
 }
 
+/// Get the local date of current time.
+///
+/// It is the date of the directory [`DataWriter`](super::writer::DataWriter)
+/// writes to. See [`datadir`](super::datadir).
+pub fn get_date() -> NaiveDate {
+    Local::now().naive_local().date()
+}
+
+/// Get the local date and time of current time.
+///
+/// It is used to place the files in the partitioned directories.
+/// See [`PartitionGranularity`](super::datadir::PartitionGranularity).
+pub fn get_datetime() -> NaiveDateTime {
+    Local::now().naive_local()
+}
+
 /// Format a timestamp to `%Y%m%d`.
 ///
 /// # Example
 ///
 /// ```
 /// use wmjtyd_libstock::file::timestamp::fmt_timestamp;
-/// use chrono::{DateTime, Local};
+/// use chrono::{DateTime, Local, NaiveDate};
 ///
 /// let local_time = Local::now();
 ///
@@ -1,17 +1,31 @@
 //! The writer daemon to write data and place file automatically
 //! without worrying about managing the path.
 
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use flume::{Receiver, Sender};
+use flume::{Receiver, Sender, TrySendError};
 use tokio::fs::OpenOptions;
 use tokio::task::JoinHandle;
 use tracing::Instrument;
 use uuid::Uuid;
 
-use super::datadir::get_ident_path;
-use crate::file::timestamp::get_timestamp;
+use super::compression::{decompressed_reader, is_compressed, Compression, MAGIC_LEN};
+use super::datadir::{
+    default_base,
+    last_segment_in,
+    partition_directory,
+    path_in,
+    segment_path_in,
+    PartitionGranularity,
+};
+use super::format::{read_header, FileHeader, LengthWidth, HEADER_LEN, MAGIC};
+use crate::file::timestamp::get_datetime;
+use crate::flag::AtomicCounter;
 
 /// A owned data entry to send to a [`DataWriter`].
 ///
@@ -42,6 +56,251 @@ impl Display for DataEntry {
     }
 }
 
+/// The configuration of a [`DataWriter`].
+///
+/// # Example
+///
+/// ```
+/// use wmjtyd_libstock::file::writer::{DataWriter, DataWriterConfig};
+///
+/// // Start a new file once the current one reaches 64 MiB.
+/// let config = DataWriterConfig::new().max_file_bytes(64 * 1024 * 1024);
+/// let writer = DataWriter::with_config(config);
+/// ```
+#[derive(Clone, Default)]
+pub struct DataWriterConfig {
+    /// The maximum size of a file in bytes.
+    ///
+    /// `None` means we never rotate the file by size.
+    max_file_bytes: Option<u64>,
+
+    /// The compression algorithm of the files.
+    compression: Compression,
+
+    /// The width of the length section of each frame.
+    length_width: LengthWidth,
+
+    /// The channel to report the failed entries to.
+    failure_sender: Option<Sender<FailedEntry>>,
+
+    /// The maximum count of the entries waiting for the daemon.
+    ///
+    /// `None` means the queue is unbounded.
+    capacity: Option<usize>,
+
+    /// How the files of a date are partitioned into the subdirectories.
+    partition: PartitionGranularity,
+
+    /// Whether to sync each frame and recover the partial frames.
+    atomic_writes: bool,
+
+    /// The hook to call with the errors of the failed entries.
+    error_hook: Option<WriteErrorHook>,
+}
+
+/// The hook called with the error of each failed entry.
+///
+/// See [`DataWriterConfig::on_error`].
+pub type WriteErrorHook = Arc<dyn Fn(&WriteError) + Send + Sync>;
+
+impl DataWriterConfig {
+    /// Create a new [`DataWriterConfig`] with the default configuration.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rotate the file by size.
+    ///
+    /// Once writing a frame would make the file larger than `max_file_bytes`,
+    /// the writer starts a new file with a sequence suffix, for example
+    /// `test.0.csv`, `test.1.csv` and so on. A frame is never split
+    /// across two files.
+    pub fn max_file_bytes(mut self, max_file_bytes: u64) -> Self {
+        self.max_file_bytes = Some(max_file_bytes);
+        self
+    }
+
+    /// Compress the written frames with the specified [`Compression`].
+    ///
+    /// The writer never appends compressed frames to an existing
+    /// uncompressed file (or vice versa), or to a file compressed with
+    /// another algorithm; it starts a new segment, such as `test.0.csv`,
+    /// instead.
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Write the frames with the specified [`LengthWidth`].
+    ///
+    /// Use [`LengthWidth::U32`] if an entry may exceed 64 KiB.
+    /// The width is recorded in the [`FileHeader`], so the writer
+    /// never appends to an existing file of another width, including
+    /// the legacy files of [`LengthWidth::U16`]; it starts a new
+    /// segment, such as `test.0.csv`, instead.
+    pub fn length_width(mut self, length_width: LengthWidth) -> Self {
+        self.length_width = length_width;
+        self
+    }
+
+    /// Report the entries failed to write to `sender`,
+    /// so they can be retried or alerted.
+    ///
+    /// The daemon never waits for the channel: if a bounded
+    /// channel is full or disconnected, the report is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wmjtyd_libstock::file::writer::{DataWriter, DataWriterConfig};
+    ///
+    /// let (sender, receiver) = flume::unbounded();
+    /// let writer = DataWriter::with_config(DataWriterConfig::new().failure_channel(sender));
+    ///
+    /// // Retry or alert in another task.
+    /// for failed in receiver.try_iter() {
+    ///     eprintln!("failed to write {}: {}", failed.entry, failed.error);
+    /// }
+    /// ```
+    pub fn failure_channel(mut self, sender: Sender<FailedEntry>) -> Self {
+        self.failure_sender = Some(sender);
+        self
+    }
+
+    /// Bound the queue of the writer to `capacity` entries.
+    ///
+    /// Once the queue is full, [`DataWriter::add_blocking`] and
+    /// [`DataWriter::add_async`] wait for the daemon to catch up,
+    /// and [`DataWriter::try_add`] returns [`WriteError::QueueFull`].
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// Partition the files of a date into the subdirectories
+    /// of the specified [`PartitionGranularity`].
+    ///
+    /// For example, with [`PartitionGranularity::Hour`], the entries
+    /// written at 15:04 on 2023-01-01 are placed in `./record/20230101/15/`.
+    /// [`FileReader`](super::reader::FileReader) reads the files of a date
+    /// in all its [`partition_directories`](super::datadir::partition_directories).
+    pub fn partition_by(mut self, granularity: PartitionGranularity) -> Self {
+        self.partition = granularity;
+        self
+    }
+
+    /// Partition the files of a date into the hour subdirectories
+    /// if `enabled`, or place them directly in the date directories.
+    ///
+    /// See [`partition_by`](Self::partition_by) for more information.
+    pub fn partition_by_hour(self, enabled: bool) -> Self {
+        let granularity = if enabled {
+            PartitionGranularity::Hour
+        } else {
+            PartitionGranularity::Day
+        };
+
+        self.partition_by(granularity)
+    }
+
+    /// Write each entry atomically if `enabled`.
+    ///
+    /// The writer syncs each frame to the disk after appending it,
+    /// so a crash during writing can only leave a partial frame at
+    /// the end of a file. When the daemon opens a file for the first
+    /// time, it truncates such a partial frame, found by walking the
+    /// length sections of the frames, before appending to it.
+    ///
+    /// Note that the frames are still appended in place, rather than
+    /// written to a temporary file and renamed, so:
+    ///
+    /// - a reader reading the file concurrently, such as a tailing
+    ///   [`AsyncFileReader`](super::reader::AsyncFileReader), may still
+    ///   see a partial frame being written;
+    /// - a partial frame left by a crash is only truncated once a writer
+    ///   writes to the file again, and stays there until then;
+    /// - the partial frames in the compressed files are never truncated,
+    ///   as their frames can't be walked without decompressing them.
+    pub fn atomic_writes(mut self, enabled: bool) -> Self {
+        self.atomic_writes = enabled;
+        self
+    }
+
+    /// Call `hook` with the error whenever an entry fails to write,
+    /// in addition to logging it and reporting it to the
+    /// [`failure_channel`](Self::failure_channel).
+    ///
+    /// The hook is called in the daemon, so it should return quickly.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicU64, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// use wmjtyd_libstock::file::writer::{DataWriter, DataWriterConfig, WriteError};
+    ///
+    /// let errors = Arc::new(AtomicU64::new(0));
+    /// let config = DataWriterConfig::new().on_error(Arc::new({
+    ///     let errors = errors.clone();
+    ///     move |error: &WriteError| {
+    ///         errors.fetch_add(1, Ordering::Relaxed);
+    ///         eprintln!("failed to write: {error}");
+    ///     }
+    /// }));
+    /// let writer = DataWriter::with_config(config);
+    /// ```
+    pub fn on_error(mut self, hook: WriteErrorHook) -> Self {
+        self.error_hook = Some(hook);
+        self
+    }
+}
+
+impl std::fmt::Debug for DataWriterConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DataWriterConfig")
+            .field("max_file_bytes", &self.max_file_bytes)
+            .field("compression", &self.compression)
+            .field("length_width", &self.length_width)
+            .field("failure_sender", &self.failure_sender)
+            .field("capacity", &self.capacity)
+            .field("partition", &self.partition)
+            .field("atomic_writes", &self.atomic_writes)
+            .field("error_hook", &self.error_hook.is_some())
+            .finish()
+    }
+}
+
+/// An entry failed to write, reported to [`DataWriterConfig::failure_channel`].
+#[derive(Debug)]
+pub struct FailedEntry {
+    /// The entry failed to write.
+    pub entry: DataEntry,
+    /// The reason of the failure.
+    pub error: WriteError,
+}
+
+/// The statistics of a [`DataWriter`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DataWriterStats {
+    /// The count of the entries written successfully.
+    pub entries_written: u64,
+    /// The bytes of the written frames, excluding the file headers.
+    pub bytes_written: u64,
+    /// The count of the entries failed to write.
+    pub entries_failed: u64,
+    /// The count of the actions waiting for the daemon.
+    pub queue_depth: usize,
+}
+
+/// The counters shared between a [`DataWriter`] and its daemon.
+#[derive(Clone, Debug, Default)]
+struct WriterCounters {
+    entries_written: AtomicCounter,
+    bytes_written: AtomicCounter,
+    entries_failed: AtomicCounter,
+}
+
 /// The action to pass to the writer daemon channel.
 #[non_exhaustive]
 enum WriterAction {
@@ -65,8 +324,8 @@ enum WriterAction {
 /// writer.start();
 ///
 /// writer.add(DataEntry {
-///    // It will be saved to './record/test20190101.csv'
-///    // according to our definition in `super::datadir`.
+///    // It will be saved to './record/20190101/test.csv'
+///    // according to our definition in `wmjtyd_libstock::file::datadir`.
 ///    filename: "test".to_string(),
 ///
 ///    // `.to_vec()` is needed to write it asynchoronously.
@@ -75,6 +334,10 @@ enum WriterAction {
 /// ```
 pub struct DataWriter {
     writer_id: Uuid,
+    config: DataWriterConfig,
+
+    /// The counters updated by the daemon.
+    counters: WriterCounters,
 
     sender: Sender<WriterAction>,
     receiver: Receiver<WriterAction>,
@@ -86,6 +349,38 @@ impl DataWriter {
         DataWriter::default()
     }
 
+    /// Create a new [`DataWriter`] with the specified [`DataWriterConfig`].
+    pub fn with_config(config: DataWriterConfig) -> DataWriter {
+        let (sender, receiver) = match config.capacity {
+            Some(capacity) => flume::bounded(capacity),
+            None => flume::unbounded(),
+        };
+
+        DataWriter {
+            // Generate a writer ID for debugging.
+            writer_id: Uuid::new_v4(),
+            config,
+            counters: WriterCounters::default(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Create a new [`DataWriter`] whose queue holds at most `capacity` entries.
+    ///
+    /// See [`DataWriterConfig::capacity`] for more information.
+    pub fn with_capacity(capacity: usize) -> DataWriter {
+        DataWriter::with_config(DataWriterConfig::new().capacity(capacity))
+    }
+
+    /// Create a new [`DataWriter`] compressing the frames
+    /// with the specified [`Compression`].
+    ///
+    /// See [`DataWriterConfig::compression`] for more information.
+    pub fn with_compression(compression: Compression) -> DataWriter {
+        DataWriter::with_config(DataWriterConfig::new().compression(compression))
+    }
+
     /// Push a [`DataEntry`] to write.
     ///
     /// # Example
@@ -96,28 +391,92 @@ impl DataWriter {
     /// let mut writer = DataWriter::new();
     ///
     /// writer.add(DataEntry {
-    ///    // It will be saved to './record/test20190101.csv'
-    ///    // according to our definition in `super::datadir`.
+    ///    // It will be saved to './record/20190101/test.csv'
+    ///    // according to our definition in `wmjtyd_libstock::file::datadir`.
     ///    filename: "test".to_string(),
     ///
     ///    // `.to_vec()` is needed to write it asynchoronously.
     ///    data: b"OwO".to_vec(),
     /// });
     /// ```
+    ///
+    /// If the queue is bounded and full, it waits for space
+    /// like [`add_blocking`](Self::add_blocking).
     pub fn add(&mut self, data: DataEntry) -> WriteResult<()> {
+        self.add_blocking(data)
+    }
+
+    /// Push a [`DataEntry`] to write, waiting for space
+    /// if the queue is bounded and full.
+    pub fn add_blocking(&self, data: DataEntry) -> WriteResult<()> {
+        self.sender
+            .send(self.file_write_action(data))
+            .map_err(|_| WriteError::PushChannelFailed)
+    }
+
+    /// Push a [`DataEntry`] to write without waiting.
+    ///
+    /// It returns [`WriteError::QueueFull`] if the queue
+    /// is bounded and full.
+    pub fn try_add(&self, data: DataEntry) -> WriteResult<()> {
+        match self.sender.try_send(self.file_write_action(data)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => Err(WriteError::QueueFull),
+            Err(TrySendError::Disconnected(_)) => Err(WriteError::PushChannelFailed),
+        }
+    }
+
+    /// Push a [`DataEntry`] to write, waiting asynchronously
+    /// for space if the queue is bounded and full.
+    pub async fn add_async(&self, data: DataEntry) -> WriteResult<()> {
+        self.sender
+            .send_async(self.file_write_action(data))
+            .await
+            .map_err(|_| WriteError::PushChannelFailed)
+    }
+
+    /// Wrap `data` as the action to send to the daemon.
+    fn file_write_action(&self, data: DataEntry) -> WriterAction {
         tracing::info!(
             "Adding data {data} to writer {writer}",
             writer = self.writer_id
         );
 
-        self.sender
-            .send(WriterAction::FileWrite(data))
-            .map_err(|_| WriteError::PushChannelFailed)
+        WriterAction::FileWrite(data)
+    }
+
+    /// Get the count of the actions waiting for the daemon.
+    pub fn queue_depth(&self) -> usize {
+        self.sender.len()
+    }
+
+    /// Get the capacity of the queue.
+    ///
+    /// It returns `None` if the queue is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.sender.capacity()
+    }
+
+    /// Get the count of the entries written successfully.
+    pub fn entries_written(&self) -> u64 {
+        self.counters.entries_written.get()
+    }
+
+    /// Get the statistics of this writer.
+    pub fn stats(&self) -> DataWriterStats {
+        DataWriterStats {
+            entries_written: self.counters.entries_written.get(),
+            bytes_written: self.counters.bytes_written.get(),
+            entries_failed: self.counters.entries_failed.get(),
+            queue_depth: self.queue_depth(),
+        }
     }
 
     /// Spawn the writer daemon.
     pub async fn start(&self) -> WriteResult<JoinHandle<()>> {
         let receiver = self.receiver.clone();
+        let config = self.config.clone();
+        let counters = self.counters.clone();
 
         tracing::info!("Starting daemon…");
         let span = tracing::info_span!(
@@ -126,30 +485,34 @@ impl DataWriter {
         );
         Ok(tokio::task::spawn(
             async move {
+                let mut state = DaemonState::default();
+
                 loop {
-                    let task = async {
-                        let action = receiver
-                            .recv_async()
-                            .await
-                            .map_err(DaemonError::RecvActionFailed)?;
+                    let action = match receiver.recv_async().await {
+                        Ok(action) => action,
+                        Err(_) => {
+                            tracing::error!("Failed to receive on the closed channel.");
+                            break;
+                        }
+                    };
 
-                        Self::process_action(action).await
+                    let entry = match action {
+                        WriterAction::FileWrite(entry) => entry,
+                        WriterAction::Stop => {
+                            tracing::debug!("Daemon has received stop signal. Exiting.");
+                            break;
+                        }
                     };
 
-                    if let Err(e) = task.await {
-                        match e {
-                            DaemonError::StopDaemon => {
-                                tracing::trace!("Received the forwarded “StopDaemon” request.");
-                                break;
-                            }
-                            DaemonError::RecvActionFailed(flume::RecvError::Disconnected) => {
-                                tracing::error!("Failed to receive on the closed channel.");
-                                break;
-                            }
-                            _ => {
-                                tracing::error!("Error happened: {e}; skipping.");
-                                continue;
-                            }
+                    match Self::write_entry(&config, &mut state, &entry).await {
+                        Ok(written) => {
+                            counters.entries_written.increment();
+                            counters.bytes_written.add(written);
+                        }
+                        Err(error) => {
+                            tracing::error!("Failed to write {entry}: {error}; skipping.");
+                            counters.entries_failed.increment();
+                            report_failure(&config, FailedEntry { entry, error });
                         }
                     }
                 }
@@ -158,7 +521,17 @@ impl DataWriter {
         ))
     }
 
+    /// Report an entry rejected before writing, for example by
+    /// a validator, as if it failed to write.
+    pub(crate) fn report_failure(&self, entry: DataEntry, error: WriteError) {
+        self.counters.entries_failed.increment();
+        report_failure(&self.config, FailedEntry { entry, error });
+    }
+
     /// Stop the writer daemon.
+    ///
+    /// If the queue is bounded and full, it waits for space,
+    /// as the stop signal is queued after the pending entries.
     pub fn stop(&self) -> WriteResult<()> {
         tracing::info!("Stopping writer {writer}…", writer = self.writer_id);
 
@@ -167,40 +540,251 @@ impl DataWriter {
             .map_err(|_| WriteError::PushChannelFailed)
     }
 
-    async fn process_action(action: WriterAction) -> Result<(), DaemonError> {
-        match action {
-            WriterAction::FileWrite(DataEntry { filename, data }) => {
-                tracing::trace!("Received a data entry. Processing…");
+    /// Write `entry`, and return the bytes written.
+    async fn write_entry(
+        config: &DataWriterConfig,
+        state: &mut DaemonState,
+        entry: &DataEntry,
+    ) -> WriteResult<u64> {
+        let DataEntry { filename, data } = entry;
+        tracing::trace!("Received a data entry. Processing…");
+
+        let directory = partition_directory(&default_base(), get_datetime(), config.partition);
+
+        // Write file to the specified path.
+        tracing::debug!("Writing ”{filename}“, data_len: {len}…", len = data.len());
+        let frame = encode_frame(data, config.length_width, config.compression)?;
+        let path_to_write = state.resolve_path(config, &directory, filename, frame.len() as u64)?;
+
+        let written = async {
+            if config.atomic_writes {
+                state.recover(&path_to_write).await?;
+            }
 
-                let timestamp = get_timestamp();
+            write_content(&path_to_write, &frame, config).await
+        };
+        if let Err(e) = written.await {
+            state.forget_segment(&directory, filename);
+            return Err(e);
+        }
 
-                // Write file to the specified path.
-                tracing::debug!("Writing ”{filename}“, data_len: {len}…", len = data.len());
-                let path_to_write = get_ident_path(&timestamp, &filename);
+        Ok(frame.len() as u64)
+    }
+}
+
+/// Report the failed entry to [`DataWriterConfig::on_error`]
+/// and [`DataWriterConfig::failure_channel`], if any.
+fn report_failure(config: &DataWriterConfig, failed: FailedEntry) {
+    if let Some(hook) = &config.error_hook {
+        hook(&failed.error);
+    }
+
+    if let Some(sender) = &config.failure_sender {
+        if let Err(e) = sender.try_send(failed) {
+            tracing::warn!("Failed to report the failed entry: {e}");
+        }
+    }
+}
+
+impl Default for DataWriter {
+    fn default() -> Self {
+        Self::with_config(DataWriterConfig::default())
+    }
+}
+
+/// The states the writer daemon keeps across actions.
+#[derive(Default)]
+struct DaemonState {
+    /// The segment being written, keyed by the unrotated path.
+    segments: HashMap<PathBuf, Segment>,
+
+    /// The files whose partial frame has been truncated,
+    /// see [`DataWriterConfig::atomic_writes`].
+    recovered: HashSet<PathBuf>,
+}
+
+/// The segment being written, see [`DataWriterConfig::max_file_bytes`].
+struct Segment {
+    /// The sequence number of the segment,
+    /// or `None` for the unrotated file.
+    index: Option<u32>,
+    /// The size of the segment, including its header.
+    size: u64,
+}
+
+impl Segment {
+    /// Find the segment of `filename` in `directory` to continue writing.
+    ///
+    /// It continues from the last file written before – the last segment,
+    /// if there is any, or the unrotated file – unless the file is
+    /// written in another format, which we can't append to. In that
+    /// case, it starts a new segment, even if rotation is disabled.
+    fn find(config: &DataWriterConfig, directory: &Path, filename: &str) -> WriteResult<Self> {
+        let rotated = config.max_file_bytes.is_some()
+            || segment_path_in(directory, filename, 0).exists();
+        let mut segment = Segment {
+            index: rotated.then(|| last_segment_in(directory, filename)),
+            size: 0,
+        };
+
+        loop {
+            let path = segment.path(directory, filename);
+            if has_format(&path, config)? {
+                segment.size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+                return Ok(segment);
+            }
+
+            tracing::warn!(
+                "{path} is written in another format. Starting a new segment.",
+                path = path.display()
+            );
+            segment.index = Some(segment.index.map_or(0, |index| index + 1));
+        }
+    }
+
+    /// Get the path of this segment.
+    fn path(&self, directory: &Path, filename: &str) -> PathBuf {
+        match self.index {
+            Some(index) => segment_path_in(directory, filename, index),
+            None => path_in(directory, filename),
+        }
+    }
+}
 
-                write_content(path_to_write, data.as_slice()).await?;
+impl DaemonState {
+    /// Get the path to write a frame of `frame_size` bytes to.
+    ///
+    /// If rotation is enabled and the frame can't fit in
+    /// the current segment, we switch to the next one.
+    fn resolve_path(
+        &mut self,
+        config: &DataWriterConfig,
+        directory: &Path,
+        filename: &str,
+        frame_size: u64,
+    ) -> WriteResult<PathBuf> {
+        // A new segment starts with the header.
+        let header_len = encode_header(config)?.len() as u64;
+        let segment = match self.segments.entry(path_in(directory, filename)) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut segment = Segment::find(config, directory, filename)?;
+                segment.size = segment.size.max(header_len);
+
+                entry.insert(segment)
             }
-            WriterAction::Stop => {
-                tracing::debug!("Daemon has received stop signal. Exiting.");
-                return Err(DaemonError::StopDaemon);
+        };
+
+        // A segment without frames always accepts the frame, so a frame
+        // larger than `max_file_bytes` still gets written.
+        if let (Some(index), Some(max_file_bytes)) = (segment.index, config.max_file_bytes) {
+            if segment.size > header_len && segment.size + frame_size > max_file_bytes {
+                segment.index = Some(index + 1);
+                segment.size = header_len;
+                tracing::debug!("Rotating {filename} to the segment {}.", index + 1);
             }
         }
 
+        segment.size += frame_size;
+        Ok(segment.path(directory, filename))
+    }
+
+    /// Truncate the partial frame at the end of `path`, if it is
+    /// the first time to write to `path`.
+    async fn recover(&mut self, path: &Path) -> WriteResult<()> {
+        if self.recovered.contains(path) {
+            return Ok(());
+        }
+
+        truncate_torn_tail(path).await?;
+        self.recovered.insert(path.to_path_buf());
+
         Ok(())
     }
+
+    /// Forget the segment of `filename`, so its size is read
+    /// from the disk again, for example after a failed write.
+    fn forget_segment(&mut self, directory: &Path, filename: &str) {
+        self.segments.remove(&path_in(directory, filename));
+    }
 }
 
-impl Default for DataWriter {
-    fn default() -> Self {
-        let (sender, receiver) = flume::unbounded();
+/// Encode `data` to a frame – the length section and the data –
+/// and compress it if needed.
+fn encode_frame(
+    data: &[u8],
+    length_width: LengthWidth,
+    compression: Compression,
+) -> WriteResult<Vec<u8>> {
+    let len = data.len();
+    let mut frame = Vec::with_capacity(length_width.bytes() + len);
+
+    // First, the length.
+    frame.extend(
+        length_width
+            .encode(len)
+            .ok_or(WriteError::FrameTooLarge(len))?,
+    );
+    // Then, the data.
+    frame.extend(data);
+
+    compression
+        .compress(&frame)
+        .map_err(WriteError::CompressFailed)
+}
 
-        Self {
-            // Generate a writer ID for debugging.
-            writer_id: Uuid::new_v4(),
-            sender,
-            receiver,
-        }
+/// Encode the headers of a new file: the magic header of
+/// the compression, and the (compressed) [`FileHeader`].
+fn encode_header(config: &DataWriterConfig) -> WriteResult<Vec<u8>> {
+    let mut header = config.compression.header();
+    let file_header = FileHeader::new(config.length_width).to_bytes();
+
+    header.extend(
+        config
+            .compression
+            .compress(&file_header)
+            .map_err(WriteError::CompressFailed)?,
+    );
+
+    Ok(header)
+}
+
+/// Check if the file at `path` can be appended to with `config`, which
+/// means it is missing, empty, or of the same compression and length width.
+///
+/// The files whose header can't be read are never appended to.
+fn has_format(path: &Path, config: &DataWriterConfig) -> WriteResult<bool> {
+    use std::io::{Read, Seek};
+
+    let mut file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+        Err(e) => return Err(WriteError::FileOpenFailed(e)),
+    };
+    let file_len = file.metadata().map_err(WriteError::FileOpenFailed)?.len();
+    if file_len == 0 {
+        return Ok(true);
+    }
+
+    // The magic header is empty for the uncompressed files.
+    let mut magic = Vec::with_capacity(MAGIC_LEN);
+    file.by_ref()
+        .take(MAGIC_LEN as u64)
+        .read_to_end(&mut magic)
+        .map_err(WriteError::FileOpenFailed)?;
+    let compression = config.compression.header();
+    let same_compression = if compression.is_empty() {
+        !is_compressed(&magic)
+    } else {
+        magic == compression
+    };
+    if !same_compression {
+        return Ok(false);
     }
+
+    file.rewind().map_err(WriteError::FileOpenFailed)?;
+    let header = decompressed_reader(file).and_then(read_header);
+    Ok(matches!(header, Ok((header, _)) if header.length_width == config.length_width))
 }
 
 async fn check_or_create_parent_dir(path: &Path) -> WriteResult<()> {
@@ -221,7 +805,11 @@ async fn check_or_create_parent_dir(path: &Path) -> WriteResult<()> {
     Ok(())
 }
 
-async fn write_content(path: impl AsRef<Path>, data: &[u8]) -> WriteResult<()> {
+async fn write_content(
+    path: impl AsRef<Path>,
+    frame: &[u8],
+    config: &DataWriterConfig,
+) -> WriteResult<()> {
     use tokio::io::AsyncWriteExt;
 
     let path = path.as_ref();
@@ -236,23 +824,120 @@ async fn write_content(path: impl AsRef<Path>, data: &[u8]) -> WriteResult<()> {
         .await
         .map_err(WriteError::FileOpenFailed)?;
 
-    // First, write length to file.
-    let data_len = (data.len() as u16).to_be_bytes();
-    file.write_all(&data_len)
+    // Write the header if we are creating this file,
+    // and skip it when appending to an existing file.
+    let is_new_file = file
+        .metadata()
         .await
-        .map_err(WriteError::LengthWriteFailed)?;
+        .map_err(WriteError::FileOpenFailed)?
+        .len()
+        == 0;
+
+    if is_new_file {
+        file.write_all(&encode_header(config)?)
+            .await
+            .map_err(WriteError::HeaderWriteFailed)?;
+    }
 
-    // Then, write data to file.
-    file.write_all(data)
+    // Then, write the frame to file.
+    file.write_all(frame)
         .await
         .map_err(WriteError::DataWriteFailed)?;
 
     // Flush the buffer.
     file.flush().await.map_err(WriteError::FlushFailed)?;
 
+    // Sync the frame to the disk before writing the next one,
+    // so that a crash can only tear the last frame.
+    if config.atomic_writes {
+        file.sync_data().await.map_err(WriteError::FlushFailed)?;
+    }
+
     Ok(())
 }
 
+/// Truncate the partial frame at the end of the file at `path`,
+/// which a crash during writing may leave, and return the bytes truncated.
+///
+/// The complete frames are found by walking their length sections
+/// from the header. A file shorter than the header is truncated to empty
+/// if it is a prefix of the header, or walked as a legacy file otherwise.
+/// The compressed files, which can't be walked, are kept as is.
+async fn truncate_torn_tail(path: &Path) -> WriteResult<u64> {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let mut file = match OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(path)
+        .await
+    {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(WriteError::FileOpenFailed(e)),
+    };
+    let file_len = file
+        .metadata()
+        .await
+        .map_err(WriteError::FileOpenFailed)?
+        .len();
+
+    let mut header = [0u8; HEADER_LEN];
+    let header = &mut header[..file_len.min(HEADER_LEN as u64) as usize];
+    file.read_exact(header)
+        .await
+        .map_err(WriteError::RecoverFailed)?;
+    if is_compressed(header) {
+        return Ok(0);
+    }
+
+    // A crash may also tear the header of a new file.
+    let torn_header = header.len() < HEADER_LEN && MAGIC.iter().zip(&*header).all(|(a, b)| a == b);
+    let complete_len = if torn_header {
+        0
+    } else {
+        let (length_width, mut offset) = match FileHeader::parse(header) {
+            Ok(Some(header)) => (header.length_width, HEADER_LEN as u64),
+            Ok(None) => (FileHeader::legacy().length_width, 0),
+            Err(e) => return Err(WriteError::RecoverFailed(e)),
+        };
+        let width = length_width.bytes();
+
+        // Read the length sections only, and skip the data.
+        let mut length_section = [0u8; 4];
+        while offset + width as u64 <= file_len {
+            file.seek(SeekFrom::Start(offset))
+                .await
+                .map_err(WriteError::RecoverFailed)?;
+            file.read_exact(&mut length_section[..width])
+                .await
+                .map_err(WriteError::RecoverFailed)?;
+
+            let next = offset + (width + length_width.decode(&length_section)) as u64;
+            if next > file_len {
+                break;
+            }
+            offset = next;
+        }
+
+        offset
+    };
+
+    let torn = file_len - complete_len;
+    if torn > 0 {
+        tracing::warn!(
+            "Truncating the partial frame of {torn} bytes at the end of {path}.",
+            path = path.display()
+        );
+        file.set_len(complete_len)
+            .await
+            .map_err(WriteError::RecoverFailed)?;
+        file.sync_all().await.map_err(WriteError::RecoverFailed)?;
+    }
+
+    Ok(torn)
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum WriteError {
     #[error("invalid path to write: {0}")]
@@ -264,29 +949,38 @@ pub enum WriteError {
     #[error("failed to push an entry to channel")]
     PushChannelFailed,
 
+    #[error("the queue of the writer is full")]
+    QueueFull,
+
     #[error("failed to open file: {0}")]
     FileOpenFailed(tokio::io::Error),
 
+    /// The length section is written with the data in a frame now,
+    /// which reports [`WriteError::DataWriteFailed`] instead.
+    #[deprecated(since = "0.4.0", note = "use `DataWriteFailed` instead")]
     #[error("failed to write length to file: {0}")]
     LengthWriteFailed(tokio::io::Error),
 
+    #[error("failed to compress data: {0}")]
+    CompressFailed(std::io::Error),
+
+    #[error("failed to write header to file: {0}")]
+    HeaderWriteFailed(tokio::io::Error),
+
     #[error("failed to write data to file: {0}")]
     DataWriteFailed(tokio::io::Error),
 
     #[error("failed to flush buffer: {0}")]
     FlushFailed(tokio::io::Error),
-}
 
-#[derive(Debug, thiserror::Error)]
-enum DaemonError {
-    #[error("writer error: {0}")]
-    WriterError(#[from] WriteError),
+    #[error("failed to recover the partial frame at the end of file: {0}")]
+    RecoverFailed(tokio::io::Error),
 
-    #[error("failed to receive action: {0}")]
-    RecvActionFailed(flume::RecvError),
+    #[error("the data of {0} bytes is too large for the frame length section")]
+    FrameTooLarge(usize),
 
-    #[error("received stop signal")]
-    StopDaemon,
+    #[error("the data is invalid: {0}")]
+    ValidationFailed(String),
 }
 
 pub type WriteResult<T> = Result<T, WriteError>;
@@ -1,7 +1,11 @@
 //! The high-performance flags for indicating the state.
 
-use std::sync::atomic::AtomicBool;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
 
 #[derive(Clone)]
 /// The flag storing a binary ([`bool`]) value.
@@ -25,12 +29,12 @@ impl BinaryFlag {
 
     /// Should we continue running?
     pub fn is_running(&self) -> bool {
-        self.0.load(std::sync::atomic::Ordering::SeqCst)
+        self.0.load(Ordering::SeqCst)
     }
 
     /// Set the running flag.
     pub fn set_running(&self, value: bool) {
-        self.0.store(value, std::sync::atomic::Ordering::SeqCst)
+        self.0.store(value, Ordering::SeqCst)
     }
 }
 
@@ -39,3 +43,552 @@ impl Default for BinaryFlag {
         Self(Arc::new(AtomicBool::new(true)))
     }
 }
+
+#[derive(Clone, Debug)]
+/// The counter storing a [`u64`] value.
+///
+/// The clones share the same counter.
+///
+/// # Example
+///
+/// ```
+/// use wmjtyd_libstock::flag::AtomicCounter;
+///
+/// let counter = AtomicCounter::new(1);
+/// assert_eq!(counter.increment(), 2);
+/// assert_eq!(counter.decrement(), 1);
+/// assert_eq!(counter.compare_and_set(1, 10), Ok(1));
+/// assert_eq!(counter.get(), 10);
+/// counter.reset();
+/// assert_eq!(counter.get(), 0);
+/// ```
+pub struct AtomicCounter(Arc<AtomicU64>);
+
+impl AtomicCounter {
+    /// Create a counter starting from `initial`.
+    pub fn new(initial: u64) -> Self {
+        Self(Arc::new(AtomicU64::new(initial)))
+    }
+
+    /// Increase the counter by 1, and return the new value.
+    ///
+    /// It wraps around on overflow.
+    pub fn increment(&self) -> u64 {
+        self.add(1)
+    }
+
+    /// Increase the counter by `value`, and return the new value.
+    ///
+    /// It wraps around on overflow.
+    pub fn add(&self, value: u64) -> u64 {
+        self.0.fetch_add(value, Ordering::SeqCst).wrapping_add(value)
+    }
+
+    /// Decrease the counter by 1, and return the new value.
+    ///
+    /// It wraps around on overflow.
+    pub fn decrement(&self) -> u64 {
+        self.0.fetch_sub(1, Ordering::SeqCst).wrapping_sub(1)
+    }
+
+    /// Reset the counter to 0.
+    pub fn reset(&self) {
+        self.0.store(0, Ordering::SeqCst)
+    }
+
+    /// Get the current value.
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Set the counter to `new` if the current value is `expected`.
+    ///
+    /// Returns `Ok(previous)` if the counter is set, otherwise
+    /// `Err(current)`.
+    pub fn compare_and_set(&self, expected: u64, new: u64) -> Result<u64, u64> {
+        self.0
+            .compare_exchange(expected, new, Ordering::SeqCst, Ordering::SeqCst)
+    }
+}
+
+impl Default for AtomicCounter {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[derive(Clone, Default)]
+/// The one-shot latch for waiting until another thread or task signals.
+///
+/// Once signalled, it stays signalled, and any subsequent
+/// `wait()` returns immediately.
+///
+/// # Example
+///
+/// ```
+/// use wmjtyd_libstock::flag::WaitFlag;
+///
+/// let flag = WaitFlag::new();
+/// let cloned = flag.clone();
+///
+/// std::thread::spawn(move || cloned.signal());
+///
+/// flag.wait();
+/// assert!(flag.is_signalled());
+/// ```
+pub struct WaitFlag(Arc<WaitFlagInner>);
+
+#[derive(Default)]
+struct WaitFlagInner {
+    signalled: Mutex<bool>,
+    condvar: Condvar,
+    notify: Notify,
+}
+
+impl WaitFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the flag, and wake all the waiters up.
+    pub fn signal(&self) {
+        let mut signalled = self
+            .0
+            .signalled
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        *signalled = true;
+
+        self.0.condvar.notify_all();
+        self.0.notify.notify_waiters();
+    }
+
+    /// Has the flag been signalled?
+    pub fn is_signalled(&self) -> bool {
+        *self
+            .0
+            .signalled
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Block the current thread until the flag is signalled.
+    pub fn wait(&self) {
+        let signalled = self
+            .0
+            .signalled
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let _signalled = self
+            .0
+            .condvar
+            .wait_while(signalled, |signalled| !*signalled)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+
+    /// Wait until the flag is signalled asynchronously.
+    pub async fn wait_async(&self) {
+        loop {
+            // Register before checking, so we won't miss the signal
+            // sent between the checking and the awaiting.
+            let notified = self.0.notify.notified();
+
+            if self.is_signalled() {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+/// The flag storing a binary ([`bool`]) value, which
+/// can be awaited until it flips to the expected value.
+///
+/// The flag is `false` by default. It is also usable in the
+/// synchronous code with [`AsyncFlag::get`].
+///
+/// # Example
+///
+/// ```
+/// use wmjtyd_libstock::flag::AsyncFlag;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let flag = AsyncFlag::new();
+/// let cloned = flag.clone();
+///
+/// let waiter = tokio::spawn(async move { cloned.wait_for(true).await });
+///
+/// flag.set();
+/// waiter.await.unwrap();
+/// assert!(flag.get());
+/// # }
+/// ```
+pub struct AsyncFlag(Arc<AsyncFlagInner>);
+
+#[derive(Default)]
+struct AsyncFlagInner {
+    value: AtomicBool,
+    notify: Notify,
+}
+
+impl AsyncFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the flag to `true`, and wake the waiters up.
+    pub fn set(&self) {
+        self.store(true)
+    }
+
+    /// Set the flag to `false`, and wake the waiters up.
+    pub fn clear(&self) {
+        self.store(false)
+    }
+
+    /// Get the current value.
+    pub fn get(&self) -> bool {
+        self.0.value.load(Ordering::Acquire)
+    }
+
+    /// Wait until the flag becomes `expected`.
+    ///
+    /// It returns immediately if the flag is already `expected`.
+    pub async fn wait_for(&self, expected: bool) {
+        loop {
+            // Register before checking, so we won't miss the
+            // flip between the checking and the awaiting.
+            let notified = self.0.notify.notified();
+
+            if self.get() == expected {
+                return;
+            }
+
+            notified.await;
+        }
+    }
+
+    fn store(&self, value: bool) {
+        let previous = self.0.value.swap(value, Ordering::AcqRel);
+
+        if previous != value {
+            self.0.notify.notify_waiters();
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+/// The flag which resets itself after the specified duration.
+///
+/// It is useful for rate-limiting and cooldown.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use wmjtyd_libstock::flag::TimedFlag;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let flag = TimedFlag::new();
+///
+/// flag.set(Duration::from_millis(10));
+/// assert!(flag.is_set());
+///
+/// tokio::time::sleep(Duration::from_millis(50)).await;
+/// assert!(!flag.is_set());
+/// # }
+/// ```
+pub struct TimedFlag(Arc<TimedFlagInner>);
+
+#[derive(Default)]
+struct TimedFlagInner {
+    value: AtomicBool,
+    reset: Mutex<PendingReset>,
+}
+
+/// The pending reset task of [`TimedFlag`].
+#[derive(Default)]
+struct PendingReset {
+    /// Increased every time the pending reset is replaced,
+    /// so an outdated reset task won't clear the flag.
+    generation: u64,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl PendingReset {
+    fn cancel(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+
+        if let Some(handle) = self.handle.take() {
+            handle.abort();
+        }
+    }
+}
+
+impl TimedFlag {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the flag, and clear it after `duration`.
+    ///
+    /// If there is a pending reset, it is replaced with the new one.
+    ///
+    /// # Panics
+    ///
+    /// It panics if it is not called in a Tokio runtime,
+    /// as it spawns a task to reset the flag.
+    pub fn set(&self, duration: Duration) {
+        let mut reset = self.0.reset.lock().unwrap_or_else(PoisonError::into_inner);
+        reset.cancel();
+
+        self.0.value.store(true, Ordering::SeqCst);
+
+        let generation = reset.generation;
+        let inner = self.0.clone();
+        reset.handle = Some(tokio::task::spawn(async move {
+            tokio::time::sleep(duration).await;
+
+            let mut reset = inner.reset.lock().unwrap_or_else(PoisonError::into_inner);
+            if reset.generation == generation {
+                inner.value.store(false, Ordering::SeqCst);
+                reset.handle = None;
+            }
+        }));
+    }
+
+    /// Is the flag set?
+    pub fn is_set(&self) -> bool {
+        self.0.value.load(Ordering::SeqCst)
+    }
+
+    /// Clear the flag now, and cancel the pending reset.
+    pub fn clear(&self) {
+        let mut reset = self.0.reset.lock().unwrap_or_else(PoisonError::into_inner);
+        reset.cancel();
+
+        self.0.value.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{AsyncFlag, AtomicCounter, TimedFlag, WaitFlag};
+
+    #[test]
+    fn test_counter_default() {
+        assert_eq!(AtomicCounter::default().get(), 0);
+    }
+
+    #[test]
+    fn test_counter_increment_decrement() {
+        let counter = AtomicCounter::new(5);
+
+        assert_eq!(counter.increment(), 6);
+        assert_eq!(counter.increment(), 7);
+        assert_eq!(counter.decrement(), 6);
+        assert_eq!(counter.add(4), 10);
+        assert_eq!(counter.get(), 10);
+    }
+
+    #[test]
+    fn test_counter_reset() {
+        let counter = AtomicCounter::new(5);
+        counter.reset();
+
+        assert_eq!(counter.get(), 0);
+    }
+
+    #[test]
+    fn test_counter_compare_and_set() {
+        let counter = AtomicCounter::new(5);
+
+        assert_eq!(counter.compare_and_set(4, 10), Err(5));
+        assert_eq!(counter.get(), 5);
+        assert_eq!(counter.compare_and_set(5, 10), Ok(5));
+        assert_eq!(counter.get(), 10);
+    }
+
+    #[test]
+    fn test_counter_shared_clone() {
+        let counter = AtomicCounter::default();
+        let cloned = counter.clone();
+
+        cloned.increment();
+        assert_eq!(counter.get(), 1);
+    }
+
+    #[test]
+    fn test_counter_concurrent_increment() {
+        const THREADS: u64 = 8;
+        const TIMES: u64 = 10000;
+
+        let counter = AtomicCounter::default();
+
+        let handles = (0..THREADS)
+            .map(|_| {
+                let counter = counter.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..TIMES {
+                        counter.increment();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        assert_eq!(counter.get(), THREADS * TIMES);
+    }
+
+    #[test]
+    fn test_wait_flag_signal_from_thread() {
+        let flag = WaitFlag::new();
+        assert!(!flag.is_signalled());
+
+        let cloned = flag.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            cloned.signal();
+        });
+
+        flag.wait();
+        assert!(flag.is_signalled());
+    }
+
+    #[test]
+    fn test_wait_flag_late_joiner() {
+        let flag = WaitFlag::new();
+        flag.signal();
+
+        // Both the existing and the cloned flag return immediately.
+        flag.wait();
+        flag.clone().wait();
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_wait_flag_async() {
+        let flag = WaitFlag::new();
+
+        let waiters = (0..4)
+            .map(|_| {
+                let flag = flag.clone();
+                tokio::task::spawn(async move { flag.wait_async().await })
+            })
+            .collect::<Vec<_>>();
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        flag.signal();
+
+        for waiter in waiters {
+            tokio::time::timeout(Duration::from_secs(5), waiter)
+                .await
+                .expect("waiter timed out")
+                .expect("waiter failed");
+        }
+
+        // Late joiners return immediately.
+        flag.wait_async().await;
+    }
+
+    #[test]
+    fn test_async_flag_get() {
+        let flag = AsyncFlag::new();
+        assert!(!flag.get());
+
+        flag.set();
+        assert!(flag.get());
+        assert!(flag.clone().get());
+
+        flag.clear();
+        assert!(!flag.get());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_flag_wakes_all_waiters() {
+        let flag = AsyncFlag::new();
+
+        let waiters = (0..4)
+            .map(|_| {
+                let flag = flag.clone();
+                tokio::task::spawn(async move { flag.wait_for(true).await })
+            })
+            .collect::<Vec<_>>();
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        flag.set();
+
+        for waiter in waiters {
+            tokio::time::timeout(Duration::from_secs(5), waiter)
+                .await
+                .expect("waiter timed out")
+                .expect("waiter failed");
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_flag_wait_for_false() {
+        let flag = AsyncFlag::new();
+        flag.set();
+
+        let cloned = flag.clone();
+        let waiter = tokio::task::spawn(async move { cloned.wait_for(false).await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        flag.clear();
+
+        tokio::time::timeout(Duration::from_secs(5), waiter)
+            .await
+            .expect("waiter timed out")
+            .expect("waiter failed");
+
+        // Already false, so it returns immediately.
+        flag.wait_for(false).await;
+    }
+
+    #[tokio::test]
+    async fn test_timed_flag_self_clears() {
+        let flag = TimedFlag::new();
+        assert!(!flag.is_set());
+
+        flag.set(Duration::from_millis(20));
+        assert!(flag.is_set());
+        assert!(flag.clone().is_set());
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!flag.is_set());
+        // It should remain clear.
+        assert!(!flag.is_set());
+    }
+
+    #[tokio::test]
+    async fn test_timed_flag_clear() {
+        let flag = TimedFlag::new();
+
+        flag.set(Duration::from_secs(60));
+        flag.clear();
+        assert!(!flag.is_set());
+    }
+
+    #[tokio::test]
+    async fn test_timed_flag_set_extends() {
+        let flag = TimedFlag::new();
+
+        flag.set(Duration::from_millis(20));
+        // Replace the pending reset with a longer one.
+        flag.set(Duration::from_secs(60));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(flag.is_set());
+    }
+}
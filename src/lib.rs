@@ -9,20 +9,30 @@
 //!   to the well-defined directory.
 //! - Using methods under [`mod@flag`] to use thread-safe, lock-free flags.
 //! - Using methods under `slack` module to send notifications to Slack with Slack Hook
-//!   (required to enable `slack` feature, which is enabled by default)
-//! - Using methods under [`mod@message`] to subscribe and publish based on Nanomsg or Zeromq.
+//!   (required to enable `slack` feature, which is enabled by default), or to Discord
+//!   (required to enable `discord` feature)
+//! - Using methods under [`mod@message`] to subscribe and publish based on Nanomsg or Zeromq,
+//!   or TCP (required to enable `tcp` feature).
+//! - Using [`error::LibstockError`] to handle the errors of any module with the stable
+//!   error codes, for example, in the bindings of other languages.
+//! - Using the functions under `ffi` module to encode and decode the structures from C
+//!   (required to enable `ffi` feature).
 //!
 //! ## License
 //!
 //! Apache-2.0
 
 pub mod data;
+pub mod error;
 pub mod file;
 pub mod flag;
 
 #[cfg(feature = "slack")]
 pub mod slack;
 
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
 pub mod message;
 
 mod compat;
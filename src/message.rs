@@ -42,6 +42,13 @@
 //!
 //! For more examples, see the respective modules.
 
+#[cfg(feature = "lz4")]
+pub mod compression;
+pub mod envelope;
+pub mod fanout;
+pub mod memory;
+pub mod metered;
+pub mod router;
 pub mod traits;
 
 #[cfg(feature = "nanomsg")]
@@ -50,6 +57,12 @@ pub mod nanomsg;
 #[cfg(feature = "zeromq")]
 pub mod zeromq;
 
+#[cfg(feature = "tcp")]
+pub mod tcp;
+
+#[cfg(feature = "crypto")]
+pub mod typed;
+
 #[derive(thiserror::Error, std::fmt::Debug)]
 pub enum MessageError {
     #[cfg(feature = "nanomsg")]
@@ -59,13 +72,46 @@ pub enum MessageError {
     #[cfg(feature = "zeromq")]
     #[error("ZeroMQ error: {0}")]
     ZeromqError(#[from] self::zeromq::ZeromqError),
+
+    #[error("In-memory transport error: {0}")]
+    MemoryError(#[from] self::memory::MemoryError),
+
+    #[cfg(feature = "tcp")]
+    #[error("TCP error: {0}")]
+    TcpError(#[from] self::tcp::TcpError),
+
+    /// When the topic to unsubscribe is not subscribed.
+    #[error("Not subscribed to the topic: {}", String::from_utf8_lossy(.0))]
+    NotSubscribed(Vec<u8>),
+
+    /// When a message can't be decompressed.
+    #[cfg(feature = "lz4")]
+    #[error("Failed to decompress the message: {0}")]
+    DecompressFailed(std::io::Error),
 }
 
 pub type MessageResult<T> = Result<T, MessageError>;
 
+/// Remove a subscription of `topic` from `subscriptions`.
+///
+/// It returns [`MessageError::NotSubscribed`] if `topic` is not in `subscriptions`.
+pub(crate) fn remove_subscription(
+    subscriptions: &mut Vec<Vec<u8>>,
+    topic: &[u8],
+) -> MessageResult<()> {
+    let index = subscriptions
+        .iter()
+        .position(|t| t == topic)
+        .ok_or_else(|| MessageError::NotSubscribed(topic.to_vec()))?;
+    subscriptions.remove(index);
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::fmt::Debug;
+    use std::time::{Duration, Instant};
 
     use super::traits::{
         AsyncPublisher,
@@ -76,6 +122,8 @@ mod tests {
         SyncPublisher,
         SyncSubscriber,
     };
+    use super::MessageError;
+    use crate::flag::BinaryFlag;
 
     fn abstract_write_function(
         mut publisher: impl Bind<Err = impl Debug> + SyncPublisher,
@@ -182,22 +230,123 @@ mod tests {
         );
     }
 
+    fn abstract_recv_timeout_function<S, E>(mut subscriber: S, addr: &str)
+    where
+        E: Debug,
+        S: Connect<Err = E> + SyncSubscriber<Err = E> + Subscribe<Err = E> + Send + 'static,
+    {
+        subscriber.connect(addr).expect("failed to connect");
+        subscriber.subscribe(b"TEST").expect("failed to subscribe");
+
+        let running = BinaryFlag::new();
+        let receiver = std::thread::spawn({
+            let running = running.clone();
+
+            move || {
+                while running.is_running() {
+                    let message = subscriber
+                        .recv_timeout(Duration::from_millis(50))
+                        .expect("failed to receive");
+
+                    // No publisher is present.
+                    assert!(message.is_none());
+                }
+            }
+        });
+
+        std::thread::sleep(Duration::from_millis(200));
+
+        let stopped_at = Instant::now();
+        running.set_running(false);
+        receiver.join().unwrap();
+
+        assert!(stopped_at.elapsed() < Duration::from_secs(1));
+    }
+
+    fn abstract_subscriptions_function<S>(mut subscriber: S, addr: &str)
+    where
+        S: Connect<Err = MessageError> + Subscribe<Err = MessageError>,
+    {
+        assert!(subscriber.subscriptions().is_empty());
+
+        subscriber.subscribe(b"BTC").expect("failed to subscribe");
+        subscriber.subscribe(b"ETH").expect("failed to subscribe");
+        subscriber.subscribe(b"BTC").expect("failed to subscribe");
+        assert_eq!(
+            subscriber.subscriptions(),
+            [b"BTC".to_vec(), b"ETH".to_vec(), b"BTC".to_vec()]
+        );
+
+        // The subscriptions are kept across the connections.
+        subscriber.connect(addr).expect("failed to connect");
+        subscriber.disconnect(addr).expect("failed to disconnect");
+        subscriber.connect(addr).expect("failed to connect");
+
+        subscriber
+            .unsubscribe(b"BTC")
+            .expect("failed to unsubscribe");
+        assert_eq!(
+            subscriber.subscriptions(),
+            [b"ETH".to_vec(), b"BTC".to_vec()]
+        );
+
+        match subscriber.unsubscribe(b"DOGE") {
+            Err(MessageError::NotSubscribed(topic)) => assert_eq!(topic, b"DOGE"),
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        subscriber.subscribe_all().expect("failed to subscribe all");
+        assert_eq!(
+            subscriber.subscriptions(),
+            [b"ETH".to_vec(), b"BTC".to_vec(), Vec::new()]
+        );
+
+        subscriber
+            .unsubscribe_all()
+            .expect("failed to unsubscribe all");
+        assert!(subscriber.subscriptions().is_empty());
+        assert!(matches!(
+            subscriber.unsubscribe(b"ETH"),
+            Err(MessageError::NotSubscribed(_))
+        ));
+
+        subscriber.disconnect(addr).expect("failed to disconnect");
+    }
+
     macro_rules! build_test {
         (
-            sync =>
+            $kind:ident =>
             func_name = $func_name:ident,
             publisher = $publisher:ident,
             subscriber = $subscriber:ident,
             read_abs = $read_abs:ident,
         ) => {
-            #[test]
-            fn $func_name() {
-                const IPC_ADDR: &str = concat!(
+            build_test!(
+                $kind =>
+                func_name = $func_name,
+                publisher = $publisher,
+                subscriber = $subscriber,
+                read_abs = $read_abs,
+                uri = concat!(
                     "ipc:///tmp/libstock_",
                     stringify!($read_abs),
                     stringify!($subscriber),
                     ".ipc"
-                );
+                ),
+            );
+        };
+
+        (
+            sync =>
+            func_name = $func_name:ident,
+            publisher = $publisher:ident,
+            subscriber = $subscriber:ident,
+            read_abs = $read_abs:ident,
+            uri = $uri:expr,
+        ) => {
+            #[test]
+            fn $func_name() {
+                const IPC_ADDR: &str = $uri;
                 let publisher = $publisher::new().expect("failed to create publisher");
                 let subscriber = $subscriber::new().expect("failed to create subscriber");
 
@@ -214,15 +363,11 @@ mod tests {
             publisher = $publisher:ident,
             subscriber = $subscriber:ident,
             read_abs = $read_abs:ident,
+            uri = $uri:expr,
         ) => {
             #[tokio::test(flavor = "multi_thread")]
             async fn $func_name() {
-                const IPC_ADDR: &str = concat!(
-                    "ipc:///tmp/libstock_",
-                    stringify!($read_abs),
-                    stringify!($subscriber),
-                    ".ipc"
-                );
+                const IPC_ADDR: &str = $uri;
                 let publisher = $publisher::new().expect("failed to create publisher");
                 let subscriber = $subscriber::new().expect("failed to create subscriber");
 
@@ -237,9 +382,65 @@ mod tests {
         };
     }
 
+    mod memory {
+        use super::super::memory::{MemoryPublisher, MemorySubscriber};
+        use super::*;
+
+        build_test!(
+            sync =>
+            func_name = sync_read,
+            publisher = MemoryPublisher,
+            subscriber = MemorySubscriber,
+            read_abs = abstract_read_function,
+            uri = "inproc-mem://libstock_sync_read",
+        );
+
+        build_test!(
+            sync =>
+            func_name = sync_iter,
+            publisher = MemoryPublisher,
+            subscriber = MemorySubscriber,
+            read_abs = abstract_iter_function,
+            uri = "inproc-mem://libstock_sync_iter",
+        );
+
+        build_test!(
+            async =>
+            func_name = async_read,
+            publisher = MemoryPublisher,
+            subscriber = MemorySubscriber,
+            read_abs = abstract_async_read_function,
+            uri = "inproc-mem://libstock_async_read",
+        );
+
+        build_test!(
+            async =>
+            func_name = async_iter,
+            publisher = MemoryPublisher,
+            subscriber = MemorySubscriber,
+            read_abs = abstract_async_stream_function,
+            uri = "inproc-mem://libstock_async_iter",
+        );
+
+        #[test]
+        fn recv_timeout() {
+            let subscriber = MemorySubscriber::new().expect("failed to create subscriber");
+
+            abstract_recv_timeout_function(subscriber, "inproc-mem://libstock_recv_timeout");
+        }
+
+        #[test]
+        fn subscriptions() {
+            let subscriber = MemorySubscriber::new().expect("failed to create subscriber");
+
+            abstract_subscriptions_function(subscriber, "inproc-mem://libstock_subscriptions");
+        }
+    }
+
     #[cfg(feature = "nanomsg")]
     mod nanomsg {
-        use super::super::nanomsg::{NanomsgPublisher, NanomsgSubscriber};
+        use super::super::nanomsg::{NanomsgError, NanomsgPublisher, NanomsgSubscriber};
+        use super::super::MessageError;
         use super::*;
 
         build_test!(
@@ -273,11 +474,64 @@ mod tests {
             subscriber = NanomsgSubscriber,
             read_abs = abstract_async_stream_function,
         );
+
+        #[test]
+        fn recv_timeout() {
+            let subscriber = NanomsgSubscriber::new().expect("failed to create subscriber");
+
+            abstract_recv_timeout_function(
+                subscriber,
+                "ipc:///tmp/libstock_recv_timeout_NanomsgSubscriber.ipc",
+            );
+        }
+
+        #[test]
+        fn subscriptions() {
+            let subscriber = NanomsgSubscriber::new().expect("failed to create subscriber");
+
+            abstract_subscriptions_function(
+                subscriber,
+                "ipc:///tmp/libstock_subscriptions_NanomsgSubscriber.ipc",
+            );
+        }
+
+        #[test]
+        fn connect_verified_unreachable() {
+            const IPC_ADDR: &str = "ipc:///tmp/libstock_connect_verified_unreachable_Nanomsg.ipc";
+            std::fs::remove_file(IPC_ADDR.trim_start_matches("ipc://")).ok();
+
+            let mut subscriber = NanomsgSubscriber::new().expect("failed to create subscriber");
+
+            let started_at = Instant::now();
+            let result = subscriber.connect_verified(IPC_ADDR, Duration::from_millis(200));
+
+            match result {
+                Err(MessageError::NanomsgError(NanomsgError::EndpointUnreachable(uri))) => {
+                    assert_eq!(uri, IPC_ADDR)
+                }
+                other => panic!("unexpected result: {other:?}"),
+            }
+            assert!(started_at.elapsed() < Duration::from_secs(1));
+        }
+
+        #[test]
+        fn connect_verified_bound() {
+            const IPC_ADDR: &str = "ipc:///tmp/libstock_connect_verified_bound_Nanomsg.ipc";
+
+            let mut publisher = NanomsgPublisher::new().expect("failed to create publisher");
+            publisher.bind(IPC_ADDR).expect("failed to bind");
+
+            let mut subscriber = NanomsgSubscriber::new().expect("failed to create subscriber");
+            subscriber
+                .connect_verified(IPC_ADDR, Duration::from_secs(1))
+                .expect("failed to connect");
+        }
     }
 
     #[cfg(feature = "zeromq")]
     mod zeromq {
-        use super::super::zeromq::{ZeromqPublisher, ZeromqSubscriber};
+        use super::super::zeromq::{ZeromqError, ZeromqPublisher, ZeromqSubscriber};
+        use super::super::MessageError;
         use super::*;
 
         build_test!(
@@ -311,5 +565,57 @@ mod tests {
             subscriber = ZeromqSubscriber,
             read_abs = abstract_async_stream_function,
         );
+
+        #[test]
+        fn recv_timeout() {
+            let subscriber = ZeromqSubscriber::new().expect("failed to create subscriber");
+
+            abstract_recv_timeout_function(
+                subscriber,
+                "ipc:///tmp/libstock_recv_timeout_ZeromqSubscriber.ipc",
+            );
+        }
+
+        #[test]
+        fn subscriptions() {
+            let subscriber = ZeromqSubscriber::new().expect("failed to create subscriber");
+
+            abstract_subscriptions_function(
+                subscriber,
+                "ipc:///tmp/libstock_subscriptions_ZeromqSubscriber.ipc",
+            );
+        }
+
+        #[test]
+        fn connect_verified_unreachable() {
+            const IPC_ADDR: &str = "ipc:///tmp/libstock_connect_verified_unreachable_Zeromq.ipc";
+            std::fs::remove_file(IPC_ADDR.trim_start_matches("ipc://")).ok();
+
+            let mut subscriber = ZeromqSubscriber::new().expect("failed to create subscriber");
+
+            let started_at = Instant::now();
+            let result = subscriber.connect_verified(IPC_ADDR, Duration::from_millis(200));
+
+            match result {
+                Err(MessageError::ZeromqError(ZeromqError::EndpointUnreachable(uri))) => {
+                    assert_eq!(uri, IPC_ADDR)
+                }
+                other => panic!("unexpected result: {other:?}"),
+            }
+            assert!(started_at.elapsed() < Duration::from_secs(1));
+        }
+
+        #[test]
+        fn connect_verified_bound() {
+            const IPC_ADDR: &str = "ipc:///tmp/libstock_connect_verified_bound_Zeromq.ipc";
+
+            let mut publisher = ZeromqPublisher::new().expect("failed to create publisher");
+            publisher.bind(IPC_ADDR).expect("failed to bind");
+
+            let mut subscriber = ZeromqSubscriber::new().expect("failed to create subscriber");
+            subscriber
+                .connect_verified(IPC_ADDR, Duration::from_secs(1))
+                .expect("failed to connect");
+        }
     }
 }
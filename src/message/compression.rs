@@ -0,0 +1,434 @@
+//! The LZ4 compression of the messages.
+//!
+//! [`CompressedMessage`] compresses a payload to a LZ4 frame.
+//! [`LZ4Publisher`] compresses each written message, and [`LZ4Subscriber`]
+//! decompresses each received message, so they can wrap any publisher
+//! and subscriber transparently.
+//!
+//! As the whole message is compressed, the topic prefix is compressed
+//! too, and the subscribers can't filter the topics. [`LZ4Subscriber`]
+//! should subscribe all the topics with [`Subscribe::subscribe_all`].
+//!
+//! # Example
+//!
+//! ```
+//! use wmjtyd_libstock::message::compression::{LZ4Publisher, LZ4Subscriber};
+//! use wmjtyd_libstock::message::memory::{MemoryPublisher, MemorySubscriber};
+//! use wmjtyd_libstock::message::traits::{Bind, Connect, Subscribe, Write};
+//!
+//! let mut publisher = LZ4Publisher::new(MemoryPublisher::new().unwrap());
+//! let mut subscriber = LZ4Subscriber::new(MemorySubscriber::new().unwrap());
+//!
+//! publisher.bind("inproc-mem://lz4-example").expect("failed to bind");
+//! subscriber.connect("inproc-mem://lz4-example").expect("failed to connect");
+//! subscriber.subscribe_all().expect("failed to subscribe");
+//!
+//! publisher.write_all(b"TEST Hello, World").expect("failed to write");
+//!
+//! let message = subscriber.next().expect("no data inside");
+//! assert_eq!(message.expect("data receiving failed"), b"TEST Hello, World");
+//! ```
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use lz4_flex::frame::{FrameDecoder, FrameEncoder};
+
+use super::traits::{
+    AsyncPublisher,
+    AsyncRead,
+    AsyncSubscriber,
+    AsyncWrite,
+    Bind,
+    Connect,
+    Publisher,
+    Read,
+    Stream,
+    StreamExt,
+    Subscribe,
+    SubscribeStreamItem,
+    Subscriber,
+    SyncPublisher,
+    SyncSubscriber,
+    Write,
+};
+use super::{MessageError, MessageResult};
+
+/// A payload compressed as a LZ4 frame.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CompressedMessage(Vec<u8>);
+
+impl CompressedMessage {
+    /// Compress `payload`.
+    pub fn compress(payload: &[u8]) -> Self {
+        let mut encoder = FrameEncoder::new(Vec::new());
+
+        // Writing to a `Vec` never fails.
+        encoder.write_all(payload).expect("failed to compress to Vec");
+        Self(encoder.finish().expect("failed to compress to Vec"))
+    }
+
+    /// Wrap the compressed bytes, such as a received message.
+    pub fn from_bytes(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    /// Decompress the payload.
+    pub fn decompress(&self) -> MessageResult<Vec<u8>> {
+        let mut payload = Vec::new();
+        FrameDecoder::new(self.0.as_slice())
+            .read_to_end(&mut payload)
+            .map_err(MessageError::DecompressFailed)?;
+
+        Ok(payload)
+    }
+
+    /// Get the compressed bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Unwrap the compressed bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+/// Copy `message` to `buf`. Like ZeroMQ, the part exceeding `buf` is truncated.
+fn copy_truncated(message: &[u8], buf: &mut [u8]) -> usize {
+    let len = message.len().min(buf.len());
+    buf[..len].copy_from_slice(&message[..len]);
+
+    len
+}
+
+/// The publisher compressing each message with LZ4.
+///
+/// Each write is compressed and sent as a message.
+#[derive(Debug)]
+pub struct LZ4Publisher<P> {
+    inner: P,
+
+    /// The compressed message pending in [`AsyncWrite::poll_write`].
+    pending: Option<CompressedMessage>,
+}
+
+impl<P> LZ4Publisher<P> {
+    /// Wrap a publisher.
+    pub fn new(inner: P) -> Self {
+        Self {
+            inner,
+            pending: None,
+        }
+    }
+
+    /// Get the reference to the underlying publisher.
+    pub fn get_ref(&self) -> &P {
+        &self.inner
+    }
+
+    /// Get the mutable reference to the underlying publisher.
+    pub fn get_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+
+    /// Unwrap this publisher, returning the underlying publisher.
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+}
+
+impl<P: Write> Write for LZ4Publisher<P> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let message = CompressedMessage::compress(buf);
+        self.inner.write_all(message.as_bytes())?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<P: AsyncWrite + Unpin> AsyncWrite for LZ4Publisher<P> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        // Keep the compressed message, so it is not compressed again
+        // when the underlying publisher is pending.
+        let message = self
+            .pending
+            .take()
+            .unwrap_or_else(|| CompressedMessage::compress(buf));
+
+        match Pin::new(&mut self.inner).poll_write(cx, message.as_bytes()) {
+            Poll::Ready(result) => Poll::Ready(result.map(|_| buf.len())),
+            Poll::Pending => {
+                self.pending = Some(message);
+                Poll::Pending
+            }
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+impl<P: Bind> Bind for LZ4Publisher<P> {
+    type Err = P::Err;
+
+    fn bind(&mut self, uri: &str) -> Result<(), Self::Err> {
+        self.inner.bind(uri)
+    }
+
+    fn unbind(&mut self, uri: &str) -> Result<(), Self::Err> {
+        self.inner.unbind(uri)
+    }
+}
+
+impl<P: Publisher> Publisher for LZ4Publisher<P> {}
+
+impl<P: SyncPublisher> SyncPublisher for LZ4Publisher<P> {}
+
+impl<P: AsyncPublisher + Unpin> AsyncPublisher for LZ4Publisher<P> {}
+
+/// The subscriber decompressing each message with LZ4.
+#[derive(Debug)]
+pub struct LZ4Subscriber<S> {
+    inner: S,
+}
+
+impl<S> LZ4Subscriber<S> {
+    /// Wrap a subscriber.
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    /// Get the reference to the underlying subscriber.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get the mutable reference to the underlying subscriber.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwrap this subscriber, returning the underlying subscriber.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+/// Decompress a received message.
+fn decompress_item(item: SubscribeStreamItem<MessageError>) -> SubscribeStreamItem<MessageError> {
+    item.and_then(|message| CompressedMessage::from_bytes(message).decompress())
+}
+
+impl<S> Iterator for LZ4Subscriber<S>
+where
+    S: Iterator<Item = SubscribeStreamItem<MessageError>>,
+{
+    type Item = SubscribeStreamItem<MessageError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(decompress_item)
+    }
+}
+
+impl<S> Read for LZ4Subscriber<S>
+where
+    S: Iterator<Item = SubscribeStreamItem<MessageError>>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match Iterator::next(self) {
+            Some(message) => {
+                let message = message.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+                Ok(copy_truncated(&message, buf))
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+impl<S> Stream for LZ4Subscriber<S>
+where
+    S: Stream<Item = SubscribeStreamItem<MessageError>> + Unpin,
+{
+    type Item = SubscribeStreamItem<MessageError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner
+            .poll_next_unpin(cx)
+            .map(|item| item.map(decompress_item))
+    }
+}
+
+impl<S> AsyncRead for LZ4Subscriber<S>
+where
+    S: Stream<Item = SubscribeStreamItem<MessageError>> + Unpin,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if let Some(message) = futures::ready!(self.poll_next_unpin(cx)) {
+            let message = message.map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            let len = copy_truncated(&message, buf.initialize_unfilled());
+            buf.advance(len);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<S: Connect> Connect for LZ4Subscriber<S> {
+    type Err = S::Err;
+
+    fn connect(&mut self, uri: &str) -> Result<(), Self::Err> {
+        self.inner.connect(uri)
+    }
+
+    fn disconnect(&mut self, uri: &str) -> Result<(), Self::Err> {
+        self.inner.disconnect(uri)
+    }
+}
+
+impl<S: Subscribe> Subscribe for LZ4Subscriber<S> {
+    type Err = S::Err;
+
+    fn subscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
+        self.inner.subscribe(topic)
+    }
+
+    fn unsubscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
+        self.inner.unsubscribe(topic)
+    }
+
+    fn subscriptions(&self) -> Vec<Vec<u8>> {
+        self.inner.subscriptions()
+    }
+}
+
+impl<S: Subscriber> Subscriber for LZ4Subscriber<S> {}
+
+impl<S> SyncSubscriber for LZ4Subscriber<S>
+where
+    S: SyncSubscriber<Err = MessageError>,
+{
+    type Err = MessageError;
+
+    fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, Self::Err> {
+        self.inner
+            .recv_timeout(timeout)?
+            .map(|message| CompressedMessage::from_bytes(message).decompress())
+            .transpose()
+    }
+
+    fn set_recv_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Self::Err> {
+        self.inner.set_recv_timeout(timeout)
+    }
+}
+
+impl<S> AsyncSubscriber for LZ4Subscriber<S>
+where
+    S: AsyncSubscriber<Err = MessageError> + Unpin,
+{
+    type Err = MessageError;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{CompressedMessage, LZ4Publisher, LZ4Subscriber};
+    use crate::message::memory::{MemoryPublisher, MemorySubscriber};
+    use crate::message::traits::{Bind, Connect, Subscribe, SyncSubscriber, Write};
+    use crate::message::MessageError;
+
+    fn connected_pair(
+        uri: &str,
+    ) -> (LZ4Publisher<MemoryPublisher>, LZ4Subscriber<MemorySubscriber>) {
+        let mut publisher = LZ4Publisher::new(MemoryPublisher::new().unwrap());
+        let mut subscriber = LZ4Subscriber::new(MemorySubscriber::new().unwrap());
+
+        publisher.bind(uri).unwrap();
+        subscriber.connect(uri).unwrap();
+        subscriber.subscribe_all().unwrap();
+
+        (publisher, subscriber)
+    }
+
+    #[test]
+    fn test_compress_decompress() {
+        let payload = b"BBO ".repeat(1000);
+        let message = CompressedMessage::compress(&payload);
+
+        assert!(message.as_bytes().len() < payload.len());
+        assert_eq!(message.decompress().unwrap(), payload);
+
+        let empty = CompressedMessage::compress(b"");
+        assert_eq!(empty.decompress().unwrap(), b"");
+    }
+
+    #[test]
+    fn test_decompress_invalid() {
+        let message = CompressedMessage::from_bytes(b"not a LZ4 frame".to_vec());
+
+        assert!(matches!(
+            message.decompress(),
+            Err(MessageError::DecompressFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_sync_roundtrip() {
+        let (mut publisher, mut subscriber) = connected_pair("inproc-mem://test_lz4_sync");
+        let timeout = Duration::from_millis(10);
+
+        publisher.write_all(b"TEST Hello, World").unwrap();
+        publisher.write_all(b"TEST Hello, LZ4").unwrap();
+
+        assert_eq!(subscriber.next().unwrap().unwrap(), b"TEST Hello, World");
+        assert_eq!(
+            subscriber.recv_timeout(timeout).unwrap().unwrap(),
+            b"TEST Hello, LZ4"
+        );
+        assert_eq!(subscriber.recv_timeout(timeout).unwrap(), None);
+
+        // The raw messages are compressed.
+        publisher.get_mut().write_all(b"TEST raw").unwrap();
+        assert!(matches!(
+            subscriber.next().unwrap(),
+            Err(MessageError::DecompressFailed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_async_roundtrip() {
+        use crate::message::traits::{AsyncWriteExt, StreamExt};
+
+        let (mut publisher, mut subscriber) = connected_pair("inproc-mem://test_lz4_async");
+
+        AsyncWriteExt::write_all(&mut publisher, b"TEST Hello, World")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            StreamExt::next(&mut subscriber).await.unwrap().unwrap(),
+            b"TEST Hello, World"
+        );
+    }
+}
@@ -0,0 +1,261 @@
+//! The topic-prefixed messages, which carry their topics.
+//!
+//! The subscribers filter the messages by the topic prefix, so the
+//! publishers usually write the topic, such as `b"BBO "`, before the
+//! payload. [`TopicPrefixedMessage`] does the concatenation and the
+//! splitting, [`TypedTopicPublisher`] prepends the topic to each
+//! published structure, and [`SubscriberExt::filter_topic`] yields the
+//! messages of a topic with the topic stripped.
+//!
+//! # Example
+//!
+//! ```
+//! use wmjtyd_libstock::message::envelope::TopicPrefixedMessage;
+//!
+//! let message = TopicPrefixedMessage::new(b"BBO ".to_vec(), b"payload".to_vec());
+//! let encoded = message.encode();
+//! assert_eq!(encoded, b"BBO payload");
+//!
+//! let decoded = TopicPrefixedMessage::decode(&encoded, 4);
+//! assert_eq!(decoded, message);
+//! ```
+
+use std::error::Error;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use super::traits::{AsyncSubscriber, Stream, StreamExt};
+use crate::data::serializer::StructSerializer;
+
+/// A message made of a topic and a payload.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TopicPrefixedMessage {
+    /// The topic, which the subscribers filter the messages with.
+    pub topic: Vec<u8>,
+
+    /// The payload after the topic.
+    pub payload: Vec<u8>,
+}
+
+impl TopicPrefixedMessage {
+    /// Create a message of `topic` with `payload`.
+    pub fn new(topic: Vec<u8>, payload: Vec<u8>) -> Self {
+        Self { topic, payload }
+    }
+
+    /// Encode the message as the topic followed by the payload.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(self.topic.len() + self.payload.len());
+        encoded.extend_from_slice(&self.topic);
+        encoded.extend_from_slice(&self.payload);
+
+        encoded
+    }
+
+    /// Decode a message whose first `topic_len` bytes are the topic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `topic_len` is greater than the length of `bytes`.
+    pub fn decode(bytes: &[u8], topic_len: usize) -> Self {
+        let (topic, payload) = bytes.split_at(topic_len);
+
+        Self::new(topic.to_vec(), payload.to_vec())
+    }
+}
+
+/// The publisher publishing the structures `T` under a fixed topic.
+///
+/// Each structure is serialized, prefixed with the topic, and written
+/// as a single message.
+#[derive(Debug)]
+pub struct TypedTopicPublisher<T, P> {
+    inner: P,
+    topic: Vec<u8>,
+    _structure: PhantomData<fn(&T)>,
+}
+
+impl<T, P> TypedTopicPublisher<T, P>
+where
+    T: StructSerializer,
+    <T as StructSerializer>::Err: Error + Send + Sync + 'static,
+    P: Write,
+{
+    /// Wrap a publisher, which should be bound, publishing under `topic`.
+    pub fn new(inner: P, topic: impl Into<Vec<u8>>) -> Self {
+        Self {
+            inner,
+            topic: topic.into(),
+            _structure: PhantomData,
+        }
+    }
+
+    /// Serialize `data`, and publish it under the topic.
+    pub fn send(&mut self, data: &T) -> TopicMessageResult<()> {
+        let mut message = self.topic.clone();
+        data.serialize(&mut message)
+            .map_err(|e| TopicMessageError::SerializeFailed(Box::new(e)))?;
+
+        self.inner
+            .write_all(&message)
+            .map_err(TopicMessageError::WriteFailed)?;
+        self.inner.flush().map_err(TopicMessageError::WriteFailed)
+    }
+
+    /// Get the topic that the structures are published under.
+    pub fn topic(&self) -> &[u8] {
+        &self.topic
+    }
+
+    /// Get the reference to the underlying publisher.
+    pub fn get_ref(&self) -> &P {
+        &self.inner
+    }
+
+    /// Get the mutable reference to the underlying publisher.
+    pub fn get_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+
+    /// Unwrap this publisher, returning the underlying publisher.
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+}
+
+/// The extension methods of [`AsyncSubscriber`].
+pub trait SubscriberExt: AsyncSubscriber {
+    /// Yield only the messages prefixed by `topic`, split into
+    /// [`TopicPrefixedMessage`]s.
+    ///
+    /// The subscriber should have subscribed `topic`, or a prefix of it.
+    fn filter_topic(self, topic: &[u8]) -> FilterTopic<Self>
+    where
+        Self: Sized,
+    {
+        FilterTopic {
+            inner: self,
+            topic: topic.to_vec(),
+        }
+    }
+}
+
+impl<S: AsyncSubscriber> SubscriberExt for S {}
+
+/// The stream returned by [`SubscriberExt::filter_topic`].
+#[derive(Debug)]
+pub struct FilterTopic<S> {
+    inner: S,
+    topic: Vec<u8>,
+}
+
+impl<S> FilterTopic<S> {
+    /// Unwrap this stream, returning the underlying subscriber.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Stream for FilterTopic<S>
+where
+    S: AsyncSubscriber + Unpin,
+{
+    type Item = Result<TopicPrefixedMessage, <S as AsyncSubscriber>::Err>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let message = match futures::ready!(self.inner.poll_next_unpin(cx)) {
+                Some(Ok(message)) => message,
+                Some(Err(e)) => return Poll::Ready(Some(Err(e))),
+                None => return Poll::Ready(None),
+            };
+
+            if message.starts_with(&self.topic) {
+                let message = TopicPrefixedMessage::decode(&message, self.topic.len());
+                return Poll::Ready(Some(Ok(message)));
+            }
+        }
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TopicMessageError {
+    #[error("failed to serialize the structure: {0}")]
+    SerializeFailed(Box<dyn Error + Send + Sync>),
+
+    #[error("failed to write the message: {0}")]
+    WriteFailed(std::io::Error),
+}
+
+pub type TopicMessageResult<T> = Result<T, TopicMessageError>;
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{SubscriberExt, TopicPrefixedMessage, TypedTopicPublisher};
+    use crate::data::serializer::StructSerializer;
+    use crate::message::memory::{MemoryPublisher, MemorySubscriber};
+    use crate::message::traits::{Bind, Connect, StreamExt, Subscribe};
+
+    /// A structure serialized as its bytes.
+    struct Raw(&'static [u8]);
+
+    impl StructSerializer for Raw {
+        type Err = std::io::Error;
+
+        fn serialize(&self, writer: &mut impl Write) -> Result<(), Self::Err> {
+            writer.write_all(self.0)
+        }
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let message = TopicPrefixedMessage::new(b"BBO ".to_vec(), b"\x01\x02".to_vec());
+
+        let encoded = message.encode();
+        assert_eq!(encoded, b"BBO \x01\x02");
+        assert_eq!(TopicPrefixedMessage::decode(&encoded, 4), message);
+
+        // The topic and the payload can be empty.
+        let empty_topic = TopicPrefixedMessage::decode(b"payload", 0);
+        assert_eq!(empty_topic.topic, b"");
+        assert_eq!(empty_topic.payload, b"payload");
+        let empty_payload = TopicPrefixedMessage::decode(b"BBO ", 4);
+        assert_eq!(empty_payload.topic, b"BBO ");
+        assert_eq!(empty_payload.payload, b"");
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_decode_topic_too_long() {
+        TopicPrefixedMessage::decode(b"BBO", 4);
+    }
+
+    #[tokio::test]
+    async fn test_publish_filter_topic() {
+        const URI: &str = "inproc-mem://test_publish_filter_topic";
+
+        let mut publisher = MemoryPublisher::new().unwrap();
+        publisher.bind(URI).unwrap();
+        let mut subscriber = MemorySubscriber::new().unwrap();
+        subscriber.connect(URI).unwrap();
+        subscriber.subscribe_all().unwrap();
+
+        let mut publisher = TypedTopicPublisher::new(publisher, b"BBO ".to_vec());
+        assert_eq!(publisher.topic(), b"BBO ");
+        publisher.get_mut().write_all(b"TRADE 1").unwrap();
+        publisher.send(&Raw(b"2")).unwrap();
+        publisher.get_mut().write_all(b"BB 3").unwrap();
+        publisher.send(&Raw(b"4")).unwrap();
+
+        let mut stream = subscriber.filter_topic(b"BBO ");
+        for payload in [b"2", b"4"] {
+            let message = stream.next().await.unwrap().unwrap();
+            assert_eq!(message.topic, b"BBO ");
+            assert_eq!(message.payload, payload);
+        }
+    }
+}
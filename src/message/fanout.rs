@@ -0,0 +1,611 @@
+//! The publishers writing every message to multiple publishers.
+//!
+//! For example, you can publish the same stream to a local `ipc://`
+//! endpoint and a remote `tcp://` endpoint with a single write.
+//! See [`FanoutPublisher`] and [`AsyncFanoutPublisher`].
+//!
+//! # Example
+//!
+//! ```
+//! use wmjtyd_libstock::message::fanout::{FanoutPolicy, FanoutPublisher, SyncFanoutChild};
+//! use wmjtyd_libstock::message::traits::Write;
+//!
+//! fn publish(local: impl SyncFanoutChild + 'static, remote: impl SyncFanoutChild + 'static) {
+//!     let mut publisher = FanoutPublisher::new(FanoutPolicy::BestEffort);
+//!
+//!     let local = publisher.push(local);
+//!     let remote = publisher.push(remote);
+//!     publisher.bind(local, "ipc:///tmp/libstock-bbo").expect("failed to bind");
+//!     publisher.bind(remote, "tcp://0.0.0.0:5555").expect("failed to bind");
+//!
+//!     publisher
+//!         .write_all(b"TEST Hello, World")
+//!         .expect("failed to write");
+//! }
+//! ```
+
+use std::error::Error;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::{fmt, io};
+
+use super::traits::{AsyncPublisher, AsyncWrite, Bind, SyncPublisher, Write};
+
+/// The boxed error of binding or unbinding a child.
+pub type BoxedError = Box<dyn Error + Send + Sync>;
+
+/// How a fanout publisher handles the failed children.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FanoutPolicy {
+    /// Stop at the first failed child, and return its error.
+    ///
+    /// The children after the failed one may not receive the message.
+    FailFast,
+    /// Deliver the message to all the children, and return
+    /// a [`PartialFailure`] if any of them failed.
+    BestEffort,
+}
+
+/// The result of each child when any of them failed
+/// under [`FanoutPolicy::BestEffort`].
+///
+/// It is wrapped in the [`io::Error`] returned by the publisher.
+/// Use [`PartialFailure::from_io_error`] to retrieve it.
+#[derive(Debug)]
+pub struct PartialFailure {
+    /// The result of each child, in the order of their indexes.
+    pub results: Vec<io::Result<()>>,
+}
+
+impl PartialFailure {
+    /// Get the [`PartialFailure`] wrapped in `error`, if any.
+    pub fn from_io_error(error: &io::Error) -> Option<&Self> {
+        error.get_ref()?.downcast_ref()
+    }
+
+    /// The indexes of the failed children.
+    pub fn failed_indexes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, result)| result.is_err().then_some(index))
+    }
+
+    /// Return an error wrapping the results if any of them failed.
+    fn check(results: Vec<io::Result<()>>) -> io::Result<()> {
+        if results.iter().all(Result::is_ok) {
+            Ok(())
+        } else {
+            Err(io::Error::new(io::ErrorKind::Other, Self { results }))
+        }
+    }
+}
+
+impl fmt::Display for PartialFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} endpoints failed",
+            self.failed_indexes().count(),
+            self.results.len()
+        )
+    }
+}
+
+impl Error for PartialFailure {}
+
+/// The synchronous child of [`FanoutPublisher`].
+///
+/// It is implemented for all the [`SyncPublisher`]s with [`Bind`].
+pub trait SyncFanoutChild: SyncPublisher + Send {
+    /// Bind this child to the specified URI.
+    fn bind_child(&mut self, uri: &str) -> Result<(), BoxedError>;
+
+    /// Unbind this child from the specified URI.
+    fn unbind_child(&mut self, uri: &str) -> Result<(), BoxedError>;
+}
+
+impl<T> SyncFanoutChild for T
+where
+    T: SyncPublisher + Bind + Send,
+    <T as Bind>::Err: Error + Send + Sync + 'static,
+{
+    fn bind_child(&mut self, uri: &str) -> Result<(), BoxedError> {
+        Ok(self.bind(uri)?)
+    }
+
+    fn unbind_child(&mut self, uri: &str) -> Result<(), BoxedError> {
+        Ok(self.unbind(uri)?)
+    }
+}
+
+/// The asynchronous child of [`AsyncFanoutPublisher`].
+///
+/// It is implemented for all the [`AsyncPublisher`]s with [`Bind`].
+pub trait AsyncFanoutChild: AsyncPublisher + Send + Unpin {
+    /// Bind this child to the specified URI.
+    fn bind_child(&mut self, uri: &str) -> Result<(), BoxedError>;
+
+    /// Unbind this child from the specified URI.
+    fn unbind_child(&mut self, uri: &str) -> Result<(), BoxedError>;
+}
+
+impl<T> AsyncFanoutChild for T
+where
+    T: AsyncPublisher + Bind + Send + Unpin,
+    <T as Bind>::Err: Error + Send + Sync + 'static,
+{
+    fn bind_child(&mut self, uri: &str) -> Result<(), BoxedError> {
+        Ok(self.bind(uri)?)
+    }
+
+    fn unbind_child(&mut self, uri: &str) -> Result<(), BoxedError> {
+        Ok(self.unbind(uri)?)
+    }
+}
+
+/// The synchronous publisher writing every message to all its children.
+///
+/// The children are addressed by the indexes returned by
+/// [`push`](Self::push), for example, to bind each of them
+/// to a different URI.
+pub struct FanoutPublisher {
+    children: Vec<Box<dyn SyncFanoutChild>>,
+    policy: FanoutPolicy,
+}
+
+impl FanoutPublisher {
+    /// Create a fanout publisher without any children.
+    pub fn new(policy: FanoutPolicy) -> Self {
+        Self {
+            children: Vec::new(),
+            policy,
+        }
+    }
+
+    /// Add a child, and return its index.
+    pub fn push(&mut self, child: impl SyncFanoutChild + 'static) -> usize {
+        self.children.push(Box::new(child));
+        self.children.len() - 1
+    }
+
+    /// The number of the children.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Are there no children?
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Bind the child at `index` to the specified URI.
+    pub fn bind(&mut self, index: usize, uri: &str) -> FanoutResult<()> {
+        child_mut(&mut self.children, index)?
+            .bind_child(uri)
+            .map_err(|source| FanoutError::BindFailed { index, source })
+    }
+
+    /// Unbind the child at `index` from the specified URI.
+    pub fn unbind(&mut self, index: usize, uri: &str) -> FanoutResult<()> {
+        child_mut(&mut self.children, index)?
+            .unbind_child(uri)
+            .map_err(|source| FanoutError::UnbindFailed { index, source })
+    }
+
+    /// Run `op` on each child according to the policy.
+    fn for_each_child(
+        &mut self,
+        mut op: impl FnMut(&mut dyn SyncFanoutChild) -> io::Result<()>,
+    ) -> io::Result<()> {
+        match self.policy {
+            FanoutPolicy::FailFast => self
+                .children
+                .iter_mut()
+                .try_for_each(|child| op(child.as_mut())),
+            FanoutPolicy::BestEffort => PartialFailure::check(
+                self.children
+                    .iter_mut()
+                    .map(|child| op(child.as_mut()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl fmt::Debug for FanoutPublisher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FanoutPublisher")
+            .field("children", &self.children.len())
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl Write for FanoutPublisher {
+    /// Write the whole `buf` to each child.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.for_each_child(|child| child.write_all(buf))?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.for_each_child(|child| child.flush())
+    }
+}
+
+impl SyncPublisher for FanoutPublisher {}
+
+/// The progress of a child in the ongoing operation
+/// of [`AsyncFanoutPublisher`].
+#[derive(Debug, Default)]
+struct ChildProgress {
+    /// The bytes written to this child.
+    written: usize,
+    /// The result of this child if it has finished.
+    result: Option<io::Result<()>>,
+}
+
+/// The asynchronous publisher writing every message to all its children.
+///
+/// The children are addressed by the indexes returned by
+/// [`push`](Self::push), for example, to bind each of them
+/// to a different URI.
+///
+/// A pending write continues from where each child stopped,
+/// so it must be polled again with the same buffer, as
+/// [`write_all`](super::traits::AsyncWriteExt::write_all) does.
+pub struct AsyncFanoutPublisher {
+    children: Vec<Box<dyn AsyncFanoutChild>>,
+    policy: FanoutPolicy,
+    progress: Vec<ChildProgress>,
+}
+
+impl AsyncFanoutPublisher {
+    /// Create a fanout publisher without any children.
+    pub fn new(policy: FanoutPolicy) -> Self {
+        Self {
+            children: Vec::new(),
+            policy,
+            progress: Vec::new(),
+        }
+    }
+
+    /// Add a child, and return its index.
+    pub fn push(&mut self, child: impl AsyncFanoutChild + 'static) -> usize {
+        self.children.push(Box::new(child));
+        self.children.len() - 1
+    }
+
+    /// The number of the children.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+
+    /// Are there no children?
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    /// Bind the child at `index` to the specified URI.
+    pub fn bind(&mut self, index: usize, uri: &str) -> FanoutResult<()> {
+        child_mut(&mut self.children, index)?
+            .bind_child(uri)
+            .map_err(|source| FanoutError::BindFailed { index, source })
+    }
+
+    /// Unbind the child at `index` from the specified URI.
+    pub fn unbind(&mut self, index: usize, uri: &str) -> FanoutResult<()> {
+        child_mut(&mut self.children, index)?
+            .unbind_child(uri)
+            .map_err(|source| FanoutError::UnbindFailed { index, source })
+    }
+
+    /// Poll `op` on each unfinished child according to the policy,
+    /// until all the children are finished.
+    fn poll_each_child(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut op: impl FnMut(
+            Pin<&mut Box<dyn AsyncFanoutChild>>,
+            &mut Context<'_>,
+            &mut usize,
+        ) -> Poll<io::Result<()>>,
+    ) -> Poll<io::Result<()>> {
+        if self.progress.len() != self.children.len() {
+            self.progress.resize_with(self.children.len(), Default::default);
+        }
+
+        let mut pending = false;
+        let mut failed = None;
+
+        for (child, progress) in self.children.iter_mut().zip(&mut self.progress) {
+            if progress.result.is_some() {
+                continue;
+            }
+
+            match op(Pin::new(child), cx, &mut progress.written) {
+                Poll::Pending => pending = true,
+                Poll::Ready(Err(e)) if self.policy == FanoutPolicy::FailFast => {
+                    failed = Some(e);
+                    break;
+                }
+                Poll::Ready(result) => progress.result = Some(result),
+            }
+        }
+
+        if let Some(e) = failed {
+            self.progress.clear();
+            return Poll::Ready(Err(e));
+        }
+
+        if pending {
+            return Poll::Pending;
+        }
+
+        let results = self
+            .progress
+            .drain(..)
+            .map(|progress| progress.result.unwrap_or(Ok(())))
+            .collect();
+
+        Poll::Ready(PartialFailure::check(results))
+    }
+}
+
+impl fmt::Debug for AsyncFanoutPublisher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncFanoutPublisher")
+            .field("children", &self.children.len())
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+impl AsyncWrite for AsyncFanoutPublisher {
+    /// Write the whole `buf` to each child.
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, io::Error>> {
+        let result = self.get_mut().poll_each_child(cx, |mut child, cx, written| {
+            while *written < buf.len() {
+                match futures::ready!(child.as_mut().poll_write(cx, &buf[*written..])) {
+                    Ok(0) => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                    Ok(n) => *written += n,
+                    Err(e) => return Poll::Ready(Err(e)),
+                }
+            }
+
+            Poll::Ready(Ok(()))
+        });
+
+        result.map_ok(|_| buf.len())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        self.get_mut()
+            .poll_each_child(cx, |child, cx, _| child.poll_flush(cx))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+        self.get_mut()
+            .poll_each_child(cx, |child, cx, _| child.poll_shutdown(cx))
+    }
+}
+
+impl AsyncPublisher for AsyncFanoutPublisher {}
+
+fn child_mut<C: ?Sized>(children: &mut [Box<C>], index: usize) -> FanoutResult<&mut C> {
+    children
+        .get_mut(index)
+        .map(AsMut::as_mut)
+        .ok_or(FanoutError::NoSuchChild(index))
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum FanoutError {
+    #[error("no such child: {0}")]
+    NoSuchChild(usize),
+
+    #[error("failed to bind the child {index}: {source}")]
+    BindFailed { index: usize, source: BoxedError },
+
+    #[error("failed to unbind the child {index}: {source}")]
+    UnbindFailed { index: usize, source: BoxedError },
+}
+
+pub type FanoutResult<T> = Result<T, FanoutError>;
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+
+    use super::{
+        AsyncFanoutPublisher,
+        FanoutError,
+        FanoutPolicy,
+        FanoutPublisher,
+        PartialFailure,
+    };
+    use crate::message::traits::{
+        AsyncPublisher,
+        AsyncWrite,
+        AsyncWriteExt,
+        Bind,
+        SyncPublisher,
+        Write,
+    };
+
+    /// The publisher recording the messages and the bound URIs.
+    #[derive(Clone, Debug, Default)]
+    struct MockPublisher {
+        broken: bool,
+        sent: Arc<Mutex<Vec<Vec<u8>>>>,
+        bound: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockPublisher {
+        fn broken() -> Self {
+            Self {
+                broken: true,
+                ..Default::default()
+            }
+        }
+
+        fn sent(&self) -> Vec<Vec<u8>> {
+            self.sent.lock().unwrap().clone()
+        }
+
+        fn send(&self, buf: &[u8]) -> io::Result<usize> {
+            if self.broken {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+
+            self.sent.lock().unwrap().push(buf.to_vec());
+            Ok(buf.len())
+        }
+    }
+
+    impl Write for MockPublisher {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.send(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl AsyncWrite for MockPublisher {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            Poll::Ready(self.send(buf))
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    impl Bind for MockPublisher {
+        type Err = io::Error;
+
+        fn bind(&mut self, uri: &str) -> Result<(), Self::Err> {
+            self.bound.lock().unwrap().push(uri.to_string());
+            Ok(())
+        }
+
+        fn unbind(&mut self, uri: &str) -> Result<(), Self::Err> {
+            self.bound.lock().unwrap().retain(|bound| bound != uri);
+            Ok(())
+        }
+    }
+
+    impl SyncPublisher for MockPublisher {}
+
+    impl AsyncPublisher for MockPublisher {}
+
+    #[test]
+    fn test_fail_fast() {
+        let healthy = MockPublisher::default();
+        let mut publisher = FanoutPublisher::new(FanoutPolicy::FailFast);
+        publisher.push(MockPublisher::broken());
+        publisher.push(healthy.clone());
+
+        let error = publisher.write_all(b"TEST Hello").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+        assert!(PartialFailure::from_io_error(&error).is_none());
+        assert!(healthy.sent().is_empty());
+    }
+
+    #[test]
+    fn test_best_effort() {
+        let healthy = MockPublisher::default();
+        let mut publisher = FanoutPublisher::new(FanoutPolicy::BestEffort);
+        publisher.push(MockPublisher::broken());
+        publisher.push(healthy.clone());
+
+        let error = publisher.write_all(b"TEST Hello").unwrap_err();
+        let failure = PartialFailure::from_io_error(&error).unwrap();
+        assert_eq!(failure.failed_indexes().collect::<Vec<_>>(), [0]);
+        assert!(failure.results[1].is_ok());
+        assert_eq!(healthy.sent(), [b"TEST Hello"]);
+    }
+
+    #[test]
+    fn test_all_healthy() {
+        let children = [MockPublisher::default(), MockPublisher::default()];
+        let mut publisher = FanoutPublisher::new(FanoutPolicy::FailFast);
+        for child in &children {
+            publisher.push(child.clone());
+        }
+
+        publisher.write_all(b"TEST Hello").unwrap();
+        publisher.flush().unwrap();
+
+        for child in &children {
+            assert_eq!(child.sent(), [b"TEST Hello"]);
+        }
+    }
+
+    #[test]
+    fn test_bind_per_child() {
+        let (local, remote) = (MockPublisher::default(), MockPublisher::default());
+        let mut publisher = FanoutPublisher::new(FanoutPolicy::FailFast);
+        let local_index = publisher.push(local.clone());
+        let remote_index = publisher.push(remote.clone());
+
+        publisher.bind(local_index, "ipc:///tmp/fanout").unwrap();
+        publisher.bind(remote_index, "tcp://0.0.0.0:5555").unwrap();
+        assert_eq!(*local.bound.lock().unwrap(), ["ipc:///tmp/fanout"]);
+        assert_eq!(*remote.bound.lock().unwrap(), ["tcp://0.0.0.0:5555"]);
+
+        publisher.unbind(remote_index, "tcp://0.0.0.0:5555").unwrap();
+        assert!(remote.bound.lock().unwrap().is_empty());
+
+        assert!(matches!(
+            publisher.bind(2, "ipc:///tmp/fanout"),
+            Err(FanoutError::NoSuchChild(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_async_fail_fast() {
+        let healthy = MockPublisher::default();
+        let mut publisher = AsyncFanoutPublisher::new(FanoutPolicy::FailFast);
+        publisher.push(MockPublisher::broken());
+        publisher.push(healthy.clone());
+
+        let error = publisher.write_all(b"TEST Hello").await.unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+        assert!(healthy.sent().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_async_best_effort() {
+        let healthy = MockPublisher::default();
+        let mut publisher = AsyncFanoutPublisher::new(FanoutPolicy::BestEffort);
+        publisher.push(MockPublisher::broken());
+        publisher.push(healthy.clone());
+
+        let error = publisher.write_all(b"TEST Hello").await.unwrap_err();
+        let failure = PartialFailure::from_io_error(&error).unwrap();
+        assert_eq!(failure.failed_indexes().collect::<Vec<_>>(), [0]);
+        assert_eq!(healthy.sent(), [b"TEST Hello"]);
+
+        publisher.flush().await.unwrap();
+    }
+}
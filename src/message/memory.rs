@@ -0,0 +1,570 @@
+//! An in-process subscriber and publisher backed by [`flume`] channels.
+//!
+//! - [`MemoryPublisher`]: Supports [`SyncPublisher`]
+//!   and [`AsyncPublisher`].
+//! - [`MemorySubscriber`]: Supports [`SyncSubscriber`]
+//!   and [`AsyncSubscriber`].
+//!
+//! The endpoints are kept by a process-wide broker and keyed by
+//! their URIs, which are in the form of `inproc-mem://name`.
+//! Like ZeroMQ, a subscriber can connect before the publisher binds,
+//! receives only the messages prefixed by one of its topics, and
+//! misses the messages published before it connected.
+//!
+//! It needs no sockets or files, so it is handy for testing the code
+//! built on the [traits](super::traits).
+//!
+//! # Example
+//!
+//! ```
+//! use wmjtyd_libstock::message::memory::{MemoryPublisher, MemorySubscriber};
+//! use wmjtyd_libstock::message::traits::{Bind, Connect, Subscribe, Write};
+//!
+//! let mut publisher = MemoryPublisher::new().expect("failed to create publisher");
+//! let mut subscriber = MemorySubscriber::new().expect("failed to create subscriber");
+//!
+//! publisher.bind("inproc-mem://example").expect("failed to bind");
+//! subscriber.connect("inproc-mem://example").expect("failed to connect");
+//! subscriber.subscribe(b"TEST").expect("failed to subscribe");
+//!
+//! publisher.write_all(b"OTHER Hello, World").expect("failed to write");
+//! publisher.write_all(b"TEST Hello, World").expect("failed to write");
+//!
+//! let message = subscriber.next().expect("no data inside");
+//! assert_eq!(message.expect("data receiving failed"), b"TEST Hello, World");
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use flume::r#async::RecvStream;
+use flume::{Receiver, RecvTimeoutError, Sender};
+use once_cell::sync::Lazy;
+
+use super::traits::{
+    AsyncPublisher,
+    AsyncRead,
+    AsyncSubscriber,
+    AsyncWrite,
+    Bind,
+    Connect,
+    Publisher,
+    Read,
+    Stream,
+    StreamExt,
+    Subscribe,
+    SubscribeStreamItem,
+    Subscriber,
+    SyncPublisher,
+    SyncSubscriber,
+    Write,
+};
+use super::{remove_subscription, MessageError, MessageResult};
+
+/// The scheme of the in-memory URIs.
+pub const SCHEME: &str = "inproc-mem://";
+
+/// The endpoints of the broker, keyed by their URIs.
+static BROKER: Lazy<Mutex<HashMap<String, SharedEndpoint>>> = Lazy::new(Default::default);
+
+/// The counter to give each subscriber a unique ID.
+static SUBSCRIBER_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// The topics a subscriber subscribed, shared with the broker.
+type Topics = Arc<RwLock<Vec<Vec<u8>>>>;
+
+/// An endpoint shared by the broker and the bound publisher.
+///
+/// The publisher writes to its endpoints without locking the broker.
+type SharedEndpoint = Arc<Mutex<Endpoint>>;
+
+#[derive(Default)]
+struct Endpoint {
+    /// Whether a publisher is bound to this endpoint.
+    bound: bool,
+    /// The subscribers connected to this endpoint.
+    peers: Vec<Peer>,
+}
+
+impl Endpoint {
+    fn is_unused(&self) -> bool {
+        !self.bound && self.peers.is_empty()
+    }
+}
+
+struct Peer {
+    id: usize,
+    sender: Sender<Vec<u8>>,
+    topics: Topics,
+}
+
+impl Peer {
+    /// Send `message` to this peer if it subscribed the topic.
+    ///
+    /// It returns `false` if the subscriber has gone.
+    fn deliver(&self, message: &[u8]) -> bool {
+        let subscribed = self
+            .topics
+            .read()
+            .expect("topics poisoned")
+            .iter()
+            .any(|topic| message.starts_with(topic));
+
+        !subscribed || self.sender.send(message.to_vec()).is_ok()
+    }
+}
+
+/// Check that `uri` is in the form of `inproc-mem://name`.
+fn check_uri(uri: &str) -> MemoryResult<()> {
+    match uri.strip_prefix(SCHEME) {
+        Some(name) if !name.is_empty() => Ok(()),
+        _ => Err(MemoryError::InvalidUri(uri.to_string())),
+    }
+}
+
+/// Call `f` with the endpoint of `uri`, creating it if absent.
+///
+/// The broker is locked during the call, so the endpoint
+/// can't be removed before `f` registers on it.
+fn with_endpoint<T>(uri: &str, f: impl FnOnce(&SharedEndpoint, &mut Endpoint) -> T) -> T {
+    let mut broker = BROKER.lock().expect("broker poisoned");
+    let shared = broker.entry(uri.to_string()).or_default();
+    let mut endpoint = shared.lock().expect("endpoint poisoned");
+
+    f(shared, &mut endpoint)
+}
+
+/// Remove the endpoint of `uri` from the broker if it is unused.
+fn remove_if_unused(uri: &str) {
+    let mut broker = BROKER.lock().expect("broker poisoned");
+
+    if let Some(endpoint) = broker.get(uri) {
+        if endpoint.lock().expect("endpoint poisoned").is_unused() {
+            broker.remove(uri);
+        }
+    }
+}
+
+/// The in-memory publisher.
+#[derive(Default)]
+pub struct MemoryPublisher {
+    /// The endpoints this publisher bound to.
+    endpoints: Vec<(String, SharedEndpoint)>,
+}
+
+impl MemoryPublisher {
+    /// Create a publisher bound to nothing.
+    pub fn new() -> MessageResult<Self> {
+        Ok(Self::default())
+    }
+
+    /// Send `buf` to the subscribers of all the bound endpoints.
+    fn publish(&self, buf: &[u8]) {
+        for (_, endpoint) in &self.endpoints {
+            let mut endpoint = endpoint.lock().expect("endpoint poisoned");
+            endpoint.peers.retain(|peer| peer.deliver(buf));
+        }
+    }
+}
+
+impl std::fmt::Debug for MemoryPublisher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let uris = self.endpoints.iter().map(|(uri, _)| uri);
+
+        f.debug_struct("MemoryPublisher")
+            .field("uris", &uris.collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Bind for MemoryPublisher {
+    type Err = MessageError;
+
+    fn bind(&mut self, uri: &str) -> Result<(), Self::Err> {
+        check_uri(uri)?;
+
+        let shared = with_endpoint(uri, |shared, endpoint| {
+            if endpoint.bound {
+                return Err(MemoryError::AddressInUse(uri.to_string()));
+            }
+
+            endpoint.bound = true;
+            Ok(shared.clone())
+        })?;
+        self.endpoints.push((uri.to_string(), shared));
+
+        Ok(())
+    }
+
+    fn unbind(&mut self, uri: &str) -> Result<(), Self::Err> {
+        let index = self
+            .endpoints
+            .iter()
+            .position(|(bound, _)| bound == uri)
+            .ok_or_else(|| MemoryError::NoSuchEndpoint(uri.to_string()))?;
+
+        let (_, endpoint) = self.endpoints.swap_remove(index);
+        endpoint.lock().expect("endpoint poisoned").bound = false;
+        remove_if_unused(uri);
+
+        Ok(())
+    }
+}
+
+impl Drop for MemoryPublisher {
+    fn drop(&mut self) {
+        // Release the addresses, so the others can bind to them.
+        for (uri, endpoint) in std::mem::take(&mut self.endpoints) {
+            endpoint.lock().expect("endpoint poisoned").bound = false;
+            remove_if_unused(&uri);
+        }
+    }
+}
+
+impl Write for MemoryPublisher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.publish(buf);
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // The messages are delivered on write.
+        Ok(())
+    }
+}
+
+impl AsyncWrite for MemoryPublisher {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        // The channels are unbounded, so the write never blocks.
+        self.publish(buf);
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Publisher for MemoryPublisher {}
+
+impl SyncPublisher for MemoryPublisher {}
+
+impl AsyncPublisher for MemoryPublisher {}
+
+/// The in-memory subscriber.
+pub struct MemorySubscriber {
+    id: usize,
+    sender: Sender<Vec<u8>>,
+    receiver: Receiver<Vec<u8>>,
+    stream: RecvStream<'static, Vec<u8>>,
+    topics: Topics,
+    /// The timeout of [`Read`] and [`Iterator`].
+    recv_timeout: Option<Duration>,
+}
+
+impl MemorySubscriber {
+    /// Create a subscriber connected to nothing.
+    pub fn new() -> MessageResult<Self> {
+        let (sender, receiver) = flume::unbounded();
+
+        Ok(Self {
+            id: SUBSCRIBER_ID.fetch_add(1, Ordering::Relaxed),
+            sender,
+            stream: receiver.clone().into_stream(),
+            receiver,
+            topics: Default::default(),
+            recv_timeout: None,
+        })
+    }
+
+    /// Receive a message, respecting the timeout set by
+    /// [`SyncSubscriber::set_recv_timeout`].
+    fn recv(&self) -> MemoryResult<Vec<u8>> {
+        // We hold a sender, so the channel never disconnects.
+        match self.recv_timeout {
+            Some(timeout) => self
+                .receiver
+                .recv_timeout(timeout)
+                .map_err(|_| MemoryError::RecvTimeout),
+            None => Ok(self.receiver.recv().expect("we hold a sender")),
+        }
+    }
+}
+
+impl std::fmt::Debug for MemorySubscriber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemorySubscriber")
+            .field("id", &self.id)
+            .field("topics", &self.topics)
+            .field("recv_timeout", &self.recv_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Connect for MemorySubscriber {
+    type Err = MessageError;
+
+    fn connect(&mut self, uri: &str) -> Result<(), Self::Err> {
+        check_uri(uri)?;
+
+        with_endpoint(uri, |_, endpoint| {
+            if !endpoint.peers.iter().any(|peer| peer.id == self.id) {
+                endpoint.peers.push(Peer {
+                    id: self.id,
+                    sender: self.sender.clone(),
+                    topics: self.topics.clone(),
+                });
+            }
+        });
+
+        Ok(())
+    }
+
+    fn disconnect(&mut self, uri: &str) -> Result<(), Self::Err> {
+        let endpoint = BROKER
+            .lock()
+            .expect("broker poisoned")
+            .get(uri)
+            .cloned()
+            .ok_or_else(|| MemoryError::NoSuchEndpoint(uri.to_string()))?;
+
+        let mut endpoint = endpoint.lock().expect("endpoint poisoned");
+        let peers = endpoint.peers.len();
+        endpoint.peers.retain(|peer| peer.id != self.id);
+        if endpoint.peers.len() == peers {
+            return Err(MemoryError::NoSuchEndpoint(uri.to_string()).into());
+        }
+        drop(endpoint);
+
+        remove_if_unused(uri);
+
+        Ok(())
+    }
+}
+
+impl Drop for MemorySubscriber {
+    fn drop(&mut self) {
+        let mut broker = BROKER.lock().expect("broker poisoned");
+
+        broker.retain(|_, endpoint| {
+            let mut endpoint = endpoint.lock().expect("endpoint poisoned");
+            endpoint.peers.retain(|peer| peer.id != self.id);
+
+            !endpoint.is_unused()
+        });
+    }
+}
+
+impl Subscribe for MemorySubscriber {
+    type Err = MessageError;
+
+    fn subscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
+        self.topics
+            .write()
+            .expect("topics poisoned")
+            .push(topic.to_vec());
+
+        Ok(())
+    }
+
+    fn unsubscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
+        let mut topics = self.topics.write().expect("topics poisoned");
+
+        // Like ZeroMQ, remove a single subscription of this topic.
+        remove_subscription(&mut topics, topic)
+    }
+
+    fn subscriptions(&self) -> Vec<Vec<u8>> {
+        self.topics.read().expect("topics poisoned").clone()
+    }
+}
+
+impl Read for MemorySubscriber {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let message = self
+            .recv()
+            .map_err(|e| io::Error::new(io::ErrorKind::WouldBlock, e))?;
+
+        // Like ZeroMQ, the part exceeding `buf` is truncated.
+        let len = message.len().min(buf.len());
+        buf[..len].copy_from_slice(&message[..len]);
+
+        Ok(len)
+    }
+}
+
+impl Iterator for MemorySubscriber {
+    type Item = SubscribeStreamItem<<Self as SyncSubscriber>::Err>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.recv().map_err(Into::into))
+    }
+}
+
+impl AsyncRead for MemorySubscriber {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let message = futures::ready!(self.stream.poll_next_unpin(cx));
+
+        if let Some(message) = message {
+            let len = message.len().min(buf.remaining());
+            buf.put_slice(&message[..len]);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Stream for MemorySubscriber {
+    type Item = SubscribeStreamItem<<Self as AsyncSubscriber>::Err>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream
+            .poll_next_unpin(cx)
+            .map(|message| message.map(Ok))
+    }
+}
+
+impl Subscriber for MemorySubscriber {}
+
+impl SyncSubscriber for MemorySubscriber {
+    type Err = MessageError;
+
+    fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, Self::Err> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(message) => Ok(Some(message)),
+            Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => Ok(None),
+        }
+    }
+
+    fn set_recv_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Self::Err> {
+        self.recv_timeout = timeout;
+
+        Ok(())
+    }
+}
+
+impl AsyncSubscriber for MemorySubscriber {
+    type Err = MessageError;
+}
+
+/// The errors of [`Memory`](self).
+#[derive(thiserror::Error, Debug)]
+pub enum MemoryError {
+    /// When the URI is not in the form of `inproc-mem://name`.
+    #[error("Invalid in-memory URI: {0}")]
+    InvalidUri(String),
+
+    /// When another publisher has bound to the URI.
+    #[error("Address in use: {0}")]
+    AddressInUse(String),
+
+    /// When we didn't bind or connect to the URI.
+    #[error("No such endpoint: {0}")]
+    NoSuchEndpoint(String),
+
+    /// When no message arrives within the receiving timeout.
+    #[error("Receiving timed out")]
+    RecvTimeout,
+}
+
+/// The result type of [`Memory`](self).
+pub type MemoryResult<T> = Result<T, MemoryError>;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{MemoryError, MemoryPublisher, MemorySubscriber};
+    use crate::message::traits::{Bind, Connect, Subscribe, SyncSubscriber, Write};
+    use crate::message::MessageError;
+
+    #[test]
+    fn test_topic_filter() {
+        const URI: &str = "inproc-mem://test_topic_filter";
+
+        let mut publisher = MemoryPublisher::new().unwrap();
+        let mut subscriber = MemorySubscriber::new().unwrap();
+        let mut unsubscribed = MemorySubscriber::new().unwrap();
+
+        // Connecting before binding is allowed.
+        subscriber.connect(URI).unwrap();
+        unsubscribed.connect(URI).unwrap();
+        publisher.bind(URI).unwrap();
+        subscriber.subscribe(b"BTC").unwrap();
+        subscriber.subscribe(b"ETH").unwrap();
+
+        for message in [&b"BTC 1"[..], b"DOGE 2", b"ETH 3", b"BT 4"] {
+            publisher.write_all(message).unwrap();
+        }
+
+        let timeout = Duration::from_millis(10);
+        assert_eq!(subscriber.recv_timeout(timeout).unwrap().unwrap(), b"BTC 1");
+        assert_eq!(subscriber.recv_timeout(timeout).unwrap().unwrap(), b"ETH 3");
+        assert_eq!(subscriber.recv_timeout(timeout).unwrap(), None);
+        assert_eq!(unsubscribed.recv_timeout(timeout).unwrap(), None);
+
+        subscriber.unsubscribe(b"BTC").unwrap();
+        publisher.write_all(b"BTC 5").unwrap();
+        assert_eq!(subscriber.recv_timeout(timeout).unwrap(), None);
+    }
+
+    #[test]
+    fn test_endpoints() {
+        const URI: &str = "inproc-mem://test_endpoints";
+
+        let mut publisher = MemoryPublisher::new().unwrap();
+        publisher.bind(URI).unwrap();
+        assert!(matches!(
+            MemoryPublisher::new().unwrap().bind(URI),
+            Err(MessageError::MemoryError(MemoryError::AddressInUse(_)))
+        ));
+
+        // The address is released on unbinding.
+        publisher.unbind(URI).unwrap();
+        MemoryPublisher::new().unwrap().bind(URI).unwrap();
+
+        assert!(matches!(
+            publisher.bind("ipc:///tmp/libstock_memory.ipc"),
+            Err(MessageError::MemoryError(MemoryError::InvalidUri(_)))
+        ));
+
+        let mut subscriber = MemorySubscriber::new().unwrap();
+        assert!(matches!(
+            subscriber.disconnect(URI),
+            Err(MessageError::MemoryError(MemoryError::NoSuchEndpoint(_)))
+        ));
+    }
+
+    #[test]
+    fn test_set_recv_timeout() {
+        let mut subscriber = MemorySubscriber::new().unwrap();
+        subscriber.connect("inproc-mem://test_set_recv_timeout").unwrap();
+        subscriber
+            .set_recv_timeout(Some(Duration::from_millis(10)))
+            .unwrap();
+
+        assert!(matches!(
+            subscriber.next(),
+            Some(Err(MessageError::MemoryError(MemoryError::RecvTimeout)))
+        ));
+    }
+}
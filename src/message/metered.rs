@@ -0,0 +1,469 @@
+//! The metrics of the publishers and subscribers.
+//!
+//! Wrap any publisher or subscriber with [`Metered`] to count
+//! the messages, the bytes and the errors passing through it,
+//! in both the synchronous and asynchronous paths.
+//!
+//! # Example
+//!
+//! ```
+//! use wmjtyd_libstock::message::metered::{MessageEvent, Metered};
+//! use wmjtyd_libstock::message::traits::{SyncPublisher, Write};
+//!
+//! fn publish(publisher: impl SyncPublisher) {
+//!     let mut publisher = Metered::new(publisher).with_hook(|event| {
+//!         if let MessageEvent::Error = event {
+//!             eprintln!("failed to publish a message");
+//!         }
+//!     });
+//!
+//!     publisher.write_all(b"TEST Hello, World").ok();
+//!     println!("{:?}", publisher.stats());
+//! }
+//! ```
+
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use super::traits::{
+    AsyncPublisher,
+    AsyncRead,
+    AsyncSubscriber,
+    AsyncWrite,
+    Bind,
+    Connect,
+    Publisher,
+    Read,
+    Stream,
+    Subscribe,
+    SubscribeStreamItem,
+    Subscriber,
+    SyncPublisher,
+    SyncSubscriber,
+    Write,
+};
+
+/// The snapshot of the counters of a [`Metered`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MessageStats {
+    /// The number of the sent messages.
+    pub msgs_sent: u64,
+    /// The number of the sent bytes.
+    pub bytes_sent: u64,
+    /// The number of the received messages.
+    pub msgs_recv: u64,
+    /// The number of the received bytes.
+    pub bytes_recv: u64,
+    /// The number of the failed reads and writes.
+    pub errors: u64,
+}
+
+/// The event reported to the hook of [`Metered`].
+///
+/// It can be used to bridge the metrics to the other systems,
+/// such as Prometheus.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MessageEvent {
+    /// A message of the specified bytes has been sent.
+    Sent(usize),
+    /// A message of the specified bytes has been received.
+    Received(usize),
+    /// A read or write has failed.
+    Error,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    msgs_sent: AtomicU64,
+    bytes_sent: AtomicU64,
+    msgs_recv: AtomicU64,
+    bytes_recv: AtomicU64,
+    errors: AtomicU64,
+}
+
+/// The shared counters of a [`Metered`].
+///
+/// It is cheap to clone, so the stats can still be read after
+/// the [`Metered`] is moved to another thread.
+#[derive(Clone, Debug, Default)]
+pub struct MessageCounters(Arc<Counters>);
+
+impl MessageCounters {
+    /// Take a snapshot of the counters.
+    pub fn stats(&self) -> MessageStats {
+        MessageStats {
+            msgs_sent: self.0.msgs_sent.load(Ordering::Relaxed),
+            bytes_sent: self.0.bytes_sent.load(Ordering::Relaxed),
+            msgs_recv: self.0.msgs_recv.load(Ordering::Relaxed),
+            bytes_recv: self.0.bytes_recv.load(Ordering::Relaxed),
+            errors: self.0.errors.load(Ordering::Relaxed),
+        }
+    }
+
+    fn record(&self, event: MessageEvent) {
+        match event {
+            MessageEvent::Sent(len) => {
+                self.0.msgs_sent.fetch_add(1, Ordering::Relaxed);
+                self.0.bytes_sent.fetch_add(len as u64, Ordering::Relaxed);
+            }
+            MessageEvent::Received(len) => {
+                self.0.msgs_recv.fetch_add(1, Ordering::Relaxed);
+                self.0.bytes_recv.fetch_add(len as u64, Ordering::Relaxed);
+            }
+            MessageEvent::Error => {
+                self.0.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+type MessageHook = Arc<dyn Fn(MessageEvent) + Send + Sync>;
+
+/// The publisher or subscriber counting the messages passing through it.
+///
+/// Each successful write (or read) is counted as a message, as
+/// Nanomsg and ZeroMQ send (or receive) a message in each call.
+pub struct Metered<T> {
+    inner: T,
+    counters: MessageCounters,
+    hook: Option<MessageHook>,
+}
+
+impl<T> Metered<T> {
+    /// Wrap `inner` with the new counters.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            counters: MessageCounters::default(),
+            hook: None,
+        }
+    }
+
+    /// Call `hook` on every counted event.
+    pub fn with_hook(mut self, hook: impl Fn(MessageEvent) + Send + Sync + 'static) -> Self {
+        self.hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Take a snapshot of the counters.
+    pub fn stats(&self) -> MessageStats {
+        self.counters.stats()
+    }
+
+    /// Get the shared counters.
+    pub fn counters(&self) -> MessageCounters {
+        self.counters.clone()
+    }
+
+    /// Get the inner publisher or subscriber.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn record(&self, event: MessageEvent) {
+        self.counters.record(event);
+
+        if let Some(hook) = &self.hook {
+            hook(event);
+        }
+    }
+
+    /// Record the result of a write.
+    fn record_sent<E>(&self, result: &Result<usize, E>) {
+        self.record(match result {
+            Ok(len) => MessageEvent::Sent(*len),
+            Err(_) => MessageEvent::Error,
+        });
+    }
+
+    /// Record the result of a read.
+    fn record_recv<E>(&self, result: &Result<usize, E>) {
+        self.record(match result {
+            Ok(len) => MessageEvent::Received(*len),
+            Err(_) => MessageEvent::Error,
+        });
+    }
+
+    /// Record the received message.
+    fn record_item<E>(&self, item: &SubscribeStreamItem<E>) {
+        self.record_recv(&item.as_ref().map(Vec::len));
+    }
+}
+
+impl<T> AsRef<T> for Metered<T> {
+    fn as_ref(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> AsMut<T> for Metered<T> {
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+}
+
+impl<T: Write> Write for Metered<T> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let result = self.inner.write(buf);
+        self.record_sent(&result);
+
+        result
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: Read> Read for Metered<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let result = self.inner.read(buf);
+        self.record_recv(&result);
+
+        result
+    }
+}
+
+impl<T, E> Iterator for Metered<T>
+where
+    T: Iterator<Item = SubscribeStreamItem<E>>,
+{
+    type Item = SubscribeStreamItem<E>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.inner.next()?;
+        self.record_item(&item);
+
+        Some(item)
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Metered<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<Result<usize, std::io::Error>> {
+        let result = Pin::new(&mut self.inner).poll_write(cx, buf);
+
+        if let Poll::Ready(result) = &result {
+            self.record_sent(result);
+        }
+
+        result
+    }
+
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), std::io::Error>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), std::io::Error>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Metered<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let previously_filled_len = buf.filled().len();
+        let result = Pin::new(&mut self.inner).poll_read(cx, buf);
+
+        if let Poll::Ready(result) = &result {
+            let len = buf.filled().len() - previously_filled_len;
+            self.record_recv(&result.as_ref().map(|_| len));
+        }
+
+        result
+    }
+}
+
+impl<T, E> Stream for Metered<T>
+where
+    T: Stream<Item = SubscribeStreamItem<E>> + Unpin,
+{
+    type Item = SubscribeStreamItem<E>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let result = Pin::new(&mut self.inner).poll_next(cx);
+
+        if let Poll::Ready(Some(item)) = &result {
+            self.record_item(item);
+        }
+
+        result
+    }
+}
+
+impl<T: Bind> Bind for Metered<T> {
+    type Err = T::Err;
+
+    fn bind(&mut self, uri: &str) -> Result<(), Self::Err> {
+        self.inner.bind(uri)
+    }
+
+    fn unbind(&mut self, uri: &str) -> Result<(), Self::Err> {
+        self.inner.unbind(uri)
+    }
+}
+
+impl<T: Connect> Connect for Metered<T> {
+    type Err = T::Err;
+
+    fn connect(&mut self, uri: &str) -> Result<(), Self::Err> {
+        self.inner.connect(uri)
+    }
+
+    fn disconnect(&mut self, uri: &str) -> Result<(), Self::Err> {
+        self.inner.disconnect(uri)
+    }
+}
+
+impl<T: Subscribe> Subscribe for Metered<T> {
+    type Err = T::Err;
+
+    fn subscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
+        self.inner.subscribe(topic)
+    }
+
+    fn unsubscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
+        self.inner.unsubscribe(topic)
+    }
+
+    fn subscriptions(&self) -> Vec<Vec<u8>> {
+        self.inner.subscriptions()
+    }
+}
+
+impl<T: Publisher> Publisher for Metered<T> {}
+
+impl<T: Subscriber> Subscriber for Metered<T> {}
+
+impl<T: SyncPublisher> SyncPublisher for Metered<T> {}
+
+impl<T: SyncSubscriber> SyncSubscriber for Metered<T> {
+    type Err = T::Err;
+
+    fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, Self::Err> {
+        let result = self.inner.recv_timeout(timeout);
+
+        match &result {
+            Ok(Some(message)) => self.record(MessageEvent::Received(message.len())),
+            Ok(None) => {}
+            Err(_) => self.record(MessageEvent::Error),
+        }
+
+        result
+    }
+
+    fn set_recv_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Self::Err> {
+        self.inner.set_recv_timeout(timeout)
+    }
+}
+
+impl<T: AsyncPublisher + Unpin> AsyncPublisher for Metered<T> {}
+
+impl<T: AsyncSubscriber + Unpin> AsyncSubscriber for Metered<T> {
+    type Err = T::Err;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use super::{MessageEvent, MessageStats, Metered};
+    use crate::message::traits::{AsyncReadExt, AsyncWriteExt, Write};
+
+    #[test]
+    fn test_count_sent() {
+        let mut publisher = Metered::new(Vec::<u8>::new());
+
+        for i in 0..10 {
+            publisher.write_all(&vec![0; i]).unwrap();
+        }
+
+        assert_eq!(
+            publisher.stats(),
+            MessageStats {
+                // `write_all` with an empty buffer writes nothing.
+                msgs_sent: 9,
+                bytes_sent: 45,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_count_received() {
+        let messages = vec![
+            Ok(b"TEST Hello".to_vec()),
+            Err(std::io::Error::from(std::io::ErrorKind::Other)),
+            Ok(b"TEST World!".to_vec()),
+        ];
+
+        let subscriber = Metered::new(messages.into_iter());
+        let counters = subscriber.counters();
+        assert_eq!(subscriber.count(), 3);
+
+        assert_eq!(
+            counters.stats(),
+            MessageStats {
+                msgs_recv: 2,
+                bytes_recv: 21,
+                errors: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_count_async() {
+        let mut publisher = Metered::new(Vec::<u8>::new());
+
+        // `Vec<u8>` is also a `Write`, so call the async methods explicitly.
+        for _ in 0..5 {
+            AsyncWriteExt::write_all(&mut publisher, b"hello").await.unwrap();
+        }
+
+        let mut subscriber = Metered::new(&publisher.as_ref()[..]);
+        let mut buf = [0; 5];
+        while AsyncReadExt::read(&mut subscriber, &mut buf).await.unwrap() != 0 {}
+
+        assert_eq!(publisher.stats().msgs_sent, 5);
+        assert_eq!(publisher.stats().bytes_sent, 25);
+        // The last read reaches EOF.
+        assert_eq!(subscriber.stats().msgs_recv, 6);
+        assert_eq!(subscriber.stats().bytes_recv, 25);
+    }
+
+    #[test]
+    fn test_hook() {
+        let events = Arc::new(AtomicUsize::new(0));
+        let mut publisher = Metered::new(Vec::<u8>::new()).with_hook({
+            let events = events.clone();
+
+            move |event| {
+                assert_eq!(event, MessageEvent::Sent(5));
+                events.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        for _ in 0..3 {
+            publisher.write_all(b"hello").unwrap();
+        }
+
+        assert_eq!(events.load(Ordering::Relaxed), 3);
+    }
+}
@@ -112,6 +112,14 @@ pub enum NanomsgError {
     /// When the `.unsubscribe()` operation failed.
     #[error("Failed to unsubscribe: {0}")]
     UnsubscribeFailed(nanomsg::Error),
+
+    /// When we can't set an option of the socket.
+    #[error("Failed to set socket option: {0}")]
+    SetOptionFailed(nanomsg::Error),
+
+    /// When no peer is present at an address within the timeout.
+    #[error("Endpoint unreachable: {0}")]
+    EndpointUnreachable(String),
 }
 
 /// The result type of [`Nanomsg`](self).
@@ -244,4 +252,83 @@ mod tests {
             }
         }
     }
+
+    mod message_size {
+        use std::time::Duration;
+
+        use crate::message::nanomsg::{NanomsgPublisher, NanomsgSubscriber};
+        use crate::message::traits::{Bind, Connect, StreamExt, Subscribe, Write};
+
+        /// The messages of the edge sizes.
+        fn messages() -> Vec<Vec<u8>> {
+            vec![Vec::new(), vec![1; 4096], vec![2; 4096 * 2 + 1]]
+        }
+
+        /// Keep publishing [`messages`] to `uri` in another thread, as
+        /// the messages published before the subscriber connected are lost.
+        fn publish_forever(uri: &'static str) {
+            let mut publisher = NanomsgPublisher::new().expect("failed to create publisher");
+            publisher.bind(uri).expect("failed to bind");
+
+            std::thread::spawn(move || loop {
+                for message in messages() {
+                    // `write_all` sends nothing for an empty message.
+                    let written = publisher.write(&message).expect("failed to write");
+                    assert_eq!(written, message.len());
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            });
+        }
+
+        fn subscribe(uri: &str) -> NanomsgSubscriber {
+            let mut subscriber = NanomsgSubscriber::new().expect("failed to create subscriber");
+            subscriber.connect(uri).expect("failed to connect");
+            subscriber.subscribe(b"").expect("failed to subscribe");
+
+            subscriber
+        }
+
+        /// Check `message` is one of the `pending` messages, and remove it.
+        fn check_received(pending: &mut Vec<Vec<u8>>, message: Vec<u8>) {
+            assert!(
+                messages().contains(&message),
+                "unexpected message of {} bytes",
+                message.len()
+            );
+            pending.retain(|m| m != &message);
+        }
+
+        #[test]
+        fn test_iter() {
+            const IPC_ADDR: &str = "ipc:///tmp/libstock_nanomsg_message_size_iter.ipc";
+            publish_forever(IPC_ADDR);
+
+            let mut pending = messages();
+            for message in subscribe(IPC_ADDR) {
+                check_received(&mut pending, message.expect("failed to receive"));
+
+                if pending.is_empty() {
+                    break;
+                }
+            }
+        }
+
+        #[tokio::test(flavor = "multi_thread")]
+        async fn test_stream() {
+            const IPC_ADDR: &str = "ipc:///tmp/libstock_nanomsg_message_size_stream.ipc";
+            publish_forever(IPC_ADDR);
+
+            let mut subscriber = Box::pin(subscribe(IPC_ADDR));
+            let mut pending = messages();
+            while let Some(message) = subscriber.next().await {
+                check_received(&mut pending, message.expect("failed to receive"));
+
+                if pending.is_empty() {
+                    break;
+                }
+            }
+
+            assert!(pending.is_empty());
+        }
+    }
 }
@@ -3,11 +3,13 @@ macro_rules! construct_nanomsg {
         name = $name:ident,
         socket_type = $socket_type:expr,
         category = $category:path
+        $(, fields = { $($field:ident: $field_ty:ty),* $(,)? })?
     ) => {
         #[doc = concat!("The [`", stringify!($category), "`] of Nanomsg.")]
         pub struct $name {
             socket: nanomsg::Socket,
             endpoint: std::collections::HashMap<String, nanomsg::Endpoint>,
+            $($($field: $field_ty,)*)?
         }
 
         impl $name {
@@ -16,6 +18,7 @@ macro_rules! construct_nanomsg {
                     socket: nanomsg::Socket::new($socket_type)
                         .map_err($crate::message::nanomsg::NanomsgError::CreateSocketFailed)?,
                     endpoint: Default::default(),
+                    $($($field: Default::default(),)*)?
                 })
             }
         }
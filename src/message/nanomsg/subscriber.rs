@@ -1,5 +1,5 @@
-use std::mem::MaybeUninit;
 use std::task::Poll;
+use std::time::{Duration, Instant};
 
 use super::common::construct_nanomsg;
 use super::NanomsgError;
@@ -14,14 +14,78 @@ use crate::message::traits::{
     Subscriber,
     SyncSubscriber,
 };
-use crate::message::MessageError;
+use crate::message::{remove_subscription, MessageError, MessageResult};
+
+/// The interval between two probes in [`NanomsgSubscriber::connect_verified`].
+const PROBE_INTERVAL: Duration = Duration::from_millis(10);
 
 construct_nanomsg!(
     name = NanomsgSubscriber,
     socket_type = nanomsg::Protocol::Sub,
-    category = Subscriber
+    category = Subscriber,
+    fields = {
+        // The persistent receive timeout set by `set_recv_timeout`.
+        recv_timeout: Option<Duration>,
+        // The subscribed topics, as Nanomsg can't list them.
+        subscriptions: Vec<Vec<u8>>,
+    }
 );
 
+impl NanomsgSubscriber {
+    /// Connect to `uri` after the peer is present.
+    ///
+    /// [`Connect::connect`] returns immediately, as Nanomsg connects
+    /// in the background. Nanomsg reports no connection events, so
+    /// this method probes `uri` until something accepts the connection,
+    /// and returns [`NanomsgError::EndpointUnreachable`] if nothing
+    /// does within `timeout`.
+    ///
+    /// Only `ipc://` and `tcp://` endpoints can be probed. The other
+    /// endpoints are connected without verification.
+    pub fn connect_verified(&mut self, uri: &str, timeout: Duration) -> MessageResult<()> {
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            match probe_endpoint(uri) {
+                Some(true) | None => break,
+                Some(false) if Instant::now() >= deadline => {
+                    return Err(NanomsgError::EndpointUnreachable(uri.to_string()).into());
+                }
+                Some(false) => std::thread::sleep(PROBE_INTERVAL),
+            }
+        }
+
+        self.connect(uri)
+    }
+}
+
+/// Check if something accepts the connections at `uri`.
+///
+/// It returns `None` if the transport of `uri` can't be probed.
+fn probe_endpoint(uri: &str) -> Option<bool> {
+    if let Some(path) = uri.strip_prefix("ipc://") {
+        return probe_ipc(path);
+    }
+
+    if let Some(addr) = uri.strip_prefix("tcp://") {
+        // Strip the local interface of `tcp://<interface>;<address>`.
+        let addr = addr.rsplit(';').next().unwrap_or(addr);
+        return Some(std::net::TcpStream::connect(addr).is_ok());
+    }
+
+    None
+}
+
+#[cfg(unix)]
+fn probe_ipc(path: &str) -> Option<bool> {
+    Some(std::os::unix::net::UnixStream::connect(path).is_ok())
+}
+
+#[cfg(not(unix))]
+fn probe_ipc(_: &str) -> Option<bool> {
+    None
+}
+
 impl Connect for NanomsgSubscriber {
     type Err = MessageError;
 
@@ -58,23 +122,54 @@ impl Iterator for NanomsgSubscriber {
     type Item = SubscribeStreamItem<<Self as SyncSubscriber>::Err>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        // The buffer can place at most 4096 bytes (4KB) of data.
-        // It is enough for our finance data.
-        let mut buf = [0; 4096];
-        let result = self.read(&mut buf).map_err(NanomsgError::ReadFailed);
+        let mut buf = Vec::new();
 
-        match result {
-            Ok(len) => {
-                let owned_data = buf[..len].to_vec();
-                Some(Ok(owned_data))
-            }
-            Err(e) => Some(Err(e.into())),
+        // Nanomsg receives a whole message of any size with `read_to_end`.
+        match self.socket.read_to_end(&mut buf) {
+            Ok(_) => Some(Ok(buf)),
+            Err(e) => Some(Err(NanomsgError::ReadFailed(e).into())),
         }
     }
 }
 
 impl SyncSubscriber for NanomsgSubscriber {
     type Err = MessageError;
+
+    fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, Self::Err> {
+        self.socket
+            .set_receive_timeout(timeout_to_millis(Some(timeout)))
+            .map_err(NanomsgError::SetOptionFailed)?;
+
+        let mut buf = Vec::new();
+        let result = self.socket.read_to_end(&mut buf);
+
+        // Restore the persistent receive timeout.
+        self.socket
+            .set_receive_timeout(timeout_to_millis(self.recv_timeout))
+            .map_err(NanomsgError::SetOptionFailed)?;
+
+        match result {
+            Ok(_) => Ok(Some(buf)),
+            Err(e) if e.kind() == std::io::ErrorKind::TimedOut => Ok(None),
+            Err(e) => Err(NanomsgError::ReadFailed(e).into()),
+        }
+    }
+
+    fn set_recv_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Self::Err> {
+        self.socket
+            .set_receive_timeout(timeout_to_millis(timeout))
+            .map_err(NanomsgError::SetOptionFailed)?;
+
+        self.recv_timeout = timeout;
+        Ok(())
+    }
+}
+
+/// Convert the timeout to the milliseconds that Nanomsg accepts.
+///
+/// `None` (waiting forever) is `-1`.
+fn timeout_to_millis(timeout: Option<Duration>) -> isize {
+    timeout.map_or(-1, |t| t.as_millis().try_into().unwrap_or(isize::MAX))
 }
 
 impl AsyncRead for NanomsgSubscriber {
@@ -110,25 +205,20 @@ impl Stream for NanomsgSubscriber {
     type Item = SubscribeStreamItem<<Self as AsyncSubscriber>::Err>;
 
     fn poll_next(
-        self: std::pin::Pin<&mut Self>,
+        mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> Poll<Option<Self::Item>> {
-        // The buffer can place at most 4096 bytes (4KB) of data.
-        // It is enough for our finance data.
-        let mut buf = create_uninitiated_array::<u8, 4096>();
-        let mut tokio_read_buf = tokio::io::ReadBuf::uninit(&mut buf);
-
-        let read_result = self
-            .poll_read(cx, &mut tokio_read_buf)
-            .map_err(NanomsgError::ReadFailed);
-
-        match read_result {
-            Poll::Ready(Ok(())) => {
-                let filled_buf = tokio_read_buf.filled().to_vec();
-                Poll::Ready(Some(Ok(filled_buf)))
+        let mut buf = Vec::new();
+
+        // Non-blocking read of a whole message, so an empty message
+        // is distinguishable from no message.
+        match self.socket.nb_read_to_end(&mut buf) {
+            Ok(_) => Poll::Ready(Some(Ok(buf))),
+            Err(nanomsg::Error::TryAgain) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
             }
-            Poll::Ready(Err(e)) => Poll::Ready(Some(Err(e.into()))),
-            Poll::Pending => Poll::Pending,
+            Err(e) => Poll::Ready(Some(Err(NanomsgError::ReadFailed(e.into()).into()))),
         }
     }
 }
@@ -141,23 +231,27 @@ impl Subscribe for NanomsgSubscriber {
     type Err = MessageError;
 
     fn subscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
-        Ok(self
-            .socket
+        self.socket
             .subscribe(topic)
-            .map_err(NanomsgError::SubscribeFailed)?)
+            .map_err(NanomsgError::SubscribeFailed)?;
+
+        self.subscriptions.push(topic.to_vec());
+        Ok(())
     }
 
     fn unsubscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
-        Ok(self
-            .socket
+        if !self.subscriptions.iter().any(|t| t == topic) {
+            return Err(MessageError::NotSubscribed(topic.to_vec()));
+        }
+
+        self.socket
             .unsubscribe(topic)
-            .map_err(NanomsgError::UnsubscribeFailed)?)
+            .map_err(NanomsgError::UnsubscribeFailed)?;
+
+        remove_subscription(&mut self.subscriptions, topic)
     }
-}
 
-/// Copy from https://doc.rust-lang.org/nightly/core/mem/union.MaybeUninit.html#method.uninit_array.
-/// We use it because of it is not stablized in Rust 1.62 yet 😞
-fn create_uninitiated_array<T, const N: usize>() -> [MaybeUninit<T>; N] {
-    // SAFETY: An uninitialized `[MaybeUninit<_>; LEN]` is valid.
-    unsafe { MaybeUninit::<[MaybeUninit<T>; N]>::uninit().assume_init() }
+    fn subscriptions(&self) -> Vec<Vec<u8>> {
+        self.subscriptions.clone()
+    }
 }
@@ -0,0 +1,383 @@
+//! The router dispatching the messages of a subscriber by their topics.
+//!
+//! A subscriber may subscribe many topics, for example, the BBO, trades
+//! and klines of several symbols. [`SubscriptionRouter`] matches the topic
+//! prefix of each message once, strips the topic, and forwards the payload
+//! to the [`Sink`] of the route.
+//!
+//! When the prefixes overlap, the longest matching prefix wins.
+//! For example, with the routes `b"bbo"` and `b"bbo.BTC"`, the message
+//! `b"bbo.BTCUSDT ..."` goes to `b"bbo.BTC"`, and the message
+//! `b"bbo.ETHUSDT ..."` goes to `b"bbo"`. The messages matching no route
+//! go to the default sink, if any.
+//!
+//! Note that the router does not subscribe the topics for you:
+//! the subscriber should have subscribed them (or `b""`) beforehand.
+//!
+//! # Example
+//!
+//! ```
+//! use wmjtyd_libstock::message::router::SubscriptionRouter;
+//!
+//! let router = SubscriptionRouter::new();
+//! let (bbo_tx, bbo_rx) = flume::unbounded();
+//! let (btc_tx, btc_rx) = flume::unbounded();
+//!
+//! router.route(b"bbo", bbo_tx);
+//! router.route(b"bbo.BTC", btc_tx);
+//!
+//! router.dispatch(b"bbo.BTCUSDT 1");
+//! router.dispatch(b"bbo.ETHUSDT 2");
+//!
+//! assert_eq!(btc_rx.recv().unwrap(), b"USDT 1");
+//! assert_eq!(bbo_rx.recv().unwrap(), b".ETHUSDT 2");
+//! ```
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use super::traits::{AsyncSubscriber, StreamExt, SyncSubscriber};
+use crate::flag::BinaryFlag;
+
+/// The destination of the routed payloads.
+pub enum Sink {
+    /// Call the callback with the payload.
+    Callback(Box<dyn FnMut(&[u8]) + Send>),
+
+    /// Send the payload to the channel.
+    Channel(flume::Sender<Vec<u8>>),
+}
+
+impl Sink {
+    /// Create a sink calling `callback` with the payload.
+    pub fn callback(callback: impl FnMut(&[u8]) + Send + 'static) -> Self {
+        Self::Callback(Box::new(callback))
+    }
+
+    fn deliver(&mut self, payload: &[u8]) {
+        match self {
+            Sink::Callback(callback) => callback(payload),
+            Sink::Channel(sender) => {
+                if sender.send(payload.to_vec()).is_err() {
+                    tracing::warn!("The receiver of a route has been dropped.");
+                }
+            }
+        }
+    }
+}
+
+impl From<flume::Sender<Vec<u8>>> for Sink {
+    fn from(sender: flume::Sender<Vec<u8>>) -> Self {
+        Self::Channel(sender)
+    }
+}
+
+impl std::fmt::Debug for Sink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sink::Callback(_) => f.write_str("Sink::Callback"),
+            Sink::Channel(_) => f.write_str("Sink::Channel"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct RouteTable {
+    routes: Vec<(Vec<u8>, Sink)>,
+    default: Option<Sink>,
+}
+
+impl RouteTable {
+    /// Find the sink with the longest prefix matching `message`,
+    /// and return the sink and the length of the prefix.
+    fn find(&mut self, message: &[u8]) -> Option<(&mut Sink, usize)> {
+        let route = self
+            .routes
+            .iter_mut()
+            .filter(|(prefix, _)| message.starts_with(prefix))
+            .max_by_key(|(prefix, _)| prefix.len());
+
+        match route {
+            Some((prefix, sink)) => Some((sink, prefix.len())),
+            None => self.default.as_mut().map(|sink| (sink, 0)),
+        }
+    }
+}
+
+/// The router dispatching the messages by their topic prefixes.
+///
+/// The clones share the same routes, so the routes can be added
+/// and removed while another thread or task is running the router.
+#[derive(Clone, Debug, Default)]
+pub struct SubscriptionRouter {
+    table: Arc<Mutex<RouteTable>>,
+}
+
+impl SubscriptionRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Route the messages starting with `prefix` to `sink`.
+    ///
+    /// If the route of `prefix` exists, its sink is replaced.
+    pub fn route(&self, prefix: &[u8], sink: impl Into<Sink>) {
+        let mut table = self.table();
+        let sink = sink.into();
+
+        match table.routes.iter_mut().find(|(p, _)| p == prefix) {
+            Some((_, existing)) => *existing = sink,
+            None => table.routes.push((prefix.to_vec(), sink)),
+        }
+    }
+
+    /// Remove the route of `prefix`.
+    ///
+    /// It returns `true` if the route existed.
+    pub fn unroute(&self, prefix: &[u8]) -> bool {
+        let mut table = self.table();
+        let len_before = table.routes.len();
+
+        table.routes.retain(|(p, _)| p != prefix);
+        table.routes.len() != len_before
+    }
+
+    /// Set the sink for the messages matching no route.
+    ///
+    /// The default sink receives the whole message.
+    /// If there is no default sink, such messages are dropped.
+    pub fn set_default(&self, sink: Option<Sink>) {
+        self.table().default = sink;
+    }
+
+    /// Dispatch a message to the sink of the longest matching prefix.
+    ///
+    /// It returns `false` if the message is dropped.
+    pub fn dispatch(&self, message: &[u8]) -> bool {
+        let mut table = self.table();
+
+        match table.find(message) {
+            Some((sink, prefix_len)) => {
+                sink.deliver(&message[prefix_len..]);
+                true
+            }
+            None => {
+                tracing::trace!("No route for the message; dropped.");
+                false
+            }
+        }
+    }
+
+    /// Dispatch the messages of `subscriber` until it ends.
+    ///
+    /// It stops at the first error of `subscriber`.
+    pub fn run<S: SyncSubscriber>(&self, subscriber: S) -> Result<(), S::Err> {
+        for message in subscriber {
+            self.dispatch(&message?);
+        }
+
+        Ok(())
+    }
+
+    /// Dispatch the messages of `subscriber` while `running` is set.
+    ///
+    /// The flag is checked every `poll_interval` at least,
+    /// so the thread can exit promptly.
+    pub fn run_while<S: SyncSubscriber>(
+        &self,
+        mut subscriber: S,
+        running: &BinaryFlag,
+        poll_interval: Duration,
+    ) -> Result<(), S::Err> {
+        while running.is_running() {
+            if let Some(message) = subscriber.recv_timeout(poll_interval)? {
+                self.dispatch(&message);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Dispatch the messages of the asynchronous `subscriber` until it ends.
+    ///
+    /// It stops at the first error of `subscriber`.
+    pub async fn run_async<S: AsyncSubscriber>(&self, subscriber: S) -> Result<(), S::Err> {
+        let mut subscriber = Box::pin(subscriber);
+
+        while let Some(message) = subscriber.next().await {
+            self.dispatch(&message?);
+        }
+
+        Ok(())
+    }
+
+    fn table(&self) -> std::sync::MutexGuard<'_, RouteTable> {
+        self.table.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::io::Read;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use super::{Sink, SubscriptionRouter};
+    use crate::message::traits::SyncSubscriber;
+
+    /// The subscriber yielding the prepared messages.
+    struct VecSubscriber(VecDeque<Vec<u8>>);
+
+    impl Read for VecSubscriber {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Ok(0)
+        }
+    }
+
+    impl Iterator for VecSubscriber {
+        type Item = Result<Vec<u8>, std::io::Error>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.pop_front().map(Ok)
+        }
+    }
+
+    impl SyncSubscriber for VecSubscriber {
+        type Err = std::io::Error;
+
+        fn recv_timeout(&mut self, _: Duration) -> Result<Option<Vec<u8>>, Self::Err> {
+            Ok(self.0.pop_front())
+        }
+
+        fn set_recv_timeout(&mut self, _: Option<Duration>) -> Result<(), Self::Err> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_longest_prefix_wins() {
+        let router = SubscriptionRouter::new();
+        let (bbo_tx, bbo_rx) = flume::unbounded();
+        let (btc_tx, btc_rx) = flume::unbounded();
+        let (default_tx, default_rx) = flume::unbounded();
+
+        // Register the longer one first to make sure
+        // the order of the routes does not matter.
+        router.route(b"bbo.BTC", btc_tx);
+        router.route(b"bbo", bbo_tx);
+        router.set_default(Some(default_tx.into()));
+
+        assert!(router.dispatch(b"bbo.BTCUSDT 1"));
+        assert!(router.dispatch(b"bbo.ETHUSDT 2"));
+        assert!(router.dispatch(b"trade.BTCUSDT 3"));
+
+        assert_eq!(btc_rx.try_recv().unwrap(), b"USDT 1");
+        assert_eq!(bbo_rx.try_recv().unwrap(), b".ETHUSDT 2");
+        assert_eq!(default_rx.try_recv().unwrap(), b"trade.BTCUSDT 3");
+        assert!(btc_rx.is_empty() && bbo_rx.is_empty() && default_rx.is_empty());
+    }
+
+    #[test]
+    fn test_unroute() {
+        let router = SubscriptionRouter::new();
+        let (bbo_tx, bbo_rx) = flume::unbounded();
+        let (btc_tx, btc_rx) = flume::unbounded();
+
+        router.route(b"bbo", bbo_tx);
+        router.route(b"bbo.BTC", btc_tx);
+
+        assert!(router.unroute(b"bbo.BTC"));
+        assert!(!router.unroute(b"bbo.BTC"));
+
+        router.dispatch(b"bbo.BTCUSDT 1");
+        assert_eq!(bbo_rx.try_recv().unwrap(), b".BTCUSDT 1");
+        assert!(btc_rx.is_empty());
+
+        // No default sink.
+        assert!(!router.dispatch(b"trade.BTCUSDT 2"));
+    }
+
+    #[test]
+    fn test_callback_sink() {
+        let router = SubscriptionRouter::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+
+        router.route(
+            b"kline ",
+            Sink::callback({
+                let received = received.clone();
+                move |payload| received.lock().unwrap().push(payload.to_vec())
+            }),
+        );
+
+        router.dispatch(b"kline 1m");
+        assert_eq!(*received.lock().unwrap(), vec![b"1m".to_vec()]);
+    }
+
+    #[test]
+    fn test_run_in_thread() {
+        let router = SubscriptionRouter::new();
+        let (trade_tx, trade_rx) = flume::unbounded();
+        router.route(b"trade ", trade_tx);
+
+        let subscriber = VecSubscriber(VecDeque::from([
+            b"trade 1".to_vec(),
+            b"bbo 2".to_vec(),
+            b"trade 3".to_vec(),
+        ]));
+
+        std::thread::spawn({
+            let router = router.clone();
+            move || router.run(subscriber)
+        })
+        .join()
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(
+            trade_rx.drain().collect::<Vec<_>>(),
+            vec![b"1".to_vec(), b"3".to_vec()]
+        );
+    }
+
+    #[cfg(feature = "nanomsg")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_run_async() {
+        use crate::message::nanomsg::{NanomsgPublisher, NanomsgSubscriber};
+        use crate::message::traits::{Bind, Connect, Subscribe, Write};
+
+        const IPC_ADDR: &str = "ipc:///tmp/libstock_router_run_async.ipc";
+
+        let mut publisher = NanomsgPublisher::new().expect("failed to create publisher");
+        publisher.bind(IPC_ADDR).expect("failed to bind");
+
+        let mut subscriber = NanomsgSubscriber::new().expect("failed to create subscriber");
+        subscriber.connect(IPC_ADDR).expect("failed to connect");
+        subscriber.subscribe(b"").expect("failed to subscribe");
+
+        let router = SubscriptionRouter::new();
+        let (bbo_tx, bbo_rx) = flume::unbounded();
+        router.route(b"bbo ", bbo_tx);
+
+        let task = tokio::task::spawn({
+            let router = router.clone();
+            async move { router.run_async(subscriber).await }
+        });
+
+        // The messages published before connected are dropped,
+        // so keep publishing until the router receives one.
+        std::thread::spawn(move || loop {
+            publisher.write_all(b"bbo hello").expect("failed to write");
+            std::thread::sleep(Duration::from_millis(50));
+        });
+
+        let payload = tokio::time::timeout(Duration::from_secs(5), bbo_rx.recv_async())
+            .await
+            .expect("timed out")
+            .unwrap();
+        assert_eq!(payload, b"hello");
+
+        task.abort();
+    }
+}
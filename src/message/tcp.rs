@@ -0,0 +1,694 @@
+//! The TCP subscriber and publisher, for connecting across machines.
+//!
+//! - [`TcpPublisher`]: Supports [`SyncPublisher`]
+//!   and [`AsyncPublisher`].
+//! - [`TcpSubscriber`]: Supports [`SyncSubscriber`]
+//!   and [`AsyncSubscriber`].
+//!
+//! The URIs are in the form of `tcp://host:port`. The publisher
+//! broadcasts each message to all the connected subscribers, as a frame
+//! of the 4-byte big-endian length followed by the message. Like ZeroMQ,
+//! a subscriber receives only the messages prefixed by one of its topics,
+//! and misses the messages published before it connected.
+//!
+//! The sockets are driven by a background runtime of this module,
+//! so both the synchronous and the asynchronous interfaces can be
+//! used with or without a tokio runtime.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use wmjtyd_libstock::message::tcp::{TcpPublisher, TcpSubscriber};
+//! use wmjtyd_libstock::message::traits::{Bind, Connect, Subscribe, Write};
+//!
+//! let mut publisher = TcpPublisher::new().expect("failed to create publisher");
+//! let mut subscriber = TcpSubscriber::new().expect("failed to create subscriber");
+//!
+//! publisher.bind("tcp://127.0.0.1:5555").expect("failed to bind");
+//! subscriber.connect("tcp://127.0.0.1:5555").expect("failed to connect");
+//! subscriber.subscribe(b"TEST").expect("failed to subscribe");
+//!
+//! publisher.write_all(b"TEST Hello, World").expect("failed to write");
+//!
+//! let message = subscriber.next().expect("no data inside");
+//! assert_eq!(message.expect("data receiving failed"), b"TEST Hello, World");
+//! ```
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use flume::r#async::RecvStream;
+use flume::{Receiver, Sender};
+use once_cell::sync::Lazy;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::runtime::Runtime;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+use super::traits::{
+    AsyncPublisher,
+    AsyncRead,
+    AsyncSubscriber,
+    AsyncWrite,
+    Bind,
+    Connect,
+    Publisher,
+    Read,
+    Stream,
+    StreamExt,
+    Subscribe,
+    SubscribeStreamItem,
+    Subscriber,
+    SyncPublisher,
+    SyncSubscriber,
+    Write,
+};
+use super::{remove_subscription, MessageError, MessageResult};
+
+/// The scheme of the TCP URIs.
+pub const SCHEME: &str = "tcp://";
+
+/// The number of the messages buffered for each subscriber.
+///
+/// A subscriber falling behind more than this loses the oldest messages.
+pub const CHANNEL_CAPACITY: usize = 1024;
+
+/// The maximum length of a frame the subscriber accepts.
+const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+/// The runtime driving the sockets.
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("libstock-tcp")
+        .enable_io()
+        .build()
+        .expect("failed to build the TCP runtime")
+});
+
+/// The topics a subscriber subscribed, shared with its connections.
+type Topics = Arc<RwLock<Vec<Vec<u8>>>>;
+
+/// The tasks of the connections a listener accepted.
+type Connections = Arc<Mutex<Vec<JoinHandle<()>>>>;
+
+/// Get the address of `uri`, which is in the form of `tcp://host:port`.
+fn parse_uri(uri: &str) -> TcpResult<&str> {
+    match uri.strip_prefix(SCHEME) {
+        Some(addr) if !addr.is_empty() => Ok(addr),
+        _ => Err(TcpError::InvalidUri(uri.to_string())),
+    }
+}
+
+/// Accept the subscribers from `listener`, and forward the messages to them.
+///
+/// The forwarding tasks are added to `connections`.
+async fn accept_subscribers(
+    listener: TcpListener,
+    sender: broadcast::Sender<Arc<Vec<u8>>>,
+    connections: Connections,
+) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, addr)) => {
+                tracing::debug!("Accepted the TCP subscriber {addr}.");
+                let connection = tokio::spawn(forward_messages(stream, sender.subscribe()));
+
+                let mut connections = connections.lock().expect("connections poisoned");
+                connections.retain(|connection| !connection.is_finished());
+                connections.push(connection);
+            }
+            Err(e) => tracing::warn!("Failed to accept a TCP subscriber: {e}"),
+        }
+    }
+}
+
+/// Write the messages from `receiver` to `stream` as frames.
+async fn forward_messages(
+    mut stream: TcpStream,
+    mut receiver: broadcast::Receiver<Arc<Vec<u8>>>,
+) {
+    stream.set_nodelay(true).ok();
+
+    loop {
+        let message = match receiver.recv().await {
+            Ok(message) => message,
+            Err(broadcast::error::RecvError::Lagged(count)) => {
+                tracing::warn!("A TCP subscriber lagged behind, dropping {count} messages.");
+                continue;
+            }
+            // The publisher has been dropped.
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        let mut frame = Vec::with_capacity(4 + message.len());
+        frame.extend_from_slice(&(message.len() as u32).to_be_bytes());
+        frame.extend_from_slice(&message);
+
+        if let Err(e) = stream.write_all(&frame).await {
+            tracing::debug!("The TCP subscriber has gone: {e}");
+            break;
+        }
+    }
+}
+
+/// Read the frames from `stream`, and send the subscribed ones to `sender`.
+async fn receive_messages(mut stream: TcpStream, sender: Sender<Vec<u8>>, topics: Topics) {
+    loop {
+        let len = match stream.read_u32().await {
+            Ok(len) => len as usize,
+            Err(e) => {
+                tracing::debug!("The TCP publisher has gone: {e}");
+                break;
+            }
+        };
+        if len > MAX_FRAME_LEN {
+            tracing::error!("The frame of {len} bytes is too large. Disconnecting.");
+            break;
+        }
+
+        let mut message = vec![0; len];
+        if let Err(e) = stream.read_exact(&mut message).await {
+            tracing::debug!("The TCP publisher has gone: {e}");
+            break;
+        }
+
+        let subscribed = topics
+            .read()
+            .expect("topics poisoned")
+            .iter()
+            .any(|topic| message.starts_with(topic));
+
+        if subscribed && sender.send(message).is_err() {
+            // The subscriber has been dropped.
+            break;
+        }
+    }
+}
+
+/// The TCP publisher.
+pub struct TcpPublisher {
+    sender: broadcast::Sender<Arc<Vec<u8>>>,
+    /// The listeners, keyed by their local addresses.
+    listeners: HashMap<SocketAddr, Listener>,
+}
+
+/// A listener of [`TcpPublisher`].
+struct Listener {
+    /// The task accepting the subscribers.
+    accepting: JoinHandle<()>,
+    /// The tasks forwarding the messages to the accepted subscribers.
+    connections: Connections,
+}
+
+impl Listener {
+    /// Stop accepting the subscribers, and close the accepted connections.
+    fn abort(&self) {
+        self.accepting.abort();
+
+        let mut connections = self.connections.lock().expect("connections poisoned");
+        for connection in connections.drain(..) {
+            connection.abort();
+        }
+    }
+}
+
+impl TcpPublisher {
+    /// Create a publisher bound to nothing.
+    pub fn new() -> MessageResult<Self> {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+
+        Ok(Self {
+            sender,
+            listeners: HashMap::new(),
+        })
+    }
+
+    /// Get the local addresses this publisher listens on.
+    ///
+    /// It is useful to get the port assigned when binding to port 0,
+    /// which is also the address to [`unbind`](Bind::unbind).
+    pub fn local_addrs(&self) -> Vec<SocketAddr> {
+        self.listeners.keys().copied().collect()
+    }
+
+    /// Send `buf` to all the connected subscribers.
+    fn publish(&self, buf: &[u8]) -> io::Result<()> {
+        if u32::try_from(buf.len()).is_err() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "the message is too large"));
+        }
+
+        // It fails if there is no subscriber, and then the message is dropped.
+        self.sender.send(Arc::new(buf.to_vec())).ok();
+
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for TcpPublisher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TcpPublisher")
+            .field("local_addrs", &self.local_addrs())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Bind for TcpPublisher {
+    type Err = MessageError;
+
+    fn bind(&mut self, uri: &str) -> Result<(), Self::Err> {
+        let addr = parse_uri(uri)?;
+
+        // Bind synchronously, so the errors are reported immediately.
+        let listener = std::net::TcpListener::bind(addr).map_err(TcpError::BindFailed)?;
+        listener
+            .set_nonblocking(true)
+            .map_err(TcpError::BindFailed)?;
+        let local_addr = listener.local_addr().map_err(TcpError::BindFailed)?;
+
+        let _guard = RUNTIME.enter();
+        let listener = TcpListener::from_std(listener).map_err(TcpError::BindFailed)?;
+        let connections = Connections::default();
+        let accepting = RUNTIME.spawn(accept_subscribers(
+            listener,
+            self.sender.clone(),
+            connections.clone(),
+        ));
+        self.listeners.insert(
+            local_addr,
+            Listener {
+                accepting,
+                connections,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Stop listening on `uri`, and close the connections accepted from it.
+    ///
+    /// `uri` is matched against the [`local_addrs`](TcpPublisher::local_addrs),
+    /// so unbind a listener bound to port 0 with its assigned port.
+    fn unbind(&mut self, uri: &str) -> Result<(), Self::Err> {
+        let listener = parse_uri(uri)?
+            .to_socket_addrs()
+            .into_iter()
+            .flatten()
+            .find_map(|addr| self.listeners.remove(&addr))
+            .ok_or_else(|| TcpError::NoSuchEndpoint(uri.to_string()))?;
+        listener.abort();
+
+        Ok(())
+    }
+}
+
+impl Drop for TcpPublisher {
+    fn drop(&mut self) {
+        for listener in self.listeners.values() {
+            listener.abort();
+        }
+    }
+}
+
+impl Write for TcpPublisher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.publish(buf)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // The messages are queued to the connections on write.
+        Ok(())
+    }
+}
+
+impl AsyncWrite for TcpPublisher {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        // The broadcast channel never blocks the sender.
+        Poll::Ready(self.publish(buf).map(|_| buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Publisher for TcpPublisher {}
+
+impl SyncPublisher for TcpPublisher {}
+
+impl AsyncPublisher for TcpPublisher {}
+
+/// The TCP subscriber.
+pub struct TcpSubscriber {
+    sender: Sender<Vec<u8>>,
+    receiver: Receiver<Vec<u8>>,
+    stream: RecvStream<'static, Vec<u8>>,
+    topics: Topics,
+    /// The connections of the connected URIs.
+    connections: HashMap<String, JoinHandle<()>>,
+    /// The timeout of [`Read`] and [`Iterator`].
+    recv_timeout: Option<Duration>,
+}
+
+impl TcpSubscriber {
+    /// Create a subscriber connected to nothing.
+    pub fn new() -> MessageResult<Self> {
+        let (sender, receiver) = flume::unbounded();
+
+        Ok(Self {
+            sender,
+            stream: receiver.clone().into_stream(),
+            receiver,
+            topics: Default::default(),
+            connections: HashMap::new(),
+            recv_timeout: None,
+        })
+    }
+
+    /// Receive a message, respecting the timeout set by
+    /// [`SyncSubscriber::set_recv_timeout`].
+    fn recv(&self) -> TcpResult<Vec<u8>> {
+        // We hold a sender, so the channel never disconnects.
+        match self.recv_timeout {
+            Some(timeout) => self
+                .receiver
+                .recv_timeout(timeout)
+                .map_err(|_| TcpError::RecvTimeout),
+            None => Ok(self.receiver.recv().expect("we hold a sender")),
+        }
+    }
+}
+
+impl std::fmt::Debug for TcpSubscriber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TcpSubscriber")
+            .field("uris", &self.connections.keys().collect::<Vec<_>>())
+            .field("topics", &self.topics)
+            .field("recv_timeout", &self.recv_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Connect for TcpSubscriber {
+    type Err = MessageError;
+
+    fn connect(&mut self, uri: &str) -> Result<(), Self::Err> {
+        let addr = parse_uri(uri)?;
+        if self.connections.contains_key(uri) {
+            return Ok(());
+        }
+
+        // Connect synchronously, so the errors are reported immediately.
+        let stream = std::net::TcpStream::connect(addr).map_err(TcpError::ConnectFailed)?;
+        stream
+            .set_nonblocking(true)
+            .map_err(TcpError::ConnectFailed)?;
+
+        let _guard = RUNTIME.enter();
+        let stream = TcpStream::from_std(stream).map_err(TcpError::ConnectFailed)?;
+        let handle = RUNTIME.spawn(receive_messages(
+            stream,
+            self.sender.clone(),
+            self.topics.clone(),
+        ));
+        self.connections.insert(uri.to_string(), handle);
+
+        Ok(())
+    }
+
+    fn disconnect(&mut self, uri: &str) -> Result<(), Self::Err> {
+        let handle = self
+            .connections
+            .remove(uri)
+            .ok_or_else(|| TcpError::NoSuchEndpoint(uri.to_string()))?;
+        handle.abort();
+
+        Ok(())
+    }
+}
+
+impl Drop for TcpSubscriber {
+    fn drop(&mut self) {
+        for handle in self.connections.values() {
+            handle.abort();
+        }
+    }
+}
+
+impl Subscribe for TcpSubscriber {
+    type Err = MessageError;
+
+    fn subscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
+        self.topics
+            .write()
+            .expect("topics poisoned")
+            .push(topic.to_vec());
+
+        Ok(())
+    }
+
+    fn unsubscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
+        let mut topics = self.topics.write().expect("topics poisoned");
+
+        // Like ZeroMQ, remove a single subscription of this topic.
+        remove_subscription(&mut topics, topic)
+    }
+
+    fn subscriptions(&self) -> Vec<Vec<u8>> {
+        self.topics.read().expect("topics poisoned").clone()
+    }
+}
+
+impl Read for TcpSubscriber {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let message = self
+            .recv()
+            .map_err(|e| io::Error::new(io::ErrorKind::WouldBlock, e))?;
+
+        // Like ZeroMQ, the part exceeding `buf` is truncated.
+        let len = message.len().min(buf.len());
+        buf[..len].copy_from_slice(&message[..len]);
+
+        Ok(len)
+    }
+}
+
+impl Iterator for TcpSubscriber {
+    type Item = SubscribeStreamItem<<Self as SyncSubscriber>::Err>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.recv().map_err(Into::into))
+    }
+}
+
+impl AsyncRead for TcpSubscriber {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let message = futures::ready!(self.stream.poll_next_unpin(cx));
+
+        if let Some(message) = message {
+            let len = message.len().min(buf.remaining());
+            buf.put_slice(&message[..len]);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Stream for TcpSubscriber {
+    type Item = SubscribeStreamItem<<Self as AsyncSubscriber>::Err>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.stream
+            .poll_next_unpin(cx)
+            .map(|message| message.map(Ok))
+    }
+}
+
+impl Subscriber for TcpSubscriber {}
+
+impl SyncSubscriber for TcpSubscriber {
+    type Err = MessageError;
+
+    fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, Self::Err> {
+        Ok(self.receiver.recv_timeout(timeout).ok())
+    }
+
+    fn set_recv_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Self::Err> {
+        self.recv_timeout = timeout;
+
+        Ok(())
+    }
+}
+
+impl AsyncSubscriber for TcpSubscriber {
+    type Err = MessageError;
+}
+
+/// The errors of [`Tcp`](self).
+#[derive(thiserror::Error, Debug)]
+pub enum TcpError {
+    /// When the URI is not in the form of `tcp://host:port`.
+    #[error("Invalid TCP URI: {0}")]
+    InvalidUri(String),
+
+    #[error("Failed to bind: {0}")]
+    BindFailed(io::Error),
+
+    #[error("Failed to connect: {0}")]
+    ConnectFailed(io::Error),
+
+    /// When we didn't bind or connect to the URI.
+    #[error("No such endpoint: {0}")]
+    NoSuchEndpoint(String),
+
+    /// When no message arrives within the receiving timeout.
+    #[error("Receiving timed out")]
+    RecvTimeout,
+}
+
+/// The result type of [`Tcp`](self).
+pub type TcpResult<T> = Result<T, TcpError>;
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{TcpError, TcpPublisher, TcpSubscriber};
+    use crate::message::traits::{Bind, Connect, Subscribe, SyncSubscriber, Write};
+    use crate::message::MessageError;
+
+    /// Bind a publisher to a free port of the loopback, and connect
+    /// a subscriber subscribing `topic` to it.
+    fn connected_pair(topic: &[u8]) -> (TcpPublisher, TcpSubscriber) {
+        let mut publisher = TcpPublisher::new().unwrap();
+        publisher.bind("tcp://127.0.0.1:0").unwrap();
+        let addr = publisher.local_addrs()[0];
+
+        let mut subscriber = TcpSubscriber::new().unwrap();
+        subscriber.subscribe(topic).unwrap();
+        subscriber.connect(&format!("tcp://{addr}")).unwrap();
+
+        (publisher, subscriber)
+    }
+
+    /// Publish `b"BTC ready"` until the subscriber receives it,
+    /// as the messages published before accepted are dropped.
+    fn wait_ready(publisher: &mut TcpPublisher, subscriber: &mut TcpSubscriber) {
+        let timeout = Duration::from_millis(10);
+
+        loop {
+            publisher.write_all(b"BTC ready").unwrap();
+
+            if subscriber.recv_timeout(timeout).unwrap().is_some() {
+                break;
+            }
+        }
+
+        // Drain the remaining readiness messages.
+        while subscriber.recv_timeout(timeout).unwrap().is_some() {}
+    }
+
+    #[test]
+    fn test_sync_loopback() {
+        let (mut publisher, mut subscriber) = connected_pair(b"BTC");
+        wait_ready(&mut publisher, &mut subscriber);
+
+        for message in [&b"BTC 1"[..], b"ETH 2", b"BTC 3", b""] {
+            publisher.write_all(message).unwrap();
+        }
+        let long_message = [b"BTC ".to_vec(), vec![42; 100_000]].concat();
+        publisher.write_all(&long_message).unwrap();
+
+        assert_eq!(subscriber.next().unwrap().unwrap(), b"BTC 1");
+        assert_eq!(subscriber.next().unwrap().unwrap(), b"BTC 3");
+        assert_eq!(subscriber.next().unwrap().unwrap(), long_message);
+
+        let timeout = Duration::from_millis(10);
+        assert_eq!(subscriber.recv_timeout(timeout).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_async_loopback() {
+        use crate::message::traits::{AsyncWriteExt, StreamExt};
+
+        let (mut publisher, mut subscriber) = connected_pair(b"BTC");
+        wait_ready(&mut publisher, &mut subscriber);
+
+        AsyncWriteExt::write_all(&mut publisher, b"ETH 1")
+            .await
+            .unwrap();
+        AsyncWriteExt::write_all(&mut publisher, b"BTC 2")
+            .await
+            .unwrap();
+
+        let message = tokio::time::timeout(Duration::from_secs(5), StreamExt::next(&mut subscriber))
+            .await
+            .expect("receiving timed out");
+        assert_eq!(message.unwrap().unwrap(), b"BTC 2");
+    }
+
+    #[test]
+    fn test_endpoints() {
+        let mut publisher = TcpPublisher::new().unwrap();
+        let mut subscriber = TcpSubscriber::new().unwrap();
+
+        assert!(matches!(
+            publisher.bind("127.0.0.1:0"),
+            Err(MessageError::TcpError(TcpError::InvalidUri(_)))
+        ));
+        assert!(matches!(
+            publisher.unbind("tcp://127.0.0.1:0"),
+            Err(MessageError::TcpError(TcpError::NoSuchEndpoint(_)))
+        ));
+        assert!(matches!(
+            subscriber.disconnect("tcp://127.0.0.1:0"),
+            Err(MessageError::TcpError(TcpError::NoSuchEndpoint(_)))
+        ));
+
+        publisher.bind("tcp://127.0.0.1:0").unwrap();
+        let uri = format!("tcp://{}", publisher.local_addrs()[0]);
+        subscriber.connect(&uri).unwrap();
+        subscriber.disconnect(&uri).unwrap();
+        publisher.unbind(&uri).unwrap();
+        assert!(publisher.local_addrs().is_empty());
+    }
+
+    #[test]
+    fn test_unbind_closes_connections() {
+        let (mut publisher, mut subscriber) = connected_pair(b"BTC");
+        wait_ready(&mut publisher, &mut subscriber);
+
+        let uri = format!("tcp://{}", publisher.local_addrs()[0]);
+
+        // Binding port 0 again listens on another assigned port.
+        publisher.bind("tcp://127.0.0.1:0").unwrap();
+        assert_eq!(publisher.local_addrs().len(), 2);
+
+        publisher.unbind(&uri).unwrap();
+        assert_eq!(publisher.local_addrs().len(), 1);
+
+        publisher.write_all(b"BTC 1").unwrap();
+        let timeout = Duration::from_millis(100);
+        assert_eq!(subscriber.recv_timeout(timeout).unwrap(), None);
+    }
+}
@@ -1,6 +1,7 @@
 //! The abstracts (traits) of [`Message`](super).
 
 pub use std::io::{Read, Write};
+use std::time::Duration;
 
 pub use futures::{Stream, StreamExt};
 pub use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
@@ -13,7 +14,32 @@ pub trait Subscribe {
     fn subscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err>;
 
     /// Unsubscribe a topic.
+    ///
+    /// It fails if `topic` is not subscribed. The implementations of this
+    /// crate return [`MessageError::NotSubscribed`](super::MessageError::NotSubscribed).
     fn unsubscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err>;
+
+    /// Get the subscribed topics, in the subscribed order.
+    ///
+    /// A topic subscribed several times is listed several times,
+    /// and should be unsubscribed as many times.
+    fn subscriptions(&self) -> Vec<Vec<u8>>;
+
+    /// Subscribe all the topics.
+    ///
+    /// It subscribes the empty topic, which every message starts with.
+    fn subscribe_all(&mut self) -> Result<(), Self::Err> {
+        self.subscribe(b"")
+    }
+
+    /// Unsubscribe all the subscribed topics.
+    fn unsubscribe_all(&mut self) -> Result<(), Self::Err> {
+        for topic in self.subscriptions() {
+            self.unsubscribe(&topic)?;
+        }
+
+        Ok(())
+    }
 }
 
 /// The trait for connecting to the specified URI after configuration.
@@ -48,6 +74,18 @@ pub trait Publisher: Bind {}
 /// and iterator ([`Iterator`]) of [`Subscriber`].
 pub trait SyncSubscriber: Read + Iterator<Item = SubscribeStreamItem<Self::Err>> {
     type Err;
+
+    /// Receive a message, waiting at most `timeout`.
+    ///
+    /// It returns `Ok(None)` if no message arrives in time,
+    /// so the caller can check if it should stop receiving.
+    fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, Self::Err>;
+
+    /// Set the timeout of the blocking [`Read`] and [`Iterator`]
+    /// operations. `None` means waiting forever, which is the default.
+    ///
+    /// When the timeout is reached, these operations return an error.
+    fn set_recv_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Self::Err>;
 }
 
 /// The trait that provides the synchronous writer ([`Write`])
@@ -0,0 +1,475 @@
+//! The typed publisher and subscriber publishing data structures directly.
+//!
+//! Each message is a 1-byte tag, which is the identifier of
+//! [`MessageTypeField`], followed by the serialized structure.
+//! [`TypedSubscriber`] dispatches on the tag, and returns an
+//! [`AnyStructure`].
+//!
+//! # Example
+//!
+//! ```
+//! use std::error::Error;
+//!
+//! use wmjtyd_libstock::data::bbo::BboStructure;
+//! use wmjtyd_libstock::message::traits::{SyncPublisher, SyncSubscriber};
+//! use wmjtyd_libstock::message::typed::{AnyStructure, TypedPublisher, TypedSubscriber};
+//!
+//! fn publish(publisher: impl SyncPublisher, bbo: &BboStructure) {
+//!     let mut publisher = TypedPublisher::new(publisher);
+//!     publisher.send(bbo).expect("failed to send");
+//! }
+//!
+//! fn receive<S>(subscriber: S)
+//! where
+//!     S: SyncSubscriber,
+//!     S::Err: Error + Send + Sync + 'static,
+//! {
+//!     let mut subscriber = TypedSubscriber::new(subscriber);
+//!
+//!     match subscriber.recv().expect("failed to receive") {
+//!         AnyStructure::Bbo(bbo) => println!("{bbo:?}"),
+//!         other => println!("other structure: {other:?}"),
+//!     }
+//! }
+//! ```
+
+use std::error::Error;
+use std::io::Write;
+
+use crate::data::bbo::BboStructure;
+use crate::data::fields::message_type::MessageType;
+use crate::data::fields::MessageTypeField;
+use crate::data::funding_rate::FundingRateStructure;
+use crate::data::kline::KlineStructure;
+use crate::data::orderbook::OrderbookStructure;
+use crate::data::serializer::{
+    FieldDeserializer,
+    FieldSerializer,
+    StructDeserializer,
+    StructSerializer,
+};
+use crate::data::trade::TradeStructure;
+use crate::message::traits::SyncSubscriber;
+
+/// The structure which can be published with [`TypedPublisher`].
+pub trait TypedStructure: StructSerializer + StructDeserializer {
+    /// The message type to tag the serialized structure with.
+    const MESSAGE_TYPE: MessageType;
+
+    /// Wrap this structure into an [`AnyStructure`].
+    fn into_any(self) -> AnyStructure;
+}
+
+macro_rules! impl_typed_structure {
+    ($($structure:ty => $variant:ident: $message_type:expr),+ $(,)?) => {
+        $(
+            impl TypedStructure for $structure {
+                const MESSAGE_TYPE: MessageType = $message_type;
+
+                fn into_any(self) -> AnyStructure {
+                    AnyStructure::$variant(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_typed_structure!(
+    BboStructure => Bbo: MessageType::BBO,
+    OrderbookStructure => Orderbook: MessageType::L2Event,
+    TradeStructure => Trade: MessageType::Trade,
+    KlineStructure => Kline: MessageType::Candlestick,
+    FundingRateStructure => FundingRate: MessageType::FundingRate,
+);
+
+/// Any of the structures received by [`TypedSubscriber`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnyStructure {
+    Bbo(BboStructure),
+    Orderbook(OrderbookStructure),
+    Trade(TradeStructure),
+    Kline(KlineStructure),
+    FundingRate(FundingRateStructure),
+}
+
+impl AnyStructure {
+    /// Decode a tagged message.
+    pub fn decode(message: &[u8]) -> TypedMessageResult<Self> {
+        let (tag, body) = match message.split_first() {
+            Some((tag, body)) => (*tag, body),
+            None => return Err(TypedMessageError::EmptyMessage),
+        };
+
+        let unknown = || TypedMessageError::UnknownMessageType {
+            tag,
+            raw: message.to_vec(),
+        };
+
+        let message_type = MessageTypeField::deserialize(&[tag])
+            .map_err(|_| unknown())?
+            .0;
+
+        match message_type {
+            MessageType::BBO => decode_body::<BboStructure>(body),
+            // Accept all the orderbook message types.
+            MessageType::L2TopK | MessageType::L2Snapshot | MessageType::L2Event => {
+                decode_body::<OrderbookStructure>(body)
+            }
+            MessageType::Trade => decode_body::<TradeStructure>(body),
+            MessageType::Candlestick => decode_body::<KlineStructure>(body),
+            MessageType::FundingRate => decode_body::<FundingRateStructure>(body),
+            _ => Err(unknown()),
+        }
+    }
+
+    /// The message type that this structure is tagged with.
+    pub fn message_type(&self) -> MessageType {
+        match self {
+            AnyStructure::Bbo(_) => BboStructure::MESSAGE_TYPE,
+            AnyStructure::Orderbook(_) => OrderbookStructure::MESSAGE_TYPE,
+            AnyStructure::Trade(_) => TradeStructure::MESSAGE_TYPE,
+            AnyStructure::Kline(_) => KlineStructure::MESSAGE_TYPE,
+            AnyStructure::FundingRate(_) => FundingRateStructure::MESSAGE_TYPE,
+        }
+    }
+}
+
+/// Deserialize `body` as `T`.
+fn decode_body<T>(mut body: &[u8]) -> TypedMessageResult<AnyStructure>
+where
+    T: TypedStructure,
+    <T as StructDeserializer>::Err: Error + Send + Sync + 'static,
+{
+    T::deserialize(&mut body)
+        .map(T::into_any)
+        .map_err(|e| TypedMessageError::DeserializeFailed {
+            message_type: T::MESSAGE_TYPE,
+            source: Box::new(e),
+        })
+}
+
+/// The publisher publishing the [`TypedStructure`]s.
+#[derive(Debug)]
+pub struct TypedPublisher<P> {
+    inner: P,
+}
+
+impl<P: Write> TypedPublisher<P> {
+    /// Wrap a publisher, which should be bound.
+    pub fn new(inner: P) -> Self {
+        Self { inner }
+    }
+
+    /// Serialize `data`, and publish it as a single message.
+    pub fn send<T>(&mut self, data: &T) -> TypedMessageResult<()>
+    where
+        T: TypedStructure,
+        <T as StructSerializer>::Err: Error + Send + Sync + 'static,
+    {
+        let tag = MessageTypeField(T::MESSAGE_TYPE)
+            .serialize()
+            .map_err(|e| TypedMessageError::SerializeFailed(Box::new(e)))?;
+
+        let mut message = tag.to_vec();
+        data.serialize(&mut message)
+            .map_err(|e| TypedMessageError::SerializeFailed(Box::new(e)))?;
+
+        self.inner
+            .write_all(&message)
+            .map_err(TypedMessageError::WriteFailed)?;
+        self.inner.flush().map_err(TypedMessageError::WriteFailed)
+    }
+
+    /// Get the reference to the underlying publisher.
+    pub fn get_ref(&self) -> &P {
+        &self.inner
+    }
+
+    /// Get the mutable reference to the underlying publisher.
+    pub fn get_mut(&mut self) -> &mut P {
+        &mut self.inner
+    }
+
+    /// Unwrap this publisher, returning the underlying publisher.
+    pub fn into_inner(self) -> P {
+        self.inner
+    }
+}
+
+/// The subscriber receiving the [`AnyStructure`]s.
+///
+/// The underlying subscriber should subscribe the topic of the tags,
+/// for example, `b""` to receive all the structures.
+#[derive(Debug)]
+pub struct TypedSubscriber<S> {
+    inner: S,
+}
+
+impl<S> TypedSubscriber<S>
+where
+    S: SyncSubscriber,
+    <S as SyncSubscriber>::Err: Error + Send + Sync + 'static,
+{
+    /// Wrap a subscriber, which should be connected.
+    pub fn new(inner: S) -> Self {
+        Self { inner }
+    }
+
+    /// Receive and decode the next structure.
+    pub fn recv(&mut self) -> TypedMessageResult<AnyStructure> {
+        self.next().unwrap_or(Err(TypedMessageError::Closed))
+    }
+
+    /// Get the reference to the underlying subscriber.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get the mutable reference to the underlying subscriber.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Unwrap this subscriber, returning the underlying subscriber.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S> Iterator for TypedSubscriber<S>
+where
+    S: SyncSubscriber,
+    <S as SyncSubscriber>::Err: Error + Send + Sync + 'static,
+{
+    type Item = TypedMessageResult<AnyStructure>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let message = self.inner.next()?;
+
+        Some(
+            message
+                .map_err(|e| TypedMessageError::ReceiveFailed(Box::new(e)))
+                .and_then(|message| AnyStructure::decode(&message)),
+        )
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum TypedMessageError {
+    #[error("failed to serialize the structure: {0}")]
+    SerializeFailed(Box<dyn Error + Send + Sync>),
+
+    #[error("failed to deserialize the {message_type:?} structure: {source}")]
+    DeserializeFailed {
+        message_type: MessageType,
+        source: Box<dyn Error + Send + Sync>,
+    },
+
+    #[error("failed to write the message: {0}")]
+    WriteFailed(std::io::Error),
+
+    #[error("failed to receive the message: {0}")]
+    ReceiveFailed(Box<dyn Error + Send + Sync>),
+
+    #[error("the subscriber has been closed")]
+    Closed,
+
+    #[error("the message is empty")]
+    EmptyMessage,
+
+    #[error("unknown message type tag: {tag}")]
+    UnknownMessageType { tag: u8, raw: Vec<u8> },
+}
+
+pub type TypedMessageResult<T> = Result<T, TypedMessageError>;
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::time::Duration;
+
+    use crypto_market_type::MarketType;
+    use crypto_message::{BboMsg, TradeMsg, TradeSide};
+
+    use super::{AnyStructure, TypedMessageError, TypedPublisher, TypedSubscriber};
+    use crate::data::bbo::BboStructure;
+    use crate::data::trade::TradeStructure;
+    use crate::message::traits::{SyncPublisher, SyncSubscriber};
+
+    /// The in-memory publisher. Each write is a message.
+    struct MemoryPublisher(flume::Sender<Vec<u8>>);
+
+    impl Write for MemoryPublisher {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0
+                .send(buf.to_vec())
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::BrokenPipe, e))?;
+
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SyncPublisher for MemoryPublisher {}
+
+    /// The in-memory subscriber receiving from [`MemoryPublisher`].
+    struct MemorySubscriber(flume::Receiver<Vec<u8>>);
+
+    impl Read for MemorySubscriber {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.next() {
+                Some(message) => {
+                    let message = message?;
+                    let len = message.len().min(buf.len());
+                    buf[..len].copy_from_slice(&message[..len]);
+
+                    Ok(len)
+                }
+                None => Ok(0),
+            }
+        }
+    }
+
+    impl Iterator for MemorySubscriber {
+        type Item = Result<Vec<u8>, std::io::Error>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.recv().ok().map(Ok)
+        }
+    }
+
+    impl SyncSubscriber for MemorySubscriber {
+        type Err = std::io::Error;
+
+        fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, Self::Err> {
+            Ok(self.0.recv_timeout(timeout).ok())
+        }
+
+        fn set_recv_timeout(&mut self, _: Option<Duration>) -> Result<(), Self::Err> {
+            Ok(())
+        }
+    }
+
+    fn memory_pipe() -> (
+        TypedPublisher<MemoryPublisher>,
+        TypedSubscriber<MemorySubscriber>,
+    ) {
+        let (sender, receiver) = flume::unbounded();
+
+        (
+            TypedPublisher::new(MemoryPublisher(sender)),
+            TypedSubscriber::new(MemorySubscriber(receiver)),
+        )
+    }
+
+    fn construct_bbo() -> BboStructure {
+        BboStructure::try_from(&BboMsg {
+            exchange: "binance".into(),
+            market_type: MarketType::Spot,
+            symbol: "BTCUSDT".into(),
+            pair: "BTC/USDT".into(),
+            msg_type: crypto_msg_type::MessageType::BBO,
+            timestamp: 12345678,
+            json: "".into(),
+            bid_price: 1.0,
+            bid_quantity_base: 2.0,
+            bid_quantity_quote: 0.0,
+            bid_quantity_contract: None,
+            ask_price: 4.0,
+            ask_quantity_base: 5.0,
+            ask_quantity_quote: 0.0,
+            ask_quantity_contract: None,
+            id: None,
+        })
+        .unwrap()
+    }
+
+    fn construct_trade() -> TradeStructure {
+        TradeStructure::try_from(&TradeMsg {
+            exchange: "binance".into(),
+            market_type: MarketType::Spot,
+            symbol: "BTCUSDT".into(),
+            pair: "BTC/USDT".into(),
+            msg_type: crypto_msg_type::MessageType::Trade,
+            timestamp: 12345678,
+            price: 3.0,
+            quantity_base: 6.0,
+            quantity_quote: 0.0,
+            quantity_contract: None,
+            side: TradeSide::Buy,
+            trade_id: "".into(),
+            json: "".into(),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_memory_roundtrip() {
+        let (mut publisher, mut subscriber) = memory_pipe();
+        let bbo = construct_bbo();
+        let trade = construct_trade();
+
+        publisher.send(&bbo).unwrap();
+        publisher.send(&trade).unwrap();
+
+        assert_eq!(subscriber.recv().unwrap(), AnyStructure::Bbo(bbo));
+        assert_eq!(subscriber.recv().unwrap(), AnyStructure::Trade(trade));
+    }
+
+    #[test]
+    fn test_unknown_tag() {
+        let (publisher, mut subscriber) = memory_pipe();
+        let mut publisher = publisher.into_inner();
+
+        publisher.write_all(b"\xffgarbage").unwrap();
+
+        match subscriber.recv() {
+            Err(TypedMessageError::UnknownMessageType { tag, raw }) => {
+                assert_eq!(tag, 0xff);
+                assert_eq!(raw, b"\xffgarbage");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_closed() {
+        let (publisher, mut subscriber) = memory_pipe();
+        drop(publisher);
+
+        assert!(matches!(subscriber.recv(), Err(TypedMessageError::Closed)));
+    }
+
+    #[cfg(feature = "nanomsg")]
+    #[test]
+    fn test_nanomsg_roundtrip() {
+        use crate::message::nanomsg::{NanomsgPublisher, NanomsgSubscriber};
+        use crate::message::traits::{Bind, Connect, Subscribe};
+
+        const IPC_ADDR: &str = "ipc:///tmp/libstock_typed_roundtrip.ipc";
+
+        let mut publisher = NanomsgPublisher::new().expect("failed to create publisher");
+        publisher.bind(IPC_ADDR).expect("failed to bind");
+        let mut publisher = TypedPublisher::new(publisher);
+
+        let mut subscriber = NanomsgSubscriber::new().expect("failed to create subscriber");
+        subscriber.connect(IPC_ADDR).expect("failed to connect");
+        subscriber.subscribe(b"").expect("failed to subscribe");
+        let mut subscriber = TypedSubscriber::new(subscriber);
+
+        let bbo = construct_bbo();
+        let expected = AnyStructure::Bbo(bbo.clone());
+
+        // The messages published before connected are dropped,
+        // so keep publishing until the subscriber receives one.
+        std::thread::spawn(move || loop {
+            publisher.send(&bbo).expect("failed to send");
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        });
+
+        assert_eq!(subscriber.recv().unwrap(), expected);
+    }
+}
@@ -124,6 +124,22 @@ pub enum ZeromqError {
     /// When the `.set_unsubscribe()` operation failed.
     #[error("Failed to unsubscribe: {0}")]
     UnsubscribeFailed(zmq2::Error),
+
+    /// When the `poll()` operation failed.
+    #[error("Failed to poll: {0}")]
+    PollFailed(zmq2::Error),
+
+    /// When we failed to set an option of the socket.
+    #[error("Failed to set socket option: {0}")]
+    SetOptionFailed(zmq2::Error),
+
+    /// When we failed to monitor the events of a socket.
+    #[error("Failed to monitor socket: {0}")]
+    MonitorFailed(zmq2::Error),
+
+    /// When no peer is present at an address within the timeout.
+    #[error("Endpoint unreachable: {0}")]
+    EndpointUnreachable(String),
 }
 
 /// The result type of [`Zeromq`](self).
@@ -131,8 +147,82 @@ pub type ZeromqResult<T> = Result<T, ZeromqError>;
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use crate as wmjtyd_libstock;
 
+    /// The message longer than the read buffer in the partial read tests.
+    const LONG_MESSAGE_LEN: usize = 100;
+
+    /// Publish the long message to `uri` repeatedly in a new thread.
+    fn publish_long_message(uri: &'static str) -> Vec<u8> {
+        use wmjtyd_libstock::message::traits::{Bind, Write};
+        use wmjtyd_libstock::message::zeromq::ZeromqPublisher;
+
+        let message = (0..LONG_MESSAGE_LEN as u8).collect::<Vec<u8>>();
+        let mut publisher = ZeromqPublisher::new().expect("failed to create publisher");
+        publisher.bind(uri).expect("failed to bind");
+
+        let published = message.clone();
+        std::thread::spawn(move || loop {
+            publisher.write_all(&published).ok();
+            std::thread::sleep(Duration::from_millis(1));
+        });
+
+        message
+    }
+
+    #[test]
+    fn test_read_partially() {
+        use wmjtyd_libstock::message::traits::{Connect, Read, Subscribe};
+        use wmjtyd_libstock::message::zeromq::ZeromqSubscriber;
+
+        const URI: &str = "ipc:///tmp/libstock_zeromq_read_partially.ipc";
+        let message = publish_long_message(URI);
+
+        let mut subscriber = ZeromqSubscriber::new().expect("failed to create subscriber");
+        subscriber.connect(URI).expect("failed to connect");
+        subscriber.subscribe(b"").expect("failed to subscribe");
+
+        // Each message is reassembled exactly, without the tails dropped.
+        for _ in 0..2 {
+            let mut received = Vec::new();
+            let mut buf = [0; 16];
+
+            while received.len() < LONG_MESSAGE_LEN {
+                let len = subscriber.read(&mut buf).expect("failed to read");
+                received.extend_from_slice(&buf[..len]);
+            }
+
+            assert_eq!(received, message);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_read_partially_async() {
+        use wmjtyd_libstock::message::traits::{AsyncReadExt, Connect, Subscribe};
+        use wmjtyd_libstock::message::zeromq::ZeromqSubscriber;
+
+        const URI: &str = "ipc:///tmp/libstock_zeromq_read_partially_async.ipc";
+        let message = publish_long_message(URI);
+
+        let mut subscriber = ZeromqSubscriber::new().expect("failed to create subscriber");
+        subscriber.connect(URI).expect("failed to connect");
+        subscriber.subscribe(b"").expect("failed to subscribe");
+
+        for _ in 0..2 {
+            let mut received = Vec::new();
+            let mut buf = [0; 16];
+
+            while received.len() < LONG_MESSAGE_LEN {
+                let len = subscriber.read(&mut buf).await.expect("failed to read");
+                received.extend_from_slice(&buf[..len]);
+            }
+
+            assert_eq!(received, message);
+        }
+    }
+
     mod changelog_0_4_0 {
         use super::*;
 
@@ -3,10 +3,16 @@ macro_rules! construct_zeromq {
         name = $name:ident,
         socket_type = $socket_type:expr,
         category = $category:path
+        $(, fields = { $($field:ident: $field_ty:ty),* $(,)? })?
     ) => {
         #[doc = concat!("The [`", stringify!($category), "`] of ZeroMQ.")]
         pub struct $name {
             socket: zmq2::Socket,
+            // The context of `socket`, to create the auxiliary sockets
+            // like monitors. Not every kind of socket needs it.
+            #[allow(dead_code)]
+            context: zmq2::Context,
+            $($($field: $field_ty,)*)?
         }
 
         impl $name {
@@ -16,7 +22,11 @@ macro_rules! construct_zeromq {
                     .socket($socket_type)
                     .map_err($crate::message::zeromq::ZeromqError::CreateSocketFailed)?;
 
-                Ok(Self { socket })
+                Ok(Self {
+                    socket,
+                    context,
+                    $($($field: Default::default(),)*)?
+                })
             }
         }
 
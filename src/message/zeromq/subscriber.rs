@@ -1,4 +1,7 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::task::Poll;
+use std::time::{Duration, Instant};
 
 use super::common::construct_zeromq;
 use crate::message::traits::{
@@ -12,15 +15,136 @@ use crate::message::traits::{
     Subscriber,
     SyncSubscriber,
 };
-use crate::message::zeromq::ZeromqError;
-use crate::message::MessageError;
+use crate::message::zeromq::{ZeromqError, ZeromqResult};
+use crate::message::{remove_subscription, MessageError, MessageResult};
+
+/// The counter to give each monitor endpoint a unique name.
+static MONITOR_ID: AtomicUsize = AtomicUsize::new(0);
 
 construct_zeromq!(
     name = ZeromqSubscriber,
     socket_type = zmq2::SocketType::SUB,
-    category = Subscriber
+    category = Subscriber,
+    fields = {
+        // The subscribed topics, as ZeroMQ can't list them.
+        subscriptions: Vec<Vec<u8>>,
+        // The undelivered tail of the last message received by `Read`
+        // or `AsyncRead`, which is served before receiving a new one.
+        pending: VecDeque<u8>,
+    }
 );
 
+impl ZeromqSubscriber {
+    /// Connect to `uri` and wait until the peer is present.
+    ///
+    /// [`Connect::connect`] returns immediately, as ZeroMQ connects
+    /// in the background. This method watches the connection events
+    /// of the socket instead, and returns [`ZeromqError::EndpointUnreachable`]
+    /// if no peer accepts the connection within `timeout`. In that case,
+    /// the pending connection is dropped.
+    ///
+    /// Only the transports reporting connection events, like `ipc://`
+    /// and `tcp://`, can be verified.
+    pub fn connect_verified(&mut self, uri: &str, timeout: Duration) -> MessageResult<()> {
+        let deadline = Instant::now() + timeout;
+        let monitor_addr = format!(
+            "inproc://libstock-zeromq-monitor-{}",
+            MONITOR_ID.fetch_add(1, Ordering::Relaxed)
+        );
+
+        // Start monitoring before connecting, so we won't miss the event.
+        self.socket
+            .monitor(&monitor_addr, zmq2::SocketEvent::CONNECTED.to_raw().into())
+            .map_err(ZeromqError::MonitorFailed)?;
+        let connected = self.connect_monitored(uri, &monitor_addr, deadline);
+
+        // Stop monitoring whether it is connected or not,
+        // so the events won't pile up in the monitor.
+        stop_monitor(&mut self.socket)?;
+
+        if !connected? {
+            self.disconnect(uri)?;
+            return Err(ZeromqError::EndpointUnreachable(uri.to_string()).into());
+        }
+
+        Ok(())
+    }
+
+    /// Connect to `uri`, and wait for the `CONNECTED` event
+    /// from the monitor at `monitor_addr` until `deadline`.
+    fn connect_monitored(
+        &mut self,
+        uri: &str,
+        monitor_addr: &str,
+        deadline: Instant,
+    ) -> MessageResult<bool> {
+        let monitor = self
+            .context
+            .socket(zmq2::SocketType::PAIR)
+            .map_err(ZeromqError::CreateSocketFailed)?;
+        monitor
+            .connect(monitor_addr)
+            .map_err(ZeromqError::MonitorFailed)?;
+
+        self.connect(uri)?;
+
+        Ok(wait_for_connected(&monitor, deadline)?)
+    }
+}
+
+/// Stop the monitor of `socket`.
+///
+/// `zmq_socket_monitor` stops the monitor with a `NULL` endpoint, which
+/// [`zmq2::Socket::monitor`] can't pass – it rejects the empty endpoint
+/// as invalid instead – so we call it directly.
+fn stop_monitor(socket: &mut zmq2::Socket) -> ZeromqResult<()> {
+    use std::os::raw::{c_char, c_int, c_void};
+
+    extern "C" {
+        fn zmq_socket_monitor(socket: *mut c_void, addr: *const c_char, events: c_int) -> c_int;
+        fn zmq_errno() -> c_int;
+    }
+
+    // SAFETY: `socket` is a valid socket while it is borrowed,
+    // and the `NULL` endpoint is accepted by `zmq_socket_monitor`.
+    if unsafe { zmq_socket_monitor(socket.as_mut_ptr(), std::ptr::null(), 0) } == -1 {
+        let errno = unsafe { zmq_errno() };
+        return Err(ZeromqError::MonitorFailed(zmq2::Error::from_raw(errno)));
+    }
+
+    Ok(())
+}
+
+/// Wait for the `CONNECTED` event from `monitor` until `deadline`.
+fn wait_for_connected(monitor: &zmq2::Socket, deadline: Instant) -> ZeromqResult<bool> {
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Ok(false);
+        }
+
+        let timeout = remaining.as_millis().try_into().unwrap_or(i64::MAX);
+        let ready = monitor
+            .poll(zmq2::POLLIN, timeout)
+            .map_err(ZeromqError::PollFailed)?;
+        if ready == 0 {
+            return Ok(false);
+        }
+
+        // An event consists of two frames: the event ID with its value,
+        // and the endpoint. The event ID is the first 2 bytes.
+        let event = monitor.recv_multipart(0).map_err(ZeromqError::RecvFailed)?;
+        let event_id = event
+            .first()
+            .and_then(|frame| frame.get(..2))
+            .map(|id| u16::from_le_bytes([id[0], id[1]]));
+
+        if event_id == Some(zmq2::SocketEvent::CONNECTED.to_raw()) {
+            return Ok(true);
+        }
+    }
+}
+
 impl Connect for ZeromqSubscriber {
     type Err = MessageError;
 
@@ -41,9 +165,20 @@ impl Connect for ZeromqSubscriber {
     }
 }
 
+/// Read the messages as a byte stream.
+///
+/// If a message is longer than the buffer, the rest of the message
+/// is kept, and returned by the subsequent reads before receiving
+/// the next message. [`Iterator::next`] always receives a whole new
+/// message, regardless of the kept part.
 impl Read for ZeromqSubscriber {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        self.socket.recv_into(buf, 0).map_err(|e| e.into())
+        if self.pending.is_empty() && !buf.is_empty() {
+            let message = self.socket.recv_bytes(0)?;
+            self.pending.extend(message);
+        }
+
+        self.pending.read(buf)
     }
 }
 
@@ -60,25 +195,28 @@ impl Iterator for ZeromqSubscriber {
     }
 }
 
+/// Read the messages as a byte stream, like the [`Read`] implementation.
 impl AsyncRead for ZeromqSubscriber {
     fn poll_read(
-        self: std::pin::Pin<&mut Self>,
+        mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
         buf: &mut tokio::io::ReadBuf<'_>,
     ) -> Poll<std::io::Result<()>> {
-        let result = self.socket.recv_msg(zmq2::DONTWAIT);
-
-        match result {
-            Ok(m) => {
-                buf.put_slice(&m);
-                Poll::Ready(Ok(()))
+        if self.pending.is_empty() && buf.remaining() > 0 {
+            match self.socket.recv_bytes(zmq2::DONTWAIT) {
+                Ok(message) => self.pending.extend(message),
+                Err(zmq2::Error::EAGAIN) => {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                Err(e) => return Poll::Ready(Err(e.into())),
             }
-            Err(zmq2::Error::EAGAIN) => {
-                cx.waker().wake_by_ref();
-                Poll::Pending
-            }
-            Err(e) => Poll::Ready(Err(e.into())),
         }
+
+        let len = self.pending.read(buf.initialize_unfilled())?;
+        buf.advance(len);
+
+        Poll::Ready(Ok(()))
     }
 }
 
@@ -106,22 +244,64 @@ impl Subscribe for ZeromqSubscriber {
     type Err = MessageError;
 
     fn subscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
-        Ok(self
-            .socket
+        self.socket
             .set_subscribe(topic)
-            .map_err(ZeromqError::SubscribeFailed)?)
+            .map_err(ZeromqError::SubscribeFailed)?;
+
+        self.subscriptions.push(topic.to_vec());
+        Ok(())
     }
 
     fn unsubscribe(&mut self, topic: &[u8]) -> Result<(), Self::Err> {
-        Ok(self
-            .socket
+        // ZeroMQ ignores the topics not subscribed silently.
+        if !self.subscriptions.iter().any(|t| t == topic) {
+            return Err(MessageError::NotSubscribed(topic.to_vec()));
+        }
+
+        self.socket
             .set_unsubscribe(topic)
-            .map_err(ZeromqError::UnsubscribeFailed)?)
+            .map_err(ZeromqError::UnsubscribeFailed)?;
+
+        remove_subscription(&mut self.subscriptions, topic)
+    }
+
+    fn subscriptions(&self) -> Vec<Vec<u8>> {
+        self.subscriptions.clone()
     }
 }
 
 impl SyncSubscriber for ZeromqSubscriber {
     type Err = MessageError;
+
+    fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>, Self::Err> {
+        let timeout = timeout.as_millis().try_into().unwrap_or(i64::MAX);
+        let ready = self
+            .socket
+            .poll(zmq2::POLLIN, timeout)
+            .map_err(ZeromqError::PollFailed)?;
+
+        if ready == 0 {
+            return Ok(None);
+        }
+
+        let data = self
+            .socket
+            .recv_bytes(zmq2::DONTWAIT)
+            .map_err(ZeromqError::RecvFailed)?;
+
+        Ok(Some(data))
+    }
+
+    fn set_recv_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Self::Err> {
+        // -1 means waiting forever.
+        let timeout = timeout.map_or(-1, |t| t.as_millis().try_into().unwrap_or(i32::MAX));
+
+        self.socket
+            .set_rcvtimeo(timeout)
+            .map_err(ZeromqError::SetOptionFailed)?;
+
+        Ok(())
+    }
 }
 
 impl AsyncSubscriber for ZeromqSubscriber {
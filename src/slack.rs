@@ -1,6 +1,160 @@
 //! The Slack hook utilities and encapsulation for sending notification.
+//!
+//! The hooks for other channels, such as [Discord](discord)
+//! (required to enable `discord` feature), are also placed here.
+//! They share the abstraction in [`traits`].
 
-use slack_hook::{Payload, PayloadBuilder, Slack};
+pub mod message;
+pub mod traits;
+
+#[cfg(feature = "discord")]
+pub mod discord;
+
+#[cfg(feature = "slack-async")]
+pub mod batcher;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use slack_hook::Slack;
+
+pub use self::message::{Severity, SlackMessage, SlackMessageBuilder};
+use self::traits::{
+    NotificationError,
+    NotificationHook,
+    NotificationHookBuilder,
+    NotificationResult,
+};
+
+/// The underlying sender of [`SlackHook`].
+///
+/// It is implemented for [`WebhookSender`], and can be replaced
+/// with a mocked one in tests.
+trait SlackSender: std::fmt::Debug + Send + Sync {
+    /// Send the JSON payload once.
+    fn send(&self, payload: &serde_json::Value) -> SlackResult<()>;
+}
+
+/// The sender posting the payloads to a Slack incoming webhook.
+#[derive(Debug)]
+struct WebhookSender {
+    /// The endpoint of the incoming webhook.
+    endpoint: String,
+    /// The HTTP client for sending the payloads.
+    client: reqwest::blocking::Client,
+}
+
+impl SlackSender for WebhookSender {
+    fn send(&self, payload: &serde_json::Value) -> SlackResult<()> {
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(payload.to_string())
+            .send()
+            .map_err(SlackError::SendRequestFailed)?;
+
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            // Slack responds the seconds to wait in `Retry-After`.
+            let retry_after_ms = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok())
+                .map_or(0, |secs| secs * 1000);
+
+            return Err(SlackError::RateLimited { retry_after_ms });
+        }
+
+        response
+            .error_for_status()
+            .map_err(SlackError::SendRequestFailed)?;
+
+        Ok(())
+    }
+}
+
+/// The policy to retry sending messages on the rate limit
+/// or the transient errors.
+///
+/// The delay doubles after each failed attempt. If Slack specifies
+/// a longer delay with `Retry-After`, the longer one is used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum attempts to send a message, including the first one.
+    pub max_attempts: u32,
+    /// The delay before the first retry, in milliseconds.
+    pub initial_delay_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    /// Don't retry.
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_delay_ms: 500,
+        }
+    }
+}
+
+/// A [Block Kit](https://api.slack.com/block-kit) block of a Slack message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SlackBlock {
+    /// A section with a `mrkdwn` text, and the optional fields.
+    ///
+    /// Each field is a `(title, value)` pair.
+    Section {
+        text: String,
+        fields: Vec<(String, String)>,
+    },
+
+    /// A divider between blocks.
+    Divider,
+}
+
+impl SlackBlock {
+    /// Convert this block to the Block Kit JSON.
+    fn to_json(&self) -> serde_json::Value {
+        use serde_json::json;
+
+        match self {
+            SlackBlock::Section { text, fields } => {
+                let mut section = json!({
+                    "type": "section",
+                    "text": { "type": "mrkdwn", "text": text },
+                });
+
+                if !fields.is_empty() {
+                    section["fields"] = fields
+                        .iter()
+                        .map(|(title, value)| {
+                            json!({ "type": "mrkdwn", "text": format!("*{title}*\n{value}") })
+                        })
+                        .collect();
+                }
+
+                section
+            }
+            SlackBlock::Divider => json!({ "type": "divider" }),
+        }
+    }
+
+    /// Render this block as the plain text.
+    fn to_plain_text(&self) -> String {
+        match self {
+            SlackBlock::Section { text, fields } => {
+                let mut rendered = text.clone();
+
+                for (title, value) in fields {
+                    rendered.push_str(&format!("\n{title}: {value}"));
+                }
+
+                rendered
+            }
+            SlackBlock::Divider => "---".to_string(),
+        }
+    }
+}
 
 /// A slack hook encapsulation for sending messages and notifications.
 ///
@@ -19,23 +173,42 @@ use slack_hook::{Payload, PayloadBuilder, Slack};
 ///
 /// println!("{hook:?}");
 /// ```
-#[derive(Debug, derive_builder::Builder)]
+#[derive(Clone, Debug, derive_builder::Builder)]
 #[builder(setter(into))]
 pub struct SlackHook {
-    /// The [`Slack`] instance for sending messages.
+    /// The sender for sending messages.
     #[builder(setter(custom))]
-    slack: Slack,
+    slack: Arc<dyn SlackSender>,
 
     /// The channel to send to.
     channel: String,
     /// The username of the sender.
     username: String,
+
+    /// Send the [`SlackBlock`]s as the Block Kit blocks.
+    ///
+    /// If disabled, [`SlackHook::send_with_blocks`] renders
+    /// the blocks as the plain text. Enabled by default.
+    #[builder(default = "true")]
+    blocks_enabled: bool,
+
+    /// The policy to retry sending messages.
+    ///
+    /// By default, it does not retry.
+    #[builder(default)]
+    retry_policy: RetryPolicy,
 }
 
 impl SlackHookBuilder {
     /// Set the endpoint URI of [`SlackHook`].
     pub fn slack_endpoint(&mut self, hook: &str) -> Result<&mut Self, slack_hook::Error> {
-        self.slack = Some(Slack::new(hook)?);
+        // Validate the endpoint.
+        Slack::new(hook)?;
+
+        self.slack = Some(Arc::new(WebhookSender {
+            endpoint: hook.to_string(),
+            client: reqwest::blocking::Client::new(),
+        }));
         Ok(self)
     }
 }
@@ -60,31 +233,613 @@ impl SlackHook {
     /// assert!(!hook.send("hello, world!").is_ok());
     /// ```
     pub fn send(&self, text: &str) -> SlackResult<()> {
-        let payload = self.build_send_payload(text)?;
+        self.send_message(&SlackMessage::new(text))
+    }
+
+    /// Send the specified [`SlackMessage`] to the slack channel.
+    ///
+    /// The channel and the username of the hook are used
+    /// if the message does not override them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wmjtyd_libstock::slack::{Severity, SlackHookBuilder, SlackMessageBuilder};
+    ///
+    /// let hook = SlackHookBuilder::default()
+    ///     .channel("#your-channel")
+    ///     .username("your-bot")
+    ///     .slack_endpoint("https://hooks.slack.com/services/your/slack/endpoint")
+    ///     .expect("failed to construct slack hook")
+    ///     .build()
+    ///     .expect("failed to construct SlackHook");
+    ///
+    /// let message = SlackMessageBuilder::default()
+    ///     .text("Daemon stopped")
+    ///     .severity(Severity::Warning)
+    ///     .build()
+    ///     .expect("failed to construct SlackMessage");
+    ///
+    /// // Should be false since I mocked the 'slack_endpoint'.
+    /// assert!(!hook.send_message(&message).is_ok());
+    /// ```
+    pub fn send_message(&self, message: &SlackMessage) -> SlackResult<()> {
+        let payload = self.build_message_payload(message);
 
-        self.slack
-            .send(&payload)
-            .map_err(SlackError::SendMessageFailed)
+        self.send_payload(&payload)
     }
 
-    /// Build the payload to send.
-    fn build_send_payload(&self, text: &str) -> SlackResult<Payload> {
-        PayloadBuilder::new()
-            .text(text)
-            .channel(&self.channel)
-            .username(&self.username)
-            .build()
-            .map_err(SlackError::ConstructPayloadFailed)
+    /// Send the specified message text with the [`SlackBlock`]s
+    /// to the slack channel.
+    ///
+    /// `text` is used as the fallback of the notifications.
+    /// If [`blocks_enabled`](SlackHookBuilder::blocks_enabled) is `false`,
+    /// the blocks are rendered as the plain text instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wmjtyd_libstock::slack::{SlackBlock, SlackHookBuilder};
+    ///
+    /// let hook = SlackHookBuilder::default()
+    ///     .channel("#your-channel")
+    ///     .username("your-bot")
+    ///     .slack_endpoint("https://hooks.slack.com/services/your/slack/endpoint")
+    ///     .expect("failed to construct slack hook")
+    ///     .build()
+    ///     .expect("failed to construct SlackHook");
+    ///
+    /// let blocks = vec![
+    ///     SlackBlock::Section {
+    ///         text: "*Daemon stopped*".to_string(),
+    ///         fields: vec![("Exchange".to_string(), "binance".to_string())],
+    ///     },
+    ///     SlackBlock::Divider,
+    /// ];
+    ///
+    /// // Should be false since I mocked the 'slack_endpoint'.
+    /// assert!(!hook.send_with_blocks("Daemon stopped", blocks).is_ok());
+    /// ```
+    pub fn send_with_blocks(&self, text: &str, blocks: Vec<SlackBlock>) -> SlackResult<()> {
+        self.send_message(&SlackMessage {
+            blocks,
+            ..SlackMessage::new(text)
+        })
+    }
+
+    /// Send the specified message text to the slack channel
+    /// without blocking the async runtime.
+    ///
+    /// The blocking HTTP request is run with [`tokio::task::spawn_blocking`],
+    /// so it must be awaited in a Tokio runtime.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use wmjtyd_libstock::slack::SlackHookBuilder;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let hook = SlackHookBuilder::default()
+    ///     .channel("#your-channel")
+    ///     .username("your-bot")
+    ///     .slack_endpoint("https://hooks.slack.com/services/your/slack/endpoint")
+    ///     .expect("failed to construct slack hook")
+    ///     .build()
+    ///     .expect("failed to construct SlackHook");
+    ///
+    /// // Should be false since I mocked the 'slack_endpoint'.
+    /// assert!(!hook.send_async("hello, world!").await.is_ok());
+    /// # }
+    /// ```
+    #[cfg(feature = "slack-async")]
+    pub fn send_async(
+        &self,
+        text: &str,
+    ) -> impl std::future::Future<Output = SlackResult<()>> + Send + 'static {
+        let payload = self.build_message_payload(&SlackMessage::new(text));
+        let hook = self.clone();
+
+        async move {
+            tokio::task::spawn_blocking(move || hook.send_payload(&payload))
+                .await
+                .map_err(SlackError::SendTaskFailed)?
+        }
+    }
+
+    /// Send the specified message text to the slack channel
+    /// in the background, without waiting for the result.
+    ///
+    /// The failure is logged with [`tracing`].
+    ///
+    /// # Panics
+    ///
+    /// It panics if it is not called in a Tokio runtime.
+    #[cfg(feature = "slack-async")]
+    pub fn send_async_fire_and_forget(&self, text: &str) {
+        let future = self.send_async(text);
+
+        tokio::task::spawn(async move {
+            if let Err(e) = future.await {
+                tracing::error!("Failed to send the Slack message: {e}");
+            }
+        });
+    }
+
+    /// Send the payload, and retry according to the [`RetryPolicy`].
+    fn send_payload(&self, payload: &serde_json::Value) -> SlackResult<()> {
+        let mut delay = Duration::from_millis(self.retry_policy.initial_delay_ms);
+        let mut attempt = 1;
+
+        loop {
+            match self.slack.send(payload) {
+                Err(e) if attempt < self.retry_policy.max_attempts && e.is_retryable() => {
+                    let wait = match e {
+                        SlackError::RateLimited { retry_after_ms } => {
+                            delay.max(Duration::from_millis(retry_after_ms))
+                        }
+                        _ => delay,
+                    };
+
+                    tracing::warn!("Failed to send the Slack message: {e}; retrying in {wait:?}");
+
+                    std::thread::sleep(wait);
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Build the payload of the [`SlackMessage`] to send.
+    ///
+    /// If [`blocks_enabled`](SlackHookBuilder::blocks_enabled) is `false`,
+    /// the blocks are rendered as the plain text.
+    fn build_message_payload(&self, message: &SlackMessage) -> serde_json::Value {
+        use serde_json::json;
+
+        let mut text = match message.severity {
+            Some(Severity::Critical) => format!("<!here> {}", message.text),
+            _ => message.text.clone(),
+        };
+
+        let blocks: Vec<serde_json::Value> = if self.blocks_enabled {
+            message.blocks.iter().map(SlackBlock::to_json).collect()
+        } else {
+            for block in &message.blocks {
+                text.push('\n');
+                text.push_str(&block.to_plain_text());
+            }
+
+            Vec::new()
+        };
+
+        let mut payload = json!({
+            "text": text,
+            "channel": message.channel.as_ref().unwrap_or(&self.channel),
+            "username": message.username.as_ref().unwrap_or(&self.username),
+        });
+
+        if let Some(icon_emoji) = &message.icon_emoji {
+            payload["icon_emoji"] = json!(icon_emoji);
+        }
+
+        match message.severity {
+            // The color is only available in the attachments.
+            Some(severity) => {
+                let mut attachment = json!({
+                    "color": severity.color(),
+                    "fallback": message.text,
+                });
+
+                if blocks.is_empty() {
+                    attachment["text"] = json!(severity.label());
+                } else {
+                    attachment["blocks"] = json!(blocks);
+                }
+
+                payload["attachments"] = json!([attachment]);
+            }
+            None if !blocks.is_empty() => payload["blocks"] = json!(blocks),
+            None => {}
+        }
+
+        payload
+    }
+}
+
+impl NotificationHook for SlackHook {
+    type Builder = SlackHookBuilder;
+
+    fn send(&self, text: &str) -> NotificationResult<()> {
+        Ok(SlackHook::send(self, text)?)
+    }
+}
+
+impl NotificationHookBuilder for SlackHookBuilder {
+    type Hook = SlackHook;
+
+    fn with_endpoint(&mut self, endpoint: &str) -> NotificationResult<&mut Self> {
+        self.slack_endpoint(endpoint)
+            .map_err(|e| NotificationError::InvalidEndpoint(e.to_string()))
+    }
+
+    fn with_username(&mut self, username: &str) -> &mut Self {
+        self.username(username)
+    }
+
+    fn build_hook(&self) -> NotificationResult<Self::Hook> {
+        self.build()
+            .map_err(|e| NotificationError::BuildFailed(e.to_string()))
     }
 }
 
 #[derive(thiserror::Error, Debug)]
 pub enum SlackError {
+    /// The payload is built as JSON directly now, which never fails.
+    #[deprecated(since = "0.4.0", note = "the payload is never constructed with slack-hook now")]
     #[error("failed to construct payload: {0}")]
     ConstructPayloadFailed(slack_hook::Error),
 
+    /// The messages are sent with `reqwest` now,
+    /// which reports [`SlackError::SendRequestFailed`] instead.
+    #[deprecated(since = "0.4.0", note = "use `SendRequestFailed` instead")]
     #[error("failed to send message: {0}")]
     SendMessageFailed(slack_hook::Error),
+
+    #[error("failed to send message: {0}")]
+    SendRequestFailed(reqwest::Error),
+
+    #[error("rate limited by Slack, retry after {retry_after_ms} ms")]
+    RateLimited { retry_after_ms: u64 },
+
+    #[cfg(feature = "slack-async")]
+    #[error("failed to run the sending task: {0}")]
+    SendTaskFailed(tokio::task::JoinError),
+
+    #[cfg(feature = "slack-async")]
+    #[error("the batcher has been closed")]
+    BatcherClosed,
+}
+
+impl SlackError {
+    /// Is it worth retrying after this error?
+    ///
+    /// It is `true` for the rate limit and the network errors.
+    fn is_retryable(&self) -> bool {
+        match self {
+            SlackError::RateLimited { .. } => true,
+            SlackError::SendRequestFailed(e) => {
+                e.is_timeout()
+                    || e.is_connect()
+                    || e.status().map_or(false, |s| s.is_server_error())
+            }
+            _ => false,
+        }
+    }
 }
 
 pub type SlackResult<T> = Result<T, SlackError>;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use super::{
+        RetryPolicy,
+        Severity,
+        SlackBlock,
+        SlackError,
+        SlackHook,
+        SlackHookBuilder,
+        SlackMessage,
+        SlackMessageBuilder,
+        SlackResult,
+        SlackSender,
+    };
+
+    /// The sender which records the payloads sent,
+    /// instead of sending them to Slack.
+    #[derive(Debug, Default)]
+    pub(super) struct MockSender {
+        sent: Mutex<Vec<String>>,
+        pub(super) calls: AtomicUsize,
+        /// The errors to return in the first sends.
+        failures: Mutex<VecDeque<SlackError>>,
+    }
+
+    impl MockSender {
+        pub(super) fn sent_text(&self, text: &str) -> bool {
+            self.sent.lock().unwrap().iter().any(|p| p.contains(text))
+        }
+    }
+
+    impl MockSender {
+        fn failing_with(failures: impl IntoIterator<Item = SlackError>) -> Self {
+            Self {
+                failures: Mutex::new(failures.into_iter().collect()),
+                ..Self::default()
+            }
+        }
+    }
+
+    impl SlackSender for MockSender {
+        fn send(&self, payload: &serde_json::Value) -> SlackResult<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+
+            if let Some(e) = self.failures.lock().unwrap().pop_front() {
+                return Err(e);
+            }
+
+            self.sent.lock().unwrap().push(payload.to_string());
+            Ok(())
+        }
+    }
+
+    fn rate_limited() -> SlackError {
+        SlackError::RateLimited { retry_after_ms: 0 }
+    }
+
+    fn retry_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            initial_delay_ms: 1,
+        }
+    }
+
+    fn mocked_builder(sender: Arc<MockSender>) -> SlackHookBuilder {
+        let mut builder = SlackHookBuilder::default();
+        builder.slack = Some(sender as Arc<dyn SlackSender>);
+        builder.channel("#test").username("tester");
+
+        builder
+    }
+
+    pub(super) fn mocked_hook(sender: Arc<MockSender>) -> SlackHook {
+        mocked_builder(sender)
+            .build()
+            .expect("failed to construct SlackHook")
+    }
+
+    fn example_blocks() -> Vec<SlackBlock> {
+        vec![
+            SlackBlock::Section {
+                text: "*Daemon stopped*".to_string(),
+                fields: vec![("Exchange".to_string(), "binance".to_string())],
+            },
+            SlackBlock::Divider,
+            SlackBlock::Section {
+                text: "Restarting.".to_string(),
+                fields: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_blocks_payload() {
+        let hook = mocked_hook(Arc::new(MockSender::default()));
+        let payload = hook.build_message_payload(&SlackMessage {
+            blocks: example_blocks(),
+            ..SlackMessage::new("Daemon stopped")
+        });
+
+        assert_eq!(
+            payload,
+            json!({
+                "text": "Daemon stopped",
+                "channel": "#test",
+                "username": "tester",
+                "blocks": [
+                    {
+                        "type": "section",
+                        "text": { "type": "mrkdwn", "text": "*Daemon stopped*" },
+                        "fields": [
+                            { "type": "mrkdwn", "text": "*Exchange*\nbinance" },
+                        ],
+                    },
+                    { "type": "divider" },
+                    {
+                        "type": "section",
+                        "text": { "type": "mrkdwn", "text": "Restarting." },
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_severity_payload() {
+        let hook = mocked_hook(Arc::new(MockSender::default()));
+
+        for (severity, text, color) in [
+            (Severity::Info, "Daemon started", "#2eb886"),
+            (Severity::Warning, "Daemon stopped", "#daa038"),
+            (Severity::Critical, "<!here> Daemon crashed", "#a30200"),
+        ] {
+            let message = SlackMessageBuilder::default()
+                .text(text.trim_start_matches("<!here> "))
+                .severity(severity)
+                .build()
+                .expect("failed to construct SlackMessage");
+
+            assert_eq!(
+                hook.build_message_payload(&message),
+                json!({
+                    "text": text,
+                    "channel": "#test",
+                    "username": "tester",
+                    "attachments": [
+                        {
+                            "color": color,
+                            "fallback": message.text,
+                            "text": severity.label(),
+                        },
+                    ],
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn test_severity_payload_with_blocks() {
+        let hook = mocked_hook(Arc::new(MockSender::default()));
+        let message = SlackMessageBuilder::default()
+            .text("Daemon stopped")
+            .blocks(vec![SlackBlock::Divider])
+            .severity(Severity::Warning)
+            .build()
+            .expect("failed to construct SlackMessage");
+
+        assert_eq!(
+            hook.build_message_payload(&message),
+            json!({
+                "text": "Daemon stopped",
+                "channel": "#test",
+                "username": "tester",
+                "attachments": [
+                    {
+                        "color": "#daa038",
+                        "fallback": "Daemon stopped",
+                        "blocks": [{ "type": "divider" }],
+                    },
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_message_overrides() {
+        let hook = mocked_hook(Arc::new(MockSender::default()));
+        let message = SlackMessageBuilder::default()
+            .text("hello")
+            .channel("#alerts")
+            .username("alerter")
+            .icon_emoji(":robot_face:")
+            .build()
+            .expect("failed to construct SlackMessage");
+
+        assert_eq!(
+            hook.build_message_payload(&message),
+            json!({
+                "text": "hello",
+                "channel": "#alerts",
+                "username": "alerter",
+                "icon_emoji": ":robot_face:",
+            })
+        );
+    }
+
+    #[test]
+    fn test_send_with_blocks() {
+        let sender = Arc::new(MockSender::default());
+        let hook = mocked_hook(sender.clone());
+
+        hook.send_with_blocks("Daemon stopped", example_blocks()).unwrap();
+        assert_eq!(sender.calls.load(Ordering::SeqCst), 1);
+        assert!(sender.sent_text(r#""type":"divider""#));
+    }
+
+    #[test]
+    fn test_send_with_blocks_disabled() {
+        let sender = Arc::new(MockSender::default());
+        let hook = mocked_builder(sender.clone())
+            .blocks_enabled(false)
+            .build()
+            .expect("failed to construct SlackHook");
+
+        hook.send_with_blocks("Daemon stopped", example_blocks()).unwrap();
+        assert_eq!(sender.calls.load(Ordering::SeqCst), 1);
+        assert!(sender.sent_text("Exchange: binance"));
+        assert!(!sender.sent_text("divider"));
+    }
+
+    #[test]
+    fn test_send() {
+        let sender = Arc::new(MockSender::default());
+        let hook = mocked_hook(sender.clone());
+
+        hook.send("hello").unwrap();
+        assert_eq!(sender.calls.load(Ordering::SeqCst), 1);
+        assert!(sender.sent_text("hello"));
+    }
+
+    #[test]
+    fn test_retry_until_success() {
+        let sender = Arc::new(MockSender::failing_with([rate_limited(), rate_limited()]));
+        let hook = mocked_builder(sender.clone())
+            .retry_policy(retry_policy(3))
+            .build()
+            .expect("failed to construct SlackHook");
+
+        hook.send("hello").unwrap();
+        assert_eq!(sender.calls.load(Ordering::SeqCst), 3);
+        assert!(sender.sent_text("hello"));
+    }
+
+    #[test]
+    fn test_retry_exhausted() {
+        let sender = Arc::new(MockSender::failing_with([
+            rate_limited(),
+            rate_limited(),
+            rate_limited(),
+        ]));
+        let hook = mocked_builder(sender.clone())
+            .retry_policy(retry_policy(2))
+            .build()
+            .expect("failed to construct SlackHook");
+
+        assert!(matches!(hook.send("hello"), Err(SlackError::RateLimited { .. })));
+        assert_eq!(sender.calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_no_retry_on_other_errors() {
+        let error = reqwest::blocking::Client::new()
+            .post("not a url")
+            .build()
+            .unwrap_err();
+        let sender = Arc::new(MockSender::failing_with([
+            SlackError::SendRequestFailed(error),
+        ]));
+        let hook = mocked_builder(sender.clone())
+            .retry_policy(retry_policy(3))
+            .build()
+            .expect("failed to construct SlackHook");
+
+        assert!(matches!(hook.send("hello"), Err(SlackError::SendRequestFailed(_))));
+        assert_eq!(sender.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[cfg(feature = "slack-async")]
+    #[tokio::test]
+    async fn test_send_async() {
+        let sender = Arc::new(MockSender::default());
+        let hook = mocked_hook(sender.clone());
+
+        hook.send_async("hello").await.unwrap();
+        assert_eq!(sender.calls.load(Ordering::SeqCst), 1);
+        assert!(sender.sent_text("hello"));
+    }
+
+    #[cfg(feature = "slack-async")]
+    #[tokio::test]
+    async fn test_send_async_fire_and_forget() {
+        let sender = Arc::new(MockSender::default());
+        let hook = mocked_hook(sender.clone());
+
+        hook.send_async_fire_and_forget("hello");
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while sender.calls.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the message should be sent");
+    }
+}
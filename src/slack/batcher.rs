@@ -0,0 +1,243 @@
+//! The batcher combining the Slack messages to avoid the rate limits.
+//! See [`SlackBatcher`].
+
+use std::time::Duration;
+
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+use super::{SlackError, SlackHook, SlackResult};
+
+/// The policy to flush the queued messages of a [`SlackBatcher`].
+///
+/// The messages are flushed when `max_messages` messages are queued,
+/// or `flush_interval_ms` has passed since the first queued message,
+/// whichever comes first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchPolicy {
+    /// The maximum messages to combine into one payload.
+    pub max_messages: usize,
+    /// The maximum time to keep a message in the queue, in milliseconds.
+    pub flush_interval_ms: u64,
+}
+
+impl Default for BatchPolicy {
+    fn default() -> Self {
+        Self {
+            max_messages: 20,
+            flush_interval_ms: 5000,
+        }
+    }
+}
+
+/// The command sent to the worker of [`SlackBatcher`].
+#[derive(Debug)]
+enum Command {
+    Push(String),
+    Flush(oneshot::Sender<SlackResult<()>>),
+}
+
+/// The batcher queuing the messages, and sending them with
+/// a [`SlackHook`] as a single message of the joined lines.
+///
+/// The messages are sent in a Tokio task, so it must be
+/// created in a Tokio runtime. The errors of the automatic
+/// flushes are logged with [`tracing`].
+///
+/// # Example
+///
+/// ```
+/// use wmjtyd_libstock::slack::batcher::{BatchPolicy, SlackBatcher};
+/// use wmjtyd_libstock::slack::SlackHookBuilder;
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let hook = SlackHookBuilder::default()
+///     .channel("#your-channel")
+///     .username("your-bot")
+///     .slack_endpoint("https://hooks.slack.com/services/your/slack/endpoint")
+///     .expect("failed to construct slack hook")
+///     .build()
+///     .expect("failed to construct SlackHook");
+///
+/// let batcher = SlackBatcher::new(hook, BatchPolicy::default());
+/// batcher.push("hello,");
+/// batcher.push("world!");
+///
+/// // Should be false since I mocked the 'slack_endpoint'.
+/// assert!(!batcher.close().await.is_ok());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SlackBatcher {
+    sender: mpsc::UnboundedSender<Command>,
+    worker: JoinHandle<SlackResult<()>>,
+}
+
+impl SlackBatcher {
+    /// Create a batcher sending the messages with `hook`.
+    ///
+    /// # Panics
+    ///
+    /// It panics if it is not called in a Tokio runtime.
+    pub fn new(hook: SlackHook, policy: BatchPolicy) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let worker = tokio::task::spawn(run_worker(hook, policy, receiver));
+
+        Self { sender, worker }
+    }
+
+    /// Queue the specified message text.
+    pub fn push(&self, text: &str) {
+        // The worker only exits after the sender is dropped.
+        let _ = self.sender.send(Command::Push(text.to_string()));
+    }
+
+    /// Send the queued messages now.
+    pub async fn flush(&self) -> SlackResult<()> {
+        let (reply, response) = oneshot::channel();
+
+        self.sender
+            .send(Command::Flush(reply))
+            .map_err(|_| SlackError::BatcherClosed)?;
+
+        response.await.map_err(|_| SlackError::BatcherClosed)?
+    }
+
+    /// Send the remaining messages, and stop the batcher.
+    pub async fn close(self) -> SlackResult<()> {
+        drop(self.sender);
+
+        self.worker.await.map_err(SlackError::SendTaskFailed)?
+    }
+}
+
+/// Receive the commands and flush the queued messages
+/// until all the senders are dropped.
+async fn run_worker(
+    hook: SlackHook,
+    policy: BatchPolicy,
+    mut receiver: mpsc::UnboundedReceiver<Command>,
+) -> SlackResult<()> {
+    let flush_interval = Duration::from_millis(policy.flush_interval_ms);
+    let mut pending = Vec::<String>::new();
+    let mut deadline = Instant::now();
+
+    loop {
+        let command = if pending.is_empty() {
+            receiver.recv().await
+        } else {
+            match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(command) => command,
+                Err(_) => {
+                    log_flush_error(flush(&hook, &mut pending).await);
+                    continue;
+                }
+            }
+        };
+
+        match command {
+            Some(Command::Push(text)) => {
+                if pending.is_empty() {
+                    deadline = Instant::now() + flush_interval;
+                }
+                pending.push(text);
+
+                if pending.len() >= policy.max_messages {
+                    log_flush_error(flush(&hook, &mut pending).await);
+                }
+            }
+            Some(Command::Flush(reply)) => {
+                let _ = reply.send(flush(&hook, &mut pending).await);
+            }
+            None => return flush(&hook, &mut pending).await,
+        }
+    }
+}
+
+/// Send the pending messages as a single message.
+async fn flush(hook: &SlackHook, pending: &mut Vec<String>) -> SlackResult<()> {
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    let text = pending.join("\n");
+    pending.clear();
+
+    hook.send_async(&text).await
+}
+
+fn log_flush_error(result: SlackResult<()>) {
+    if let Err(e) = result {
+        tracing::error!("Failed to send the batched Slack messages: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+
+    use super::{BatchPolicy, SlackBatcher};
+    use crate::slack::tests::{mocked_hook, MockSender};
+
+    #[tokio::test]
+    async fn test_flush_on_max_messages() {
+        let sender = Arc::new(MockSender::default());
+        let batcher = SlackBatcher::new(
+            mocked_hook(sender.clone()),
+            BatchPolicy {
+                max_messages: 3,
+                flush_interval_ms: 60_000,
+            },
+        );
+
+        for text in ["a", "b", "c", "d"] {
+            batcher.push(text);
+        }
+        batcher.close().await.unwrap();
+
+        assert_eq!(sender.calls.load(Ordering::SeqCst), 2);
+        assert!(sender.sent_text(r#"a\nb\nc"#));
+        assert!(sender.sent_text(r#""text":"d""#));
+    }
+
+    #[tokio::test]
+    async fn test_flush_on_interval() {
+        let sender = Arc::new(MockSender::default());
+        let batcher = SlackBatcher::new(
+            mocked_hook(sender.clone()),
+            BatchPolicy {
+                max_messages: 100,
+                flush_interval_ms: 10,
+            },
+        );
+
+        batcher.push("hello");
+        batcher.push("world");
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while sender.calls.load(Ordering::SeqCst) == 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("the messages should be flushed");
+
+        assert!(sender.sent_text(r#"hello\nworld"#));
+    }
+
+    #[tokio::test]
+    async fn test_manual_flush() {
+        let sender = Arc::new(MockSender::default());
+        let batcher = SlackBatcher::new(mocked_hook(sender.clone()), BatchPolicy::default());
+
+        batcher.flush().await.unwrap();
+        assert_eq!(sender.calls.load(Ordering::SeqCst), 0);
+
+        batcher.push("hello");
+        batcher.flush().await.unwrap();
+        assert_eq!(sender.calls.load(Ordering::SeqCst), 1);
+    }
+}
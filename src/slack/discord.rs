@@ -0,0 +1,159 @@
+//! The Discord hook encapsulation for sending notification.
+
+use super::traits::{
+    NotificationError,
+    NotificationHook,
+    NotificationHookBuilder,
+    NotificationResult,
+};
+
+/// A Discord webhook encapsulation for sending messages and notifications.
+///
+/// It can be used in place of [`SlackHook`](super::SlackHook)
+/// with [`NotificationHook`].
+///
+/// # Example
+///
+/// ```
+/// use wmjtyd_libstock::slack::discord::DiscordHookBuilder;
+///
+/// let hook = DiscordHookBuilder::default()
+///     .username("your-bot")
+///     .endpoint("https://discord.com/api/webhooks/your/discord/endpoint")
+///     .expect("failed to construct discord hook")
+///     .build()
+///     .expect("failed to construct DiscordHook");
+///
+/// // Should be false since I mocked the 'endpoint'.
+/// assert!(!hook.send("hello, world!").is_ok());
+/// ```
+#[derive(Clone, Debug, derive_builder::Builder)]
+#[builder(setter(into))]
+pub struct DiscordHook {
+    /// The endpoint of the webhook.
+    #[builder(setter(custom))]
+    endpoint: String,
+
+    /// The username of the sender.
+    ///
+    /// If not specified, the default name of the webhook is used.
+    #[builder(default, setter(strip_option))]
+    username: Option<String>,
+
+    /// The HTTP client for sending the messages.
+    #[builder(setter(skip))]
+    client: reqwest::blocking::Client,
+}
+
+impl DiscordHookBuilder {
+    /// Set the endpoint URI of [`DiscordHook`].
+    pub fn endpoint(&mut self, hook: &str) -> DiscordResult<&mut Self> {
+        reqwest::Url::parse(hook).map_err(|e| DiscordError::InvalidEndpoint(e.to_string()))?;
+
+        self.endpoint = Some(hook.to_string());
+        Ok(self)
+    }
+}
+
+impl DiscordHook {
+    /// Send the specified message text to the Discord channel.
+    pub fn send(&self, text: &str) -> DiscordResult<()> {
+        self.client
+            .post(&self.endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(self.build_send_payload(text).to_string())
+            .send()
+            .and_then(reqwest::blocking::Response::error_for_status)
+            .map_err(DiscordError::SendMessageFailed)?;
+
+        Ok(())
+    }
+
+    /// Build the payload to send.
+    fn build_send_payload(&self, text: &str) -> serde_json::Value {
+        let mut payload = serde_json::json!({ "content": text });
+
+        if let Some(username) = &self.username {
+            payload["username"] = username.as_str().into();
+        }
+
+        payload
+    }
+}
+
+impl NotificationHook for DiscordHook {
+    type Builder = DiscordHookBuilder;
+
+    fn send(&self, text: &str) -> NotificationResult<()> {
+        Ok(DiscordHook::send(self, text)?)
+    }
+}
+
+impl NotificationHookBuilder for DiscordHookBuilder {
+    type Hook = DiscordHook;
+
+    fn with_endpoint(&mut self, endpoint: &str) -> NotificationResult<&mut Self> {
+        Ok(self.endpoint(endpoint)?)
+    }
+
+    fn with_username(&mut self, username: &str) -> &mut Self {
+        self.username(username)
+    }
+
+    fn build_hook(&self) -> NotificationResult<Self::Hook> {
+        self.build()
+            .map_err(|e| NotificationError::BuildFailed(e.to_string()))
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum DiscordError {
+    #[error("invalid endpoint: {0}")]
+    InvalidEndpoint(String),
+
+    #[error("failed to send message: {0}")]
+    SendMessageFailed(reqwest::Error),
+}
+
+pub type DiscordResult<T> = Result<T, DiscordError>;
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::DiscordHook;
+    use crate::slack::traits::{NotificationHook, NotificationHookBuilder};
+
+    const ENDPOINT: &str = "https://discord.com/api/webhooks/your/discord/endpoint";
+
+    #[test]
+    fn test_payload() {
+        let hook = DiscordHook::builder()
+            .with_endpoint(ENDPOINT)
+            .unwrap()
+            .with_username("tester")
+            .build_hook()
+            .unwrap();
+
+        assert_eq!(
+            hook.build_send_payload("hello"),
+            json!({ "content": "hello", "username": "tester" })
+        );
+    }
+
+    #[test]
+    fn test_payload_without_username() {
+        let hook = DiscordHook::builder()
+            .with_endpoint(ENDPOINT)
+            .unwrap()
+            .build_hook()
+            .unwrap();
+
+        assert_eq!(hook.build_send_payload("hello"), json!({ "content": "hello" }));
+    }
+
+    #[test]
+    fn test_invalid_endpoint() {
+        assert!(DiscordHook::builder().with_endpoint("not a url").is_err());
+    }
+}
@@ -0,0 +1,97 @@
+//! The rich messages sent with [`SlackHook::send_message`](super::SlackHook::send_message).
+//! See [`SlackMessage`].
+
+use super::SlackBlock;
+
+/// The severity of a [`SlackMessage`].
+///
+/// It is shown as the color of the message attachment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Severity {
+    Info,
+    Warning,
+    /// The critical alerts also mention `@here`.
+    Critical,
+}
+
+impl Severity {
+    /// The color of the attachment.
+    pub fn color(&self) -> &'static str {
+        match self {
+            Severity::Info => "#2eb886",
+            Severity::Warning => "#daa038",
+            Severity::Critical => "#a30200",
+        }
+    }
+
+    /// The label of this severity.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Critical => "Critical",
+        }
+    }
+}
+
+/// A rich Slack message.
+///
+/// # Example
+///
+/// ```
+/// use wmjtyd_libstock::slack::{Severity, SlackBlock, SlackMessageBuilder};
+///
+/// let message = SlackMessageBuilder::default()
+///     .text("Daemon stopped")
+///     .blocks(vec![SlackBlock::Divider])
+///     .severity(Severity::Critical)
+///     .channel("#alerts")
+///     .icon_emoji(":rotating_light:")
+///     .build()
+///     .expect("failed to construct SlackMessage");
+///
+/// println!("{message:?}");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, derive_builder::Builder)]
+#[builder(setter(into))]
+pub struct SlackMessage {
+    /// The text of the message.
+    ///
+    /// It is also the fallback of the notifications
+    /// when `blocks` is specified.
+    pub text: String,
+
+    /// The [Block Kit](https://api.slack.com/block-kit) blocks.
+    #[builder(default)]
+    pub blocks: Vec<SlackBlock>,
+
+    /// The severity of the message.
+    #[builder(default, setter(strip_option))]
+    pub severity: Option<Severity>,
+
+    /// The channel to send to, instead of the channel of the hook.
+    #[builder(default, setter(strip_option))]
+    pub channel: Option<String>,
+
+    /// The username of the sender, instead of the username of the hook.
+    #[builder(default, setter(strip_option))]
+    pub username: Option<String>,
+
+    /// The emoji as the icon of the sender, for example `:robot_face:`.
+    #[builder(default, setter(strip_option))]
+    pub icon_emoji: Option<String>,
+}
+
+impl SlackMessage {
+    /// Create a message with the plain text only.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            blocks: Vec::new(),
+            severity: None,
+            channel: None,
+            username: None,
+            icon_emoji: None,
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! The abstracts (traits) of the notification hooks.
+//!
+//! With [`NotificationHook`], you can switch the channel
+//! to send notifications to by changing one type:
+//!
+//! ```
+//! use wmjtyd_libstock::slack::traits::{
+//!     NotificationHook,
+//!     NotificationHookBuilder,
+//!     NotificationResult,
+//! };
+//! use wmjtyd_libstock::slack::SlackHook;
+//!
+//! fn notify<H: NotificationHook>(endpoint: &str) -> NotificationResult<()> {
+//!     let hook = H::builder()
+//!         .with_endpoint(endpoint)?
+//!         .with_username("your-bot")
+//!         .build_hook()?;
+//!
+//!     hook.send("hello, world!")
+//! }
+//!
+//! // Should be false since I mocked the endpoint.
+//! // Besides, the channel of SlackHook is not specified.
+//! assert!(notify::<SlackHook>("https://hooks.slack.com/services/your/slack/endpoint").is_err());
+//! ```
+
+use futures::future::BoxFuture;
+
+use super::SlackError;
+
+/// The hook for sending notifications to a channel.
+pub trait NotificationHook: Clone + Send + Sync + 'static {
+    /// The builder of this hook.
+    type Builder: NotificationHookBuilder<Hook = Self>;
+
+    /// Create a builder of this hook.
+    fn builder() -> Self::Builder {
+        Self::Builder::default()
+    }
+
+    /// Send the specified text as a notification.
+    fn send(&self, text: &str) -> NotificationResult<()>;
+
+    /// Send the specified text as a notification
+    /// without blocking the async runtime.
+    ///
+    /// By default, it runs [`NotificationHook::send`] with
+    /// [`tokio::task::spawn_blocking`], so it must be awaited
+    /// in a Tokio runtime.
+    fn send_async(&self, text: &str) -> BoxFuture<'static, NotificationResult<()>> {
+        let hook = self.clone();
+        let text = text.to_string();
+
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || hook.send(&text))
+                .await
+                .map_err(NotificationError::SendTaskFailed)?
+        })
+    }
+}
+
+/// The builder of a [`NotificationHook`].
+pub trait NotificationHookBuilder: Default {
+    /// The hook to build.
+    type Hook: NotificationHook;
+
+    /// Set the endpoint URI to send notifications to.
+    fn with_endpoint(&mut self, endpoint: &str) -> NotificationResult<&mut Self>;
+
+    /// Set the username of the sender.
+    fn with_username(&mut self, username: &str) -> &mut Self;
+
+    /// Build the hook.
+    fn build_hook(&self) -> NotificationResult<Self::Hook>;
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum NotificationError {
+    #[error("invalid endpoint: {0}")]
+    InvalidEndpoint(String),
+
+    #[error("failed to build the hook: {0}")]
+    BuildFailed(String),
+
+    #[error("failed to run the sending task: {0}")]
+    SendTaskFailed(tokio::task::JoinError),
+
+    #[error("Slack error: {0}")]
+    SlackError(#[from] SlackError),
+
+    #[cfg(feature = "discord")]
+    #[error("Discord error: {0}")]
+    DiscordError(#[from] super::discord::DiscordError),
+}
+
+pub type NotificationResult<T> = Result<T, NotificationError>;
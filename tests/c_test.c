@@ -0,0 +1,93 @@
+/*
+ * The round-trip test of the C interface.
+ *
+ * Build and run it from the root of the repository:
+ *
+ *     cargo rustc --release --no-default-features --features ffi --crate-type staticlib
+ *     cc -Wall -Iinclude tests/c_test.c target/release/libwmjtyd_libstock.a \
+ *         -lpthread -ldl -lm -o target/c_test
+ *     ./target/c_test
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "libstock.h"
+
+#define CHECK(cond)                                                    \
+    do {                                                               \
+        if (!(cond)) {                                                 \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,     \
+                    __LINE__, #cond);                                  \
+            exit(1);                                                   \
+        }                                                              \
+    } while (0)
+
+static const char *BBO_JSON =
+    "{\"exchange_timestamp\":1672531200000,"
+    "\"received_timestamp\":1672531200001,"
+    "\"exchange_type\":\"binance\","
+    "\"market_type\":\"spot\","
+    "\"message_type\":\"bbo\","
+    "\"symbol\":{\"symbol\":1,\"pair\":\"BTC/USDT\"},"
+    "\"asks\":{\"price\":\"12345.6\",\"quantity_base\":\"1.5\"},"
+    "\"bids\":{\"price\":\"12344.9\",\"quantity_base\":\"2\"},"
+    "\"asks_quantity_quote\":\"0\","
+    "\"asks_quantity_contract\":null,"
+    "\"bids_quantity_quote\":\"0\","
+    "\"bids_quantity_contract\":null,"
+    "\"id\":null,"
+    "\"end\":null}";
+
+static void test_bbo_roundtrip(void) {
+    uint8_t encoded[256];
+    size_t encoded_len = sizeof(encoded);
+    CHECK(libstock_encode_bbo(BBO_JSON, encoded, &encoded_len) == LIBSTOCK_OK);
+
+    char decoded[1024];
+    size_t decoded_len = sizeof(decoded);
+    CHECK(libstock_decode_bbo(encoded, encoded_len, decoded, &decoded_len) == LIBSTOCK_OK);
+    CHECK(decoded_len == strlen(decoded) + 1);
+    CHECK(strstr(decoded, "\"pair\":\"BTC/USDT\"") != NULL);
+    CHECK(strstr(decoded, "\"price\":\"12345.6\"") != NULL);
+
+    /* Encoding the decoded JSON gives the same bytes. */
+    uint8_t reencoded[256];
+    size_t reencoded_len = sizeof(reencoded);
+    CHECK(libstock_encode_bbo(decoded, reencoded, &reencoded_len) == LIBSTOCK_OK);
+    CHECK(reencoded_len == encoded_len);
+    CHECK(memcmp(reencoded, encoded, encoded_len) == 0);
+}
+
+static void test_buffer_too_small(void) {
+    size_t required = 0;
+    CHECK(libstock_encode_bbo(BBO_JSON, NULL, &required) == LIBSTOCK_ERR_BUFFER_TOO_SMALL);
+    CHECK(required > 0);
+
+    uint8_t *encoded = malloc(required);
+    CHECK(encoded != NULL);
+    CHECK(libstock_encode_bbo(BBO_JSON, encoded, &required) == LIBSTOCK_OK);
+    free(encoded);
+}
+
+static void test_errors(void) {
+    uint8_t buf[256];
+    size_t len = sizeof(buf);
+    CHECK(libstock_encode_bbo(NULL, buf, &len) == LIBSTOCK_ERR_NULL_POINTER);
+    CHECK(libstock_encode_bbo("{}", buf, &len) == LIBSTOCK_ERR_INVALID_JSON);
+
+    char json[256];
+    size_t json_len = sizeof(json);
+    uint8_t garbage[1] = {0};
+    CHECK(libstock_decode_bbo(garbage, sizeof(garbage), json, &json_len) <= -1000);
+}
+
+int main(void) {
+    test_bbo_roundtrip();
+    test_buffer_too_small();
+    test_errors();
+
+    puts("all tests passed");
+    return 0;
+}